@@ -71,22 +71,138 @@ pub struct Resource {
 pub struct Target {
     pub resource: Resource,
     pub actions: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub environment: Option<DenyEnvironment>,
+}
+
+/// Scopes a deny rule to service providers and licenses, an empty list doesn't narrow the deny
+#[derive(
+    Deserialize, Serialize, Eq, PartialEq, Clone, Debug, Default, FromJsonQueryResult, ToSchema,
+)]
+#[serde(rename_all = "camelCase")]
+pub struct DenyEnvironment {
+    #[serde(default)]
+    pub service_providers: Vec<String>,
+    #[serde(default)]
+    pub licenses: Vec<String>,
+}
+
+#[derive(
+    Deserialize, Serialize, Eq, PartialEq, Clone, Debug, Default, FromJsonQueryResult, ToSchema,
+)]
+pub struct Permit {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<Condition>,
+    /// Obligations the service provider has to fulfil when it acts on the permit
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub obligations: Vec<Obligation>,
+    /// Advice for the service provider, e.g. "contact the owner before sharing the data"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub advice: Vec<String>,
+}
+
+/// An obligation such as "log-access", "max-records" with value "100" or "delete-after-days"
+/// with value "30"
+#[derive(Deserialize, Serialize, Eq, PartialEq, Clone, Debug, FromJsonQueryResult, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct Obligation {
+    pub id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<String>,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Clone, Debug, FromJsonQueryResult, ToSchema)]
 pub struct Deny {
     pub target: Target,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conditions: Vec<Condition>,
 }
 
 #[derive(Deserialize, Serialize, Eq, PartialEq, Clone, Debug, FromJsonQueryResult, ToSchema)]
 #[serde(tag = "effect")]
 pub enum ResourceRule {
-    Permit,
+    Permit(Permit),
     Deny(Deny),
 }
 
+/// A condition that has to hold at decision time for a rule to apply
+#[derive(Deserialize, Serialize, Eq, PartialEq, Clone, Debug, FromJsonQueryResult, ToSchema)]
+#[serde(tag = "type")]
+pub enum Condition {
+    TimeWindow(TimeWindow),
+    IpRange(IpRange),
+    Environment(EnvironmentAttribute),
+}
+
+/// Recurring window, e.g. weekdays 08:00-18:00 in Europe/Amsterdam. A window that ends
+/// before it starts runs over midnight.
+#[derive(Deserialize, Serialize, Eq, PartialEq, Clone, Debug, FromJsonQueryResult, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeWindow {
+    /// Days of the week ("Mon", "Tue", ...) the window applies to, every day when empty
+    #[serde(default)]
+    pub weekdays: Vec<String>,
+    /// Start of the window as "HH:MM"
+    pub start: String,
+    /// End of the window as "HH:MM"
+    pub end: String,
+    /// IANA timezone, e.g. "Europe/Amsterdam"
+    pub timezone: String,
+}
+
+/// The "ip" environment attribute of the delegation request must be in one of the ranges
+#[derive(Deserialize, Serialize, Eq, PartialEq, Clone, Debug, FromJsonQueryResult, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct IpRange {
+    /// CIDR notation, e.g. "192.168.0.0/16"
+    pub ranges: Vec<String>,
+}
+
+/// An environment attribute of the delegation request, e.g. "region", must have one of the values
+#[derive(Deserialize, Serialize, Eq, PartialEq, Clone, Debug, FromJsonQueryResult, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentAttribute {
+    pub attribute: String,
+    pub values: Vec<String>,
+}
+
 #[derive(Serialize, Deserialize, Clone, FromJsonQueryResult, Debug, PartialEq, Eq, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct Environment {
     pub service_providers: Vec<String>,
 }
+
+/// How a party authenticates with the satellite
+#[derive(Deserialize, Serialize, Eq, PartialEq, Clone, Debug, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PartyAuthentication {
+    Certificates,
+    Spor,
+}
+
+/// A predicate over the satellite registration of a party. A policy set with the access subject
+/// "*" applies to every party whose registration satisfies all of the attributes
+#[derive(
+    Deserialize, Serialize, Eq, PartialEq, Clone, Debug, Default, FromJsonQueryResult, ToSchema
+)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct PartyAttributes {
+    /// Adherence status of the party, e.g. "Active"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub adherence_status: Option<String>,
+    /// The adherence of the party may not have ended at decision time
+    #[serde(default)]
+    pub adherence_valid: bool,
+    /// How the party authenticates, "certificates" or "spor"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub authentication: Option<PartyAuthentication>,
+    /// Roles the party holds at the satellite, e.g. "ServiceProvider"
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub roles: Vec<String>,
+    /// Certifications the party holds at the satellite
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub certifications: Vec<String>,
+    /// Dataspace the party takes part in according to its agreements at the satellite
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dataspace: Option<String>,
+}
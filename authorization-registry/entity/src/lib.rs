@@ -5,7 +5,13 @@ pub mod prelude;
 pub mod company;
 pub mod delegation_evidence;
 pub mod ishare_user;
+pub mod license;
+pub mod party_group;
+pub mod party_group_member;
 pub mod policy;
+pub mod policy_issuer_setting;
 pub mod policy_set;
 pub mod policy_set_template;
+pub mod policy_set_version;
+pub mod resource_type;
 pub mod audit_event;
@@ -2,11 +2,44 @@
 
 use sea_orm::entity::prelude::*;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::delegation_evidence::PartyAttributes;
 
 fn default_created() -> DateTimeUtc {
     chrono::Utc::now()
 }
 
+fn default_revision() -> i32 {
+    1
+}
+
+// policy sets that are pending approval or rejected are never used for delegation evidence
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    EnumIter,
+    DeriveActiveEnum,
+    Serialize,
+    Deserialize,
+    ToSchema,
+)]
+#[sea_orm(rs_type = "String", db_type = "String(None)")]
+#[serde(rename_all = "snake_case")]
+pub enum PolicySetStatus {
+    #[default]
+    #[sea_orm(string_value = "active")]
+    Active,
+    #[sea_orm(string_value = "pending")]
+    Pending,
+    #[sea_orm(string_value = "rejected")]
+    Rejected,
+}
+
 #[derive(Clone, Debug, PartialEq, DeriveEntityModel, Eq, Serialize, Deserialize)]
 #[sea_orm(table_name = "policy_set")]
 pub struct Model {
@@ -21,6 +54,27 @@ pub struct Model {
     #[sea_orm(default_value = "now()")]
     #[serde(default = "default_created")]
     pub created: DateTimeUtc,
+    #[serde(default)]
+    pub not_before: Option<DateTimeUtc>,
+    #[serde(default)]
+    pub not_on_or_after: Option<DateTimeUtc>,
+    #[sea_orm(column_type = "JsonBinary", nullable)]
+    #[serde(default)]
+    pub access_subject_attributes: Option<PartyAttributes>,
+    #[sea_orm(column_type = "Text", nullable)]
+    #[serde(default)]
+    pub access_subject_user: Option<String>,
+    // incremented on every change to the policy set or its policies
+    #[sea_orm(default_value = 1)]
+    #[serde(default = "default_revision")]
+    pub revision: i32,
+    // deleted policy sets are kept until the retention period has passed, so they can be restored
+    #[serde(default)]
+    pub deleted_at: Option<DateTimeUtc>,
+    // policy sets created on behalf of an issuer that requires approval start as pending
+    #[sea_orm(default_value = "active")]
+    #[serde(default)]
+    pub status: PolicySetStatus,
 }
 
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
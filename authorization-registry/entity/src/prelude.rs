@@ -2,4 +2,10 @@
 
 pub use super::company::Entity as Company;
 pub use super::ishare_user::Entity as IshareUser;
+pub use super::license::Entity as License;
+pub use super::party_group::Entity as PartyGroup;
+pub use super::party_group_member::Entity as PartyGroupMember;
 pub use super::policy::Entity as Policy;
+pub use super::policy_issuer_setting::Entity as PolicyIssuerSetting;
+pub use super::policy_set_version::Entity as PolicySetVersion;
+pub use super::resource_type::Entity as ResourceType;
@@ -6,6 +6,17 @@ mod m20250613_125956_policy_set_template_description_column;
 mod m20250619_124921_add_audit_log_table;
 mod m20250624_113240_policy_set_creation_column;
 mod m20250728_104738_audit_log_entry;
+mod m20261017_090000_policy_set_validity_window;
+mod m20261017_100000_normalize_party_identifiers;
+mod m20261017_110000_license;
+mod m20261018_090000_party_group;
+mod m20261018_100000_access_subject_attributes;
+mod m20261018_110000_access_subject_user;
+mod m20261018_120000_resource_type;
+mod m20261018_130000_policy_set_version;
+mod m20261018_140000_policy_set_revision;
+mod m20261018_150000_policy_set_deleted_at;
+mod m20261018_160000_policy_set_approval;
 
 pub struct Migrator;
 
@@ -19,6 +30,17 @@ impl MigratorTrait for Migrator {
             Box::new(m20250619_124921_add_audit_log_table::Migration),
             Box::new(m20250624_113240_policy_set_creation_column::Migration),
             Box::new(m20250728_104738_audit_log_entry::Migration),
+            Box::new(m20261017_090000_policy_set_validity_window::Migration),
+            Box::new(m20261017_100000_normalize_party_identifiers::Migration),
+            Box::new(m20261017_110000_license::Migration),
+            Box::new(m20261018_090000_party_group::Migration),
+            Box::new(m20261018_100000_access_subject_attributes::Migration),
+            Box::new(m20261018_110000_access_subject_user::Migration),
+            Box::new(m20261018_120000_resource_type::Migration),
+            Box::new(m20261018_130000_policy_set_version::Migration),
+            Box::new(m20261018_140000_policy_set_revision::Migration),
+            Box::new(m20261018_150000_policy_set_deleted_at::Migration),
+            Box::new(m20261018_160000_policy_set_approval::Migration),
         ]
     }
 }
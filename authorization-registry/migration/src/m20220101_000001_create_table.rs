@@ -32,7 +32,7 @@ pub enum PolicySet {
 }
 
 #[derive(DeriveIden)]
-enum Policy {
+pub enum Policy {
     Table,
     Id,
     Identifiers,
@@ -0,0 +1,144 @@
+use sea_orm_migration::prelude::*;
+
+use crate::m20220101_000001_create_table::{Policy, PolicySet};
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+const POLICY_SET_PARTIES_INDEX: &str = "idx_policy_set_access_subject_policy_issuer";
+const POLICY_SET_ISSUER_INDEX: &str = "idx_policy_set_policy_issuer";
+const POLICY_POLICY_SET_INDEX: &str = "idx_policy_policy_set";
+
+// the original party identifiers of the rows that were normalized, to restore them on down
+const POLICY_SET_ORIGINAL_TABLE: &str = "policy_set_original_party_identifiers";
+const POLICY_ORIGINAL_TABLE: &str = "policy_original_service_providers";
+
+// eori shaped identifiers like 'EU.EORI.NL1' and party groups are case insensitive and brought
+// into upper case, other identifiers like DIDs are case sensitive and only trimmed
+fn normalized(party_id: &str) -> String {
+    format!(
+        "(case when trim({0}) ~* '^([a-z]{{2}}\\.|group:)' then upper(trim({0})) else trim({0}) end)",
+        party_id
+    )
+}
+
+fn normalized_service_providers() -> String {
+    format!(
+        "array(select {} from unnest(service_providers) with ordinality as s(sp, n) order by n)",
+        normalized("sp")
+    )
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // party identifiers are matched exactly, so existing rows are brought into the same
+        // canonical form that is applied on write. the original identifiers are kept
+        manager
+            .get_connection()
+            .execute_unprepared(&format!(
+                r#"
+                    create table {policy_set_original} as
+                    select id, policy_issuer, access_subject
+                    from policy_set
+                    where
+                        policy_issuer <> {policy_issuer}
+                        or access_subject <> {access_subject};
+
+                    update policy_set
+                    set
+                        policy_issuer = {policy_issuer},
+                        access_subject = {access_subject}
+                    where id in (select id from {policy_set_original});
+
+                    create table {policy_original} as
+                    select id, service_providers
+                    from policy
+                    where service_providers <> {service_providers};
+
+                    update policy
+                    set service_providers = {service_providers}
+                    where id in (select id from {policy_original});
+                "#,
+                policy_set_original = POLICY_SET_ORIGINAL_TABLE,
+                policy_original = POLICY_ORIGINAL_TABLE,
+                policy_issuer = normalized("policy_issuer"),
+                access_subject = normalized("access_subject"),
+                service_providers = normalized_service_providers(),
+            ))
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(POLICY_SET_PARTIES_INDEX)
+                    .table(PolicySet::Table)
+                    .col(PolicySet::AccessSubject)
+                    .col(PolicySet::PolicyIssuer)
+                    .to_owned(),
+            )
+            .await?;
+
+        // delegation chains are followed by policy issuer
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(POLICY_SET_ISSUER_INDEX)
+                    .table(PolicySet::Table)
+                    .col(PolicySet::PolicyIssuer)
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_index(
+                Index::create()
+                    .if_not_exists()
+                    .name(POLICY_POLICY_SET_INDEX)
+                    .table(Policy::Table)
+                    .col(Policy::PolicySet)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        for (name, table) in [
+            (POLICY_POLICY_SET_INDEX, Policy::Table.into_iden()),
+            (POLICY_SET_ISSUER_INDEX, PolicySet::Table.into_iden()),
+            (POLICY_SET_PARTIES_INDEX, PolicySet::Table.into_iden()),
+        ] {
+            manager
+                .drop_index(Index::drop().name(name).table(table).to_owned())
+                .await?;
+        }
+
+        manager
+            .get_connection()
+            .execute_unprepared(&format!(
+                r#"
+                    update policy_set ps
+                    set
+                        policy_issuer = o.policy_issuer,
+                        access_subject = o.access_subject
+                    from {policy_set_original} o
+                    where ps.id = o.id;
+
+                    update policy p
+                    set service_providers = o.service_providers
+                    from {policy_original} o
+                    where p.id = o.id;
+
+                    drop table {policy_set_original};
+                    drop table {policy_original};
+                "#,
+                policy_set_original = POLICY_SET_ORIGINAL_TABLE,
+                policy_original = POLICY_ORIGINAL_TABLE,
+            ))
+            .await?;
+
+        Ok(())
+    }
+}
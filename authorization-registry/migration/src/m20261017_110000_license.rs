@@ -0,0 +1,53 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum License {
+    Table,
+    Code,
+    Description,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(License::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(License::Code)
+                            .text()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(License::Description).text().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        // the licenses that existing policy sets already grant are the initial registry
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                    insert into license (code, description)
+                    select distinct unnest(licenses), 'Granted by policy sets created before the license registry'
+                    from policy_set
+                    on conflict (code) do nothing
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(License::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,83 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum PartyGroup {
+    Table,
+    Name,
+    Description,
+}
+
+#[derive(DeriveIden)]
+pub enum PartyGroupMember {
+    Table,
+    GroupName,
+    PartyId,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(PartyGroup::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PartyGroup::Name)
+                            .text()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(PartyGroup::Description).text().not_null())
+                    .to_owned(),
+            )
+            .await?;
+
+        manager
+            .create_table(
+                Table::create()
+                    .table(PartyGroupMember::Table)
+                    .if_not_exists()
+                    .col(ColumnDef::new(PartyGroupMember::GroupName).text().not_null())
+                    .col(ColumnDef::new(PartyGroupMember::PartyId).text().not_null())
+                    .primary_key(
+                        Index::create()
+                            .col(PartyGroupMember::GroupName)
+                            .col(PartyGroupMember::PartyId),
+                    )
+                    .foreign_key(
+                        ForeignKey::create()
+                            .name("fk-party_group_member-party_group")
+                            .from(PartyGroupMember::Table, PartyGroupMember::GroupName)
+                            .to(PartyGroup::Table, PartyGroup::Name)
+                            .on_delete(ForeignKeyAction::Cascade),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // memberships are looked up by party when delegation evidence is created
+        manager
+            .create_index(
+                Index::create()
+                    .name("idx-party_group_member-party_id")
+                    .table(PartyGroupMember::Table)
+                    .col(PartyGroupMember::PartyId)
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PartyGroupMember::Table).to_owned())
+            .await?;
+
+        manager
+            .drop_table(Table::drop().table(PartyGroup::Table).to_owned())
+            .await
+    }
+}
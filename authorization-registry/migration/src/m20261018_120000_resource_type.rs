@@ -0,0 +1,52 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum ResourceType {
+    Table,
+    Name,
+    Description,
+    Actions,
+    Attributes,
+    IdentifierFormat,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .create_table(
+                Table::create()
+                    .table(ResourceType::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(ResourceType::Name)
+                            .text()
+                            .not_null()
+                            .primary_key(),
+                    )
+                    .col(ColumnDef::new(ResourceType::Description).text().not_null())
+                    .col(
+                        ColumnDef::new(ResourceType::Actions)
+                            .array(ColumnType::Text)
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(ResourceType::Attributes)
+                            .array(ColumnType::Text)
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(ResourceType::IdentifierFormat).text().null())
+                    .to_owned(),
+            )
+            .await
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(ResourceType::Table).to_owned())
+            .await
+    }
+}
@@ -0,0 +1,107 @@
+use sea_orm_migration::prelude::*;
+
+#[derive(DeriveMigrationName)]
+pub struct Migration;
+
+#[derive(DeriveIden)]
+pub enum PolicySetVersion {
+    Table,
+    PolicySetId,
+    Version,
+    Created,
+    Change,
+    Snapshot,
+}
+
+#[async_trait::async_trait]
+impl MigrationTrait for Migration {
+    async fn up(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        // versions are kept when their policy set is deleted, so there is no foreign key
+        manager
+            .create_table(
+                Table::create()
+                    .table(PolicySetVersion::Table)
+                    .if_not_exists()
+                    .col(
+                        ColumnDef::new(PolicySetVersion::PolicySetId)
+                            .uuid()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PolicySetVersion::Version)
+                            .integer()
+                            .not_null(),
+                    )
+                    .col(
+                        ColumnDef::new(PolicySetVersion::Created)
+                            .timestamp_with_time_zone()
+                            .not_null(),
+                    )
+                    .col(ColumnDef::new(PolicySetVersion::Change).text().not_null())
+                    .col(
+                        ColumnDef::new(PolicySetVersion::Snapshot)
+                            .json_binary()
+                            .not_null(),
+                    )
+                    .primary_key(
+                        Index::create()
+                            .col(PolicySetVersion::PolicySetId)
+                            .col(PolicySetVersion::Version),
+                    )
+                    .to_owned(),
+            )
+            .await?;
+
+        // the current state of existing policy sets becomes their first version
+        manager
+            .get_connection()
+            .execute_unprepared(
+                r#"
+                    insert into policy_set_version (policy_set_id, version, created, change, snapshot)
+                    select
+                        ps.id,
+                        1,
+                        ps.created,
+                        'created',
+                        json_build_object(
+                            'policy_set_id', ps.id,
+                            'access_subject', ps.access_subject,
+                            'policy_issuer', ps.policy_issuer,
+                            'licenses', ps.licenses,
+                            'max_delegation_depth', ps.max_delegation_depth,
+                            'not_before', ps.not_before,
+                            'not_on_or_after', ps.not_on_or_after,
+                            'created', ps.created,
+                            'access_subject_attributes', ps.access_subject_attributes,
+                            'access_subject_user', ps.access_subject_user,
+                            'policies', coalesce(
+                                json_agg(
+                                    json_build_object(
+                                        'id', p.id,
+                                        'identifiers', p.identifiers,
+                                        'attributes', p.attributes,
+                                        'actions', p.actions,
+                                        'service_providers', p.service_providers,
+                                        'resource_type', p.resource_type,
+                                        'rules', p.rules
+                                    )
+                                ) filter (where p.id is not null),
+                                '[]'
+                            )
+                        )
+                    from policy_set ps
+                    left join policy p on p.policy_set = ps.id
+                    group by ps.id;
+                "#,
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn down(&self, manager: &SchemaManager) -> Result<(), DbErr> {
+        manager
+            .drop_table(Table::drop().table(PolicySetVersion::Table).to_owned())
+            .await
+    }
+}
@@ -1,5 +1,10 @@
+use std::collections::HashMap;
+
 use ishare::ishare::AllowedDataspaces;
 use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::is_same_party;
 
 fn default_listen_address() -> String {
     "0.0.0.0:4000".to_string()
@@ -13,6 +18,18 @@ fn default_de_expiry_seconds() -> i64 {
     3600
 }
 
+fn default_delegation_cache_ttl_seconds() -> i64 {
+    60
+}
+
+fn default_party_registration_cache_ttl_seconds() -> i64 {
+    300
+}
+
+fn default_previous_step_max_age_seconds() -> i64 {
+    30
+}
+
 fn default_deploy_route() -> String {
     "/api".to_owned()
 }
@@ -29,6 +46,14 @@ fn default_delegation_allows_service_providers() -> bool {
     true
 }
 
+fn default_deleted_policy_set_retention_days() -> i64 {
+    30
+}
+
+fn default_rejected_policy_set_retention_days() -> i64 {
+    30
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct NavigationConfig {
     pub passport: String,
@@ -82,6 +107,38 @@ fn default_service_name() -> String {
     "Dexes Authorization Registry".to_owned()
 }
 
+// how the outcomes of several delegation chains that match the same requested policy set are
+// merged into a single evidence policy set
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum CombiningAlgorithm {
+    // a requested policy is denied when any of the matching chains denies it
+    DenyOverrides,
+    // a requested policy is permitted when any of the matching chains permits it
+    PermitOverrides,
+    // the matching chain with the oldest policy set of the policy issuer decides
+    FirstApplicable,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct CombiningAlgorithmConfig {
+    // without a default every matching policy set results in its own evidence policy set
+    #[serde(default)]
+    pub default: Option<CombiningAlgorithm>,
+    #[serde(default)]
+    pub policy_issuers: HashMap<String, CombiningAlgorithm>,
+}
+
+impl CombiningAlgorithmConfig {
+    pub fn for_policy_issuer(&self, policy_issuer: &str) -> Option<CombiningAlgorithm> {
+        self.policy_issuers
+            .iter()
+            .find(|(pi, _)| is_same_party(pi, policy_issuer))
+            .map(|(_, algorithm)| *algorithm)
+            .or(self.default)
+    }
+}
+
 #[derive(Deserialize, Clone, Debug)]
 pub struct Config {
     pub frontend: FrontendConfig,
@@ -102,6 +159,19 @@ pub struct Config {
     pub listen_address: String,
     #[serde(default = "default_de_expiry_seconds")]
     pub de_expiry_seconds: i64,
+    // decisions are cached and invalidated per process. set to 0 to disable the cache when more
+    // than one instance serves the same database, other instances never see the invalidations
+    #[serde(default = "default_delegation_cache_ttl_seconds")]
+    pub delegation_cache_ttl_seconds: i64,
+    // how long the roles, certifications and dataspaces of a party at the satellite are reused.
+    // set to 0 to fetch them for every delegation request that depends on them
+    #[serde(default = "default_party_registration_cache_ttl_seconds")]
+    pub party_registration_cache_ttl_seconds: i64,
+    // the used previous steps are remembered per process, so replays are only detected when a
+    // single instance serves delegation requests. with more instances a previous step can be
+    // replayed once on every instance until it is older than this
+    #[serde(default = "default_previous_step_max_age_seconds")]
+    pub previous_step_max_age_seconds: i64,
     #[serde(default = "default_deploy_route")]
     pub deploy_route: String,
     pub seed_folder: Option<String>,
@@ -111,9 +181,22 @@ pub struct Config {
     pub validate_m2m_certificate: bool,
     #[serde(default = "default_delegation_allows_service_providers")]
     pub delegation_allows_service_providers: bool,
+    #[serde(default)]
+    pub combining_algorithm: CombiningAlgorithmConfig,
+    // address ranges of the reverse proxies whose X-Forwarded-For header is trusted for the 'ip'
+    // attribute of the delegation request environment
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
     pub dataspace_config: Option<AllowedDataspaces>,
     #[serde(default = "default_service_name")]
     pub service_name: String,
+    // deleted policy sets can be restored until they have been deleted for this many days
+    #[serde(default = "default_deleted_policy_set_retention_days")]
+    pub deleted_policy_set_retention_days: i64,
+    // rejected policy sets are listed apart from the current ones until they have been rejected for
+    // this many days
+    #[serde(default = "default_rejected_policy_set_retention_days")]
+    pub rejected_policy_set_retention_days: i64,
 }
 
 pub fn read_config(path: String) -> Config {
@@ -0,0 +1,110 @@
+use anyhow::Context;
+use ar_entity::license::ActiveModel as ActiveLicense;
+use ar_entity::license::Entity as License;
+use ar_entity::license::Model as LicenseModel;
+use sea_orm::{entity::*, query::*, sea_query::Expr, ActiveValue, ConnectionTrait, EntityTrait};
+
+pub async fn get_all_licenses<T: ConnectionTrait>(db: &T) -> anyhow::Result<Vec<LicenseModel>> {
+    let licenses = License::find()
+        .order_by_asc(ar_entity::license::Column::Code)
+        .all(db)
+        .await
+        .context("Error retrieving licenses from db")?;
+
+    Ok(licenses)
+}
+
+pub async fn get_license_by_code<T: ConnectionTrait>(
+    code: &str,
+    db: &T,
+) -> anyhow::Result<Option<LicenseModel>> {
+    let license = License::find_by_id(code.to_owned())
+        .one(db)
+        .await
+        .context(format!(
+            "Error retrieving license from db with code '{}'",
+            code
+        ))?;
+
+    Ok(license)
+}
+
+// returns the codes that are not in the license registry
+pub async fn get_unknown_licenses<T: ConnectionTrait>(
+    codes: &Vec<String>,
+    db: &T,
+) -> anyhow::Result<Vec<String>> {
+    if codes.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let known: Vec<String> = License::find()
+        .filter(ar_entity::license::Column::Code.is_in(codes.clone()))
+        .all(db)
+        .await
+        .context("Error retrieving licenses from db")?
+        .into_iter()
+        .map(|l| l.code)
+        .collect();
+
+    Ok(codes
+        .iter()
+        .filter(|c| !known.contains(c))
+        .cloned()
+        .collect())
+}
+
+pub async fn insert_license<T: ConnectionTrait>(
+    code: &str,
+    description: &str,
+    db: &T,
+) -> anyhow::Result<String> {
+    let active_model = ActiveLicense {
+        code: ActiveValue::set(code.to_owned()),
+        description: ActiveValue::set(description.to_owned()),
+    };
+
+    let code = License::insert(active_model)
+        .exec(db)
+        .await
+        .context(format!(
+            "Error inserting license into db with code '{}'",
+            code
+        ))?
+        .last_insert_id;
+
+    Ok(code)
+}
+
+// the number of stored policy sets, including the ones in the trash, that grant the license
+pub async fn get_policy_set_count_with_license<T: ConnectionTrait>(
+    code: &str,
+    db: &T,
+) -> anyhow::Result<u64> {
+    let count = ar_entity::policy_set::Entity::find()
+        .filter(Expr::cust_with_values(
+            "$1 = any(licenses)",
+            [code.to_owned()],
+        ))
+        .count(db)
+        .await
+        .context(format!(
+            "Error counting policy sets with license '{}' in db",
+            code
+        ))?;
+
+    Ok(count)
+}
+
+pub async fn delete_license<T: ConnectionTrait>(code: &str, db: &T) -> anyhow::Result<()> {
+    tracing::info!("Deleting license with code: {}", code);
+    License::delete_by_id(code.to_owned())
+        .exec(db)
+        .await
+        .context(format!(
+            "Error deleting license from db with code '{}'",
+            code
+        ))?;
+
+    Ok(())
+}
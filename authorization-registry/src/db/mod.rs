@@ -1,4 +1,9 @@
 pub mod company;
+pub mod license;
+pub mod party_group;
 pub mod policy;
+pub mod policy_issuer_setting;
 pub mod policy_set_template;
+pub mod policy_version;
+pub mod resource_type;
 pub mod user;
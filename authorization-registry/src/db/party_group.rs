@@ -0,0 +1,197 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use ar_entity::party_group::ActiveModel as ActivePartyGroup;
+use ar_entity::party_group::Entity as PartyGroup;
+use ar_entity::party_group::Model as PartyGroupModel;
+use ar_entity::party_group_member::ActiveModel as ActivePartyGroupMember;
+use ar_entity::party_group_member::Entity as PartyGroupMember;
+use sea_orm::{entity::*, query::*, ActiveValue, ConnectionTrait, EntityTrait};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PartyGroupWithMembers {
+    pub name: String,
+    pub description: String,
+    pub members: Vec<String>,
+}
+
+pub async fn get_all_party_groups<T: ConnectionTrait>(
+    db: &T,
+) -> anyhow::Result<Vec<PartyGroupWithMembers>> {
+    let groups = PartyGroup::find()
+        .order_by_asc(ar_entity::party_group::Column::Name)
+        .find_with_related(PartyGroupMember)
+        .all(db)
+        .await
+        .context("Error retrieving party groups from db")?;
+
+    Ok(groups
+        .into_iter()
+        .map(|(group, members)| PartyGroupWithMembers {
+            name: group.name,
+            description: group.description,
+            members: members.into_iter().map(|m| m.party_id).collect(),
+        })
+        .collect())
+}
+
+pub async fn get_party_group_by_name<T: ConnectionTrait>(
+    name: &str,
+    db: &T,
+) -> anyhow::Result<Option<PartyGroupModel>> {
+    let group = PartyGroup::find_by_id(name.to_owned())
+        .one(db)
+        .await
+        .context(format!(
+            "Error retrieving party group from db with name '{}'",
+            name
+        ))?;
+
+    Ok(group)
+}
+
+// the members of the party groups by the name of the group
+pub async fn get_members_of_party_groups<T: ConnectionTrait>(
+    names: &Vec<String>,
+    db: &T,
+) -> anyhow::Result<HashMap<String, Vec<String>>> {
+    if names.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let members = PartyGroupMember::find()
+        .filter(ar_entity::party_group_member::Column::GroupName.is_in(names.clone()))
+        .all(db)
+        .await
+        .context("Error retrieving members of party groups from db")?;
+
+    let mut groups: HashMap<String, Vec<String>> = HashMap::new();
+    for member in members.into_iter() {
+        groups
+            .entry(member.group_name)
+            .or_default()
+            .push(member.party_id);
+    }
+
+    Ok(groups)
+}
+
+pub async fn get_party_group_members<T: ConnectionTrait>(
+    name: &str,
+    db: &T,
+) -> anyhow::Result<Vec<String>> {
+    let members = PartyGroupMember::find()
+        .filter(ar_entity::party_group_member::Column::GroupName.eq(name))
+        .order_by_asc(ar_entity::party_group_member::Column::PartyId)
+        .all(db)
+        .await
+        .context(format!(
+            "Error retrieving members of party group '{}' from db",
+            name
+        ))?;
+
+    Ok(members.into_iter().map(|m| m.party_id).collect())
+}
+
+// returns the names that are not party groups
+pub async fn get_unknown_party_groups<T: ConnectionTrait>(
+    names: &Vec<String>,
+    db: &T,
+) -> anyhow::Result<Vec<String>> {
+    if names.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let known: Vec<String> = PartyGroup::find()
+        .filter(ar_entity::party_group::Column::Name.is_in(names.clone()))
+        .all(db)
+        .await
+        .context("Error retrieving party groups from db")?
+        .into_iter()
+        .map(|g| g.name)
+        .collect();
+
+    Ok(names
+        .iter()
+        .filter(|n| !known.contains(n))
+        .cloned()
+        .collect())
+}
+
+pub async fn insert_party_group<T: ConnectionTrait>(
+    name: &str,
+    description: &str,
+    db: &T,
+) -> anyhow::Result<()> {
+    let active_model = ActivePartyGroup {
+        name: ActiveValue::set(name.to_owned()),
+        description: ActiveValue::set(description.to_owned()),
+    };
+
+    PartyGroup::insert(active_model)
+        .exec(db)
+        .await
+        .context(format!(
+            "Error inserting party group into db with name '{}'",
+            name
+        ))?;
+
+    Ok(())
+}
+
+pub async fn delete_party_group<T: ConnectionTrait>(name: &str, db: &T) -> anyhow::Result<()> {
+    tracing::info!("Deleting party group with name: {}", name);
+    PartyGroup::delete_by_id(name.to_owned())
+        .exec(db)
+        .await
+        .context(format!(
+            "Error deleting party group from db with name '{}'",
+            name
+        ))?;
+
+    Ok(())
+}
+
+pub async fn insert_party_group_members<T: ConnectionTrait>(
+    name: &str,
+    party_ids: &Vec<String>,
+    db: &T,
+) -> anyhow::Result<()> {
+    if party_ids.is_empty() {
+        return Ok(());
+    }
+
+    let members = party_ids.iter().map(|party_id| ActivePartyGroupMember {
+        group_name: ActiveValue::set(name.to_owned()),
+        party_id: ActiveValue::set(party_id.to_owned()),
+    });
+
+    PartyGroupMember::insert_many(members)
+        .exec(db)
+        .await
+        .context(format!(
+            "Error inserting members of party group '{}' into db",
+            name
+        ))?;
+
+    Ok(())
+}
+
+pub async fn delete_party_group_member<T: ConnectionTrait>(
+    name: &str,
+    party_id: &str,
+    db: &T,
+) -> anyhow::Result<u64> {
+    let result = PartyGroupMember::delete_by_id((name.to_owned(), party_id.to_owned()))
+        .exec(db)
+        .await
+        .context(format!(
+            "Error deleting member '{}' of party group '{}' from db",
+            party_id, name
+        ))?;
+
+    Ok(result.rows_affected)
+}
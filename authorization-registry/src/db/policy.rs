@@ -1,15 +1,19 @@
 use anyhow::{bail, Context};
-use ar_entity::delegation_evidence::{Policy, ResourceRule};
+use ar_entity::delegation_evidence::{PartyAttributes, Policy, ResourceRule};
+use ar_entity::policy_set::PolicySetStatus;
 use chrono::Utc;
+use sea_orm::sea_query::Expr;
 use sea_orm::{self, ConnectionTrait, QueryFilter, TransactionTrait};
 use sea_orm::{
-    entity::*, DatabaseConnection, EntityTrait, FromJsonQueryResult, FromQueryResult, JsonValue,
-    Statement,
+    entity::*, DatabaseConnection, DatabaseTransaction, EntityTrait, FromJsonQueryResult,
+    FromQueryResult, JsonValue, Statement,
 };
 use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::utils::{normalize_party_id, normalize_party_ids, PARTY_GROUP_PREFIX};
+
 pub async fn get_policy(
     policy_set_id: Uuid,
     policy_id: Uuid,
@@ -72,7 +76,7 @@ pub async fn get_policies_by_policy_set(
     Ok(policies)
 }
 
-#[derive(FromJsonQueryResult, Serialize, Deserialize, Debug, ToSchema)]
+#[derive(FromJsonQueryResult, Serialize, Deserialize, Debug, Clone, ToSchema)]
 pub struct DelegationEvidencePolicy {
     pub id: Uuid,
     pub identifiers: Vec<String>,
@@ -81,9 +85,31 @@ pub struct DelegationEvidencePolicy {
     pub actions: Vec<String>,
     pub service_providers: Vec<String>,
     pub rules: Vec<ResourceRule>,
+    // the members of the party groups among the service providers
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub service_provider_members: Vec<String>,
 }
 
-#[derive(Deserialize, Serialize, Debug, FromQueryResult, ToSchema)]
+impl DelegationEvidencePolicy {
+    pub fn to_policy(&self) -> Policy {
+        Policy {
+            target: ar_entity::delegation_evidence::ResourceTarget {
+                resource: ar_entity::delegation_evidence::Resource {
+                    resource_type: self.resource_type.clone(),
+                    identifiers: self.identifiers.clone(),
+                    attributes: self.attributes.clone(),
+                },
+                actions: self.actions.clone(),
+                environment: ar_entity::delegation_evidence::Environment {
+                    service_providers: self.service_providers.clone(),
+                },
+            },
+            rules: self.rules.clone(),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, FromQueryResult, ToSchema)]
 pub struct MatchingPolicySetRow {
     pub policy_set_id: Uuid,
     pub access_subject: String,
@@ -91,6 +117,31 @@ pub struct MatchingPolicySetRow {
     pub policies: Vec<DelegationEvidencePolicy>,
     pub licenses: Vec<String>,
     pub max_delegation_depth: i32,
+    #[serde(default)]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub not_before: Option<chrono::DateTime<Utc>>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub not_on_or_after: Option<chrono::DateTime<Utc>>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub created: Option<chrono::DateTime<Utc>>,
+    // the members of the party group when the access subject is one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub access_subject_members: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_subject_attributes: Option<PartyAttributes>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_subject_user: Option<String>,
+    // not known for policy sets that aren't stored
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<PolicySetStatus>,
+    // only set in listings of deleted policy sets
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub deleted_at: Option<chrono::DateTime<Utc>>,
 }
 
 #[derive(Deserialize, Serialize, Debug, ToSchema)]
@@ -109,22 +160,68 @@ struct Count {
     count: i64,
 }
 
+// how the access subject and policy issuer filters of a policy set listing are applied
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PartyMatch {
+    // the normalized party identifier has to be equal
+    Exact,
+    // the party identifier only has to contain the filter, ignoring case
+    Fuzzy,
+}
+
+fn party_condition(
+    column: &str,
+    party_id: &str,
+    party_match: PartyMatch,
+    values: &mut Vec<Value>,
+) -> String {
+    match party_match {
+        PartyMatch::Exact => {
+            values.push(normalize_party_id(party_id).into());
+            format!("{} = ${}", column, values.len())
+        }
+        PartyMatch::Fuzzy => {
+            values.push(format!("%{}%", party_id.trim()).into());
+            format!("{} ilike ${}", column, values.len())
+        }
+    }
+}
+
+// the policy sets a listing is limited to
+#[derive(Clone, Copy)]
+pub enum PolicySetListing {
+    Current,
+    Deleted,
+    PendingApproval,
+    Rejected,
+}
+
 fn build_policy_set_condition(
     access_subject: Option<String>,
     policy_issuer: Option<String>,
+    party_match: PartyMatch,
     q: Option<String>,
+    listing: PolicySetListing,
     values: &mut Vec<Value>,
 ) -> String {
     let mut conditions = Vec::new();
 
     if let Some(access_subject) = access_subject {
-        conditions.push(format!("access_subject like ${}", values.len() + 1));
-        values.push(format!("%{}%", &access_subject).into());
+        conditions.push(party_condition(
+            "access_subject",
+            &access_subject,
+            party_match,
+            values,
+        ));
     }
 
     if let Some(policy_issuer) = policy_issuer {
-        conditions.push(format!("policy_issuer like ${}", values.len() + 1));
-        values.push(format!("%{}%", &policy_issuer).into());
+        conditions.push(party_condition(
+            "policy_issuer",
+            &policy_issuer,
+            party_match,
+            values,
+        ));
     }
 
     let condition = if conditions.len() > 0 {
@@ -152,7 +249,16 @@ fn build_policy_set_condition(
         None => "".to_string(),
     };
 
-    let non_empty_conditions: Vec<String> = [condition, query_condition]
+    let listing_condition = match listing {
+        PolicySetListing::Current => "ps.deleted_at is null and ps.status <> 'rejected'".to_owned(),
+        PolicySetListing::Deleted => "ps.deleted_at is not null".to_owned(),
+        PolicySetListing::PendingApproval => {
+            "ps.deleted_at is null and ps.status = 'pending'".to_owned()
+        }
+        PolicySetListing::Rejected => "ps.deleted_at is null and ps.status = 'rejected'".to_owned(),
+    };
+
+    let non_empty_conditions: Vec<String> = [condition, query_condition, listing_condition]
         .into_iter()
         .filter(|q| q.len() > 0)
         .collect();
@@ -169,12 +275,20 @@ fn build_policy_set_condition(
 pub async fn get_total_number_of_policy_sets(
     access_subject: Option<String>,
     policy_issuer: Option<String>,
+    party_match: PartyMatch,
     q: Option<String>,
+    listing: PolicySetListing,
     db: &DatabaseConnection,
 ) -> anyhow::Result<i64> {
     let mut values = Vec::new();
-    let joined_condition =
-        build_policy_set_condition(access_subject, policy_issuer, q, &mut values);
+    let joined_condition = build_policy_set_condition(
+        access_subject,
+        policy_issuer,
+        party_match,
+        q,
+        listing,
+        &mut values,
+    );
 
     let sql = format!(
         r#"
@@ -201,7 +315,100 @@ pub async fn get_total_number_of_policy_sets(
 pub async fn get_policy_sets_with_policies(
     access_subject: Option<String>,
     policy_issuer: Option<String>,
+    party_match: PartyMatch,
+    q: Option<String>,
+    skip: Option<u32>,
+    limit: Option<u32>,
+    db: &DatabaseConnection,
+) -> anyhow::Result<PolicySetsWithPagination> {
+    query_policy_sets_with_policies(
+        access_subject,
+        policy_issuer,
+        party_match,
+        q,
+        PolicySetListing::Current,
+        skip,
+        limit,
+        db,
+    )
+    .await
+}
+
+// the deleted policy sets that haven't been purged yet, most recently created first
+pub async fn get_deleted_policy_sets_with_policies(
+    access_subject: Option<String>,
+    policy_issuer: Option<String>,
+    party_match: PartyMatch,
+    q: Option<String>,
+    skip: Option<u32>,
+    limit: Option<u32>,
+    db: &DatabaseConnection,
+) -> anyhow::Result<PolicySetsWithPagination> {
+    query_policy_sets_with_policies(
+        access_subject,
+        policy_issuer,
+        party_match,
+        q,
+        PolicySetListing::Deleted,
+        skip,
+        limit,
+        db,
+    )
+    .await
+}
+
+// the policy sets that were created on behalf of the policy issuer and wait for its approval
+pub async fn get_pending_policy_sets_with_policies(
+    access_subject: Option<String>,
+    policy_issuer: Option<String>,
+    party_match: PartyMatch,
+    q: Option<String>,
+    skip: Option<u32>,
+    limit: Option<u32>,
+    db: &DatabaseConnection,
+) -> anyhow::Result<PolicySetsWithPagination> {
+    query_policy_sets_with_policies(
+        access_subject,
+        policy_issuer,
+        party_match,
+        q,
+        PolicySetListing::PendingApproval,
+        skip,
+        limit,
+        db,
+    )
+    .await
+}
+
+// the policy sets the policy issuer rejected that haven't been purged yet
+pub async fn get_rejected_policy_sets_with_policies(
+    access_subject: Option<String>,
+    policy_issuer: Option<String>,
+    party_match: PartyMatch,
+    q: Option<String>,
+    skip: Option<u32>,
+    limit: Option<u32>,
+    db: &DatabaseConnection,
+) -> anyhow::Result<PolicySetsWithPagination> {
+    query_policy_sets_with_policies(
+        access_subject,
+        policy_issuer,
+        party_match,
+        q,
+        PolicySetListing::Rejected,
+        skip,
+        limit,
+        db,
+    )
+    .await
+}
+
+async fn query_policy_sets_with_policies(
+    access_subject: Option<String>,
+    policy_issuer: Option<String>,
+    party_match: PartyMatch,
     q: Option<String>,
+    listing: PolicySetListing,
     skip: Option<u32>,
     limit: Option<u32>,
     db: &DatabaseConnection,
@@ -211,7 +418,9 @@ pub async fn get_policy_sets_with_policies(
     let joined_condition = build_policy_set_condition(
         access_subject.clone(),
         policy_issuer.clone(),
+        party_match,
         q.clone(),
+        listing,
         &mut values,
     );
 
@@ -237,6 +446,14 @@ pub async fn get_policy_sets_with_policies(
             ps.policy_issuer as policy_issuer,
             ps.licenses as licenses,
             ps.max_delegation_depth as max_delegation_depth,
+            ps.not_before as not_before,
+            ps.not_on_or_after as not_on_or_after,
+            ps.created as created,
+            ps.access_subject_attributes as access_subject_attributes,
+            ps.access_subject_user as access_subject_user,
+            ps.revision as revision,
+            ps.status as status,
+            ps.deleted_at as deleted_at,
             coalesce(
                 array_agg(
                     json_build_object(
@@ -291,9 +508,10 @@ pub async fn get_policy_sets_with_policies(
     let policy_sets = policy_sets_parse_result
         .context("Error parsing policy sets 'QueryResult' into 'MatchingPolicySetRow'")?;
 
-    let total_count = get_total_number_of_policy_sets(access_subject, policy_issuer, q, db)
-        .await
-        .context("Error getting total number of policy sets")?;
+    let total_count =
+        get_total_number_of_policy_sets(access_subject, policy_issuer, party_match, q, listing, db)
+            .await
+            .context("Error getting total number of policy sets")?;
 
     Ok(PolicySetsWithPagination {
         data: policy_sets,
@@ -301,7 +519,42 @@ pub async fn get_policy_sets_with_policies(
     })
 }
 
+// policy sets and policies refer to a party group as 'GROUP:<name>', the members of the groups are
+// selected with them so that they can be matched against the parties of a delegation request
+fn access_subject_members_sql() -> String {
+    format!(
+        "array(select m.party_id from party_group_member m where ps.access_subject = '{}' || m.group_name)",
+        PARTY_GROUP_PREFIX
+    )
+}
+
+fn service_provider_members_sql() -> String {
+    format!(
+        "array(select m.party_id from party_group_member m where '{}' || m.group_name = any(p.service_providers))",
+        PARTY_GROUP_PREFIX
+    )
+}
+
+// the access subject is the party or a party group the party is a member of. policy sets with
+// access subject attributes are matched against the satellite registration of the party afterwards
+fn access_subject_or_group_condition(column: &str, parameter_index: usize) -> String {
+    format!(
+        "({0} = ${1} or {0} in (select '{2}' || m.group_name from party_group_member m where m.party_id = ${1}) or {0}_attributes is not null)",
+        column, parameter_index, PARTY_GROUP_PREFIX
+    )
+}
+
+// deleted policy sets, policy sets that aren't active and policy sets outside their validity window
+// are never used for evidence
+fn in_effect_condition(parameter_index: usize) -> String {
+    format!(
+        "ps.deleted_at is null and ps.status = 'active' and (ps.not_before is null or ps.not_before <= ${0}) and (ps.not_on_or_after is null or ps.not_on_or_after > ${0})",
+        parameter_index
+    )
+}
+
 pub async fn get_policy_sets_with_policies_for_creating_de(
+    now: chrono::DateTime<Utc>,
     access_subject: String,
     policy_issuer: String,
     db: &DatabaseConnection,
@@ -309,11 +562,21 @@ pub async fn get_policy_sets_with_policies_for_creating_de(
     let mut values: Vec<Value> = Vec::new();
     let mut conditions = Vec::new();
 
-    conditions.push(format!("access_subject like ${}", values.len() + 1));
-    values.push(format!("%{}%", &access_subject).into());
+    values.push(normalize_party_id(&access_subject).into());
+    conditions.push(access_subject_or_group_condition(
+        "access_subject",
+        values.len(),
+    ));
+
+    conditions.push(party_condition(
+        "policy_issuer",
+        &policy_issuer,
+        PartyMatch::Exact,
+        &mut values,
+    ));
 
-    conditions.push(format!("policy_issuer like ${}", values.len() + 1));
-    values.push(format!("%{}%", &policy_issuer).into());
+    conditions.push(in_effect_condition(values.len() + 1));
+    values.push(now.into());
 
     let condition = if conditions.len() > 0 {
         let joined_conditions: String = conditions.join(" and ");
@@ -330,6 +593,14 @@ pub async fn get_policy_sets_with_policies_for_creating_de(
                 ps.policy_issuer as policy_issuer,
                 ps.licenses as licenses,
                 ps.max_delegation_depth as max_delegation_depth,
+                ps.not_before as not_before,
+                ps.not_on_or_after as not_on_or_after,
+                ps.created as created,
+                ps.access_subject_attributes as access_subject_attributes,
+                ps.access_subject_user as access_subject_user,
+                ps.revision as revision,
+                ps.status as status,
+                {access_subject_members} as access_subject_members,
                 coalesce(
                     array_agg(
                         json_build_object(
@@ -343,6 +614,8 @@ pub async fn get_policy_sets_with_policies_for_creating_de(
                             p.actions,
                             'service_providers',
                             p.service_providers,
+                            'service_provider_members',
+                            {service_provider_members},
                             'resource_type',
                             p.resource_type,
                             'rules',
@@ -361,6 +634,8 @@ pub async fn get_policy_sets_with_policies_for_creating_de(
                 ps.id 
         "#,
         condition,
+        access_subject_members = access_subject_members_sql(),
+        service_provider_members = service_provider_members_sql(),
     );
 
     let stmt =
@@ -382,9 +657,379 @@ pub async fn get_policy_sets_with_policies_for_creating_de(
     Ok(policy_sets)
 }
 
-pub async fn get_policy_set_with_policies(
-    policy_set_id: &Uuid,
+// returns the valid policy sets that are issued to a user of the access subject, by any policy
+// issuer
+pub async fn get_policy_sets_with_policies_for_user(
+    now: chrono::DateTime<Utc>,
+    access_subject: String,
+    user_id: String,
+    db: &DatabaseConnection,
+) -> anyhow::Result<Vec<MatchingPolicySetRow>> {
+    let values: Vec<Value> = vec![
+        normalize_party_id(&access_subject).into(),
+        user_id.into(),
+        now.into(),
+    ];
+
+    let sql = format!(
+        r#"
+            select
+                ps.id as policy_set_id,
+                ps.access_subject as access_subject,
+                ps.policy_issuer as policy_issuer,
+                ps.licenses as licenses,
+                ps.max_delegation_depth as max_delegation_depth,
+                ps.not_before as not_before,
+                ps.not_on_or_after as not_on_or_after,
+                ps.created as created,
+                ps.access_subject_attributes as access_subject_attributes,
+                ps.access_subject_user as access_subject_user,
+                ps.revision as revision,
+                ps.status as status,
+                {access_subject_members} as access_subject_members,
+                coalesce(
+                    array_agg(
+                        json_build_object(
+                            'id',
+                            p.id,
+                            'identifiers',
+                            p.identifiers,
+                            'attributes',
+                            p.attributes,
+                            'actions',
+                            p.actions,
+                            'service_providers',
+                            p.service_providers,
+                            'service_provider_members',
+                            {service_provider_members},
+                            'resource_type',
+                            p.resource_type,
+                            'rules',
+                            p.rules
+                        )
+                    ) filter (where p.id is not null),
+                    '{{}}'
+                ) as policies
+            from
+                policy_set ps
+            left join
+                policy p
+                    on p.policy_set = ps.id
+            where ps.access_subject = $1 and ps.access_subject_user = $2 and {in_effect}
+            group by
+                ps.id
+            order by
+                ps.created
+        "#,
+        access_subject_members = access_subject_members_sql(),
+        service_provider_members = service_provider_members_sql(),
+        in_effect = in_effect_condition(3),
+    );
+
+    let stmt = Statement::from_sql_and_values(sea_orm::DatabaseBackend::Postgres, sql, values);
+
+    let raw_result = JsonValue::find_by_statement(stmt)
+        .all(db)
+        .await
+        .context("Error fetching policy sets of user from database")?;
+
+    let policy_sets_parse_result: Result<Vec<MatchingPolicySetRow>, serde_json::Error> = raw_result
+        .iter()
+        .map(|r| serde_json::from_value::<MatchingPolicySetRow>(r.to_owned()))
+        .collect();
+
+    let policy_sets = policy_sets_parse_result
+        .context("Error parsing policy sets 'QueryResult' into 'MatchingPolicySetRow'")?;
+
+    Ok(policy_sets)
+}
+
+// returns the policy sets that can be part of a delegation chain starting at the policy issuer:
+// every set that allows further delegation issued by a party reachable from the policy issuer
+// within `max_chain_length` hops, plus the sets those parties issued to the access subject. a
+// policy set issued to a party group makes every member of the group reachable
+pub async fn get_policy_sets_with_policies_for_delegation_chains(
+    now: chrono::DateTime<Utc>,
+    access_subject: String,
+    policy_issuer: String,
+    max_chain_length: i32,
+    db: &DatabaseConnection,
+) -> anyhow::Result<Vec<MatchingPolicySetRow>> {
+    let sql = format!(
+        r#"
+            with recursive reachable (party, hops) as (
+                select $1::text, 0
+                union
+                select
+                    unnest(array[ps.access_subject] || {access_subject_members}),
+                    r.hops + 1
+                from
+                    policy_set ps
+                join
+                    reachable r
+                        on ps.policy_issuer = r.party
+                where
+                    ps.max_delegation_depth > 0
+                    and r.hops < $3
+                    and {0}
+            )
+            select
+                ps.id as policy_set_id,
+                ps.access_subject as access_subject,
+                ps.policy_issuer as policy_issuer,
+                ps.licenses as licenses,
+                ps.max_delegation_depth as max_delegation_depth,
+                ps.not_before as not_before,
+                ps.not_on_or_after as not_on_or_after,
+                ps.created as created,
+                ps.access_subject_attributes as access_subject_attributes,
+                ps.access_subject_user as access_subject_user,
+                ps.revision as revision,
+                ps.status as status,
+                {access_subject_members} as access_subject_members,
+                coalesce(
+                    array_agg(
+                        json_build_object(
+                            'id',
+                            p.id,
+                            'identifiers',
+                            p.identifiers,
+                            'attributes',
+                            p.attributes,
+                            'actions',
+                            p.actions,
+                            'service_providers',
+                            p.service_providers,
+                            'service_provider_members',
+                            {service_provider_members},
+                            'resource_type',
+                            p.resource_type,
+                            'rules',
+                            p.rules
+                        )
+                    ) filter (where p.id is not null),
+                    '{{}}'
+                ) as policies
+            from
+                policy_set ps
+            left join
+                policy p
+                    on p.policy_set = ps.id
+            where (
+                ps.policy_issuer in (select party from reachable)
+                and (ps.max_delegation_depth > 0 or {1})
+                and {0}
+            )
+            group by
+                ps.id
+        "#,
+        in_effect_condition(4),
+        access_subject_or_group_condition("ps.access_subject", 2),
+        access_subject_members = access_subject_members_sql(),
+        service_provider_members = service_provider_members_sql(),
+    );
+
+    let stmt = Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        sql,
+        vec![
+            normalize_party_id(&policy_issuer).into(),
+            normalize_party_id(&access_subject).into(),
+            max_chain_length.into(),
+            now.into(),
+        ],
+    );
+
+    let raw_result = JsonValue::find_by_statement(stmt)
+        .all(db)
+        .await
+        .context("Error fetching delegation chain policy sets from database")?;
+
+    let policy_sets_parse_result: Result<Vec<MatchingPolicySetRow>, serde_json::Error> = raw_result
+        .iter()
+        .map(|r| serde_json::from_value::<MatchingPolicySetRow>(r.to_owned()))
+        .collect();
+
+    let policy_sets = policy_sets_parse_result
+        .context("Error parsing policy sets 'QueryResult' into 'MatchingPolicySetRow'")?;
+
+    Ok(policy_sets)
+}
+
+#[derive(Deserialize)]
+struct NextNotBeforeRow {
+    not_before: Option<chrono::DateTime<Utc>>,
+}
+
+// returns the earliest start after `now` of the policy sets that could become part of a decision
+// between the policy issuer and the access subject once they are valid: the policy sets issued by
+// the policy issuer or to the access subject and the policy sets that allow further delegation
+pub async fn get_next_not_before_for_delegation(
+    now: chrono::DateTime<Utc>,
+    access_subject: String,
+    policy_issuer: String,
     db: &DatabaseConnection,
+) -> anyhow::Result<Option<chrono::DateTime<Utc>>> {
+    let sql = format!(
+        r#"
+            select
+                min(ps.not_before) as not_before
+            from
+                policy_set ps
+            where
+                ps.not_before > $3
+                and ps.deleted_at is null
+                and ps.status = 'active'
+                and (ps.policy_issuer = $1 or ps.max_delegation_depth > 0 or {0})
+        "#,
+        access_subject_or_group_condition("ps.access_subject", 2),
+    );
+
+    let stmt = Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        sql,
+        vec![
+            normalize_party_id(&policy_issuer).into(),
+            normalize_party_id(&access_subject).into(),
+            now.into(),
+        ],
+    );
+
+    let raw_result = JsonValue::find_by_statement(stmt)
+        .one(db)
+        .await
+        .context("Error fetching next start of policy sets from database")?;
+
+    let Some(raw_result) = raw_result else {
+        return Ok(None);
+    };
+
+    let row = serde_json::from_value::<NextNotBeforeRow>(raw_result)
+        .context("Error parsing next start of policy sets")?;
+
+    Ok(row.not_before)
+}
+
+// returns a superset of the policy sets needed to evaluate delegation requests of all the
+// policy issuers for all the access subjects: the policy sets issued by a policy issuer or by a
+// party that can be reached from one of them through policy sets that allow further delegation,
+// that either allow further delegation themselves or are issued to one of the access subjects
+pub async fn get_policy_sets_with_policies_for_policy_issuers(
+    now: chrono::DateTime<Utc>,
+    policy_issuers: Vec<String>,
+    access_subjects: Vec<String>,
+    max_chain_length: i32,
+    db: &DatabaseConnection,
+) -> anyhow::Result<Vec<MatchingPolicySetRow>> {
+    let sql = format!(
+        r#"
+            with recursive reachable (party, hops) as (
+                select unnest($1::text[]), 0
+                union
+                select
+                    unnest(array[ps.access_subject] || {access_subject_members}),
+                    r.hops + 1
+                from
+                    policy_set ps
+                join
+                    reachable r
+                        on ps.policy_issuer = r.party
+                where
+                    ps.max_delegation_depth > 0
+                    and r.hops < $2
+                    and {0}
+            )
+            select
+                ps.id as policy_set_id,
+                ps.access_subject as access_subject,
+                ps.policy_issuer as policy_issuer,
+                ps.licenses as licenses,
+                ps.max_delegation_depth as max_delegation_depth,
+                ps.not_before as not_before,
+                ps.not_on_or_after as not_on_or_after,
+                ps.created as created,
+                ps.access_subject_attributes as access_subject_attributes,
+                ps.access_subject_user as access_subject_user,
+                ps.revision as revision,
+                ps.status as status,
+                {access_subject_members} as access_subject_members,
+                coalesce(
+                    array_agg(
+                        json_build_object(
+                            'id',
+                            p.id,
+                            'identifiers',
+                            p.identifiers,
+                            'attributes',
+                            p.attributes,
+                            'actions',
+                            p.actions,
+                            'service_providers',
+                            p.service_providers,
+                            'service_provider_members',
+                            {service_provider_members},
+                            'resource_type',
+                            p.resource_type,
+                            'rules',
+                            p.rules
+                        )
+                    ) filter (where p.id is not null),
+                    '{{}}'
+                ) as policies
+            from
+                policy_set ps
+            left join
+                policy p
+                    on p.policy_set = ps.id
+            where (
+                ps.policy_issuer in (select party from reachable)
+                and (
+                    ps.max_delegation_depth > 0
+                    or ps.access_subject = any($4::text[])
+                    or ps.access_subject in (select '{party_group_prefix}' || m.group_name from party_group_member m where m.party_id = any($4::text[]))
+                    or ps.access_subject_attributes is not null
+                )
+                and {0}
+            )
+            group by
+                ps.id
+        "#,
+        in_effect_condition(3),
+        party_group_prefix = PARTY_GROUP_PREFIX,
+        access_subject_members = access_subject_members_sql(),
+        service_provider_members = service_provider_members_sql(),
+    );
+
+    let stmt = Statement::from_sql_and_values(
+        sea_orm::DatabaseBackend::Postgres,
+        sql,
+        vec![
+            normalize_party_ids(&policy_issuers).into(),
+            max_chain_length.into(),
+            now.into(),
+            normalize_party_ids(&access_subjects).into(),
+        ],
+    );
+
+    let raw_result = JsonValue::find_by_statement(stmt)
+        .all(db)
+        .await
+        .context("Error fetching policy sets of policy issuers from database")?;
+
+    let policy_sets_parse_result: Result<Vec<MatchingPolicySetRow>, serde_json::Error> = raw_result
+        .iter()
+        .map(|r| serde_json::from_value::<MatchingPolicySetRow>(r.to_owned()))
+        .collect();
+
+    let policy_sets = policy_sets_parse_result
+        .context("Error parsing policy sets 'QueryResult' into 'MatchingPolicySetRow'")?;
+
+    Ok(policy_sets)
+}
+
+pub async fn get_policy_set_with_policies<C: ConnectionTrait>(
+    policy_set_id: &Uuid,
+    db: &C,
 ) -> anyhow::Result<Option<MatchingPolicySetRow>> {
     let sql = r#"
             select
@@ -393,6 +1038,13 @@ pub async fn get_policy_set_with_policies(
             ps.policy_issuer as policy_issuer,
             ps.licenses as licenses,
             ps.max_delegation_depth as max_delegation_depth,
+            ps.not_before as not_before,
+            ps.not_on_or_after as not_on_or_after,
+            ps.created as created,
+            ps.access_subject_attributes as access_subject_attributes,
+            ps.access_subject_user as access_subject_user,
+            ps.revision as revision,
+            ps.status as status,
             coalesce(
                 array_agg(
                     json_build_object(
@@ -420,7 +1072,7 @@ pub async fn get_policy_set_with_policies(
             policy p
                 on p.policy_set = ps.id
         where (
-            ps.id = $1
+            ps.id = $1 and ps.deleted_at is null
         )
         group by
             ps.id
@@ -448,29 +1100,46 @@ pub async fn get_policy_set_with_policies(
     Ok(policy_set_option)
 }
 
-#[derive(Deserialize, ToSchema)]
+#[derive(Deserialize, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct AccessSubjectTarget {
     pub access_subject: String,
+    // the access subject '*' applies to every party whose satellite registration has the attributes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_subject_attributes: Option<PartyAttributes>,
+    // the policy set only applies to delegation requests made for this user of the access subject
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub access_subject_user: Option<String>,
 }
 
 pub async fn insert_policy_set<C: ConnectionTrait>(
     now: chrono::DateTime<Utc>,
+    policy_set_id: Uuid,
     target: &AccessSubjectTarget,
     policy_issuer: &str,
     licences: &Vec<String>,
     max_delegation_depth: &i32,
+    not_before: Option<chrono::DateTime<Utc>>,
+    not_on_or_after: Option<chrono::DateTime<Utc>>,
+    status: PolicySetStatus,
     db: &C,
 ) -> anyhow::Result<Uuid> {
-    let policy_set_id = Uuid::new_v4();
-
     let active_policy_set = ar_entity::policy_set::ActiveModel {
         id: sea_orm::ActiveValue::Set(policy_set_id),
         licenses: sea_orm::ActiveValue::Set(licences.clone()),
-        access_subject: sea_orm::ActiveValue::set(target.access_subject.clone()),
-        policy_issuer: sea_orm::ActiveValue::set(policy_issuer.to_owned()),
+        access_subject: sea_orm::ActiveValue::set(normalize_party_id(&target.access_subject)),
+        policy_issuer: sea_orm::ActiveValue::set(normalize_party_id(policy_issuer)),
         max_delegation_depth: sea_orm::ActiveValue::set(max_delegation_depth.to_owned()),
         created: sea_orm::ActiveValue::set(now),
+        not_before: sea_orm::ActiveValue::set(not_before),
+        not_on_or_after: sea_orm::ActiveValue::set(not_on_or_after),
+        access_subject_attributes: sea_orm::ActiveValue::set(
+            target.access_subject_attributes.clone(),
+        ),
+        access_subject_user: sea_orm::ActiveValue::set(target.access_subject_user.clone()),
+        revision: sea_orm::ActiveValue::set(1),
+        deleted_at: sea_orm::ActiveValue::set(None),
+        status: sea_orm::ActiveValue::set(status),
     };
 
     let policy_set_id = ar_entity::policy_set::Entity::insert(active_policy_set)
@@ -482,6 +1151,61 @@ pub async fn insert_policy_set<C: ConnectionTrait>(
     Ok(policy_set_id)
 }
 
+// overwrites all fields of the policy set except its identifier and creation time
+pub async fn update_policy_set<C: ConnectionTrait>(
+    policy_set_id: &Uuid,
+    target: &AccessSubjectTarget,
+    policy_issuer: &str,
+    licences: &Vec<String>,
+    max_delegation_depth: &i32,
+    not_before: Option<chrono::DateTime<Utc>>,
+    not_on_or_after: Option<chrono::DateTime<Utc>>,
+    db: &C,
+) -> anyhow::Result<()> {
+    let policy_set = ar_entity::policy_set::Entity::find_by_id(*policy_set_id)
+        .one(db)
+        .await
+        .context("Error retrieving policy set from db")?;
+
+    let mut active_policy_set = match policy_set {
+        None => bail!("policy set with id '{}' not found", policy_set_id),
+        Some(policy_set) => policy_set.into_active_model(),
+    };
+
+    active_policy_set.access_subject = ActiveValue::set(normalize_party_id(&target.access_subject));
+    active_policy_set.policy_issuer = ActiveValue::set(normalize_party_id(policy_issuer));
+    active_policy_set.licenses = ActiveValue::set(licences.clone());
+    active_policy_set.max_delegation_depth = ActiveValue::set(max_delegation_depth.to_owned());
+    active_policy_set.not_before = ActiveValue::set(not_before);
+    active_policy_set.not_on_or_after = ActiveValue::set(not_on_or_after);
+    active_policy_set.access_subject_attributes =
+        ActiveValue::set(target.access_subject_attributes.clone());
+    active_policy_set.access_subject_user = ActiveValue::set(target.access_subject_user.clone());
+
+    active_policy_set
+        .update(db)
+        .await
+        .context("Error updating policy set in db")?;
+
+    Ok(())
+}
+
+pub async fn delete_policies_by_policy_set<C: ConnectionTrait>(
+    policy_set_id: &Uuid,
+    db: &C,
+) -> anyhow::Result<()> {
+    ar_entity::policy::Entity::delete_many()
+        .filter(ar_entity::policy::Column::PolicySet.eq(*policy_set_id))
+        .exec(db)
+        .await
+        .context(format!(
+            "Error deleting policies for policy set: {}",
+            policy_set_id
+        ))?;
+
+    Ok(())
+}
+
 pub async fn insert_policy<C: ConnectionTrait>(
     policy_set_id: Uuid,
     policy: &ar_entity::delegation_evidence::Policy,
@@ -493,9 +1217,9 @@ pub async fn insert_policy<C: ConnectionTrait>(
         id: sea_orm::ActiveValue::set(policy_id),
         attributes: sea_orm::ActiveValue::set(policy.target.resource.attributes.clone()),
         identifiers: sea_orm::ActiveValue::set(policy.target.resource.identifiers.clone()),
-        service_providers: sea_orm::ActiveValue::set(
-            policy.target.environment.service_providers.clone(),
-        ),
+        service_providers: sea_orm::ActiveValue::set(normalize_party_ids(
+            &policy.target.environment.service_providers,
+        )),
         policy_set: sea_orm::ActiveValue::set(policy_set_id),
         actions: sea_orm::ActiveValue::set(policy.target.actions.clone()),
         resource_type: sea_orm::ActiveValue::set(policy.target.resource.resource_type.clone()),
@@ -530,8 +1254,9 @@ pub async fn replace_policy<C: ConnectionTrait>(
 
     active_policy.attributes = ActiveValue::set(new_policy.target.resource.attributes.clone());
     active_policy.identifiers = ActiveValue::set(new_policy.target.resource.identifiers.clone());
-    active_policy.service_providers =
-        ActiveValue::set(new_policy.target.environment.service_providers.clone());
+    active_policy.service_providers = ActiveValue::set(normalize_party_ids(
+        &new_policy.target.environment.service_providers,
+    ));
     active_policy.actions = ActiveValue::set(new_policy.target.actions.clone());
     active_policy.resource_type =
         ActiveValue::set(new_policy.target.resource.resource_type.clone());
@@ -547,32 +1272,157 @@ pub async fn replace_policy<C: ConnectionTrait>(
     Ok(policy)
 }
 
-pub async fn delete_policy_set<T: TransactionTrait>(
+// deleted policy sets are kept with their policies, until they are restored or purged
+pub async fn delete_policy_set<C: ConnectionTrait>(
+    now: chrono::DateTime<Utc>,
     policy_set_id: &Uuid,
-    db: &T,
+    db: &C,
 ) -> anyhow::Result<()> {
-    let transaction = db.begin().await.context("Error opening db transaction")?;
+    ar_entity::policy_set::Entity::update_many()
+        .col_expr(
+            ar_entity::policy_set::Column::DeletedAt,
+            Expr::value(Some(now)),
+        )
+        .filter(ar_entity::policy_set::Column::Id.eq(*policy_set_id))
+        .filter(ar_entity::policy_set::Column::DeletedAt.is_null())
+        .exec(db)
+        .await
+        .context(format!("Error deleting policy set: {}", policy_set_id))?;
 
-    ar_entity::policy::Entity::delete_many()
-        .filter(ar_entity::policy::Column::PolicySet.eq(*policy_set_id))
-        .exec(&transaction)
+    Ok(())
+}
+
+pub async fn update_policy_set_status<C: ConnectionTrait>(
+    policy_set_id: &Uuid,
+    status: PolicySetStatus,
+    db: &C,
+) -> anyhow::Result<()> {
+    ar_entity::policy_set::Entity::update_many()
+        .col_expr(ar_entity::policy_set::Column::Status, Expr::value(status))
+        .filter(ar_entity::policy_set::Column::Id.eq(*policy_set_id))
+        .exec(db)
         .await
         .context(format!(
-            "Error deleting policies for policy set: {}",
+            "Error updating status of policy set: {}",
+            policy_set_id
+        ))?;
+
+    Ok(())
+}
+
+// only changes the status of a policy set that is still pending, returns the number of changed
+// policy sets so a concurrent decision on the same policy set is noticed
+pub async fn update_pending_policy_set_status<C: ConnectionTrait>(
+    policy_set_id: &Uuid,
+    status: PolicySetStatus,
+    db: &C,
+) -> anyhow::Result<u64> {
+    let result = ar_entity::policy_set::Entity::update_many()
+        .col_expr(ar_entity::policy_set::Column::Status, Expr::value(status))
+        .filter(ar_entity::policy_set::Column::Id.eq(*policy_set_id))
+        .filter(ar_entity::policy_set::Column::Status.eq(PolicySetStatus::Pending))
+        .filter(ar_entity::policy_set::Column::DeletedAt.is_null())
+        .exec(db)
+        .await
+        .context(format!(
+            "Error updating status of pending policy set: {}",
             policy_set_id
         ))?;
 
-    ar_entity::policy_set::Entity::delete_by_id(*policy_set_id)
+    Ok(result.rows_affected)
+}
+
+pub async fn restore_deleted_policy_set<C: ConnectionTrait>(
+    policy_set_id: &Uuid,
+    db: &C,
+) -> anyhow::Result<()> {
+    ar_entity::policy_set::Entity::update_many()
+        .col_expr(
+            ar_entity::policy_set::Column::DeletedAt,
+            Expr::value(Option::<chrono::DateTime<Utc>>::None),
+        )
+        .filter(ar_entity::policy_set::Column::Id.eq(*policy_set_id))
+        .exec(db)
+        .await
+        .context(format!("Error restoring policy set: {}", policy_set_id))?;
+
+    Ok(())
+}
+
+// removes the policy sets that were deleted before the given time together with their policies,
+// returns the identifiers of the purged policy sets
+pub async fn purge_deleted_policy_sets(
+    deleted_before: chrono::DateTime<Utc>,
+    db: &DatabaseConnection,
+) -> anyhow::Result<Vec<Uuid>> {
+    let transaction = db.begin().await.context("Error opening db transaction")?;
+
+    let policy_set_ids: Vec<Uuid> = ar_entity::policy_set::Entity::find()
+        .filter(ar_entity::policy_set::Column::DeletedAt.lt(deleted_before))
+        .all(&transaction)
+        .await
+        .context("Error retrieving deleted policy sets from db")?
+        .into_iter()
+        .map(|ps| ps.id)
+        .collect();
+
+    purge_policy_sets(policy_set_ids, transaction).await
+}
+
+// removes the policy sets that were rejected before the given time together with their policies,
+// returns the identifiers of the purged policy sets
+pub async fn purge_rejected_policy_sets(
+    rejected_before: chrono::DateTime<Utc>,
+    db: &DatabaseConnection,
+) -> anyhow::Result<Vec<Uuid>> {
+    let transaction = db.begin().await.context("Error opening db transaction")?;
+
+    let policy_set_ids: Vec<Uuid> = ar_entity::policy_set::Entity::find()
+        .filter(ar_entity::policy_set::Column::Status.eq(PolicySetStatus::Rejected))
+        .filter(ar_entity::policy_set::Column::DeletedAt.is_null())
+        .filter(Expr::cust_with_values(
+            r#"(
+                select max(v.created) from policy_set_version v
+                where v.policy_set_id = policy_set.id and v.change = 'rejected'
+            ) < $1"#,
+            [rejected_before],
+        ))
+        .all(&transaction)
+        .await
+        .context("Error retrieving rejected policy sets from db")?
+        .into_iter()
+        .map(|ps| ps.id)
+        .collect();
+
+    purge_policy_sets(policy_set_ids, transaction).await
+}
+
+async fn purge_policy_sets(
+    policy_set_ids: Vec<Uuid>,
+    transaction: DatabaseTransaction,
+) -> anyhow::Result<Vec<Uuid>> {
+    if policy_set_ids.is_empty() {
+        return Ok(policy_set_ids);
+    }
+
+    ar_entity::policy::Entity::delete_many()
+        .filter(ar_entity::policy::Column::PolicySet.is_in(policy_set_ids.clone()))
         .exec(&transaction)
         .await
-        .context(format!("Error deleting policy set: {}", policy_set_id))?;
+        .context("Error purging policies of policy sets")?;
+
+    ar_entity::policy_set::Entity::delete_many()
+        .filter(ar_entity::policy_set::Column::Id.is_in(policy_set_ids.clone()))
+        .exec(&transaction)
+        .await
+        .context("Error purging policy sets")?;
 
     transaction
         .commit()
         .await
         .context("Error commiting transaction to database")?;
 
-    Ok(())
+    Ok(policy_set_ids)
 }
 
 pub async fn get_policy_set_by_id(
@@ -580,11 +1430,152 @@ pub async fn get_policy_set_by_id(
     db: &DatabaseConnection,
 ) -> anyhow::Result<Option<ar_entity::policy_set::Model>> {
     ar_entity::policy_set::Entity::find_by_id(*id)
+        .filter(ar_entity::policy_set::Column::DeletedAt.is_null())
         .one(db)
         .await
         .context(format!("Error retrieving from db policy set: {}", id))
 }
 
+pub async fn get_deleted_policy_set_by_id(
+    id: &Uuid,
+    db: &DatabaseConnection,
+) -> anyhow::Result<Option<ar_entity::policy_set::Model>> {
+    ar_entity::policy_set::Entity::find_by_id(*id)
+        .filter(ar_entity::policy_set::Column::DeletedAt.is_not_null())
+        .one(db)
+        .await
+        .context(format!(
+            "Error retrieving from db deleted policy set: {}",
+            id
+        ))
+}
+
+// increments the revision of the policy set, when expected revisions are given only if the policy
+// set has one of them. returns the new revision, or none when the policy set doesn't exist, is
+// deleted or has another revision. the update locks the policy set until the transaction ends, so
+// concurrent changes that expect the same revision can't both succeed
+pub async fn increment_policy_set_revision<C: ConnectionTrait>(
+    policy_set_id: &Uuid,
+    expected_revisions: Option<&Vec<i32>>,
+    db: &C,
+) -> anyhow::Result<Option<i32>> {
+    let mut update = ar_entity::policy_set::Entity::update_many()
+        .col_expr(
+            ar_entity::policy_set::Column::Revision,
+            Expr::col(ar_entity::policy_set::Column::Revision).add(1),
+        )
+        .filter(ar_entity::policy_set::Column::Id.eq(*policy_set_id))
+        .filter(ar_entity::policy_set::Column::DeletedAt.is_null());
+
+    if let Some(expected_revisions) = expected_revisions {
+        update = update.filter(
+            ar_entity::policy_set::Column::Revision.is_in(expected_revisions.iter().copied()),
+        );
+    }
+
+    let result = update
+        .exec(db)
+        .await
+        .context("Error incrementing policy set revision")?;
+
+    if result.rows_affected == 0 {
+        return Ok(None);
+    }
+
+    let policy_set = ar_entity::policy_set::Entity::find_by_id(*policy_set_id)
+        .one(db)
+        .await
+        .context("Error retrieving policy set from db")?;
+
+    Ok(policy_set.map(|ps| ps.revision))
+}
+
+pub async fn update_policy_set_metadata<C: ConnectionTrait>(
+    policy_set_id: &Uuid,
+    access_subject: &str,
+    licenses: &Vec<String>,
+    max_delegation_depth: i32,
+    db: &C,
+) -> anyhow::Result<ar_entity::policy_set::Model> {
+    let policy_set = ar_entity::policy_set::Entity::find_by_id(*policy_set_id)
+        .one(db)
+        .await
+        .context("Error retrieving policy set from db")?;
+
+    let mut active_policy_set = match policy_set {
+        None => bail!("policy set with id '{}' not found", policy_set_id),
+        Some(policy_set) => policy_set.into_active_model(),
+    };
+
+    active_policy_set.access_subject = ActiveValue::set(normalize_party_id(access_subject));
+    active_policy_set.licenses = ActiveValue::set(licenses.clone());
+    active_policy_set.max_delegation_depth = ActiveValue::set(max_delegation_depth);
+
+    let policy_set = active_policy_set
+        .update(db)
+        .await
+        .context("Error saving updated policy set to db")?;
+
+    Ok(policy_set)
+}
+
+// brings the policy set and its policies back to the state of the snapshot, the policies keep the
+// identifiers they had in the snapshot
+pub async fn restore_policy_set<C: ConnectionTrait>(
+    snapshot: &MatchingPolicySetRow,
+    db: &C,
+) -> anyhow::Result<()> {
+    let policy_set = ar_entity::policy_set::Entity::find_by_id(snapshot.policy_set_id)
+        .one(db)
+        .await
+        .context("Error retrieving policy set from db")?;
+
+    let mut active_policy_set = match policy_set {
+        None => bail!("policy set with id '{}' not found", snapshot.policy_set_id),
+        Some(policy_set) => policy_set.into_active_model(),
+    };
+
+    active_policy_set.access_subject = ActiveValue::set(snapshot.access_subject.clone());
+    active_policy_set.licenses = ActiveValue::set(snapshot.licenses.clone());
+    active_policy_set.max_delegation_depth = ActiveValue::set(snapshot.max_delegation_depth);
+    active_policy_set.not_before = ActiveValue::set(snapshot.not_before);
+    active_policy_set.not_on_or_after = ActiveValue::set(snapshot.not_on_or_after);
+    active_policy_set.access_subject_attributes =
+        ActiveValue::set(snapshot.access_subject_attributes.clone());
+    active_policy_set.access_subject_user = ActiveValue::set(snapshot.access_subject_user.clone());
+
+    active_policy_set
+        .update(db)
+        .await
+        .context("Error restoring policy set in db")?;
+
+    ar_entity::policy::Entity::delete_many()
+        .filter(ar_entity::policy::Column::PolicySet.eq(snapshot.policy_set_id))
+        .exec(db)
+        .await
+        .context("Error deleting policies of restored policy set")?;
+
+    for policy in snapshot.policies.iter() {
+        let active_policy = ar_entity::policy::ActiveModel {
+            id: sea_orm::ActiveValue::set(policy.id),
+            attributes: sea_orm::ActiveValue::set(policy.attributes.clone()),
+            identifiers: sea_orm::ActiveValue::set(policy.identifiers.clone()),
+            service_providers: sea_orm::ActiveValue::set(policy.service_providers.clone()),
+            policy_set: sea_orm::ActiveValue::set(snapshot.policy_set_id),
+            actions: sea_orm::ActiveValue::set(policy.actions.clone()),
+            resource_type: sea_orm::ActiveValue::set(policy.resource_type.clone()),
+            rules: sea_orm::ActiveValue::set(policy.rules.clone()),
+        };
+
+        ar_entity::policy::Entity::insert(active_policy)
+            .exec(db)
+            .await
+            .context("Error inserting restored policy into db")?;
+    }
+
+    Ok(())
+}
+
 pub async fn add_policy_to_policy_set<T: ConnectionTrait>(
     policy_set_id: &Uuid,
     policy_args: Policy,
@@ -599,9 +1590,9 @@ pub async fn add_policy_to_policy_set<T: ConnectionTrait>(
         actions: sea_orm::ActiveValue::set(policy_args.target.actions),
         resource_type: sea_orm::ActiveValue::set(policy_args.target.resource.resource_type),
         rules: sea_orm::ActiveValue::set(policy_args.rules),
-        service_providers: sea_orm::ActiveValue::set(
-            policy_args.target.environment.service_providers,
-        ),
+        service_providers: sea_orm::ActiveValue::set(normalize_party_ids(
+            &policy_args.target.environment.service_providers,
+        )),
         policy_set: sea_orm::ActiveValue::set(*policy_set_id),
     };
 
@@ -0,0 +1,55 @@
+use anyhow::Context;
+use ar_entity::policy_issuer_setting::ActiveModel as ActivePolicyIssuerSetting;
+use ar_entity::policy_issuer_setting::Entity as PolicyIssuerSetting;
+use ar_entity::policy_issuer_setting::Model as PolicyIssuerSettingModel;
+use sea_orm::{entity::*, ActiveValue, ConnectionTrait, EntityTrait};
+
+use crate::utils::normalize_party_id;
+
+pub async fn get_policy_issuer_setting<T: ConnectionTrait>(
+    policy_issuer: &str,
+    db: &T,
+) -> anyhow::Result<Option<PolicyIssuerSettingModel>> {
+    let setting = PolicyIssuerSetting::find_by_id(normalize_party_id(policy_issuer))
+        .one(db)
+        .await
+        .context(format!(
+            "Error retrieving settings from db of policy issuer '{}'",
+            policy_issuer
+        ))?;
+
+    Ok(setting)
+}
+
+pub async fn save_policy_issuer_setting<T: ConnectionTrait>(
+    setting: &PolicyIssuerSettingModel,
+    db: &T,
+) -> anyhow::Result<()> {
+    let policy_issuer = normalize_party_id(&setting.policy_issuer);
+    let existing = get_policy_issuer_setting(&policy_issuer, db).await?;
+
+    let active_model = ActivePolicyIssuerSetting {
+        policy_issuer: ActiveValue::unchanged(policy_issuer.clone()),
+        require_policy_set_approval: ActiveValue::set(setting.require_policy_set_approval),
+    };
+
+    match existing {
+        Some(_) => {
+            active_model.update(db).await.context(format!(
+                "Error updating settings in db of policy issuer '{}'",
+                policy_issuer
+            ))?;
+        }
+        None => {
+            PolicyIssuerSetting::insert(active_model)
+                .exec(db)
+                .await
+                .context(format!(
+                    "Error inserting settings into db of policy issuer '{}'",
+                    policy_issuer
+                ))?;
+        }
+    }
+
+    Ok(())
+}
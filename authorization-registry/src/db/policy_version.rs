@@ -0,0 +1,150 @@
+use anyhow::Context;
+use ar_entity::policy_set_version::{
+    ActiveModel as ActivePolicySetVersion, Column, Entity as PolicySetVersionEntity,
+};
+use chrono::{DateTime, Utc};
+use sea_orm::{entity::*, query::*, ConnectionTrait, EntityTrait};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use super::policy::{self as policy_store, MatchingPolicySetRow};
+
+// the kind of change that produced a version of a policy set
+pub enum PolicySetChange {
+    Created,
+    PolicyAdded,
+    PolicyReplaced,
+    PolicyRemoved,
+    MetadataEdited,
+    RolledBack,
+    Imported,
+    Restored,
+    Approved,
+    Rejected,
+}
+
+impl PolicySetChange {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::PolicyAdded => "policy_added",
+            Self::PolicyReplaced => "policy_replaced",
+            Self::PolicyRemoved => "policy_removed",
+            Self::MetadataEdited => "metadata_edited",
+            Self::RolledBack => "rolled_back",
+            Self::Imported => "imported",
+            Self::Restored => "restored",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct PolicySetVersionSummary {
+    pub policy_set_id: Uuid,
+    pub version: i32,
+    #[schema(value_type = String, format = DateTime)]
+    pub created: DateTime<Utc>,
+    pub change: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+pub struct PolicySetVersion {
+    #[serde(flatten)]
+    pub summary: PolicySetVersionSummary,
+    pub policy_set: MatchingPolicySetRow,
+}
+
+impl TryFrom<ar_entity::policy_set_version::Model> for PolicySetVersion {
+    type Error = anyhow::Error;
+
+    fn try_from(model: ar_entity::policy_set_version::Model) -> anyhow::Result<Self> {
+        let policy_set = serde_json::from_value(model.snapshot)
+            .context("Error parsing policy set version snapshot")?;
+
+        Ok(Self {
+            summary: PolicySetVersionSummary {
+                policy_set_id: model.policy_set_id,
+                version: model.version,
+                created: model.created,
+                change: model.change,
+            },
+            policy_set,
+        })
+    }
+}
+
+// stores the current state of the policy set as its next version, this is done in the transaction
+// that changes the policy set so every committed change has a version
+pub async fn insert_policy_set_version<C: ConnectionTrait>(
+    now: DateTime<Utc>,
+    policy_set_id: &Uuid,
+    change: PolicySetChange,
+    db: &C,
+) -> anyhow::Result<i32> {
+    let snapshot = policy_store::get_policy_set_with_policies(policy_set_id, db)
+        .await?
+        .context(format!("policy set with id '{}' not found", policy_set_id))?;
+
+    let latest = PolicySetVersionEntity::find()
+        .filter(Column::PolicySetId.eq(*policy_set_id))
+        .order_by_desc(Column::Version)
+        .one(db)
+        .await
+        .context("Error retrieving latest policy set version from db")?;
+    let version = latest.map(|v| v.version).unwrap_or(0) + 1;
+
+    let active_version = ActivePolicySetVersion {
+        policy_set_id: ActiveValue::Set(*policy_set_id),
+        version: ActiveValue::Set(version),
+        created: ActiveValue::Set(now),
+        change: ActiveValue::Set(change.as_str().to_owned()),
+        snapshot: ActiveValue::Set(
+            serde_json::to_value(&snapshot).context("Error serializing policy set snapshot")?,
+        ),
+    };
+
+    PolicySetVersionEntity::insert(active_version)
+        .exec(db)
+        .await
+        .context("Error inserting policy set version into db")?;
+
+    Ok(version)
+}
+
+pub async fn get_policy_set_versions<C: ConnectionTrait>(
+    policy_set_id: &Uuid,
+    db: &C,
+) -> anyhow::Result<Vec<PolicySetVersionSummary>> {
+    let versions = PolicySetVersionEntity::find()
+        .filter(Column::PolicySetId.eq(*policy_set_id))
+        .order_by_asc(Column::Version)
+        .all(db)
+        .await
+        .context("Error retrieving policy set versions from db")?;
+
+    Ok(versions
+        .into_iter()
+        .map(|v| PolicySetVersionSummary {
+            policy_set_id: v.policy_set_id,
+            version: v.version,
+            created: v.created,
+            change: v.change,
+        })
+        .collect())
+}
+
+pub async fn get_policy_set_version<C: ConnectionTrait>(
+    policy_set_id: &Uuid,
+    version: i32,
+    db: &C,
+) -> anyhow::Result<Option<PolicySetVersion>> {
+    let version = PolicySetVersionEntity::find_by_id((*policy_set_id, version))
+        .one(db)
+        .await
+        .context("Error retrieving policy set version from db")?;
+
+    version.map(PolicySetVersion::try_from).transpose()
+}
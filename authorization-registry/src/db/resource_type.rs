@@ -0,0 +1,117 @@
+use anyhow::Context;
+use ar_entity::resource_type::ActiveModel as ActiveResourceType;
+use ar_entity::resource_type::Entity as ResourceType;
+use ar_entity::resource_type::Model as ResourceTypeModel;
+use sea_orm::{entity::*, query::*, ActiveValue, ConnectionTrait, EntityTrait, PaginatorTrait};
+
+pub async fn get_all_resource_types<T: ConnectionTrait>(
+    db: &T,
+) -> anyhow::Result<Vec<ResourceTypeModel>> {
+    let resource_types = ResourceType::find()
+        .order_by_asc(ar_entity::resource_type::Column::Name)
+        .all(db)
+        .await
+        .context("Error retrieving resource types from db")?;
+
+    Ok(resource_types)
+}
+
+pub async fn get_resource_type_by_name<T: ConnectionTrait>(
+    name: &str,
+    db: &T,
+) -> anyhow::Result<Option<ResourceTypeModel>> {
+    let resource_type = ResourceType::find_by_id(name.to_owned())
+        .one(db)
+        .await
+        .context(format!(
+            "Error retrieving resource type from db with name '{}'",
+            name
+        ))?;
+
+    Ok(resource_type)
+}
+
+// whether any resource type is registered
+pub async fn has_resource_types<T: ConnectionTrait>(db: &T) -> anyhow::Result<bool> {
+    let count = ResourceType::find()
+        .count(db)
+        .await
+        .context("Error counting resource types in db")?;
+
+    Ok(count > 0)
+}
+
+// returns the registered resource types among the names
+pub async fn get_resource_types_by_names<T: ConnectionTrait>(
+    names: &Vec<String>,
+    db: &T,
+) -> anyhow::Result<Vec<ResourceTypeModel>> {
+    if names.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let resource_types = ResourceType::find()
+        .filter(ar_entity::resource_type::Column::Name.is_in(names.clone()))
+        .all(db)
+        .await
+        .context("Error retrieving resource types from db")?;
+
+    Ok(resource_types)
+}
+
+pub async fn insert_resource_type<T: ConnectionTrait>(
+    resource_type: &ResourceTypeModel,
+    db: &T,
+) -> anyhow::Result<String> {
+    let active_model = ActiveResourceType {
+        name: ActiveValue::set(resource_type.name.to_owned()),
+        description: ActiveValue::set(resource_type.description.to_owned()),
+        actions: ActiveValue::set(resource_type.actions.clone()),
+        attributes: ActiveValue::set(resource_type.attributes.clone()),
+        identifier_format: ActiveValue::set(resource_type.identifier_format.clone()),
+    };
+
+    let name = ResourceType::insert(active_model)
+        .exec(db)
+        .await
+        .context(format!(
+            "Error inserting resource type into db with name '{}'",
+            resource_type.name
+        ))?
+        .last_insert_id;
+
+    Ok(name)
+}
+
+pub async fn update_resource_type<T: ConnectionTrait>(
+    resource_type: &ResourceTypeModel,
+    db: &T,
+) -> anyhow::Result<()> {
+    let active_model = ActiveResourceType {
+        name: ActiveValue::unchanged(resource_type.name.to_owned()),
+        description: ActiveValue::set(resource_type.description.to_owned()),
+        actions: ActiveValue::set(resource_type.actions.clone()),
+        attributes: ActiveValue::set(resource_type.attributes.clone()),
+        identifier_format: ActiveValue::set(resource_type.identifier_format.clone()),
+    };
+
+    active_model.update(db).await.context(format!(
+        "Error updating resource type in db with name '{}'",
+        resource_type.name
+    ))?;
+
+    Ok(())
+}
+
+pub async fn delete_resource_type<T: ConnectionTrait>(name: &str, db: &T) -> anyhow::Result<()> {
+    tracing::info!("Deleting resource type with name: {}", name);
+    ResourceType::delete_by_id(name.to_owned())
+        .exec(db)
+        .await
+        .context(format!(
+            "Error deleting resource type from db with name '{}'",
+            name
+        ))?;
+
+    Ok(())
+}
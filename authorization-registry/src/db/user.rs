@@ -44,7 +44,7 @@ pub async fn insert_if_not_exists<T: ConnectionTrait>(
     return Ok(user_id);
 }
 
-pub async fn _get_user_by_id(
+pub async fn get_user_by_id(
     id: &str,
     db: &DatabaseConnection,
 ) -> anyhow::Result<Option<UserModel>> {
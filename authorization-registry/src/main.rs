@@ -1,7 +1,9 @@
-use crate::config::FrontendConfig;
+use crate::config::{CombiningAlgorithmConfig, FrontendConfig};
 use crate::routes::audit_log::get_audit_log_routes;
+use crate::services::decision_cache::DecisionCache;
 use crate::services::idp_connector::IdpConnector;
 use crate::services::ishare_provider::{ISHAREProvider, SatelliteProvider};
+use crate::services::previous_steps::PreviousStepsVerifier;
 use crate::services::server_token::ServerToken;
 use ar_migration::{Migrator, MigratorTrait};
 
@@ -16,6 +18,7 @@ use routes::connect::get_connect_routes;
 use routes::delegation::get_delegation_routes;
 use routes::policy_set::get_policy_set_routes;
 use routes::policy_set_template::get_policy_set_template_routes;
+use routes::resource_type::get_resource_type_routes;
 use sea_orm::Database;
 use sea_orm::DatabaseConnection;
 use seed::apply_seeds;
@@ -78,14 +81,31 @@ Admin routes provide additional capabilities for managing policies across all pa
     modifiers(&SecurityAddon),
     paths(
         routes::delegation::post_delegation,
+        routes::delegation::post_delegation_simulation,
+        routes::delegation::post_delegation_batch,
+        routes::delegation::post_delegation_verification,
         routes::capabilities::get_capabilities,
         routes::connect::get_machine_token,
         routes::connect::get_auth,
         routes::connect::get_auth_callback,
         routes::policy_set::get_all_policy_sets,
+        routes::policy_set::get_policy_sets_granted_to_me,
         routes::policy_set::get_policy_set,
         routes::policy_set::insert_policy_set,
         routes::policy_set::delete_policy_set,
+        routes::policy_set::edit_policy_set,
+        routes::policy_set::get_policy_set_versions,
+        routes::policy_set::get_policy_set_version,
+        routes::policy_set::rollback_policy_set,
+        routes::policy_set::export_policy_sets,
+        routes::policy_set::get_deleted_policy_sets,
+        routes::policy_set::restore_policy_set,
+        routes::policy_set::get_policy_issuer_settings,
+        routes::policy_set::save_policy_issuer_settings,
+        routes::policy_set::get_pending_policy_sets,
+        routes::policy_set::get_rejected_policy_sets,
+        routes::policy_set::approve_policy_set,
+        routes::policy_set::reject_policy_set,
         routes::policy_set::add_policy_to_policy_set,
         routes::policy_set::delete_policy_from_policy_set,
         routes::policy_set::replace_policy_in_policy_set,
@@ -93,14 +113,41 @@ Admin routes provide additional capabilities for managing policies across all pa
         routes::admin::add_policy_to_policy_set,
         routes::admin::replace_policy_in_policy_set,
         routes::admin::delete_policy_set,
+        routes::admin::edit_policy_set,
+        routes::admin::get_policy_set_versions,
+        routes::admin::get_policy_set_version,
+        routes::admin::rollback_policy_set,
+        routes::admin::export_policy_sets,
+        routes::admin::import_policy_sets,
+        routes::admin::get_deleted_policy_sets,
+        routes::admin::restore_policy_set,
+        routes::admin::get_pending_policy_sets,
+        routes::admin::get_rejected_policy_sets,
+        routes::admin::approve_policy_set,
+        routes::admin::reject_policy_set,
         routes::admin::delete_policy_from_policy_set,
         routes::admin::get_policy_set,
         routes::admin::insert_policy_set,
         routes::admin::get_all_policy_sets,
         routes::admin::insert_policy_set_template,
         routes::admin::delete_policy_set_template,
+        routes::admin::get_delegation_cache_stats,
+        routes::admin::get_all_licenses,
+        routes::admin::insert_license,
+        routes::admin::delete_license,
+        routes::admin::get_all_resource_types,
+        routes::admin::insert_resource_type,
+        routes::admin::update_resource_type,
+        routes::admin::delete_resource_type,
+        routes::admin::get_all_party_groups,
+        routes::admin::insert_party_group,
+        routes::admin::delete_party_group,
+        routes::admin::add_party_group_member,
+        routes::admin::delete_party_group_member,
         routes::policy_set_template::get_policy_set_template,
         routes::policy_set_template::get_policy_set_templates,
+        routes::resource_type::get_resource_types,
+        routes::resource_type::get_resource_type,
     )
 )]
 struct ApiDoc;
@@ -138,6 +185,8 @@ pub struct AppConfig {
     pub client_eori: String,
     pub validate_m2m_certificate: bool,
     pub delegation_allows_service_providers: bool,
+    pub combining_algorithm: CombiningAlgorithmConfig,
+    pub trusted_proxies: Vec<String>,
     pub frontend: FrontendConfig,
     pub service_name: String,
 }
@@ -148,6 +197,8 @@ pub struct AppState {
     satellite_provider: Arc<dyn SatelliteProvider>,
     time_provider: Arc<dyn TimeProvider>,
     de_expiry_seconds: i64,
+    decision_cache: Arc<DecisionCache>,
+    previous_steps_verifier: Arc<PreviousStepsVerifier>,
     config: Arc<AppConfig>,
 }
 
@@ -166,6 +217,7 @@ pub fn get_app(db: DatabaseConnection, app_state: AppState, disable_cors_check:
     let policy_set_routes = get_policy_set_routes(app_state.server_token.clone());
     let capabilities_routes = get_capabilities_routes();
     let policy_set_template_routes = get_policy_set_template_routes(app_state.server_token.clone());
+    let resource_type_routes = get_resource_type_routes(app_state.server_token.clone());
     let audit_log_routes = get_audit_log_routes(app_state.server_token.clone());
     let config_routes = routes::config::get_config_routes();
 
@@ -176,6 +228,7 @@ pub fn get_app(db: DatabaseConnection, app_state: AppState, disable_cors_check:
         .nest("/policy-set", policy_set_routes)
         .nest("/capabilities", capabilities_routes)
         .nest("/policy-set-template", policy_set_template_routes)
+        .nest("/resource-type", resource_type_routes)
         .nest("/audit-log", audit_log_routes.clone())
         .nest("/audit-log/", audit_log_routes)
         .nest("/config", config_routes)
@@ -242,7 +295,7 @@ async fn main() {
         ISHARE::new(
             config.client_cert_path,
             config.client_cert_pass,
-            config.satellite_url,
+            config.satellite_url.clone(),
             Some(config.ishare_ca_path),
             config.client_eori.clone(),
             config.satellite_eori,
@@ -252,19 +305,31 @@ async fn main() {
     );
     let idp_connector =
         IdpConnector::new(config.idp_url, config.client_eori.clone(), config.idp_eori);
-    let sat_provider = ISHAREProvider::new(ishare.clone(), &db, &idp_connector);
-    let time_provider = RealTimeProvider::new();
+    let sat_provider = ISHAREProvider::new(
+        ishare.clone(),
+        &config.satellite_url,
+        &db,
+        &idp_connector,
+        config.party_registration_cache_ttl_seconds,
+    );
+    let time_provider: Arc<dyn TimeProvider> = Arc::new(RealTimeProvider::new());
     let app_state = AppState {
         server_token: Arc::new(server_token),
         satellite_provider: Arc::new(sat_provider),
-        time_provider: Arc::new(time_provider),
+        time_provider: time_provider.clone(),
         de_expiry_seconds: config.de_expiry_seconds,
+        decision_cache: Arc::new(DecisionCache::new(config.delegation_cache_ttl_seconds)),
+        previous_steps_verifier: Arc::new(PreviousStepsVerifier::new(
+            config.previous_step_max_age_seconds,
+        )),
         config: Arc::new(AppConfig {
             deploy_route: config.deploy_route.clone(),
             client_eori: config.client_eori.clone(),
             allowed_company_id: config.allowed_company_id.clone(),
             validate_m2m_certificate: config.validate_m2m_certificate,
             delegation_allows_service_providers: config.delegation_allows_service_providers,
+            combining_algorithm: config.combining_algorithm,
+            trusted_proxies: config.trusted_proxies,
             frontend: config.frontend,
             service_name: config.service_name,
         }),
@@ -272,11 +337,23 @@ async fn main() {
 
     tracing::info!("application config --- [{:?}]", app_state.config);
 
+    services::policy_trash::spawn_purge_task(
+        config.deleted_policy_set_retention_days,
+        config.rejected_policy_set_retention_days,
+        time_provider,
+        db.clone(),
+    );
+
     let app = get_app(db, app_state, config.disable_cors_check);
 
     let listener = tokio::net::TcpListener::bind(config.listen_address)
         .await
         .unwrap();
 
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<std::net::SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
@@ -1,9 +1,12 @@
 use anyhow::Context;
 use ar_entity::delegation_evidence::Policy;
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State},
+    http::{header::CONTENT_TYPE, HeaderMap, HeaderName},
     middleware::{from_fn, from_fn_with_state},
-    routing::{delete, get, post},
+    response::Response,
+    routing::{delete, get, post, put},
     Extension, Json, Router,
 };
 use axum_extra::extract::WithRejection;
@@ -15,21 +18,37 @@ use utoipa::ToSchema;
 use uuid::Uuid;
 
 use crate::{
-    db::policy::{self as policy_store, MatchingPolicySetRow, PolicySetsWithPagination},
+    db::license as license_store,
+    db::party_group::{self as party_group_store, PartyGroupWithMembers},
+    db::policy::{
+        self as policy_store, MatchingPolicySetRow, PartyMatch, PolicySetsWithPagination,
+    },
+    db::policy_version::{self as policy_version_store, PolicySetVersion, PolicySetVersionSummary},
+    db::resource_type as resource_type_store,
     error::ExpectedError,
     services::{
-        audit_log::{
-            log_event, PolicyAdded, PolicyRemoved, PolicyReplaced, PolicySetDeletedEventMetadata,
-            PolicySetEditedEventMetadata,
+        audit_log::{log_event, EventType, PartyGroupMembersEventMetadata},
+        decision_cache::DecisionCacheStats,
+        policy::{EditPolicySetMetadata, InsertPolicySetWithPolicies},
+        policy_approval as policy_approval_service,
+        policy_bulk::{
+            self as policy_bulk_service, ExportFormat, ExportedPolicySet, ImportMode,
+            PolicySetImportResult,
         },
-        policy::InsertPolicySetWithPolicies,
+        policy_trash as policy_trash_service, policy_version as policy_version_service,
+        resource_type as resource_type_service,
     },
 };
 use crate::{db::policy_set_template::InsertPolicySetTemplate, services::policy as policy_service};
 use crate::{error::AppError, error::ErrorResponse, AppState};
 use crate::{
     middleware::{auth_role_middleware, extract_human_middleware, extract_role_middleware},
+    routes::policy_set::{edited_policy_set_response, policy_set_response},
     services::server_token::ServerToken,
+    utils::{
+        extract_if_match_revisions, normalize_party_group_name, normalize_party_id,
+        normalize_party_ids,
+    },
 };
 
 pub fn get_admin_routes(
@@ -46,17 +65,57 @@ pub fn get_admin_routes(
             "/policy-set",
             post(insert_policy_set).get(get_all_policy_sets),
         )
+        .route("/policy-set/export", get(export_policy_sets))
+        .route("/policy-set/import", post(import_policy_sets))
+        .route("/policy-set/trash", get(get_deleted_policy_sets))
+        .route("/policy-set/pending", get(get_pending_policy_sets))
+        .route("/policy-set/rejected", get(get_rejected_policy_sets))
         .route(
             "/policy-set/:id",
-            get(get_policy_set).delete(delete_policy_set),
+            get(get_policy_set)
+                .delete(delete_policy_set)
+                .patch(edit_policy_set),
         )
         .route("/policy-set/:id/policy", post(add_policy_to_policy_set))
+        .route("/policy-set/:id/restore", post(restore_policy_set))
+        .route("/policy-set/:id/approve", post(approve_policy_set))
+        .route("/policy-set/:id/reject", post(reject_policy_set))
         .route(
             "/policy-set/:id/policy/:policy_id",
             delete(delete_policy_from_policy_set)
                 .put(replace_policy_in_policy_set)
                 .get(get_policy),
         )
+        .route("/policy-set/:id/version", get(get_policy_set_versions))
+        .route(
+            "/policy-set/:id/version/:version",
+            get(get_policy_set_version),
+        )
+        .route(
+            "/policy-set/:id/version/:version/rollback",
+            post(rollback_policy_set),
+        )
+        .route("/delegation-cache", get(get_delegation_cache_stats))
+        .route("/license", post(insert_license).get(get_all_licenses))
+        .route("/license/:code", delete(delete_license))
+        .route(
+            "/resource-type",
+            post(insert_resource_type).get(get_all_resource_types),
+        )
+        .route(
+            "/resource-type/:name",
+            put(update_resource_type).delete(delete_resource_type),
+        )
+        .route(
+            "/party-group",
+            post(insert_party_group).get(get_all_party_groups),
+        )
+        .route("/party-group/:name", delete(delete_party_group))
+        .route("/party-group/:name/member", post(add_party_group_member))
+        .route(
+            "/party-group/:name/member/:party_id",
+            delete(delete_party_group_member),
+        )
         .layer(from_fn_with_state(
             vec!["dexspace_admin".to_owned()],
             auth_role_middleware,
@@ -142,7 +201,10 @@ async fn insert_policy_set_template(
     State(app_state): State<AppState>,
     WithRejection(Json(body), _): WithRejection<Json<InsertPolicySetTemplate>, AppError>,
 ) -> Result<Json<InsertPolicySetTemplateResponse>, AppError> {
+    resource_type_service::validate_template_resource_types(&body.policies, &db).await?;
+
     for p in body.policies.iter() {
+        policy_service::validate_template_policy(p)?;
         for sp in p.service_providers.iter() {
             app_state
                 .satellite_provider
@@ -228,7 +290,8 @@ async fn get_policy(
     path = "/admin/policy-set/{id}/policy",
     tag = "Policy Management - Admin",
     params(
-        ("id" = Uuid, Path, description = "Identifier of the policy set")
+        ("id" = Uuid, Path, description = "Identifier of the policy set"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
     ),
     request_body(
         content = Policy,
@@ -262,6 +325,12 @@ async fn get_policy(
             description = "Policy set not found",
             content_type = "application/json",
             example = json!(ErrorResponse::new("Policy set not found"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
         )
     )
  )]
@@ -270,50 +339,19 @@ async fn add_policy_to_policy_set(
     Extension(db): Extension<DatabaseConnection>,
     WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
     State(app_state): State<AppState>,
+    headers: HeaderMap,
     Json(body): Json<Policy>,
 ) -> Result<Json<ar_entity::policy::Model>, AppError> {
-    for sp in body.target.environment.service_providers.iter() {
-        app_state
-            .satellite_provider
-            .validate_party(app_state.time_provider.now(), sp)
-            .await
-            .map_err(|e| {
-                AppError::Expected(ExpectedError {
-                    status_code: StatusCode::BAD_REQUEST,
-                    message: format!(
-                        "Unable to verify service provider '{}' as valid iSHARE party",
-                        &sp
-                    ),
-                    reason: format!("{:?}", e),
-                    metadata: None,
-                })
-            })?;
-    }
-
-    let transaction = db.begin().await.context("error starting db connection")?;
-
-    let policy = policy_store::add_policy_to_policy_set(&id, body, &transaction).await?;
-
-    log_event(
+    let policy = policy_service::add_policy_to_policy_set_admin(
         app_state.time_provider.now(),
-        id.to_string(),
-        crate::services::audit_log::EventType::ArPolicySetEdited(PolicySetEditedEventMetadata {
-            policy_set_id: id.to_owned(),
-            edited_type: crate::services::audit_log::EditedType::PolicyAdded(PolicyAdded {
-                policy_id: policy.id,
-            }),
-        }),
-        None,
-        None,
-        &transaction,
+        &id,
+        body,
+        &extract_if_match_revisions(&headers),
+        app_state.satellite_provider,
+        &db,
+        &app_state.decision_cache,
     )
-    .await
-    .context("error logging policy added event")?;
-
-    transaction
-        .commit()
-        .await
-        .context("error commiting transaction to db")?;
+    .await?;
 
     Ok(Json(policy))
 }
@@ -325,7 +363,8 @@ async fn add_policy_to_policy_set(
     tag = "Policy Management - Admin",
     params(
         ("policy_set_id" = Uuid, Path, description = "Identifier of the policy set"),
-        ("policy_id" = Uuid, Path, description = "Identifier of the policy to replace")
+        ("policy_id" = Uuid, Path, description = "Identifier of the policy to replace"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
     ),
     request_body(
         content = Policy,
@@ -359,6 +398,12 @@ async fn add_policy_to_policy_set(
             description = "Policy set or policy not found",
             content_type = "application/json",
             example = json!(ErrorResponse::new("Policy set or policy not found"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
         )
     )
  )]
@@ -367,62 +412,103 @@ async fn replace_policy_in_policy_set(
     Extension(db): Extension<DatabaseConnection>,
     WithRejection(Path((policy_set_id, policy_id)), _): WithRejection<Path<(Uuid, Uuid)>, AppError>,
     State(app_state): State<AppState>,
+    headers: HeaderMap,
     Json(body): Json<Policy>,
 ) -> Result<Json<ar_entity::policy::Model>, AppError> {
-    for sp in body.target.environment.service_providers.iter() {
-        app_state
-            .satellite_provider
-            .validate_party(app_state.time_provider.now(), sp)
-            .await
-            .map_err(|e| {
-                AppError::Expected(ExpectedError {
-                    status_code: StatusCode::BAD_REQUEST,
-                    message: format!(
-                        "Unable to verify service provider '{}' as valid iSHARE party",
-                        &sp
-                    ),
-                    reason: format!("{:?}", e),
-                    metadata: None,
-                })
-            })?;
-    }
-
-    let transaction = db.begin().await.context("error starting db transaction")?;
+    let policy = policy_service::replace_policy_in_policy_set_admin(
+        app_state.time_provider.now(),
+        policy_set_id,
+        policy_id,
+        body,
+        &extract_if_match_revisions(&headers),
+        app_state.satellite_provider,
+        &db,
+        &app_state.decision_cache,
+    )
+    .await?;
 
-    let policy = policy_store::replace_policy(policy_set_id, policy_id, &body, &db).await?;
+    Ok(Json(policy))
+}
 
-    log_event(
+/// Edit the access subject, licenses or maximum delegation depth of a policy set (admin access)
+#[utoipa::path(
+    patch,
+    path = "/admin/policy-set/{id}",
+    tag = "Policy Management - Admin",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the policy set to edit"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
+    ),
+    request_body(
+        content = EditPolicySetMetadata,
+        description = "Fields of the policy set to change, omitted fields are left unchanged",
+        content_type = "application/json"
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Policy set successfully edited",
+            content_type = "application/json",
+            body = MatchingPolicySetRow
+        ),
+        (
+            status = 400,
+            description = "Invalid policy set",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unknown license(s): ISHARE.9999"))
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 404,
+            description = "Policy set not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set not found"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
+        )
+    )
+ )]
+async fn edit_policy_set(
+    Extension(db): Extension<DatabaseConnection>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    Json(body): Json<EditPolicySetMetadata>,
+) -> Result<Response, AppError> {
+    let policy_set = policy_service::edit_policy_set_metadata_admin(
         app_state.time_provider.now(),
-        policy_set_id.to_string(),
-        crate::services::audit_log::EventType::ArPolicySetEdited(PolicySetEditedEventMetadata {
-            policy_set_id: policy_set_id.to_owned(),
-            edited_type: crate::services::audit_log::EditedType::PolicyReplaced(PolicyReplaced {
-                old_policy_id: policy_id.to_owned(),
-                new_policy_id: policy.id.to_owned(),
-            }),
-        }),
-        None,
-        None,
-        &transaction,
+        &id,
+        &body,
+        &extract_if_match_revisions(&headers),
+        app_state.satellite_provider,
+        &db,
+        &app_state.decision_cache,
     )
-    .await
-    .context("Error logging policy set edited event")?;
-
-    transaction
-        .commit()
-        .await
-        .context("error commiting transaction to db")?;
+    .await?;
 
-    Ok(Json(policy))
+    Ok(edited_policy_set_response(policy_set))
 }
 
-/// Delete a policy set (admin access)
+/// Delete a policy set, it can be restored until the retention period has passed (admin access)
 #[utoipa::path(
     delete,
     path = "/admin/policy-set/{id}",
     tag = "Policy Management - Admin",
     params(
-        ("id" = Uuid, Path, description = "Identifier of the policy set to delete")
+        ("id" = Uuid, Path, description = "Identifier of the policy set to delete"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
     ),
     security(
         ("h2m_bearer_admin" = [])
@@ -443,6 +529,12 @@ async fn replace_policy_in_policy_set(
             description = "Policy set not found",
             content_type = "application/json",
             example = json!(ErrorResponse::new("Policy set not found"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
         )
     )
  )]
@@ -450,30 +542,16 @@ async fn delete_policy_set(
     Extension(db): Extension<DatabaseConnection>,
     WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
     State(app_state): State<AppState>,
+    headers: HeaderMap,
 ) -> Result<(), AppError> {
-    let transaction = db.begin().await.context("error starting db transaction")?;
-
-    policy_store::delete_policy_set(&id, &transaction).await?;
-
-    log_event(
+    policy_service::delete_policy_set_admin(
         app_state.time_provider.now(),
-        id.to_string(),
-        crate::services::audit_log::EventType::ArPolicySetDeleted(PolicySetDeletedEventMetadata {
-            policy_set_id: id.to_owned(),
-        }),
-        None,
-        None,
-        &transaction,
+        &id,
+        &extract_if_match_revisions(&headers),
+        &db,
+        &app_state.decision_cache,
     )
     .await
-    .context("Error logging policy set deleted event")?;
-
-    transaction
-        .commit()
-        .await
-        .context("error commiting transaction to db")?;
-
-    Ok(())
 }
 
 /// Delete a policy from a policy set (admin access)
@@ -483,7 +561,8 @@ async fn delete_policy_set(
     tag = "Policy Management - Admin",
     params(
         ("policy_set_id" = Uuid, Path, description = "Identifier of the policy set"),
-        ("policy_id" = Uuid, Path, description = "Identifier of the policy to delete")
+        ("policy_id" = Uuid, Path, description = "Identifier of the policy to delete"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
     ),
     security(
         ("h2m_bearer_admin" = [])
@@ -504,6 +583,12 @@ async fn delete_policy_set(
             description = "Policy or policy set not found",
             content_type = "application/json",
             example = json!(ErrorResponse::new("Policy not found"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
         )
     )
  )]
@@ -511,33 +596,17 @@ async fn delete_policy_from_policy_set(
     Extension(db): Extension<DatabaseConnection>,
     WithRejection(Path((policy_set_id, policy_id)), _): WithRejection<Path<(Uuid, Uuid)>, AppError>,
     State(app_state): State<AppState>,
+    headers: HeaderMap,
 ) -> Result<(), AppError> {
-    let transaction = db.begin().await.context("error starting db transaction")?;
-
-    policy_store::delete_policy(&policy_id, &transaction).await?;
-
-    log_event(
+    policy_service::remove_policy_from_policy_set_admin(
         app_state.time_provider.now(),
-        policy_set_id.to_string(),
-        crate::services::audit_log::EventType::ArPolicySetEdited(PolicySetEditedEventMetadata {
-            policy_set_id: policy_set_id.to_owned(),
-            edited_type: crate::services::audit_log::EditedType::PolicyRemoved(PolicyRemoved {
-                policy_id: policy_id.to_owned(),
-            }),
-        }),
-        None,
-        None,
-        &transaction,
+        &policy_set_id,
+        &policy_id,
+        &extract_if_match_revisions(&headers),
+        &db,
+        &app_state.decision_cache,
     )
     .await
-    .context("Error logging policy set edited event")?;
-
-    transaction
-        .commit()
-        .await
-        .context("error commiting transaction to db")?;
-
-    Ok(())
 }
 
 /// Get a policy set by ID (admin access)
@@ -546,7 +615,8 @@ async fn delete_policy_from_policy_set(
     path = "/admin/policy-set/{id}",
     tag = "Policy Management - Admin",
     params(
-        ("id" = Uuid, Path, description = "Identifier of the policy set to retrieve")
+        ("id" = Uuid, Path, description = "Identifier of the policy set to retrieve"),
+        ("If-None-Match" = Option<String>, Header, description = "Respond with 304 when the policy set still has this ETag")
     ),
     security(
         ("h2m_bearer_admin" = [])
@@ -569,17 +639,22 @@ async fn delete_policy_from_policy_set(
             description = "Policy set not found",
             content_type = "application/json",
             example = json!(ErrorResponse::new("Can't find policy set"))
+        ),
+        (
+            status = 304,
+            description = "The policy set still has the ETag of the If-None-Match header"
         )
     )
  )]
 async fn get_policy_set(
     Extension(db): Extension<DatabaseConnection>,
     WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
-) -> Result<Json<MatchingPolicySetRow>, AppError> {
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
     let ps = policy_store::get_policy_set_with_policies(&id, &db).await?;
 
     match ps {
-        Some(ps) => Ok(Json(ps)),
+        Some(ps) => Ok(policy_set_response(&headers, ps)),
         None => Err(AppError::Expected(ExpectedError {
             status_code: StatusCode::NOT_FOUND,
             message: "Can't find policy set".to_owned(),
@@ -589,36 +664,23 @@ async fn get_policy_set(
     }
 }
 
-#[derive(Serialize, ToSchema)]
-struct InsertPolicySetResponse {
-    uuid: Uuid,
-}
-
-/// Create a new policy set (admin access)
+/// Get the versions of a policy set, including the versions of deleted policy sets (admin access)
 #[utoipa::path(
-    post,
-    path = "/admin/policy-set",
+    get,
+    path = "/admin/policy-set/{id}/version",
     tag = "Policy Management - Admin",
-    request_body(
-        content = InsertPolicySetWithPolicies,
-        description = "Policy set details and its initial policies",
-        content_type = "application/json"
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the policy set")
     ),
     security(
         ("h2m_bearer_admin" = [])
     ),
     responses(
         (
-            status = 201,
-            description = "Policy set successfully created",
-            content_type = "application/json",
-            body = InsertPolicySetResponse
-        ),
-        (
-            status = 400,
-            description = "Invalid policy set definition",
+            status = 200,
+            description = "Versions of the policy set, oldest first",
             content_type = "application/json",
-            example = json!(ErrorResponse::new("Invalid policy set format"))
+            body = Vec<PolicySetVersionSummary>
         ),
         (
             status = 401,
@@ -628,46 +690,23 @@ struct InsertPolicySetResponse {
         )
     )
  )]
-async fn insert_policy_set(
+async fn get_policy_set_versions(
     Extension(db): Extension<DatabaseConnection>,
-    State(app_state): State<AppState>,
-    WithRejection(Json(body), _): WithRejection<Json<InsertPolicySetWithPolicies>, AppError>,
-) -> Result<Json<InsertPolicySetResponse>, AppError> {
-    let policy_set_id = policy_service::insert_policy_set_with_policies_admin(
-        app_state.time_provider.now(),
-        &body,
-        &db,
-        app_state.satellite_provider,
-    )
-    .await?;
-
-    let response = InsertPolicySetResponse {
-        uuid: policy_set_id,
-    };
-
-    Ok(Json(response))
-}
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
+) -> Result<Json<Vec<PolicySetVersionSummary>>, AppError> {
+    let versions = policy_version_store::get_policy_set_versions(&id, &db).await?;
 
-#[derive(Deserialize)]
-struct GetPolicySetsQuery {
-    access_subject: Option<String>,
-    policy_issuer: Option<String>,
-    q: Option<String>,
-    limit: Option<u32>,
-    skip: Option<u32>,
+    Ok(Json(versions))
 }
 
-/// List all policy sets with optional filtering (admin access)
+/// Get a version of a policy set (admin access)
 #[utoipa::path(
     get,
-    path = "/admin/policy-set",
+    path = "/admin/policy-set/{id}/version/{version}",
     tag = "Policy Management - Admin",
     params(
-        ("access_subject" = Option<String>, Query, description = "Filter by access subject"),
-        ("policy_issuer" = Option<String>, Query, description = "Filter by policy issuer"),
-        ("limit" = Option<u32>, Query, description = "Limit the number of results for pagination"),
-        ("skip" = Option<u32>, Query, description = "Skip a number of results for pagination"),
-        ("q" = Option<String>, Query, description = "Filter on any match in the policy set"),
+        ("id" = Uuid, Path, description = "Identifier of the policy set"),
+        ("version" = i32, Path, description = "Version of the policy set")
     ),
     security(
         ("h2m_bearer_admin" = [])
@@ -675,34 +714,1591 @@ struct GetPolicySetsQuery {
     responses(
         (
             status = 200,
-            description = "List of policy sets matching the filter criteria",
+            description = "Policy set as it was in the version",
             content_type = "application/json",
-            body = Vec<MatchingPolicySetRow>
+            body = PolicySetVersion
         ),
         (
             status = 401,
             description = "Authentication failed",
             content_type = "application/json",
             example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 404,
+            description = "Policy set version not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Can't find policy set version"))
         )
     )
  )]
-async fn get_all_policy_sets(
-    Query(query): Query<GetPolicySetsQuery>,
+async fn get_policy_set_version(
     Extension(db): Extension<DatabaseConnection>,
-) -> Result<Json<PolicySetsWithPagination>, AppError> {
-    let policy_sets = policy_store::get_policy_sets_with_policies(
-        query.access_subject,
-        query.policy_issuer,
-        query.q,
-        query.skip,
-        query.limit,
-        &db,
-    )
-    .await
-    .context("Error getting policicy sets")?;
+    WithRejection(Path((id, version)), _): WithRejection<Path<(Uuid, i32)>, AppError>,
+) -> Result<Json<PolicySetVersion>, AppError> {
+    let policy_set_version =
+        policy_version_store::get_policy_set_version(&id, version, &db).await?;
 
-    Ok(Json(policy_sets))
+    match policy_set_version {
+        Some(policy_set_version) => Ok(Json(policy_set_version)),
+        None => Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::NOT_FOUND,
+            message: "Can't find policy set version".to_owned(),
+            reason: "Can't find policy set version".to_owned(),
+            metadata: None,
+        })),
+    }
+}
+
+/// Roll a policy set back to one of its versions, recorded as a new version (admin access)
+#[utoipa::path(
+    post,
+    path = "/admin/policy-set/{id}/version/{version}/rollback",
+    tag = "Policy Management - Admin",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the policy set"),
+        ("version" = i32, Path, description = "Version of the policy set to roll back to"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "New version of the policy set created by the rollback",
+            content_type = "application/json",
+            body = PolicySetVersion
+        ),
+        (
+            status = 400,
+            description = "The version is no longer a valid policy set",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unknown license(s): ISHARE.9999"))
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 404,
+            description = "Policy set or version not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Can't find policy set version"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
+        )
+    )
+ )]
+async fn rollback_policy_set(
+    Extension(db): Extension<DatabaseConnection>,
+    WithRejection(Path((id, version)), _): WithRejection<Path<(Uuid, i32)>, AppError>,
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<PolicySetVersion>, AppError> {
+    let policy_set_version = policy_version_service::rollback_policy_set_admin(
+        app_state.time_provider.now(),
+        &id,
+        version,
+        &extract_if_match_revisions(&headers),
+        app_state.satellite_provider,
+        &db,
+        &app_state.decision_cache,
+    )
+    .await?;
+
+    Ok(Json(policy_set_version))
+}
+
+#[derive(Serialize, ToSchema)]
+struct InsertPolicySetResponse {
+    uuid: Uuid,
+}
+
+/// Create a new policy set (admin access)
+#[utoipa::path(
+    post,
+    path = "/admin/policy-set",
+    tag = "Policy Management - Admin",
+    request_body(
+        content = InsertPolicySetWithPolicies,
+        description = "Policy set details and its initial policies",
+        content_type = "application/json"
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 201,
+            description = "Policy set successfully created",
+            content_type = "application/json",
+            body = InsertPolicySetResponse
+        ),
+        (
+            status = 400,
+            description = "Invalid policy set definition",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Invalid policy set format"))
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn insert_policy_set(
+    Extension(db): Extension<DatabaseConnection>,
+    State(app_state): State<AppState>,
+    WithRejection(Json(body), _): WithRejection<Json<InsertPolicySetWithPolicies>, AppError>,
+) -> Result<Json<InsertPolicySetResponse>, AppError> {
+    let policy_set_id = policy_service::insert_policy_set_with_policies_admin(
+        app_state.time_provider.now(),
+        &body,
+        &db,
+        app_state.satellite_provider,
+        &app_state.decision_cache,
+    )
+    .await?;
+
+    let response = InsertPolicySetResponse {
+        uuid: policy_set_id,
+    };
+
+    Ok(Json(response))
+}
+
+/// Get delegation decision cache statistics (admin access)
+#[utoipa::path(
+    get,
+    path = "/admin/delegation-cache",
+    tag = "Delegation - Admin",
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Number of cached decisions and the hits and misses since startup",
+            content_type = "application/json",
+            body = DecisionCacheStats
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn get_delegation_cache_stats(State(app_state): State<AppState>) -> Json<DecisionCacheStats> {
+    Json(app_state.decision_cache.stats())
+}
+
+/// List the license registry (admin access)
+#[utoipa::path(
+    get,
+    path = "/admin/license",
+    tag = "License - Admin",
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Licenses that can be granted by policy sets",
+            content_type = "application/json",
+            body = Vec<ar_entity::license::Model>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn get_all_licenses(
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<Vec<ar_entity::license::Model>>, AppError> {
+    let licenses = license_store::get_all_licenses(&db).await?;
+
+    Ok(Json(licenses))
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+struct InsertLicense {
+    code: String,
+    description: String,
+}
+
+/// Add a license to the license registry (admin access)
+#[utoipa::path(
+    post,
+    path = "/admin/license",
+    tag = "License - Admin",
+    request_body(
+        content = InsertLicense,
+        description = "License code, e.g. 'ISHARE.0001', and its description",
+        content_type = "application/json"
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "License successfully added",
+            content_type = "application/json",
+            body = ar_entity::license::Model
+        ),
+        (
+            status = 400,
+            description = "Invalid license",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("License code cannot be empty"))
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 409,
+            description = "License already exists",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("License already exists"))
+        )
+    )
+ )]
+async fn insert_license(
+    Extension(db): Extension<DatabaseConnection>,
+    WithRejection(Json(body), _): WithRejection<Json<InsertLicense>, AppError>,
+) -> Result<Json<ar_entity::license::Model>, AppError> {
+    let code = body.code.trim();
+
+    if code.is_empty() {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::BAD_REQUEST,
+            message: "License code cannot be empty".to_owned(),
+            reason: "empty license code".to_owned(),
+            metadata: None,
+        }));
+    }
+
+    if license_store::get_license_by_code(code, &db)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::CONFLICT,
+            message: "License already exists".to_owned(),
+            reason: format!("license '{}' is already in the license registry", code),
+            metadata: None,
+        }));
+    }
+
+    license_store::insert_license(code, &body.description, &db).await?;
+
+    Ok(Json(ar_entity::license::Model {
+        code: code.to_owned(),
+        description: body.description,
+    }))
+}
+
+/// Remove a license from the license registry (admin access)
+///
+/// A license can only be removed when no stored policy set grants it, including the policy sets in
+/// the trash.
+#[utoipa::path(
+    delete,
+    path = "/admin/license/{code}",
+    tag = "License - Admin",
+    params(
+        ("code" = String, Path, description = "Code of the license")
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "License successfully removed"
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 404,
+            description = "License not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("License not found"))
+        ),
+        (
+            status = 409,
+            description = "License is granted by policy sets",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("License is granted by policy sets"))
+        )
+    )
+ )]
+async fn delete_license(
+    Extension(db): Extension<DatabaseConnection>,
+    WithRejection(Path(code), _): WithRejection<Path<String>, AppError>,
+) -> Result<(), AppError> {
+    if license_store::get_license_by_code(&code, &db)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::NOT_FOUND,
+            message: "License not found".to_owned(),
+            reason: format!("license '{}' is not in the license registry", code),
+            metadata: None,
+        }));
+    }
+
+    let policy_set_count = license_store::get_policy_set_count_with_license(&code, &db).await?;
+    if policy_set_count > 0 {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::CONFLICT,
+            message: "License is granted by policy sets".to_owned(),
+            reason: format!(
+                "license '{}' is granted by {} policy set(s)",
+                code, policy_set_count
+            ),
+            metadata: None,
+        }));
+    }
+
+    license_store::delete_license(&code, &db).await?;
+
+    Ok(())
+}
+
+/// List the resource type registry (admin access)
+#[utoipa::path(
+    get,
+    path = "/admin/resource-type",
+    tag = "Resource Type - Admin",
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Resource types that policies are validated against",
+            content_type = "application/json",
+            body = Vec<ar_entity::resource_type::Model>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn get_all_resource_types(
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<Vec<ar_entity::resource_type::Model>>, AppError> {
+    let resource_types = resource_type_store::get_all_resource_types(&db).await?;
+
+    Ok(Json(resource_types))
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct UpdateResourceType {
+    description: String,
+    actions: Vec<String>,
+    #[serde(default)]
+    attributes: Vec<String>,
+    #[serde(default)]
+    identifier_format: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct InsertResourceType {
+    name: String,
+    #[serde(flatten)]
+    resource_type: UpdateResourceType,
+}
+
+fn to_resource_type_model(name: &str, body: UpdateResourceType) -> ar_entity::resource_type::Model {
+    ar_entity::resource_type::Model {
+        name: name.to_owned(),
+        description: body.description,
+        actions: body.actions,
+        attributes: body.attributes,
+        identifier_format: body.identifier_format,
+    }
+}
+
+/// Add a resource type to the resource type registry (admin access)
+///
+/// Policies and policy set templates with a registered resource type are validated against its actions, attributes and identifier format. As long as the registry is empty policies are not validated, once it has resource types a policy with a resource type that is not registered is rejected.
+#[utoipa::path(
+    post,
+    path = "/admin/resource-type",
+    tag = "Resource Type - Admin",
+    request_body(
+        content = InsertResourceType,
+        description = "Resource type, e.g. 'DMI.DataAccess', with the actions, attributes and identifier format policies can use",
+        content_type = "application/json"
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Resource type successfully added",
+            content_type = "application/json",
+            body = ar_entity::resource_type::Model
+        ),
+        (
+            status = 400,
+            description = "Invalid resource type, the invalid fields are in the metadata",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Invalid resource type"))
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 409,
+            description = "Resource type already exists",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Resource type already exists"))
+        )
+    )
+ )]
+async fn insert_resource_type(
+    Extension(db): Extension<DatabaseConnection>,
+    WithRejection(Json(body), _): WithRejection<Json<InsertResourceType>, AppError>,
+) -> Result<Json<ar_entity::resource_type::Model>, AppError> {
+    let resource_type = to_resource_type_model(body.name.trim(), body.resource_type);
+    resource_type_service::validate_resource_type(&resource_type)?;
+
+    if resource_type_store::get_resource_type_by_name(&resource_type.name, &db)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::CONFLICT,
+            message: "Resource type already exists".to_owned(),
+            reason: format!(
+                "resource type '{}' is already in the resource type registry",
+                resource_type.name
+            ),
+            metadata: None,
+        }));
+    }
+
+    resource_type_store::insert_resource_type(&resource_type, &db).await?;
+
+    Ok(Json(resource_type))
+}
+
+/// Update a resource type in the resource type registry (admin access)
+///
+/// Existing policies are not validated again, new and changed policies are validated against the updated resource type.
+#[utoipa::path(
+    put,
+    path = "/admin/resource-type/{name}",
+    tag = "Resource Type - Admin",
+    params(
+        ("name" = String, Path, description = "Name of the resource type")
+    ),
+    request_body(
+        content = UpdateResourceType,
+        description = "The actions, attributes and identifier format policies can use",
+        content_type = "application/json"
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Resource type successfully updated",
+            content_type = "application/json",
+            body = ar_entity::resource_type::Model
+        ),
+        (
+            status = 400,
+            description = "Invalid resource type, the invalid fields are in the metadata",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Invalid resource type"))
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 404,
+            description = "Resource type not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Resource type not found"))
+        )
+    )
+ )]
+async fn update_resource_type(
+    Extension(db): Extension<DatabaseConnection>,
+    WithRejection(Path(name), _): WithRejection<Path<String>, AppError>,
+    WithRejection(Json(body), _): WithRejection<Json<UpdateResourceType>, AppError>,
+) -> Result<Json<ar_entity::resource_type::Model>, AppError> {
+    let resource_type = to_resource_type_model(&name, body);
+    resource_type_service::validate_resource_type(&resource_type)?;
+
+    if resource_type_store::get_resource_type_by_name(&name, &db)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::NOT_FOUND,
+            message: "Resource type not found".to_owned(),
+            reason: format!(
+                "resource type '{}' is not in the resource type registry",
+                name
+            ),
+            metadata: None,
+        }));
+    }
+
+    resource_type_store::update_resource_type(&resource_type, &db).await?;
+
+    Ok(Json(resource_type))
+}
+
+/// Remove a resource type from the resource type registry (admin access)
+///
+/// Policies with the resource type are kept, they are no longer validated.
+#[utoipa::path(
+    delete,
+    path = "/admin/resource-type/{name}",
+    tag = "Resource Type - Admin",
+    params(
+        ("name" = String, Path, description = "Name of the resource type")
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Resource type successfully removed"
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 404,
+            description = "Resource type not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Resource type not found"))
+        )
+    )
+ )]
+async fn delete_resource_type(
+    Extension(db): Extension<DatabaseConnection>,
+    WithRejection(Path(name), _): WithRejection<Path<String>, AppError>,
+) -> Result<(), AppError> {
+    if resource_type_store::get_resource_type_by_name(&name, &db)
+        .await?
+        .is_none()
+    {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::NOT_FOUND,
+            message: "Resource type not found".to_owned(),
+            reason: format!(
+                "resource type '{}' is not in the resource type registry",
+                name
+            ),
+            metadata: None,
+        }));
+    }
+
+    resource_type_store::delete_resource_type(&name, &db).await?;
+
+    Ok(())
+}
+
+/// List the party groups and their members (admin access)
+#[utoipa::path(
+    get,
+    path = "/admin/party-group",
+    tag = "Party Group - Admin",
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Party groups that can be used as access subject or service provider",
+            content_type = "application/json",
+            body = Vec<PartyGroupWithMembers>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn get_all_party_groups(
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<Vec<PartyGroupWithMembers>>, AppError> {
+    let party_groups = party_group_store::get_all_party_groups(&db).await?;
+
+    Ok(Json(party_groups))
+}
+
+fn party_group_not_found(name: &str) -> AppError {
+    AppError::Expected(ExpectedError {
+        status_code: StatusCode::NOT_FOUND,
+        message: "Party group not found".to_owned(),
+        reason: format!("party group '{}' does not exist", name),
+        metadata: None,
+    })
+}
+
+// validates the new members of a party group as iSHARE parties
+async fn validate_party_group_members(
+    party_ids: &Vec<String>,
+    app_state: &AppState,
+) -> Result<(), AppError> {
+    for party_id in party_ids.iter() {
+        if party_id.is_empty() {
+            return Err(AppError::Expected(ExpectedError {
+                status_code: StatusCode::BAD_REQUEST,
+                message: "Party id of a party group member cannot be empty".to_owned(),
+                reason: "empty party id".to_owned(),
+                metadata: None,
+            }));
+        }
+
+        app_state
+            .satellite_provider
+            .validate_party(app_state.time_provider.now(), party_id)
+            .await
+            .map_err(|e| {
+                AppError::Expected(ExpectedError {
+                    status_code: StatusCode::BAD_REQUEST,
+                    message: format!(
+                        "Unable to verify party group member '{}' as valid iSHARE party",
+                        party_id
+                    ),
+                    reason: format!("{:?}", e),
+                    metadata: None,
+                })
+            })?;
+    }
+
+    Ok(())
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+struct InsertPartyGroup {
+    name: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    members: Vec<String>,
+}
+
+/// Add a party group (admin access)
+///
+/// Policy sets can refer to the group as access subject or service provider with 'group:<name>'.
+#[utoipa::path(
+    post,
+    path = "/admin/party-group",
+    tag = "Party Group - Admin",
+    request_body(
+        content = InsertPartyGroup,
+        description = "Name of the party group, its description and the party ids of its members",
+        content_type = "application/json"
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Party group successfully added",
+            content_type = "application/json",
+            body = PartyGroupWithMembers
+        ),
+        (
+            status = 400,
+            description = "Invalid party group",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Party group name cannot be empty"))
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 409,
+            description = "Party group already exists",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Party group already exists"))
+        )
+    )
+ )]
+async fn insert_party_group(
+    Extension(db): Extension<DatabaseConnection>,
+    State(app_state): State<AppState>,
+    WithRejection(Json(body), _): WithRejection<Json<InsertPartyGroup>, AppError>,
+) -> Result<Json<PartyGroupWithMembers>, AppError> {
+    let name = normalize_party_group_name(&body.name);
+
+    if name.is_empty() {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::BAD_REQUEST,
+            message: "Party group name cannot be empty".to_owned(),
+            reason: "empty party group name".to_owned(),
+            metadata: None,
+        }));
+    }
+
+    if party_group_store::get_party_group_by_name(&name, &db)
+        .await?
+        .is_some()
+    {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::CONFLICT,
+            message: "Party group already exists".to_owned(),
+            reason: format!("party group '{}' already exists", name),
+            metadata: None,
+        }));
+    }
+
+    let mut members = normalize_party_ids(&body.members);
+    members.sort();
+    members.dedup();
+
+    validate_party_group_members(&members, &app_state).await?;
+
+    let transaction = db.begin().await.context("error starting db transaction")?;
+
+    party_group_store::insert_party_group(&name, &body.description, &transaction).await?;
+    party_group_store::insert_party_group_members(&name, &members, &transaction).await?;
+
+    if !members.is_empty() {
+        log_event(
+            app_state.time_provider.now(),
+            name.clone(),
+            EventType::ArPartyGroupMembersAdded(PartyGroupMembersEventMetadata {
+                group_name: name.clone(),
+                party_ids: members.clone(),
+            }),
+            None,
+            None,
+            &transaction,
+        )
+        .await
+        .context("Error logging party group members added event")?;
+    }
+
+    transaction
+        .commit()
+        .await
+        .context("error commiting transaction to db")?;
+
+    Ok(Json(PartyGroupWithMembers {
+        name,
+        description: body.description,
+        members,
+    }))
+}
+
+/// Remove a party group (admin access)
+///
+/// Policy sets that refer to the group no longer apply to its former members.
+#[utoipa::path(
+    delete,
+    path = "/admin/party-group/{name}",
+    tag = "Party Group - Admin",
+    params(
+        ("name" = String, Path, description = "Name of the party group")
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Party group successfully removed"
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 404,
+            description = "Party group not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Party group not found"))
+        )
+    )
+ )]
+async fn delete_party_group(
+    Extension(db): Extension<DatabaseConnection>,
+    State(app_state): State<AppState>,
+    WithRejection(Path(name), _): WithRejection<Path<String>, AppError>,
+) -> Result<(), AppError> {
+    let name = normalize_party_group_name(&name);
+
+    if party_group_store::get_party_group_by_name(&name, &db)
+        .await?
+        .is_none()
+    {
+        return Err(party_group_not_found(&name));
+    }
+
+    let transaction = db.begin().await.context("error starting db transaction")?;
+
+    let members = party_group_store::get_party_group_members(&name, &transaction).await?;
+    party_group_store::delete_party_group(&name, &transaction).await?;
+
+    if !members.is_empty() {
+        log_event(
+            app_state.time_provider.now(),
+            name.clone(),
+            EventType::ArPartyGroupMembersRemoved(PartyGroupMembersEventMetadata {
+                group_name: name.clone(),
+                party_ids: members,
+            }),
+            None,
+            None,
+            &transaction,
+        )
+        .await
+        .context("Error logging party group members removed event")?;
+    }
+
+    transaction
+        .commit()
+        .await
+        .context("error commiting transaction to db")?;
+
+    app_state.decision_cache.invalidate_all();
+
+    Ok(())
+}
+
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+struct AddPartyGroupMember {
+    party_id: String,
+}
+
+/// Add a member to a party group (admin access)
+#[utoipa::path(
+    post,
+    path = "/admin/party-group/{name}/member",
+    tag = "Party Group - Admin",
+    params(
+        ("name" = String, Path, description = "Name of the party group")
+    ),
+    request_body(
+        content = AddPartyGroupMember,
+        description = "Party id of the new member",
+        content_type = "application/json"
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Member successfully added, returns the party group",
+            content_type = "application/json",
+            body = PartyGroupWithMembers
+        ),
+        (
+            status = 400,
+            description = "Invalid party",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unable to verify party group member 'NL.EORI' as valid iSHARE party"))
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 404,
+            description = "Party group not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Party group not found"))
+        )
+    )
+ )]
+async fn add_party_group_member(
+    Extension(db): Extension<DatabaseConnection>,
+    State(app_state): State<AppState>,
+    WithRejection(Path(name), _): WithRejection<Path<String>, AppError>,
+    WithRejection(Json(body), _): WithRejection<Json<AddPartyGroupMember>, AppError>,
+) -> Result<Json<PartyGroupWithMembers>, AppError> {
+    let name = normalize_party_group_name(&name);
+    let party_id = normalize_party_id(&body.party_id);
+
+    let Some(party_group) = party_group_store::get_party_group_by_name(&name, &db).await? else {
+        return Err(party_group_not_found(&name));
+    };
+
+    let members = party_group_store::get_party_group_members(&name, &db).await?;
+
+    if !members.contains(&party_id) {
+        validate_party_group_members(&vec![party_id.clone()], &app_state).await?;
+
+        let transaction = db.begin().await.context("error starting db transaction")?;
+
+        party_group_store::insert_party_group_members(&name, &vec![party_id.clone()], &transaction)
+            .await?;
+
+        log_event(
+            app_state.time_provider.now(),
+            name.clone(),
+            EventType::ArPartyGroupMembersAdded(PartyGroupMembersEventMetadata {
+                group_name: name.clone(),
+                party_ids: vec![party_id.clone()],
+            }),
+            None,
+            None,
+            &transaction,
+        )
+        .await
+        .context("Error logging party group members added event")?;
+
+        transaction
+            .commit()
+            .await
+            .context("error commiting transaction to db")?;
+
+        app_state.decision_cache.invalidate_all();
+    }
+
+    let members = party_group_store::get_party_group_members(&name, &db).await?;
+
+    Ok(Json(PartyGroupWithMembers {
+        name: party_group.name,
+        description: party_group.description,
+        members,
+    }))
+}
+
+/// Remove a member from a party group (admin access)
+#[utoipa::path(
+    delete,
+    path = "/admin/party-group/{name}/member/{party_id}",
+    tag = "Party Group - Admin",
+    params(
+        ("name" = String, Path, description = "Name of the party group"),
+        ("party_id" = String, Path, description = "Party id of the member")
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Member successfully removed"
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 404,
+            description = "Party group or member not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Party group member not found"))
+        )
+    )
+ )]
+async fn delete_party_group_member(
+    Extension(db): Extension<DatabaseConnection>,
+    State(app_state): State<AppState>,
+    WithRejection(Path((name, party_id)), _): WithRejection<Path<(String, String)>, AppError>,
+) -> Result<(), AppError> {
+    let name = normalize_party_group_name(&name);
+    let party_id = normalize_party_id(&party_id);
+
+    let transaction = db.begin().await.context("error starting db transaction")?;
+
+    let deleted =
+        party_group_store::delete_party_group_member(&name, &party_id, &transaction).await?;
+
+    if deleted == 0 {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::NOT_FOUND,
+            message: "Party group member not found".to_owned(),
+            reason: format!("'{}' is not a member of party group '{}'", party_id, name),
+            metadata: None,
+        }));
+    }
+
+    log_event(
+        app_state.time_provider.now(),
+        name.clone(),
+        EventType::ArPartyGroupMembersRemoved(PartyGroupMembersEventMetadata {
+            group_name: name.clone(),
+            party_ids: vec![party_id],
+        }),
+        None,
+        None,
+        &transaction,
+    )
+    .await
+    .context("Error logging party group members removed event")?;
+
+    transaction
+        .commit()
+        .await
+        .context("error commiting transaction to db")?;
+
+    app_state.decision_cache.invalidate_all();
+
+    Ok(())
+}
+
+#[derive(Deserialize)]
+struct GetPolicySetsQuery {
+    access_subject: Option<String>,
+    policy_issuer: Option<String>,
+    q: Option<String>,
+    limit: Option<u32>,
+    skip: Option<u32>,
+}
+
+/// List all policy sets with optional filtering, without the rejected ones (admin access)
+#[utoipa::path(
+    get,
+    path = "/admin/policy-set",
+    tag = "Policy Management - Admin",
+    params(
+        ("access_subject" = Option<String>, Query, description = "Filter by access subject"),
+        ("policy_issuer" = Option<String>, Query, description = "Filter by policy issuer"),
+        ("limit" = Option<u32>, Query, description = "Limit the number of results for pagination"),
+        ("skip" = Option<u32>, Query, description = "Skip a number of results for pagination"),
+        ("q" = Option<String>, Query, description = "Filter on any match in the policy set"),
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "List of policy sets matching the filter criteria",
+            content_type = "application/json",
+            body = Vec<MatchingPolicySetRow>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn get_all_policy_sets(
+    Query(query): Query<GetPolicySetsQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<PolicySetsWithPagination>, AppError> {
+    let policy_sets = policy_store::get_policy_sets_with_policies(
+        query.access_subject,
+        query.policy_issuer,
+        PartyMatch::Fuzzy,
+        query.q,
+        query.skip,
+        query.limit,
+        &db,
+    )
+    .await
+    .context("Error getting policicy sets")?;
+
+    Ok(Json(policy_sets))
+}
+
+/// List the deleted policy sets with optional filtering (admin access)
+#[utoipa::path(
+    get,
+    path = "/admin/policy-set/trash",
+    tag = "Policy Management - Admin",
+    params(
+        ("access_subject" = Option<String>, Query, description = "Filter by access subject"),
+        ("policy_issuer" = Option<String>, Query, description = "Filter by policy issuer"),
+        ("limit" = Option<u32>, Query, description = "Limit the number of results for pagination"),
+        ("skip" = Option<u32>, Query, description = "Skip a number of results for pagination"),
+        ("q" = Option<String>, Query, description = "Filter on any match in the policy set"),
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "List of deleted policy sets matching the filter criteria",
+            content_type = "application/json",
+            body = Vec<MatchingPolicySetRow>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn get_deleted_policy_sets(
+    Query(query): Query<GetPolicySetsQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<PolicySetsWithPagination>, AppError> {
+    let policy_sets = policy_store::get_deleted_policy_sets_with_policies(
+        query.access_subject,
+        query.policy_issuer,
+        PartyMatch::Fuzzy,
+        query.q,
+        query.skip,
+        query.limit,
+        &db,
+    )
+    .await
+    .context("Error getting deleted policy sets")?;
+
+    Ok(Json(policy_sets))
+}
+
+/// Restore a deleted policy set (admin access)
+#[utoipa::path(
+    post,
+    path = "/admin/policy-set/{id}/restore",
+    tag = "Policy Management - Admin",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the deleted policy set")
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Policy set successfully restored",
+            content_type = "application/json",
+            body = MatchingPolicySetRow
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 404,
+            description = "Deleted policy set not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Can't find deleted policy set"))
+        )
+    )
+ )]
+async fn restore_policy_set(
+    Extension(db): Extension<DatabaseConnection>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
+    State(app_state): State<AppState>,
+) -> Result<Response, AppError> {
+    let policy_set = policy_trash_service::restore_policy_set_admin(
+        app_state.time_provider.now(),
+        &id,
+        &db,
+        &app_state.decision_cache,
+    )
+    .await?;
+
+    Ok(edited_policy_set_response(policy_set))
+}
+
+/// List the pending policy sets with optional filtering (admin access)
+#[utoipa::path(
+    get,
+    path = "/admin/policy-set/pending",
+    tag = "Policy Management - Admin",
+    params(
+        ("access_subject" = Option<String>, Query, description = "Filter by access subject"),
+        ("policy_issuer" = Option<String>, Query, description = "Filter by policy issuer"),
+        ("limit" = Option<u32>, Query, description = "Limit the number of results for pagination"),
+        ("skip" = Option<u32>, Query, description = "Skip a number of results for pagination"),
+        ("q" = Option<String>, Query, description = "Filter on any match in the policy set"),
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "List of pending policy sets matching the filter criteria",
+            content_type = "application/json",
+            body = Vec<MatchingPolicySetRow>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn get_pending_policy_sets(
+    Query(query): Query<GetPolicySetsQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<PolicySetsWithPagination>, AppError> {
+    let policy_sets = policy_store::get_pending_policy_sets_with_policies(
+        query.access_subject,
+        query.policy_issuer,
+        PartyMatch::Fuzzy,
+        query.q,
+        query.skip,
+        query.limit,
+        &db,
+    )
+    .await
+    .context("Error getting pending policy sets")?;
+
+    Ok(Json(policy_sets))
+}
+
+/// List the rejected policy sets with optional filtering (admin access)
+#[utoipa::path(
+    get,
+    path = "/admin/policy-set/rejected",
+    tag = "Policy Management - Admin",
+    params(
+        ("access_subject" = Option<String>, Query, description = "Filter by access subject"),
+        ("policy_issuer" = Option<String>, Query, description = "Filter by policy issuer"),
+        ("limit" = Option<u32>, Query, description = "Limit the number of results for pagination"),
+        ("skip" = Option<u32>, Query, description = "Skip a number of results for pagination"),
+        ("q" = Option<String>, Query, description = "Filter on any match in the policy set"),
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "List of rejected policy sets matching the filter criteria",
+            content_type = "application/json",
+            body = Vec<MatchingPolicySetRow>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn get_rejected_policy_sets(
+    Query(query): Query<GetPolicySetsQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<PolicySetsWithPagination>, AppError> {
+    let policy_sets = policy_store::get_rejected_policy_sets_with_policies(
+        query.access_subject,
+        query.policy_issuer,
+        PartyMatch::Fuzzy,
+        query.q,
+        query.skip,
+        query.limit,
+        &db,
+    )
+    .await
+    .context("Error getting rejected policy sets")?;
+
+    Ok(Json(policy_sets))
+}
+
+/// Approve a pending policy set (admin access)
+#[utoipa::path(
+    post,
+    path = "/admin/policy-set/{id}/approve",
+    tag = "Policy Management - Admin",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the pending policy set"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Policy set successfully approved",
+            content_type = "application/json",
+            body = MatchingPolicySetRow
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 404,
+            description = "Policy set not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Can't find policy set"))
+        ),
+        (
+            status = 409,
+            description = "The policy set isn't pending approval",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set is not pending approval"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
+        )
+    )
+ )]
+async fn approve_policy_set(
+    Extension(db): Extension<DatabaseConnection>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let policy_set = policy_approval_service::approve_policy_set_admin(
+        app_state.time_provider.now(),
+        &id,
+        &extract_if_match_revisions(&headers),
+        &db,
+        &app_state.decision_cache,
+    )
+    .await?;
+
+    Ok(edited_policy_set_response(policy_set))
+}
+
+/// Reject a pending policy set (admin access)
+#[utoipa::path(
+    post,
+    path = "/admin/policy-set/{id}/reject",
+    tag = "Policy Management - Admin",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the pending policy set"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Policy set successfully rejected",
+            content_type = "application/json",
+            body = MatchingPolicySetRow
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 404,
+            description = "Policy set not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Can't find policy set"))
+        ),
+        (
+            status = 409,
+            description = "The policy set isn't pending approval",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set is not pending approval"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
+        )
+    )
+ )]
+async fn reject_policy_set(
+    Extension(db): Extension<DatabaseConnection>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let policy_set = policy_approval_service::reject_policy_set_admin(
+        app_state.time_provider.now(),
+        &id,
+        &extract_if_match_revisions(&headers),
+        &db,
+        &app_state.decision_cache,
+    )
+    .await?;
+
+    Ok(edited_policy_set_response(policy_set))
+}
+
+#[derive(Deserialize)]
+struct ExportPolicySetsQuery {
+    access_subject: Option<String>,
+    policy_issuer: Option<String>,
+    q: Option<String>,
+    #[serde(default)]
+    format: ExportFormat,
+}
+
+/// Export policy sets with optional filtering, as JSON or NDJSON (admin access)
+#[utoipa::path(
+    get,
+    path = "/admin/policy-set/export",
+    tag = "Policy Management - Admin",
+    params(
+        ("access_subject" = Option<String>, Query, description = "Filter by access subject"),
+        ("policy_issuer" = Option<String>, Query, description = "Filter by policy issuer"),
+        ("q" = Option<String>, Query, description = "Filter on any match in the policy set"),
+        ("format" = Option<ExportFormat>, Query, description = "'json' (default) or 'ndjson'"),
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "The policy sets with their policies, in a form that can be imported",
+            content_type = "application/json",
+            body = Vec<ExportedPolicySet>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn export_policy_sets(
+    Query(query): Query<ExportPolicySetsQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<([(HeaderName, &'static str); 1], String), AppError> {
+    let policy_sets = policy_bulk_service::export_policy_sets(
+        query.access_subject,
+        query.policy_issuer,
+        PartyMatch::Fuzzy,
+        query.q,
+        &db,
+    )
+    .await?;
+
+    let (content_type, body) = policy_bulk_service::serialize_export(&policy_sets, query.format)?;
+
+    Ok(([(CONTENT_TYPE, content_type)], body))
+}
+
+#[derive(Deserialize)]
+struct ImportPolicySetsQuery {
+    #[serde(default)]
+    mode: ImportMode,
+    #[serde(default)]
+    dry_run: bool,
+}
+
+/// Import policy sets from a JSON array or NDJSON, all or none are imported (admin access)
+#[utoipa::path(
+    post,
+    path = "/admin/policy-set/import",
+    tag = "Policy Management - Admin",
+    params(
+        ("mode" = Option<ImportMode>, Query, description = "'create_only' (default) rejects policy sets that already exist, 'upsert' overwrites them"),
+        ("dry_run" = Option<bool>, Query, description = "Only validate the import"),
+    ),
+    request_body(
+        content = Vec<ExportedPolicySet>,
+        description = "Policy sets as exported, as a JSON array or as NDJSON with content type 'application/x-ndjson'",
+        content_type = "application/json"
+    ),
+    security(
+        ("h2m_bearer_admin" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "The policy sets that are, or in a dry run would be, created and updated",
+            content_type = "application/json",
+            body = PolicySetImportResult
+        ),
+        (
+            status = 400,
+            description = "One or more policy sets can't be imported, they are listed in 'entryErrors' of the metadata",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Invalid policy set import"))
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn import_policy_sets(
+    Query(query): Query<ImportPolicySetsQuery>,
+    Extension(db): Extension<DatabaseConnection>,
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Json<PolicySetImportResult>, AppError> {
+    let content_type = headers.get(CONTENT_TYPE).and_then(|ct| ct.to_str().ok());
+    let policy_sets = policy_bulk_service::parse_import(content_type, &body)?;
+
+    let result = policy_bulk_service::import_policy_sets(
+        app_state.time_provider.now(),
+        policy_sets,
+        query.mode,
+        query.dry_run,
+        app_state.satellite_provider,
+        &db,
+        &app_state.decision_cache,
+    )
+    .await?;
+
+    Ok(Json(result))
 }
 
 #[cfg(test)]
@@ -718,7 +2314,7 @@ mod test {
         http::{Request, StatusCode},
     };
     use http_body_util::BodyExt;
-    use reqwest::header::AUTHORIZATION;
+    use reqwest::header::{AUTHORIZATION, ETAG, IF_MATCH, IF_NONE_MATCH};
     use serde_json::json;
     use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
     use tower::ServiceExt;
@@ -774,6 +2370,67 @@ mod test {
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn test_insert_policy_set_template_invalid_policy(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        let app = get_test_app(db);
+
+        let template = |identifiers: serde_json::Value, rule: serde_json::Value| {
+            json!({
+                "name": "Usual dexspace data consumer stuff",
+                "policies": [
+                  {
+                    "resource_type": "Fishes",
+                    "identifiers": identifiers,
+                    "attributes": ["*"],
+                    "actions": ["Read"],
+                    "service_providers": ["NL.EORI.LIFEELEC4DMI"],
+                    "rules": [rule]
+                  }
+                ]
+            })
+        };
+
+        // the policies of a template are validated like the policies of a policy set
+        for (template, status) in [
+            (
+                template(json!(["regex:("]), json!({ "effect": "Permit" })),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                template(
+                    json!(["*"]),
+                    json!({
+                        "effect": "Permit",
+                        "conditions": [{
+                            "type": "TimeWindow",
+                            "start": "08:00",
+                            "end": "18:00",
+                            "timezone": "Mars/Olympus_Mons"
+                        }]
+                    }),
+                ),
+                StatusCode::BAD_REQUEST,
+            ),
+            (
+                template(
+                    json!(["regex:^fish-[0-9]+$"]),
+                    json!({ "effect": "Permit" }),
+                ),
+                StatusCode::OK,
+            ),
+        ] {
+            let response =
+                admin_request(&app, "POST", "/admin/policy-set-template", Some(template)).await;
+            assert_eq!(response.status(), status);
+        }
+
+        Ok(())
+    }
+
     #[sqlx::test]
     async fn test_insert_policy_set_template_with_description(
         _pool_options: PgPoolOptions,
@@ -806,14 +2463,205 @@ mod test {
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/admin/policy-set-template")
-                    .method("POST")
+                    .uri("/admin/policy-set-template")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_insert_delete_policy_set_template(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        let app = get_test_app(db.clone());
+
+        let request_body = create_request_body(&json!({
+            "name": "Usual dexspace data consumer stuff",
+            "access_subject": "hello",
+            "policy_issuer": "hello again",
+            "policies": [
+              {
+                "resource_type": "Fishes",
+                "identifiers": ["*"],
+                "attributes": ["*"],
+                "actions": ["Read", "Delete", "Create", "Edit"],
+                "service_providers": ["NL.EORI.LIFEELEC4DMI"],
+                "rules": [
+                  {
+                    "effect": "Permit"
+                  }
+                ]
+              }
+            ]
+        }));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/policy-set-template")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let body: InsertPolicySetTemplateResponse = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        let app = get_test_app(db);
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri(format!("/admin/policy-set-template/{}", body.uuid))
+                    .method("DELETE")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_get_policy_sets(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
+
+        let app = get_test_app(db);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/policy-set")
+                    .method("GET")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: PolicySetsWithPagination = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(body.data.len(), 5);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_get_policy_sets_limit(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
+
+        let app = get_test_app(db);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/policy-set?limit=2")
+                    .method("GET")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: PolicySetsWithPagination = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(body.data.len(), 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_get_policy_sets_skip(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
+
+        let _policy_set4 = load_policy_set_fixture("./fixtures/policy_set4.json");
+
+        let app = get_test_app(db);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/policy-set?skip=3")
+                    .method("GET")
                     .header(
                         AUTHORIZATION,
                         server_token::server_token_test_helper::get_human_token_header(None, None),
                     )
                     .header("Content-Type", "application/json")
-                    .body(Body::new(request_body))
+                    .body(Body::empty())
                     .unwrap(),
             )
             .await
@@ -821,68 +2669,84 @@ mod test {
 
         assert_eq!(response.status(), StatusCode::OK);
 
+        let body: PolicySetsWithPagination = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(body.data.len(), 2);
+
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_insert_delete_policy_set_template(
+    async fn test_get_policy_sets_skip_limit(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
-        let app = get_test_app(db.clone());
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
 
-        let request_body = create_request_body(&json!({
-            "name": "Usual dexspace data consumer stuff",
-            "access_subject": "hello",
-            "policy_issuer": "hello again",
-            "policies": [
-              {
-                "resource_type": "Fishes",
-                "identifiers": ["*"],
-                "attributes": ["*"],
-                "actions": ["Read", "Delete", "Create", "Edit"],
-                "service_providers": ["NL.EORI.LIFEELEC4DMI"],
-                "rules": [
-                  {
-                    "effect": "Permit"
-                  }
-                ]
-              }
-            ]
-        }));
+        let _policy_set4 = load_policy_set_fixture("./fixtures/policy_set4.json");
+
+        let app = get_test_app(db);
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/admin/policy-set-template")
-                    .method("POST")
+                    .uri("/admin/policy-set?skip=2&limit=2")
+                    .method("GET")
                     .header(
                         AUTHORIZATION,
                         server_token::server_token_test_helper::get_human_token_header(None, None),
                     )
                     .header("Content-Type", "application/json")
-                    .body(Body::new(request_body))
+                    .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        let body: InsertPolicySetTemplateResponse = serde_json::from_str(
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: PolicySetsWithPagination = serde_json::from_str(
             std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
         )
         .unwrap();
 
+        assert_eq!(body.data.len(), 2);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_get_policy_sets_filter_as(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
+
         let app = get_test_app(db);
+
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri(format!("/admin/policy-set-template/{}", body.uuid))
-                    .method("DELETE")
+                    .uri("/admin/policy-set?access_subject=NL.44444")
+                    .method("GET")
                     .header(
                         AUTHORIZATION,
                         server_token::server_token_test_helper::get_human_token_header(None, None),
                     )
+                    .header("Content-Type", "application/json")
                     .body(Body::empty())
                     .unwrap(),
             )
@@ -891,11 +2755,18 @@ mod test {
 
         assert_eq!(response.status(), StatusCode::OK);
 
+        let body: PolicySetsWithPagination = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(body.data.len(), 4);
+
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_get_policy_sets(
+    async fn test_get_policy_sets_query_as(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
@@ -903,15 +2774,15 @@ mod test {
         insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set7.json", &db).await;
 
         let app = get_test_app(db);
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/admin/policy-set")
+                    .uri("/admin/policy-set?q=NL.CONSUME")
                     .method("GET")
                     .header(
                         AUTHORIZATION,
@@ -931,13 +2802,13 @@ mod test {
         )
         .unwrap();
 
-        assert_eq!(body.data.len(), 5);
+        assert_eq!(body.data.len(), 1);
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_get_policy_sets_limit(
+    async fn test_get_policy_sets_query_pi(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
@@ -945,15 +2816,15 @@ mod test {
         insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set6.json", &db).await;
 
         let app = get_test_app(db);
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/admin/policy-set?limit=2")
+                    .uri("/admin/policy-set?q=NL.CONSUME")
                     .method("GET")
                     .header(
                         AUTHORIZATION,
@@ -973,13 +2844,13 @@ mod test {
         )
         .unwrap();
 
-        assert_eq!(body.data.len(), 2);
+        assert_eq!(body.data.len(), 1);
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_get_policy_sets_skip(
+    async fn test_get_policy_sets_filter_resource_type(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
@@ -987,17 +2858,15 @@ mod test {
         insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
-
-        let _policy_set4 = load_policy_set_fixture("./fixtures/policy_set4.json");
+        insert_policy_set_fixture("./fixtures/policy_set6.json", &db).await;
 
         let app = get_test_app(db);
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/admin/policy-set?skip=3")
+                    .uri("/admin/policy-set?q=Amazinggg")
                     .method("GET")
                     .header(
                         AUTHORIZATION,
@@ -1017,13 +2886,13 @@ mod test {
         )
         .unwrap();
 
-        assert_eq!(body.data.len(), 2);
+        assert_eq!(body.data.len(), 1);
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_get_policy_sets_skip_limit(
+    async fn test_get_policy_sets_filter_identifier(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
@@ -1033,15 +2902,14 @@ mod test {
         insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
-
-        let _policy_set4 = load_policy_set_fixture("./fixtures/policy_set4.json");
+        insert_policy_set_fixture("./fixtures/policy_set6.json", &db).await;
 
         let app = get_test_app(db);
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/admin/policy-set?skip=2&limit=2")
+                    .uri("/admin/policy-set?q=CrazyID")
                     .method("GET")
                     .header(
                         AUTHORIZATION,
@@ -1061,13 +2929,13 @@ mod test {
         )
         .unwrap();
 
-        assert_eq!(body.data.len(), 2);
+        assert_eq!(body.data.len(), 1);
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_get_policy_sets_filter_as(
+    async fn test_get_policy_sets_filter_att(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
@@ -1077,13 +2945,14 @@ mod test {
         insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set6.json", &db).await;
 
         let app = get_test_app(db);
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/admin/policy-set?access_subject=NL.44444")
+                    .uri("/admin/policy-set?q=CrazyATT")
                     .method("GET")
                     .header(
                         AUTHORIZATION,
@@ -1103,13 +2972,13 @@ mod test {
         )
         .unwrap();
 
-        assert_eq!(body.data.len(), 4);
+        assert_eq!(body.data.len(), 1);
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_get_policy_sets_query_as(
+    async fn test_get_policy_sets_filter_sp(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
@@ -1117,15 +2986,16 @@ mod test {
         insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set7.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set6.json", &db).await;
 
         let app = get_test_app(db);
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/admin/policy-set?q=NL.CONSUME")
+                    .uri("/admin/policy-set?q=CrazyATT")
                     .method("GET")
                     .header(
                         AUTHORIZATION,
@@ -1151,7 +3021,7 @@ mod test {
     }
 
     #[sqlx::test]
-    async fn test_get_policy_sets_query_pi(
+    async fn test_get_policy_sets_filter_pi(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
@@ -1159,15 +3029,15 @@ mod test {
         insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
         insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set6.json", &db).await;
 
         let app = get_test_app(db);
 
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/admin/policy-set?q=NL.CONSUME")
+                    .uri("/admin/policy-set?policy_issuer=NL.44444")
                     .method("GET")
                     .header(
                         AUTHORIZATION,
@@ -1193,30 +3063,81 @@ mod test {
     }
 
     #[sqlx::test]
-    async fn test_get_policy_sets_filter_resource_type(
+    async fn test_insert_policy_set(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set6.json", &db).await;
 
         let app = get_test_app(db);
 
+        let request_body = create_request_body(&json!(
+            {
+                "policies": [{
+                    "target": {
+                        "resource": {
+                            "type": "test-iden2",
+                            "identifiers": ["test", "test-2"],
+                            "attributes": ["*"]
+                        },
+                        "actions": ["Read"],
+                        "environment": {
+                            "serviceProviders": ["asdf"]
+                        }
+                    },
+                    "rules": [
+                        {
+                            "effect": "Permit"
+                        }
+                    ]
+                }, {
+                    "target": {
+                        "resource": {
+                            "type": "test-iden",
+                            "identifiers": ["test4", "test-5"],
+                            "attributes": ["*"]
+                        },
+                        "actions": ["*"],
+                        "environment": {
+                            "serviceProviders": ["fffd"]
+                        }
+                    },
+                    "rules": [
+                        {
+                            "effect": "Permit"
+                        },
+                        {
+                            "effect": "Deny",
+                            "target": {
+                                "resource": {
+                                    "attributes": ["zinger"],
+                                    "identifiers": ["*"],
+                                    "type":"test-iden"
+                                },
+                                "actions": ["*"]
+                            }
+                        }
+                    ]
+                }],
+                "target": {
+                    "accessSubject": "sadfasdf"
+                },
+                "policyIssuer": "sss",
+                "licences": [],
+                "maxDelegationDepth": 2
+        }));
+
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/admin/policy-set?q=Amazinggg")
-                    .method("GET")
+                    .uri("/admin/policy-set")
+                    .method("POST")
                     .header(
                         AUTHORIZATION,
                         server_token::server_token_test_helper::get_human_token_header(None, None),
                     )
                     .header("Content-Type", "application/json")
-                    .body(Body::empty())
+                    .body(Body::new(request_body))
                     .unwrap(),
             )
             .await
@@ -1224,268 +3145,755 @@ mod test {
 
         assert_eq!(response.status(), StatusCode::OK);
 
-        let body: PolicySetsWithPagination = serde_json::from_str(
+        Ok(())
+    }
+
+    async fn admin_request(
+        app: &axum::Router,
+        method: &str,
+        uri: &str,
+        body: Option<serde_json::Value>,
+    ) -> axum::response::Response {
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .uri(uri)
+                    .method(method)
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(match body {
+                        Some(body) => Body::new(create_request_body(&body)),
+                        None => Body::empty(),
+                    })
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_license_registry(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        let app = get_test_app(db.clone());
+
+        let policy_set = json!({
+            "policies": [{
+                "target": {
+                    "resource": {
+                        "type": "test-license",
+                        "identifiers": ["*"],
+                        "attributes": ["*"]
+                    },
+                    "actions": ["Read"],
+                    "environment": {
+                        "serviceProviders": ["asdf"]
+                    }
+                },
+                "rules": [
+                    {
+                        "effect": "Permit"
+                    }
+                ]
+            }],
+            "target": {
+                "accessSubject": "sadfasdf"
+            },
+            "policyIssuer": "sss",
+            "licences": ["ISHARE.0001"],
+            "maxDelegationDepth": 0
+        });
+
+        // the license is not registered yet
+        let response =
+            admin_request(&app, "POST", "/admin/policy-set", Some(policy_set.clone())).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let license = json!({
+            "code": "ISHARE.0001",
+            "description": "Data may only be used for the purpose it was provided for"
+        });
+        let response = admin_request(&app, "POST", "/admin/license", Some(license.clone())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = admin_request(&app, "POST", "/admin/license", Some(license)).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        let response = admin_request(&app, "GET", "/admin/license", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Vec<ar_entity::license::Model> = serde_json::from_str(
             std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
         )
         .unwrap();
+        assert_eq!(body.len(), 1);
 
-        assert_eq!(body.data.len(), 1);
+        let response = admin_request(&app, "POST", "/admin/policy-set", Some(policy_set)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: serde_json::Value = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+        let policy_set_id = body["uuid"].as_str().unwrap().to_owned();
+
+        // the license can't be removed while a policy set grants it
+        let response = admin_request(&app, "DELETE", "/admin/license/ISHARE.0001", None).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        // policy sets in the trash still grant it until they are purged
+        let response = admin_request(
+            &app,
+            "DELETE",
+            &format!("/admin/policy-set/{}", policy_set_id),
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = admin_request(&app, "DELETE", "/admin/license/ISHARE.0001", None).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        crate::services::policy_trash::purge_deleted_policy_sets(chrono::Utc::now(), 0, &db)
+            .await
+            .unwrap();
+
+        let response = admin_request(&app, "DELETE", "/admin/license/ISHARE.0001", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = admin_request(&app, "DELETE", "/admin/license/ISHARE.0001", None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_get_policy_sets_filter_identifier(
+    async fn test_resource_type_registry(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set6.json", &db).await;
-
         let app = get_test_app(db);
 
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .uri("/admin/policy-set?q=CrazyID")
-                    .method("GET")
-                    .header(
-                        AUTHORIZATION,
-                        server_token::server_token_test_helper::get_human_token_header(None, None),
-                    )
-                    .header("Content-Type", "application/json")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
+        let policy_set = |identifier: &str, action: &str| {
+            json!({
+                "policies": [{
+                    "target": {
+                        "resource": {
+                            "type": "test-container",
+                            "identifiers": [identifier],
+                            "attributes": ["*"]
+                        },
+                        "actions": [action],
+                        "environment": {
+                            "serviceProviders": ["asdf"]
+                        }
+                    },
+                    "rules": [
+                        {
+                            "effect": "Permit"
+                        }
+                    ]
+                }],
+                "target": {
+                    "accessSubject": "sadfasdf"
+                },
+                "policyIssuer": "sss",
+                "licences": [],
+                "maxDelegationDepth": 0
+            })
+        };
+
+        // an empty registry doesn't validate the resource types
+        let response = admin_request(
+            &app,
+            "POST",
+            "/admin/policy-set",
+            Some(policy_set("truck-1", "Fly")),
+        )
+        .await;
         assert_eq!(response.status(), StatusCode::OK);
 
-        let body: PolicySetsWithPagination = serde_json::from_str(
+        let response = admin_request(
+            &app,
+            "POST",
+            "/admin/resource-type",
+            Some(json!({ "name": "test-container", "description": "", "actions": [] })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let resource_type = json!({
+            "name": "test-container",
+            "description": "Containers",
+            "actions": ["Read"],
+            "identifierFormat": "urn:container:*"
+        });
+        let response = admin_request(
+            &app,
+            "POST",
+            "/admin/resource-type",
+            Some(resource_type.clone()),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response =
+            admin_request(&app, "POST", "/admin/resource-type", Some(resource_type)).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        // once the registry is in use every resource type has to be registered
+        let mut truck_policy_set = policy_set("truck-1", "Read");
+        truck_policy_set["policies"][0]["target"]["resource"]["type"] = json!("test-truck");
+        let response =
+            admin_request(&app, "POST", "/admin/policy-set", Some(truck_policy_set)).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = serde_json::from_str(
             std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
         )
         .unwrap();
+        assert_eq!(
+            body["metadata"]["fieldErrors"][0]["field"],
+            "policies[0].target.resource.type"
+        );
+
+        let response = admin_request(
+            &app,
+            "POST",
+            "/admin/policy-set",
+            Some(policy_set("truck-1", "Fly")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: serde_json::Value = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+        let fields: Vec<&str> = body["metadata"]["fieldErrors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|e| e["field"].as_str().unwrap())
+            .collect();
+        assert_eq!(
+            fields,
+            vec![
+                "policies[0].target.actions[0]",
+                "policies[0].target.resource.identifiers[0]"
+            ]
+        );
 
-        assert_eq!(body.data.len(), 1);
-
-        Ok(())
-    }
-
-    #[sqlx::test]
-    async fn test_get_policy_sets_filter_att(
-        _pool_options: PgPoolOptions,
-        conn_option: PgConnectOptions,
-    ) -> sqlx::Result<()> {
-        let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set6.json", &db).await;
+        let response = admin_request(
+            &app,
+            "POST",
+            "/admin/policy-set",
+            Some(policy_set("urn:container:1", "Read")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
 
-        let app = get_test_app(db);
+        let template = |action: &str| {
+            json!({
+                "name": "Containers",
+                "policies": [{
+                    "resource_type": "test-container",
+                    "identifiers": ["*"],
+                    "attributes": ["*"],
+                    "actions": [action],
+                    "service_providers": [],
+                    "rules": [{ "effect": "Permit" }]
+                }]
+            })
+        };
+        let response = admin_request(
+            &app,
+            "POST",
+            "/admin/policy-set-template",
+            Some(template("Delete")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = admin_request(
+            &app,
+            "PUT",
+            "/admin/resource-type/test-container",
+            Some(json!({ "description": "Containers", "actions": ["Read", "Delete"] })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = admin_request(
+            &app,
+            "POST",
+            "/admin/policy-set-template",
+            Some(template("Delete")),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
 
+        // the registry is available to every authenticated party
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .uri("/admin/policy-set?q=CrazyATT")
+                    .uri("/resource-type/test-container")
                     .method("GET")
                     .header(
                         AUTHORIZATION,
-                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                        server_token::server_token_test_helper::get_machine_token_header(None),
                     )
-                    .header("Content-Type", "application/json")
                     .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
-
         assert_eq!(response.status(), StatusCode::OK);
-
-        let body: PolicySetsWithPagination = serde_json::from_str(
+        let body: ar_entity::resource_type::Model = serde_json::from_str(
             std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
         )
         .unwrap();
+        assert_eq!(body.actions, vec!["Read", "Delete"]);
+        assert_eq!(body.identifier_format, None);
 
-        assert_eq!(body.data.len(), 1);
+        let response =
+            admin_request(&app, "DELETE", "/admin/resource-type/test-container", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response =
+            admin_request(&app, "DELETE", "/admin/resource-type/test-container", None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_get_policy_sets_filter_sp(
+    async fn test_party_group_membership(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
+        use sea_orm::{EntityTrait, QueryOrder};
+
         let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set6.json", &db).await;
+        let app = get_test_app(db.clone());
 
-        let app = get_test_app(db);
+        let policy_set = json!({
+            "policies": [{
+                "target": {
+                    "resource": {
+                        "type": "test-party-group",
+                        "identifiers": ["*"],
+                        "attributes": ["*"]
+                    },
+                    "actions": ["Read"],
+                    "environment": {
+                        "serviceProviders": ["group:terminals"]
+                    }
+                },
+                "rules": [
+                    {
+                        "effect": "Permit"
+                    }
+                ]
+            }],
+            "target": {
+                "accessSubject": "group:carriers"
+            },
+            "policyIssuer": "sss",
+            "licences": [],
+            "maxDelegationDepth": 0
+        });
+
+        // the party groups don't exist yet
+        let response =
+            admin_request(&app, "POST", "/admin/policy-set", Some(policy_set.clone())).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let carriers = json!({
+            "name": "carriers",
+            "description": "Carriers of the dataspace",
+            "members": ["nl.carrier.1", "NL.CARRIER.2"]
+        });
+        let response =
+            admin_request(&app, "POST", "/admin/party-group", Some(carriers.clone())).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = admin_request(&app, "POST", "/admin/party-group", Some(carriers)).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let response = admin_request(
+            &app,
+            "POST",
+            "/admin/party-group",
+            Some(json!({ "name": "terminals", "description": "Terminals" })),
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
 
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .uri("/admin/policy-set?q=CrazyATT")
-                    .method("GET")
-                    .header(
-                        AUTHORIZATION,
-                        server_token::server_token_test_helper::get_human_token_header(None, None),
-                    )
-                    .header("Content-Type", "application/json")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
+        let response = admin_request(&app, "POST", "/admin/policy-set", Some(policy_set)).await;
+        assert_eq!(response.status(), StatusCode::OK);
 
+        let response = admin_request(
+            &app,
+            "POST",
+            "/admin/party-group/terminals/member",
+            Some(json!({ "partyId": "NL.TERMINAL" })),
+        )
+        .await;
         assert_eq!(response.status(), StatusCode::OK);
 
-        let body: PolicySetsWithPagination = serde_json::from_str(
+        let response = admin_request(
+            &app,
+            "DELETE",
+            "/admin/party-group/CARRIERS/member/NL.CARRIER.1",
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = admin_request(
+            &app,
+            "DELETE",
+            "/admin/party-group/CARRIERS/member/NL.CARRIER.1",
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = admin_request(&app, "GET", "/admin/party-group", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Vec<crate::db::party_group::PartyGroupWithMembers> = serde_json::from_str(
             std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
         )
         .unwrap();
+        assert_eq!(
+            body.iter()
+                .map(|g| (g.name.as_str(), g.members.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("CARRIERS", vec!["NL.CARRIER.2".to_owned()]),
+                ("TERMINALS", vec!["NL.TERMINAL".to_owned()])
+            ]
+        );
 
-        assert_eq!(body.data.len(), 1);
+        let response = admin_request(&app, "DELETE", "/admin/party-group/carriers", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = admin_request(&app, "DELETE", "/admin/party-group/carriers", None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let events = ar_entity::audit_event::Entity::find()
+            .order_by_asc(ar_entity::audit_event::Column::Timestamp)
+            .all(&db)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|e| e.event_type.starts_with("dmi:ar:party_group:"))
+            .map(|e| (e.event_type, e.context.unwrap()["party_ids"].clone()))
+            .collect::<Vec<_>>();
+        assert_eq!(events.len(), 4);
+        assert!(events.contains(&(
+            "dmi:ar:party_group:members_added".to_owned(),
+            json!(["NL.CARRIER.1", "NL.CARRIER.2"])
+        )));
+        assert!(events.contains(&(
+            "dmi:ar:party_group:members_added".to_owned(),
+            json!(["NL.TERMINAL"])
+        )));
+        assert!(events.contains(&(
+            "dmi:ar:party_group:members_removed".to_owned(),
+            json!(["NL.CARRIER.1"])
+        )));
+        assert!(events.contains(&(
+            "dmi:ar:party_group:members_removed".to_owned(),
+            json!(["NL.CARRIER.2"])
+        )));
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_get_policy_sets_filter_pi(
+    async fn test_policy_set_import_export(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
         insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set5.json", &db).await;
-
         let app = get_test_app(db);
 
+        let import = |query: &str, content_type: &str, body: String| {
+            Request::builder()
+                .uri(format!("/admin/policy-set/import{}", query))
+                .method("POST")
+                .header(
+                    AUTHORIZATION,
+                    server_token::server_token_test_helper::get_human_token_header(None, None),
+                )
+                .header("Content-Type", content_type)
+                .body(Body::new(body))
+                .unwrap()
+        };
+        let body_json = |response: axum::response::Response| async {
+            let bytes = response.into_body().collect().await.unwrap().to_bytes();
+            serde_json::from_slice::<serde_json::Value>(&bytes).unwrap()
+        };
+
+        let response =
+            admin_request(&app, "GET", "/admin/policy-set/export?format=ndjson", None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("content-type").unwrap(),
+            "application/x-ndjson"
+        );
+        let export = String::from_utf8(
+            response
+                .into_body()
+                .collect()
+                .await
+                .unwrap()
+                .to_bytes()
+                .to_vec(),
+        )
+        .unwrap();
+        assert_eq!(export.lines().count(), 1);
+        let mut exported: serde_json::Value =
+            serde_json::from_str(export.lines().next().unwrap()).unwrap();
+        assert_eq!(exported["id"], "84b7fba4-05f3-4af8-9d84-dde384abe881");
+        assert_eq!(exported["policies"].as_array().unwrap().len(), 1);
+
+        // existing policy sets are not overwritten when only creating
         let response = app
-            .oneshot(
-                Request::builder()
-                    .uri("/admin/policy-set?policy_issuer=NL.44444")
-                    .method("GET")
-                    .header(
-                        AUTHORIZATION,
-                        server_token::server_token_test_helper::get_human_token_header(None, None),
-                    )
-                    .header("Content-Type", "application/json")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
+            .clone()
+            .oneshot(import("", "application/x-ndjson", export.clone()))
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let error = body_json(response).await;
+        assert_eq!(error["metadata"]["entryErrors"][0]["index"], 0);
+
+        exported["maxDelegationDepth"] = json!(0);
+        let mut new_policy_set = exported.clone();
+        new_policy_set.as_object_mut().unwrap().remove("id");
+        let policy_sets = json!([exported, new_policy_set]);
 
+        let response = app
+            .clone()
+            .oneshot(import(
+                "?mode=upsert&dry_run=true",
+                "application/json",
+                policy_sets.to_string(),
+            ))
+            .await
+            .unwrap();
         assert_eq!(response.status(), StatusCode::OK);
+        let result = body_json(response).await;
+        assert_eq!(result["dryRun"], true);
+        assert_eq!(result["policySets"][0]["action"], "updated");
+        assert_eq!(result["policySets"][1]["action"], "created");
+
+        let response = admin_request(&app, "GET", "/admin/policy-set", None).await;
+        assert_eq!(
+            body_json(response).await["data"].as_array().unwrap().len(),
+            1
+        );
+
+        // nothing is imported when one of the policy sets is invalid
+        let mut invalid = new_policy_set.clone();
+        invalid["licences"] = json!(["ISHARE.9999"]);
+        let response = app
+            .clone()
+            .oneshot(import(
+                "?mode=upsert",
+                "application/json",
+                json!([exported, invalid]).to_string(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let error = body_json(response).await;
+        assert_eq!(error["metadata"]["entryErrors"][0]["index"], 1);
 
-        let body: PolicySetsWithPagination = serde_json::from_str(
-            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        let response = app
+            .clone()
+            .oneshot(import(
+                "?mode=upsert",
+                "application/json",
+                policy_sets.to_string(),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let result = body_json(response).await;
+        assert_eq!(result["dryRun"], false);
+        assert!(result["policySets"][1]["id"].is_string());
+
+        let response = admin_request(
+            &app,
+            "GET",
+            "/admin/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881",
+            None,
         )
-        .unwrap();
+        .await;
+        assert_eq!(body_json(response).await["max_delegation_depth"], 0);
 
-        assert_eq!(body.data.len(), 1);
+        let response = admin_request(&app, "GET", "/admin/policy-set", None).await;
+        assert_eq!(
+            body_json(response).await["data"].as_array().unwrap().len(),
+            2
+        );
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_insert_policy_set(
+    async fn test_replace_policy_with_if_match(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
-
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
         let app = get_test_app(db);
 
-        let request_body = create_request_body(&json!(
-            {
-                "policies": [{
+        let uri = "/admin/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881";
+        let replace = |identifier: &str| {
+            Request::builder()
+                .uri(format!(
+                    "{}/policy/564f3b46-7127-4c3c-a0b8-2859c01cc9c1",
+                    uri
+                ))
+                .method("PUT")
+                .header(
+                    AUTHORIZATION,
+                    server_token::server_token_test_helper::get_human_token_header(None, None),
+                )
+                .header("Content-Type", "application/json")
+                .header(IF_MATCH, "\"1\"")
+                .body(Body::new(create_request_body(&json!({
                     "target": {
                         "resource": {
-                            "type": "test-iden2",
-                            "identifiers": ["test", "test-2"],
+                            "type": "TestResource",
+                            "identifiers": [identifier],
                             "attributes": ["*"]
                         },
                         "actions": ["Read"],
                         "environment": {
-                            "serviceProviders": ["asdf"]
-                        }
-                    },
-                    "rules": [
-                        {
-                            "effect": "Permit"
-                        }
-                    ]
-                }, {
-                    "target": {
-                        "resource": {
-                            "type": "test-iden",
-                            "identifiers": ["test4", "test-5"],
-                            "attributes": ["*"]
-                        },
-                        "actions": ["*"],
-                        "environment": {
-                            "serviceProviders": ["fffd"]
+                            "serviceProviders": ["good-company"]
                         }
                     },
-                    "rules": [
-                        {
-                            "effect": "Permit"
-                        },
-                        {
-                            "effect": "Deny",
-                            "target": {
-                                "resource": {
-                                    "attributes": ["zinger"],
-                                    "identifiers": ["*"],
-                                    "type":"test-iden"
-                                },
-                                "actions": ["*"]
-                            }
-                        }
-                    ]
-                }],
-                "target": {
-                    "accessSubject": "sadfasdf"
-                },
-                "policyIssuer": "sss",
-                "licences": [],
-                "maxDelegationDepth": 2
-        }));
+                    "rules": [{ "effect": "Permit" }]
+                }))))
+                .unwrap()
+        };
+
+        let response = admin_request(&app, "GET", uri, None).await;
+        assert_eq!(response.headers()[ETAG], "\"1\"");
+
+        let response = app.clone().oneshot(replace("first")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // the second admin still has the first revision and doesn't overwrite the first change
+        let response = app.clone().oneshot(replace("second")).await.unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
 
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .uri("/admin/policy-set")
-                    .method("POST")
+                    .uri(uri)
+                    .method("GET")
                     .header(
                         AUTHORIZATION,
                         server_token::server_token_test_helper::get_human_token_header(None, None),
                     )
-                    .header("Content-Type", "application/json")
-                    .body(Body::new(request_body))
+                    .header(IF_NONE_MATCH, "\"1\"")
+                    .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[ETAG], "\"2\"");
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let policy_set: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(policy_set["policies"][0]["identifiers"], json!(["first"]));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_restore_deleted_policy_set(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+
+        let app = get_test_app(db);
+        let uri = "/admin/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881";
+
+        let response = admin_request(&app, "DELETE", uri, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = admin_request(&app, "GET", uri, None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        // the deleted policy set isn't changed any further
+        let response = admin_request(&app, "DELETE", uri, None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        let policy_uri = format!("{}/policy/564f3b46-7127-4c3c-a0b8-2859c01cc9c1", uri);
+        let response = admin_request(&app, "DELETE", &policy_uri, None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = admin_request(
+            &app,
+            "GET",
+            "/admin/policy-set/trash?policy_issuer=NL.24244",
+            None,
+        )
+        .await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let policy_sets: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert_eq!(policy_sets["data"].as_array().unwrap().len(), 1);
+
+        let response = admin_request(
+            &app,
+            "GET",
+            "/admin/policy-set/trash?policy_issuer=NL.44444",
+            None,
+        )
+        .await;
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let policy_sets: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert!(policy_sets["data"].as_array().unwrap().is_empty());
+
+        // the resource type of the deleted policy set is unknown once the registry is in use
+        let restore_uri = format!("{}/restore", uri);
+        let resource_type = json!({
+            "name": "test-container",
+            "description": "Containers",
+            "actions": ["Read"]
+        });
+        let response =
+            admin_request(&app, "POST", "/admin/resource-type", Some(resource_type)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = admin_request(&app, "POST", &restore_uri, None).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let resource_type = json!({
+            "name": "TestResource",
+            "description": "Test resources",
+            "actions": ["Read", "Delete"]
+        });
+        let response =
+            admin_request(&app, "POST", "/admin/resource-type", Some(resource_type)).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let response = admin_request(&app, "POST", &restore_uri, None).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[ETAG], "\"3\"");
+
+        let response = admin_request(&app, "POST", &restore_uri, None).await;
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = admin_request(&app, "GET", "/admin/policy-set/trash", None).await;
+        let bytes = response.into_body().collect().await.unwrap().to_bytes();
+        let policy_sets: serde_json::Value = serde_json::from_slice(&bytes).unwrap();
+        assert!(policy_sets["data"].as_array().unwrap().is_empty());
 
+        let response = admin_request(&app, "GET", uri, None).await;
         assert_eq!(response.status(), StatusCode::OK);
 
         Ok(())
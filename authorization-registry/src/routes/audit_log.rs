@@ -72,9 +72,11 @@ mod tests {
     use crate::routes::policy_set::InsertPolicySetResponse;
     use crate::services::audit_log::{
         AuditEventWithIssAndSub, EditedType, PolicyAdded, PolicyRemoved, PolicyReplaced,
+        PolicySetMetadata,
     };
     use crate::services::server_token;
     use crate::test_helpers::helpers::{create_request_body, get_test_app, init_test_db};
+    use ar_entity::policy_set::PolicySetStatus;
     use axum::body::Body;
     use axum::http::{Request, StatusCode};
     use http_body_util::BodyExt;
@@ -1730,6 +1732,186 @@ mod tests {
         Ok(())
     }
 
+    #[sqlx::test]
+    async fn test_restore_policy_set_audit_event(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set_audit_log.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+
+        let app = get_test_app(db.clone());
+
+        for (uri, method) in [
+            ("/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881", "DELETE"),
+            (
+                "/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881/restore",
+                "POST",
+            ),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri(uri)
+                        .method(method)
+                        .header(
+                            AUTHORIZATION,
+                            server_token::server_token_test_helper::get_human_token_header(
+                                Some("NL.24244".to_owned()),
+                                None,
+                            ),
+                        )
+                        .header("Content-Type", "application/json")
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+
+        let audit_log_response = get_test_app(db.clone())
+            .oneshot(
+                Request::builder()
+                    .uri("/audit-log")
+                    .method("GET")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            Some("lovely-user".to_owned()),
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(audit_log_response.status(), StatusCode::OK);
+
+        let audit_log: Vec<AuditEventWithIssAndSub> = serde_json::from_str(
+            std::str::from_utf8(
+                &audit_log_response
+                    .into_body()
+                    .collect()
+                    .await
+                    .unwrap()
+                    .to_bytes(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let events: Vec<AuditEventWithIssAndSub> = audit_log
+            .into_iter()
+            .filter(|a| a.event_type == "dmi:ar:policy_set:restored")
+            .collect();
+
+        assert_eq!(events.len(), 1);
+
+        let context: HashMap<String, String> = events.get(0).unwrap().context.clone();
+
+        assert_eq!(
+            context.get("policy_set_id").unwrap(),
+            "84b7fba4-05f3-4af8-9d84-dde384abe881"
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_approve_policy_set_audit_event(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set_audit_log.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+
+        let policy_set_id = Uuid::parse_str("84b7fba4-05f3-4af8-9d84-dde384abe881").unwrap();
+        crate::db::policy::update_policy_set_status(&policy_set_id, PolicySetStatus::Pending, &db)
+            .await
+            .unwrap();
+
+        let app = get_test_app(db.clone());
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881/approve")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.24244".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let audit_log_response = get_test_app(db.clone())
+            .oneshot(
+                Request::builder()
+                    .uri("/audit-log")
+                    .method("GET")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            Some("lovely-user".to_owned()),
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(audit_log_response.status(), StatusCode::OK);
+
+        let audit_log: Vec<AuditEventWithIssAndSub> = serde_json::from_str(
+            std::str::from_utf8(
+                &audit_log_response
+                    .into_body()
+                    .collect()
+                    .await
+                    .unwrap()
+                    .to_bytes(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let events: Vec<AuditEventWithIssAndSub> = audit_log
+            .into_iter()
+            .filter(|a| a.event_type == "dmi:ar:policy_set:approved")
+            .collect();
+
+        assert_eq!(events.len(), 1);
+
+        let context: HashMap<String, String> = events.get(0).unwrap().context.clone();
+
+        assert_eq!(
+            context.get("policy_set_id").unwrap(),
+            "84b7fba4-05f3-4af8-9d84-dde384abe881"
+        );
+
+        Ok(())
+    }
+
     #[sqlx::test]
     async fn test_replace_policy_in_policy_set_audit_event_admin(
         _pool_options: PgPoolOptions,
@@ -1852,4 +2034,101 @@ mod tests {
 
         Ok(())
     }
+
+    #[sqlx::test]
+    async fn test_edit_policy_set_audit_event_admin(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set_audit_log.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+
+        let app = get_test_app(db.clone());
+
+        let request_body = create_request_body(&json!({
+            "maxDelegationDepth": 0
+        }));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881")
+                    .method("PATCH")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.24244".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let audit_log_response = get_test_app(db.clone())
+            .oneshot(
+                Request::builder()
+                    .uri("/audit-log")
+                    .method("GET")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            Some("lovely-user".to_owned()),
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(audit_log_response.status(), StatusCode::OK);
+
+        let audit_log: Vec<AuditEventWithIssAndSub> = serde_json::from_str(
+            std::str::from_utf8(
+                &audit_log_response
+                    .into_body()
+                    .collect()
+                    .await
+                    .unwrap()
+                    .to_bytes(),
+            )
+            .unwrap(),
+        )
+        .unwrap();
+
+        let events: Vec<AuditEventWithIssAndSub> = audit_log
+            .into_iter()
+            .filter(|a| a.event_type == "dmi:ar:policy_set:edited")
+            .collect();
+
+        assert_eq!(events.len(), 1);
+
+        let context: HashMap<String, String> = events.get(0).unwrap().context.clone();
+
+        assert_eq!(
+            context.get("policy_set_id").unwrap(),
+            "84b7fba4-05f3-4af8-9d84-dde384abe881",
+        );
+        assert_eq!(context.get("edit_type").unwrap(), "MetadataEdited");
+
+        let previous: PolicySetMetadata =
+            serde_json::from_str(context.get("previous").unwrap()).unwrap();
+        let current: PolicySetMetadata =
+            serde_json::from_str(context.get("current").unwrap()).unwrap();
+
+        assert_eq!(previous.max_delegation_depth, 2);
+        assert_eq!(current.max_delegation_depth, 0);
+        assert_eq!(current.access_subject, "NL.44444");
+
+        Ok(())
+    }
 }
@@ -1,5 +1,8 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
 use anyhow::Context;
-use axum::extract::State;
+use axum::extract::{ConnectInfo, Query, State};
 use axum::http::HeaderMap;
 use axum::middleware::from_fn_with_state;
 use axum::response::{IntoResponse, Response};
@@ -12,21 +15,175 @@ use serde::{Deserialize, Serialize};
 
 use crate::error::{AppError, ErrorResponse, ExpectedError};
 use crate::middleware::extract_role_middleware;
-use crate::services::audit_log::log_event;
-use crate::services::delegation as delegation_service;
+use crate::services::audit_log::{log_event, log_events};
+use crate::services::condition as condition_service;
+use crate::services::delegation::{
+    self as delegation_service, DelegationTokenVerification, IntendedAccess,
+    IssuedDelegationEvidence, PolicySetChanges, PolicyTrace,
+};
+use crate::services::ishare_provider::SatelliteProvider;
+use crate::services::policy as policy_service;
+use crate::services::resource_type as resource_type_service;
 use crate::services::server_token::{Role, ServerToken};
+use crate::utils::is_same_party;
 use crate::AppState;
-use ishare::delegation_request::DelegationRequestContainer;
+use ishare::delegation_request::{DelegationRequest, DelegationRequestContainer};
+use ishare::ishare::PartyInfo;
 
 pub fn get_delegation_routes(server_token: std::sync::Arc<ServerToken>) -> Router<AppState> {
     Router::new()
         .route("/", post(post_delegation))
+        .route("/simulate", post(post_delegation_simulation))
+        .route("/batch", post(post_delegation_batch))
+        .route("/verify", post(post_delegation_verification))
         .layer(from_fn_with_state(server_token, extract_role_middleware))
 }
 
 #[derive(Deserialize, Serialize, utoipa::ToSchema)]
 struct DelegationResponse {
     delegation_token: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explanation: Option<Vec<PolicyTrace>>,
+}
+
+#[derive(Serialize)]
+struct DelegationEvidenceResponse {
+    #[serde(flatten)]
+    container: IssuedDelegationEvidence,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    explanation: Option<Vec<PolicyTrace>>,
+}
+
+#[derive(Deserialize)]
+struct DelegationQuery {
+    explain: Option<bool>,
+}
+
+#[derive(Deserialize, Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegationRequestBody {
+    #[serde(flatten)]
+    pub container: DelegationRequestContainer,
+    /// Attributes of the request environment, e.g. 'region', that conditions on rules are evaluated against. The 'ip' attribute is always the address the request comes from
+    #[serde(default)]
+    pub environment: HashMap<String, String>,
+    /// Licenses a policy set must grant to match the delegation request, e.g. 'ISHARE.0001'
+    #[serde(default)]
+    pub licenses: Vec<String>,
+    /// The user of the access subject the delegation request is made for, only accepted from the access subject itself and from a human token only for its own user. Policy sets issued to a user only apply to requests for that user. The evidence stays issued to the access subject and names the user in 'accessSubjectUser' of its target
+    #[serde(default)]
+    pub access_subject_user: Option<String>,
+}
+
+impl DelegationRequestBody {
+    fn subject_details<'a>(
+        &'a self,
+        party_info: Option<&'a PartyInfo>,
+        satellite_provider: &'a dyn SatelliteProvider,
+    ) -> delegation_service::AccessSubjectDetails<'a> {
+        delegation_service::AccessSubjectDetails {
+            party_info,
+            satellite_provider: Some(satellite_provider),
+            user: self.access_subject_user.as_deref(),
+        }
+    }
+}
+
+// the access subject requests the rights of its users, with a machine token for any of them and
+// with a human token only for the user behind it
+fn check_access_subject_user(role: &Role, request: &DelegationRequestBody) -> Result<(), AppError> {
+    let Some(access_subject_user) = &request.access_subject_user else {
+        return Ok(());
+    };
+
+    if !is_same_party(
+        &role.get_company_id(),
+        &request.container.delegation_request.target.access_subject,
+    ) {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::FORBIDDEN,
+            message: "not allowed to request delegation evidence for a user of another company"
+                .to_owned(),
+            reason: format!(
+                "company: {} requested delegation evidence for user: {} of access subject: {}",
+                role.get_company_id(),
+                access_subject_user,
+                &request.container.delegation_request.target.access_subject
+            ),
+            metadata: None,
+        }));
+    }
+
+    match role.get_user_id() {
+        Some(user_id) if &user_id != access_subject_user => {
+            Err(AppError::Expected(ExpectedError {
+                status_code: StatusCode::FORBIDDEN,
+                message: "not allowed to request delegation evidence for another user".to_owned(),
+                reason: format!(
+                    "user: {} requested delegation evidence for user: {}",
+                    user_id, access_subject_user
+                ),
+                metadata: None,
+            }))
+        }
+        _ => Ok(()),
+    }
+}
+
+// the environment the rule conditions are evaluated against, with the address the request comes
+// from instead of an 'ip' the caller supplied
+fn get_request_environment(
+    app_state: &AppState,
+    connect_info: &Option<ConnectInfo<SocketAddr>>,
+    headers: &HeaderMap,
+    environment: &HashMap<String, String>,
+) -> HashMap<String, String> {
+    let client_ip = condition_service::get_client_ip(
+        connect_info
+            .as_ref()
+            .map(|ConnectInfo(address)| address.ip()),
+        headers
+            .get("x-forwarded-for")
+            .and_then(|header| header.to_str().ok()),
+        &app_state.config.trusted_proxies,
+    );
+
+    condition_service::with_client_ip(environment, client_ip)
+}
+
+fn validate_requested_policies(delegation_request: &DelegationRequest) -> Result<(), AppError> {
+    for ps in &delegation_request.policy_sets {
+        for policy in &ps.policies {
+            if policy.target.resource.resource_type == "*" {
+                return Err(AppError::Expected(ExpectedError {
+                    status_code: StatusCode::BAD_REQUEST,
+                    message: "resource type cannot be '*'".to_owned(),
+                    reason: "'*' used as resource type in policy set".to_owned(),
+                    metadata: None,
+                }));
+            }
+
+            if policy.target.resource.identifiers.len() == 0 {
+                return Err(AppError::Expected(ExpectedError {
+                    status_code: StatusCode::BAD_REQUEST,
+                    message: "identifiers is empty'".to_owned(),
+                    reason: "identifiers in policy set cannot be an empty array".to_owned(),
+                    metadata: None,
+                }));
+            }
+
+            if policy.target.resource.attributes.len() == 0 {
+                return Err(AppError::Expected(ExpectedError {
+                    status_code: StatusCode::BAD_REQUEST,
+                    message: "attributes is empty".to_owned(),
+                    reason: "attributes in policy set cannot be an empty array".to_owned(),
+                    metadata: None,
+                }));
+            }
+        }
+    }
+
+    Ok(())
 }
 
 /// Obtain Delegation Evidence
@@ -36,7 +193,10 @@ struct DelegationResponse {
     tag = "Delegation",
     request_body(
         description="Delegation Request",
-        content((DelegationRequestContainer))
+        content((DelegationRequestBody))
+    ),
+    params(
+        ("explain" = Option<bool>, Query, description = "Add a trace explaining the effect of every requested policy. Only allowed for the policy issuer and access subject"),
     ),
     security(
         ("bearer" = [])
@@ -65,11 +225,38 @@ struct DelegationResponse {
 #[axum_macros::debug_handler]
 async fn post_delegation(
     headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
     Extension(db): Extension<DatabaseConnection>,
     Extension(role): Extension<Role>,
     app_state: State<AppState>,
-    body: WithRejection<Json<DelegationRequestContainer>, AppError>,
+    Query(query): Query<DelegationQuery>,
+    request_body: WithRejection<Json<DelegationRequestBody>, AppError>,
 ) -> Result<Response, AppError> {
+    let body = &request_body.container;
+    let explain = query.explain.unwrap_or(false);
+
+    // the trace reveals the policies of the policy issuer, only the parties of the delegation may see it
+    if explain
+        && !is_same_party(
+            &role.get_company_id(),
+            &body.delegation_request.policy_issuer,
+        )
+        && !is_same_party(
+            &role.get_company_id(),
+            &body.delegation_request.target.access_subject,
+        )
+    {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::FORBIDDEN,
+            message: "not allowed to explain delegation evidence".to_owned(),
+            reason: format!(
+                "company: {} is neither policy issuer nor access subject of the delegation request",
+                &role.get_company_id()
+            ),
+            metadata: None,
+        }));
+    }
+
     match app_state
         .satellite_provider
         .validate_party(
@@ -92,7 +279,7 @@ async fn post_delegation(
         }
     }
 
-    match app_state
+    let access_subject_info = match app_state
         .satellite_provider
         .validate_party(
             app_state.time_provider.now(),
@@ -100,7 +287,7 @@ async fn post_delegation(
         )
         .await
     {
-        Ok(_) => {}
+        Ok(party_info) => party_info,
         Err(e) => {
             return Err(AppError::Expected(ExpectedError {
                 status_code: StatusCode::BAD_REQUEST,
@@ -112,7 +299,7 @@ async fn post_delegation(
                 metadata: None,
             }))
         }
-    }
+    };
 
     let now = app_state.time_provider.now();
 
@@ -135,7 +322,10 @@ async fn post_delegation(
         &body.previous_steps,
         app_state.config.delegation_allows_service_providers,
         app_state.satellite_provider.clone(),
-    ) {
+        &app_state.previous_steps_verifier,
+    )
+    .await?
+    {
         return Err(AppError::Expected(ExpectedError {
             status_code: StatusCode::BAD_REQUEST,
             message: format!("not allowed to request delegation evidence"),
@@ -147,44 +337,33 @@ async fn post_delegation(
         }));
     }
 
-    for ps in &body.delegation_request.policy_sets {
-        for policy in &ps.policies {
-            if policy.target.resource.resource_type == "*" {
-                return Err(AppError::Expected(ExpectedError {
-                    status_code: StatusCode::BAD_REQUEST,
-                    message: "resource type cannot be '*'".to_owned(),
-                    reason: "'*' used as resource type in policy set".to_owned(),
-                    metadata: None,
-                }));
-            }
-
-            if policy.target.resource.identifiers.len() == 0 {
-                return Err(AppError::Expected(ExpectedError {
-                    status_code: StatusCode::BAD_REQUEST,
-                    message: "identifiers is empty'".to_owned(),
-                    reason: "identifiers in policy set cannot be an empty array".to_owned(),
-                    metadata: None,
-                }));
-            }
-
-            if policy.target.resource.attributes.len() == 0 {
-                return Err(AppError::Expected(ExpectedError {
-                    status_code: StatusCode::BAD_REQUEST,
-                    message: "attributes is empty".to_owned(),
-                    reason: "attributes in policy set cannot be an empty array".to_owned(),
-                    metadata: None,
-                }));
-            }
-        }
-    }
-
-    let delegation_evidence_container = delegation_service::create_delegation_evidence(
-        &body.delegation_request,
-        app_state.time_provider.clone(),
-        app_state.de_expiry_seconds,
-        &db,
-    )
-    .await?;
+    check_access_subject_user(&role, &request_body)?;
+    validate_requested_policies(&body.delegation_request)?;
+
+    let environment = get_request_environment(
+        &app_state,
+        &connect_info,
+        &headers,
+        &request_body.environment,
+    );
+
+    let (delegation_evidence_container, explanation) =
+        delegation_service::create_cached_delegation_evidence(
+            &body.delegation_request,
+            request_body.subject_details(
+                Some(&access_subject_info),
+                app_state.satellite_provider.as_ref(),
+            ),
+            &environment,
+            &request_body.licenses,
+            &app_state.config.combining_algorithm,
+            explain,
+            &app_state.decision_cache,
+            app_state.time_provider.clone(),
+            app_state.de_expiry_seconds,
+            &db,
+        )
+        .await?;
 
     let token = app_state
         .satellite_provider
@@ -192,9 +371,14 @@ async fn post_delegation(
         .context("Error creating delegation token")?;
 
     let response = match headers.get(ACCEPT).map(|x| x.as_bytes()) {
-        Some(b"application/json") => Json(delegation_evidence_container).into_response(),
+        Some(b"application/json") => Json(DelegationEvidenceResponse {
+            container: delegation_evidence_container,
+            explanation,
+        })
+        .into_response(),
         _ => Json(DelegationResponse {
             delegation_token: token,
+            explanation,
         })
         .into_response(),
     };
@@ -202,101 +386,585 @@ async fn post_delegation(
     return Ok(response);
 }
 
-#[cfg(test)]
-mod test {
-    use ar_entity::delegation_evidence::{
-        Environment, Policy, Resource, ResourceRule, ResourceTarget,
-    };
-    use ishare::delegation_evidence::DelegationEvidenceContainer;
-
-    use crate::db::policy::AccessSubjectTarget;
-    use crate::fixtures::fixtures::insert_policy_set_fixture;
-    use crate::services::policy::{
-        insert_policy_set_with_policies_into_db, InsertPolicySetWithPolicies,
-    };
-    use crate::services::server_token;
-    use axum::{
-        body::Body,
-        http::{Request, StatusCode},
-    };
-    use http_body_util::BodyExt;
-    use reqwest::header::AUTHORIZATION;
-    use serde_json;
-    use serde_json::json;
-    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
-    use tower::ServiceExt;
-
-    use super::super::super::test_helpers::helpers::*;
+#[derive(Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulateDelegationRequestBody {
+    #[serde(flatten)]
+    pub request: DelegationRequestBody,
+    /// Hypothetical policy sets to add, remove or replace before evaluating the delegation request
+    #[serde(default)]
+    pub changes: PolicySetChanges,
+}
 
-    #[sqlx::test]
-    async fn test_delegation_evidence_not_as_or_pi(
-        _pool_options: PgPoolOptions,
-        conn_option: PgConnectOptions,
-    ) -> sqlx::Result<()> {
-        let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+/// Simulate Delegation Evidence
+///
+/// Evaluates the delegation request against the stored policy sets with the hypothetical changes applied. Nothing is stored, the evidence is not signed and no audit event is written. Only the policy issuer can simulate, and only changes to its own policy sets.
+#[utoipa::path(
+    post,
+    path = "/delegation/simulate",
+    tag = "Delegation",
+    request_body(
+        description="Delegation Request with hypothetical policy set changes",
+        content((SimulateDelegationRequestBody))
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "OK. JSON with unsigned delegation evidence",
+            content_type = "application/json",
+        ),
+        (
+            status = 400,
+            description = "Malformed request",
+            content_type = "application/text/plain; charset=utf-8",
+            body = String,
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized")),
+        ),
+        (
+            status = 403,
+            description = "Not the policy issuer of the delegation request",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("not allowed to simulate delegation evidence")),
+        ),
+    )
+)]
+async fn post_delegation_simulation(
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(role): Extension<Role>,
+    app_state: State<AppState>,
+    WithRejection(Json(body), _): WithRejection<Json<SimulateDelegationRequestBody>, AppError>,
+) -> Result<Json<IssuedDelegationEvidence>, AppError> {
+    let delegation_request = &body.request.container.delegation_request;
+    let now = app_state.time_provider.now();
 
-        let app = get_test_app(db);
-        let request_body = create_request_body(&json!({
-            "delegationRequest": {
-                "policyIssuer": "NL.24244",
-                "target": {
-                    "accessSubject": "NL.44444"
-                },
-                "policySets": [
-                    {
-                        "policies": [
-                            {
-                                "target": {
-                                    "resource": {
-                                        "type": "TestResource",
-                                        "identifiers": ["test4"],
-                                        "attributes": ["zingers"]
-                                    },
-                                    "actions": ["Read", "Delete"],
-                                    "environment": {
-                                        "serviceProviders": ["good-company"]
-                                    }
-                                },
-                                "rules": [
-                                    {
-                                        "effect": "Permit"
-                                    }
-                                ]
-                            }
-                        ]
-                    }
-                ]
-            }
+    // a simulation evaluates the policies of the policy issuer with changes of its choosing, only
+    // the policy issuer can run it
+    if !is_same_party(&role.get_company_id(), &delegation_request.policy_issuer) {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::FORBIDDEN,
+            message: format!("not allowed to simulate delegation evidence"),
+            reason: format!(
+                "company: {} is not the policy issuer of the delegation request",
+                &role.get_company_id()
+            ),
+            metadata: None,
         }));
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .uri("/delegation")
-                    .method("POST")
-                    .header(
-                        AUTHORIZATION,
-                        server_token::server_token_test_helper::get_human_token_header(
-                            Some("OtherCompany".to_owned()),
-                            None,
-                        ),
-                    )
-                    .header("Content-Type", "application/json")
-                    .header("Accept", "application/json")
-                    .body(Body::new(request_body))
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
-
-        Ok(())
     }
 
-    #[sqlx::test]
-    async fn test_delegation_evidence(
-        _pool_options: PgPoolOptions,
+    check_access_subject_user(&role, &body.request)?;
+    validate_requested_policies(delegation_request)?;
+    delegation_service::validate_policy_set_changes(
+        &delegation_request.policy_issuer,
+        &body.changes,
+        &db,
+    )
+    .await?;
+
+    for policy_set in body
+        .changes
+        .add
+        .iter()
+        .chain(body.changes.replace.iter().map(|r| &r.policy_set))
+    {
+        policy_service::validate_policy_set_validity_window(policy_set)?;
+        policy_service::validate_policy_set_access_subject(policy_set)?;
+        policy_service::validate_policy_set_licenses(policy_set, &db).await?;
+        policy_service::validate_policy_set_party_groups(policy_set, &db).await?;
+        policy_service::validate_policy_set_access_subject_user(policy_set, &db).await?;
+        resource_type_service::validate_policies_resource_types(&policy_set.policies, &db).await?;
+        for policy in policy_set.policies.iter() {
+            policy_service::validate_policy(policy)?;
+        }
+    }
+
+    // without a satellite registration the policy sets with access subject attributes don't apply
+    let access_subject_info = app_state
+        .satellite_provider
+        .validate_party(now, &delegation_request.target.access_subject)
+        .await
+        .ok();
+
+    let environment = get_request_environment(
+        &app_state,
+        &connect_info,
+        &headers,
+        &body.request.environment,
+    );
+
+    let delegation_evidence_container = delegation_service::simulate_delegation_evidence(
+        delegation_request,
+        body.request.subject_details(
+            access_subject_info.as_ref(),
+            app_state.satellite_provider.as_ref(),
+        ),
+        &environment,
+        &body.request.licenses,
+        &app_state.config.combining_algorithm,
+        &body.changes,
+        app_state.time_provider.clone(),
+        app_state.de_expiry_seconds,
+        &db,
+    )
+    .await?;
+
+    Ok(Json(delegation_evidence_container))
+}
+
+// the maximum number of delegation requests in a batch
+const MAX_BATCH_SIZE: usize = 100;
+
+#[derive(Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct BatchDelegationRequestBody {
+    pub requests: Vec<DelegationRequestBody>,
+}
+
+#[derive(Deserialize, Serialize, utoipa::ToSchema)]
+struct BatchDelegationError {
+    status: u16,
+    error: String,
+}
+
+#[derive(Deserialize, Serialize, utoipa::ToSchema)]
+struct BatchDelegationResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    delegation_token: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<BatchDelegationError>,
+}
+
+#[derive(Deserialize, Serialize, utoipa::ToSchema)]
+struct BatchDelegationResponse {
+    results: Vec<BatchDelegationResult>,
+}
+
+impl From<AppError> for BatchDelegationError {
+    fn from(error: AppError) -> Self {
+        match error {
+            AppError::Expected(e) => {
+                tracing::info!("{:?}", e);
+                BatchDelegationError {
+                    status: e.status_code.as_u16(),
+                    error: e.message,
+                }
+            }
+            e => {
+                tracing::error!("{:?}", e);
+                BatchDelegationError {
+                    status: StatusCode::INTERNAL_SERVER_ERROR.as_u16(),
+                    error: "Something unexpected went wrong".to_owned(),
+                }
+            }
+        }
+    }
+}
+
+fn check_party(
+    parties: &HashMap<String, Result<PartyInfo, String>>,
+    party: &str,
+    role_name: &str,
+) -> Result<(), AppError> {
+    match parties.get(party) {
+        Some(Ok(_)) => Ok(()),
+        error => Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::BAD_REQUEST,
+            message: format!("{} is not valid iSHARE party", role_name),
+            reason: format!(
+                "Unable to verify {}: '{} as valid iSHARE party | {}",
+                role_name,
+                party,
+                error
+                    .and_then(|e| e.as_ref().err())
+                    .cloned()
+                    .unwrap_or_default()
+            ),
+            metadata: None,
+        })),
+    }
+}
+
+/// Obtain Delegation Evidence for many Delegation Requests
+///
+/// Every delegation request gets its own result, in the order of the requests, with either a signed delegation token or an error.
+#[utoipa::path(
+    post,
+    path = "/delegation/batch",
+    tag = "Delegation",
+    request_body(
+        description="Delegation Requests",
+        content((BatchDelegationRequestBody))
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "OK. A result per delegation request",
+            content_type = "application/json",
+            body = BatchDelegationResponse,
+        ),
+        (
+            status = 400,
+            description = "Malformed request",
+            content_type = "application/text/plain; charset=utf-8",
+            body = String,
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized")),
+        ),
+    )
+)]
+async fn post_delegation_batch(
+    headers: HeaderMap,
+    connect_info: Option<ConnectInfo<SocketAddr>>,
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(role): Extension<Role>,
+    app_state: State<AppState>,
+    WithRejection(Json(body), _): WithRejection<Json<BatchDelegationRequestBody>, AppError>,
+) -> Result<Json<BatchDelegationResponse>, AppError> {
+    if body.requests.len() > MAX_BATCH_SIZE {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::BAD_REQUEST,
+            message: format!(
+                "a batch can't contain more than {} requests",
+                MAX_BATCH_SIZE
+            ),
+            reason: format!("batch contains {} requests", body.requests.len()),
+            metadata: None,
+        }));
+    }
+
+    let now = app_state.time_provider.now();
+    let company_id = role.get_company_id();
+
+    // every party is validated once, no matter how many requests it is part of
+    let mut parties: HashMap<String, Result<PartyInfo, String>> = HashMap::new();
+    for request in body.requests.iter() {
+        let delegation_request = &request.container.delegation_request;
+        for party in [
+            &delegation_request.policy_issuer,
+            &delegation_request.target.access_subject,
+        ] {
+            if !parties.contains_key(party) {
+                let party_info = app_state
+                    .satellite_provider
+                    .validate_party(now, party)
+                    .await
+                    .map_err(|e| e.to_string());
+                parties.insert(party.to_owned(), party_info);
+            }
+        }
+    }
+
+    let mut results: Vec<Result<(), AppError>> = vec![];
+    let mut audit_events = vec![];
+    for request in body.requests.iter() {
+        let delegation_request = &request.container.delegation_request;
+
+        let result = check_party(&parties, &delegation_request.policy_issuer, "policy issuer")
+            .and_then(|_| {
+                check_party(
+                    &parties,
+                    &delegation_request.target.access_subject,
+                    "access subject",
+                )
+            })
+            .map(|_| {
+                audit_events.push((
+                    "".to_owned(),
+                    crate::services::audit_log::EventType::DmiDelegationRequest(
+                        delegation_request.clone(),
+                    ),
+                ))
+            });
+
+        let result = match result {
+            Ok(_) => match delegation_service::check_delegation_access(
+                now,
+                &company_id,
+                delegation_request,
+                &request.container.previous_steps,
+                app_state.config.delegation_allows_service_providers,
+                app_state.satellite_provider.clone(),
+                &app_state.previous_steps_verifier,
+            )
+            .await
+            {
+                Ok(true) => Ok(()),
+                Ok(false) => Err(AppError::Expected(ExpectedError {
+                    status_code: StatusCode::BAD_REQUEST,
+                    message: format!("not allowed to request delegation evidence"),
+                    reason: format!(
+                        "company: {} is not allowed to request delegation evidence",
+                        &company_id
+                    ),
+                    metadata: None,
+                })),
+                Err(e) => Err(e),
+            },
+            Err(e) => Err(e),
+        }
+        .and_then(|_| check_access_subject_user(&role, request))
+        .and_then(|_| validate_requested_policies(delegation_request));
+
+        results.push(result);
+    }
+
+    log_events(now, audit_events, &db).await?;
+
+    let environments: Vec<HashMap<String, String>> = body
+        .requests
+        .iter()
+        .map(|request| {
+            get_request_environment(&app_state, &connect_info, &headers, &request.environment)
+        })
+        .collect();
+
+    let accepted: Vec<(
+        &DelegationRequest,
+        &HashMap<String, String>,
+        &Vec<String>,
+        delegation_service::AccessSubjectDetails,
+    )> = body
+        .requests
+        .iter()
+        .zip(environments.iter())
+        .zip(results.iter())
+        .filter(|(_, result)| result.is_ok())
+        .map(|((request, environment), _)| {
+            let delegation_request = &request.container.delegation_request;
+            (
+                delegation_request,
+                environment,
+                &request.licenses,
+                request.subject_details(
+                    parties
+                        .get(&delegation_request.target.access_subject)
+                        .and_then(|p| p.as_ref().ok()),
+                    app_state.satellite_provider.as_ref(),
+                ),
+            )
+        })
+        .collect();
+
+    let mut evidence = delegation_service::create_delegation_evidence_batch(
+        &accepted,
+        &app_state.config.combining_algorithm,
+        app_state.time_provider.clone(),
+        app_state.de_expiry_seconds,
+        &db,
+    )
+    .await?
+    .into_iter();
+
+    let results = results
+        .into_iter()
+        .map(|result| {
+            let token = result.and_then(|_| {
+                let delegation_evidence_container = evidence
+                    .next()
+                    .context("Missing delegation evidence of batch item")?;
+
+                Ok(app_state
+                    .satellite_provider
+                    .create_delegation_token(&company_id, &delegation_evidence_container)
+                    .context("Error creating delegation token")?)
+            });
+
+            match token {
+                Ok(token) => BatchDelegationResult {
+                    delegation_token: Some(token),
+                    error: None,
+                },
+                Err(e) => BatchDelegationResult {
+                    delegation_token: None,
+                    error: Some(e.into()),
+                },
+            }
+        })
+        .collect();
+
+    Ok(Json(BatchDelegationResponse { results }))
+}
+
+#[derive(Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VerifyDelegationTokenBody {
+    pub delegation_token: String,
+    #[serde(flatten)]
+    pub intended: IntendedAccess,
+}
+
+/// Verify Delegation Token
+///
+/// Verifies the signature, expiry and audience of a delegation token issued to the caller by this registry or another authorization registry known to the satellite and returns whether its delegation evidence permits the intended action on the resource.
+#[utoipa::path(
+    post,
+    path = "/delegation/verify",
+    tag = "Delegation",
+    request_body(
+        description="Delegation token with the intended action on a resource",
+        content((VerifyDelegationTokenBody))
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "OK. Whether the delegation evidence in the token permits the action",
+            content_type = "application/json",
+            body = DelegationTokenVerification,
+        ),
+        (
+            status = 400,
+            description = "Malformed request or invalid delegation token",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("delegation token is invalid: token has expired")),
+        ),
+        (
+            status = 401,
+            description = "Unauthorized",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized")),
+        ),
+    )
+)]
+async fn post_delegation_verification(
+    Extension(role): Extension<Role>,
+    app_state: State<AppState>,
+    WithRejection(Json(body), _): WithRejection<Json<VerifyDelegationTokenBody>, AppError>,
+) -> Result<Json<DelegationTokenVerification>, AppError> {
+    // only the party a delegation token is issued to can verify it
+    let verification = delegation_service::verify_delegation_token(
+        app_state.time_provider.now(),
+        &body.delegation_token,
+        &role.get_company_id(),
+        &body.intended,
+        &app_state.config.client_eori,
+        app_state.satellite_provider.clone(),
+    )
+    .await?;
+
+    Ok(Json(verification))
+}
+
+#[cfg(test)]
+mod test {
+    use std::net::SocketAddr;
+
+    use ar_entity::delegation_evidence::{
+        Environment, Permit, Policy, Resource, ResourceRule, ResourceTarget,
+    };
+    use ar_entity::policy_set::PolicySetStatus;
+    use ishare::delegation_evidence::DelegationEvidenceContainer;
+
+    use crate::db::party_group as party_group_store;
+    use crate::db::policy::AccessSubjectTarget;
+    use crate::fixtures::fixtures::insert_policy_set_fixture;
+    use crate::services::policy::{
+        insert_policy_set_with_policies_into_db, InsertPolicySetWithPolicies,
+    };
+    use crate::services::server_token;
+    use crate::TimeProvider;
+    use axum::{
+        body::Body,
+        extract::ConnectInfo,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use reqwest::header::AUTHORIZATION;
+    use sea_orm::{DatabaseConnection, EntityTrait, PaginatorTrait};
+    use serde_json;
+    use serde_json::json;
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+    use tower::ServiceExt;
+
+    use super::super::super::test_helpers::helpers::*;
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_not_as_or_pi(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "TestResource",
+                                        "identifiers": ["test4"],
+                                        "attributes": ["zingers"]
+                                    },
+                                    "actions": ["Read", "Delete"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("OtherCompany".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence(
+        _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
@@ -398,53 +1066,1978 @@ mod test {
                                         "identifiers": ["test4"],
                                         "attributes": ["zingers"]
                                     },
-                                    "actions": ["Read", "Delete"],
-                                    "environment": {
-                                        "serviceProviders": ["good-company"]
-                                    }
-                                },
-                                "rules": [
-                                    {
-                                        "effect": "Permit"
-                                    }
-                                ]
-                            }
-                        ]
-                    }
-                ]
-            }
-        }));
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .uri("/delegation")
-                    .method("POST")
-                    .header(
-                        AUTHORIZATION,
-                        server_token::server_token_test_helper::get_human_token_header(
-                            Some("NL.44444".to_owned()),
-                            None,
-                        ),
-                    )
-                    .header("Content-Type", "application/json")
-                    .body(Body::new(request_body))
+                                    "actions": ["Read", "Delete"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_wildcard(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "test-iden",
+                                        "identifiers": ["test4"],
+                                        "attributes": ["zingers"]
+                                    },
+                                    "actions": ["Read", "Delete"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: DelegationEvidenceContainer = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(body.delegation_evidence.policy_sets.len() > 0, true);
+
+        for ps in body.delegation_evidence.policy_sets.iter() {
+            for p in ps.policies.iter() {
+                assert_eq!(p.rules.get(0).unwrap().effect, "Permit")
+            }
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_unallowed_action(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "test-iden",
+                                        "identifiers": ["test4"],
+                                        "attributes": ["zingers"]
+                                    },
+                                    "actions": ["Read", "Delete", "Fish"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: DelegationEvidenceContainer = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(body.delegation_evidence.policy_sets.len() == 1, true);
+        assert_eq!(
+            body.delegation_evidence
+                .policy_sets
+                .get(0)
+                .unwrap()
+                .policies
+                .get(0)
+                .unwrap()
+                .rules
+                .get(0)
+                .unwrap()
+                .effect
+                == "Deny",
+            true
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_specfic_identifier_match(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "test-iden",
+                                        "identifiers": ["specific"],
+                                        "attributes": ["zingers"]
+                                    },
+                                    "actions": ["Read", "Delete"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: DelegationEvidenceContainer = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(body.delegation_evidence.policy_sets.len() > 0, true);
+
+        for ps in body.delegation_evidence.policy_sets.iter() {
+            for p in ps.policies.iter() {
+                assert_eq!(p.rules.get(0).unwrap().effect, "Deny")
+            }
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_resource_type_start(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "*",
+                                        "identifiers": ["specific"],
+                                        "attributes": ["zingers"]
+                                    },
+                                    "actions": ["Read", "Delete"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_pi_different_as(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+
+        let pi = "1".to_owned();
+        let as1 = "2".to_owned();
+        let as2 = "3".to_owned();
+        let target_id = "target".to_owned();
+        let resource_type = "Resource".to_owned();
+        let actions = vec!["Read".to_owned()];
+        let environment = Environment {
+            service_providers: vec!["service".to_owned()],
+        };
+
+        insert_policy_set_with_policies_into_db(
+            chrono::Utc::now(),
+            &InsertPolicySetWithPolicies {
+                policy_issuer: pi.clone(),
+                target: AccessSubjectTarget {
+                    access_subject: as1.clone(),
+                    access_subject_attributes: None,
+                    access_subject_user: None,
+                },
+                licences: vec![],
+                policies: vec![Policy {
+                    target: ResourceTarget {
+                        resource: Resource {
+                            identifiers: vec![target_id],
+                            attributes: vec!["*".to_owned()],
+                            resource_type: resource_type,
+                        },
+                        actions,
+                        environment,
+                    },
+                    rules: vec![ResourceRule::Permit(Permit::default())],
+                }],
+                max_delegation_depth: 1,
+                not_before: None,
+                not_on_or_after: None,
+            },
+            PolicySetStatus::Active,
+            &db,
+        )
+        .await
+        .unwrap();
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": pi,
+                "target": {
+                    "accessSubject": as2
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "Resource",
+                                        "identifiers": ["target"],
+                                        "attributes": ["*"]
+                                    },
+                                    "actions": ["Read"],
+                                    "environment": {
+                                        "serviceProviders": ["service"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some(pi),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: DelegationEvidenceContainer = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        tracing::info!("{:?}", body);
+
+        assert_eq!(body.delegation_evidence.policy_sets.len() > 0, true);
+
+        for ps in body.delegation_evidence.policy_sets.iter() {
+            for p in ps.policies.iter() {
+                assert_eq!(p.rules.get(0).unwrap().effect, "Deny")
+            }
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_no_identifier(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "something",
+                                        "identifiers": [],
+                                        "attributes": ["zingers"]
+                                    },
+                                    "actions": ["Read", "Delete"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_no_attributes(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "something",
+                                        "identifiers": ["zainger"],
+                                        "attributes": []
+                                    },
+                                    "actions": ["Read", "Delete"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_specfic_identifier_no_match(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "test-iden",
+                                        "identifiers": ["something else"],
+                                        "attributes": ["zingers"]
+                                    },
+                                    "actions": ["Read", "Delete"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: DelegationEvidenceContainer = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(body.delegation_evidence.policy_sets.len() > 0, true);
+
+        for ps in body.delegation_evidence.policy_sets.iter() {
+            for p in ps.policies.iter() {
+                assert_eq!(p.rules.get(0).unwrap().effect, "Permit")
+            }
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_delegation_chain(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set8.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set9.json", &db).await;
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "test-chain",
+                                        "identifiers": ["test4"],
+                                        "attributes": ["zingers"]
+                                    },
+                                    "actions": ["Read"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: DelegationEvidenceContainer = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(body.delegation_evidence.policy_sets.len(), 1);
+
+        let policy_set = body.delegation_evidence.policy_sets.get(0).unwrap();
+        assert_eq!(policy_set.max_delegation_depth, 0);
+        assert_eq!(
+            policy_set.target.environment.licenses,
+            vec!["ISHARE.0001".to_owned()]
+        );
+        assert_eq!(
+            policy_set
+                .policies
+                .get(0)
+                .unwrap()
+                .rules
+                .get(0)
+                .unwrap()
+                .effect,
+            "Permit"
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_delegation_chain_not_permitted_by_every_hop(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set8.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set9.json", &db).await;
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "test-chain",
+                                        "identifiers": ["test4"],
+                                        "attributes": ["zingers"]
+                                    },
+                                    "actions": ["Read", "Delete"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: DelegationEvidenceContainer = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(body.delegation_evidence.policy_sets.len(), 1);
+
+        let policy_set = body.delegation_evidence.policy_sets.get(0).unwrap();
+        assert_eq!(
+            policy_set
+                .policies
+                .get(0)
+                .unwrap()
+                .rules
+                .get(0)
+                .unwrap()
+                .effect,
+            "Deny"
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_policy_set_expired(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set10.json", &db).await;
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "test-expired",
+                                        "identifiers": ["test4"],
+                                        "attributes": ["zingers"]
+                                    },
+                                    "actions": ["Read"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: DelegationEvidenceContainer = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        let policy_set = body.delegation_evidence.policy_sets.get(0).unwrap();
+        assert_eq!(
+            policy_set
+                .policies
+                .get(0)
+                .unwrap()
+                .rules
+                .get(0)
+                .unwrap()
+                .effect,
+            "Deny"
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_capped_at_validity_window(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set11.json", &db).await;
+
+        let app = get_test_app(db);
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "test-window",
+                                        "identifiers": ["test4"],
+                                        "attributes": ["zingers"]
+                                    },
+                                    "actions": ["Read"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: DelegationEvidenceContainer = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        let policy_set = body.delegation_evidence.policy_sets.get(0).unwrap();
+        assert_eq!(
+            policy_set
+                .policies
+                .get(0)
+                .unwrap()
+                .rules
+                .get(0)
+                .unwrap()
+                .effect,
+            "Permit"
+        );
+        // capped at the end of the validity window of the policy set (2024-05-09T10:00:00Z)
+        assert_eq!(body.delegation_evidence.not_on_or_after, 1715248800);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_conditional_rule(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set12.json", &db).await;
+
+        // the fake time is a thursday at 11:33 in Amsterdam, so only the ip range decides. the ip
+        // in the environment of the request is ignored, the address of the connection decides
+        for (ip, expected_effect) in [("10.20.30.40", "Permit"), ("192.168.1.1", "Deny")] {
+            let app = get_test_app(db.clone());
+            let request_body = create_request_body(&json!({
+                "delegationRequest": {
+                    "policyIssuer": "NL.24244",
+                    "target": {
+                        "accessSubject": "NL.44444"
+                    },
+                    "policySets": [
+                        {
+                            "policies": [
+                                {
+                                    "target": {
+                                        "resource": {
+                                            "type": "test-conditional",
+                                            "identifiers": ["test4"],
+                                            "attributes": ["zingers"]
+                                        },
+                                        "actions": ["Read"],
+                                        "environment": {
+                                            "serviceProviders": ["good-company"]
+                                        }
+                                    },
+                                    "rules": [
+                                        {
+                                            "effect": "Permit"
+                                        }
+                                    ]
+                                }
+                            ]
+                        }
+                    ]
+                },
+                "environment": {
+                    "ip": "10.20.30.40"
+                }
+            }));
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/delegation")
+                        .method("POST")
+                        .header(
+                            AUTHORIZATION,
+                            server_token::server_token_test_helper::get_human_token_header(
+                                Some("NL.44444".to_owned()),
+                                None,
+                            ),
+                        )
+                        .header("Content-Type", "application/json")
+                        .header("Accept", "application/json")
+                        .extension(ConnectInfo(SocketAddr::new(ip.parse().unwrap(), 443)))
+                        .body(Body::new(request_body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body: DelegationEvidenceContainer = serde_json::from_str(
+                std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes())
+                    .unwrap(),
+            )
+            .unwrap();
+
+            let policy_set = body.delegation_evidence.policy_sets.get(0).unwrap();
+            assert_eq!(
+                policy_set
+                    .policies
+                    .get(0)
+                    .unwrap()
+                    .rules
+                    .get(0)
+                    .unwrap()
+                    .effect,
+                expected_effect
+            );
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_obligations(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set15.json", &db).await;
+        let app = get_test_app(db);
+
+        // the second decision is served from the decision cache
+        for _ in 0..2 {
+            let request_body = create_request_body(&json!({
+                "delegationRequest": {
+                    "policyIssuer": "NL.24244",
+                    "target": {
+                        "accessSubject": "NL.44444"
+                    },
+                    "policySets": [
+                        {
+                            "policies": [
+                                {
+                                    "target": {
+                                        "resource": {
+                                            "type": "test-obligations",
+                                            "identifiers": ["container-1"],
+                                            "attributes": ["*"]
+                                        },
+                                        "actions": ["Read"],
+                                        "environment": {
+                                            "serviceProviders": ["good-company"]
+                                        }
+                                    },
+                                    "rules": [
+                                        {
+                                            "effect": "Permit"
+                                        }
+                                    ]
+                                }
+                            ]
+                        }
+                    ]
+                }
+            }));
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri("/delegation?explain=true")
+                        .method("POST")
+                        .header(
+                            AUTHORIZATION,
+                            server_token::server_token_test_helper::get_human_token_header(
+                                Some("NL.44444".to_owned()),
+                                None,
+                            ),
+                        )
+                        .header("Content-Type", "application/json")
+                        .header("Accept", "application/json")
+                        .body(Body::new(request_body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body: serde_json::Value =
+                serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                    .unwrap();
+
+            let rule = &body["delegationEvidence"]["policySets"][0]["policies"][0]["rules"][0];
+            assert_eq!(rule["effect"], "Permit");
+            assert_eq!(
+                rule["obligations"],
+                json!([
+                    { "id": "log-access" },
+                    { "id": "max-records", "value": "100" },
+                    { "id": "delete-after-days", "value": "30" }
+                ])
+            );
+            assert_eq!(
+                rule["advice"],
+                json!(["contact the data owner before sharing the data"])
+            );
+
+            let trace = &body["explanation"][0];
+            assert_eq!(trace["obligations"], rule["obligations"]);
+            assert_eq!(
+                trace["matchingChains"][0]["hops"][0]["rules"][0]["advice"],
+                rule["advice"]
+            );
+        }
+
+        Ok(())
+    }
+
+    async fn party_request_effect(
+        app: &axum::Router,
+        resource_type: &str,
+        access_subject: &str,
+        service_provider: &str,
+    ) -> String {
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": access_subject
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": resource_type,
+                                        "identifiers": ["*"],
+                                        "attributes": ["*"]
+                                    },
+                                    "actions": ["Read"],
+                                    "environment": {
+                                        "serviceProviders": [service_provider]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            }
+        }));
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some(access_subject.to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: DelegationEvidenceContainer = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        body.delegation_evidence.policy_sets[0].policies[0].rules[0]
+            .effect
+            .clone()
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_access_subject_attributes(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set17.json", &db).await;
+        let app = get_test_app(db);
+
+        // the satellite registration of every test party is active and uses certificates
+        for access_subject in ["NL.44444", "NL.55555", "NL.44444"] {
+            assert_eq!(
+                party_request_effect(
+                    &app,
+                    "test-party-attributes",
+                    access_subject,
+                    "good-company"
+                )
+                .await,
+                "Permit"
+            );
+        }
+
+        // decisions that depend on the satellite registration are not cached
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/delegation-cache")
+                    .method("GET")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: serde_json::Value = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(body["hits"], 0);
+        assert_eq!(body["entries"], 0);
+
+        // a party that has to use spor doesn't match
+        insert_attributes_policy_set(
+            &app,
+            "test-party-attributes-spor",
+            json!({ "authentication": "spor" }),
+        )
+        .await;
+        assert_eq!(
+            party_request_effect(
+                &app,
+                "test-party-attributes-spor",
+                "NL.44444",
+                "good-company"
+            )
+            .await,
+            "Deny"
+        );
+
+        // the roles and dataspace of the party come from its satellite registration
+        insert_attributes_policy_set(
+            &app,
+            "test-party-attributes-roles",
+            json!({ "roles": ["ServiceConsumer"], "dataspace": "test-dataspace" }),
+        )
+        .await;
+        insert_attributes_policy_set(
+            &app,
+            "test-party-attributes-ar",
+            json!({ "roles": ["AuthorisationRegistry"] }),
+        )
+        .await;
+
+        assert_eq!(
+            party_request_effect(
+                &app,
+                "test-party-attributes-roles",
+                "NL.44444",
+                "good-company"
+            )
+            .await,
+            "Permit"
+        );
+        assert_eq!(
+            party_request_effect(&app, "test-party-attributes-ar", "NL.44444", "good-company")
+                .await,
+            "Deny"
+        );
+
+        Ok(())
+    }
+
+    async fn insert_attributes_policy_set(
+        app: &axum::Router,
+        resource_type: &str,
+        access_subject_attributes: serde_json::Value,
+    ) {
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/policy-set")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(create_request_body(&json!({
+                        "policies": [{
+                            "target": {
+                                "resource": {
+                                    "type": resource_type,
+                                    "identifiers": ["*"],
+                                    "attributes": ["*"]
+                                },
+                                "actions": ["Read"],
+                                "environment": {
+                                    "serviceProviders": ["good-company"]
+                                }
+                            },
+                            "rules": [{ "effect": "Permit" }]
+                        }],
+                        "target": {
+                            "accessSubject": "*",
+                            "accessSubjectAttributes": access_subject_attributes
+                        },
+                        "policyIssuer": "NL.24244",
+                        "licences": [],
+                        "maxDelegationDepth": 0
+                    }))))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    async fn user_request(
+        app: &axum::Router,
+        authorization: String,
+        access_subject_user: Option<&str>,
+    ) -> axum::response::Response {
+        let request_body = create_request_body(&json!({
+            "delegationRequest": {
+                "policyIssuer": "NL.24244",
+                "target": {
+                    "accessSubject": "NL.44444"
+                },
+                "policySets": [
+                    {
+                        "policies": [
+                            {
+                                "target": {
+                                    "resource": {
+                                        "type": "test-access-subject-user",
+                                        "identifiers": ["*"],
+                                        "attributes": ["*"]
+                                    },
+                                    "actions": ["Read"],
+                                    "environment": {
+                                        "serviceProviders": ["good-company"]
+                                    }
+                                },
+                                "rules": [
+                                    {
+                                        "effect": "Permit"
+                                    }
+                                ]
+                            }
+                        ]
+                    }
+                ]
+            },
+            "accessSubjectUser": access_subject_user
+        }));
+
+        app.clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation")
+                    .method("POST")
+                    .header(AUTHORIZATION, authorization)
+                    .header("Content-Type", "application/json")
+                    .header("Accept", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap()
+    }
+
+    async fn response_effect(response: axum::response::Response) -> String {
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: DelegationEvidenceContainer = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        body.delegation_evidence.policy_sets[0].policies[0].rules[0]
+            .effect
+            .clone()
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_access_subject_user(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set18.json", &db).await;
+        let app = get_test_app(db);
+
+        let user_token = server_token::server_token_test_helper::get_human_token_header(
+            Some("NL.44444".to_owned()),
+            Some("bad-user".to_owned()),
+        );
+        let other_user_token = server_token::server_token_test_helper::get_human_token_header(
+            Some("NL.44444".to_owned()),
+            Some("other-user".to_owned()),
+        );
+        let machine_token = server_token::server_token_test_helper::get_machine_token_header(Some(
+            "NL.44444".to_owned(),
+        ));
+        let issuer_machine_token = server_token::server_token_test_helper::get_machine_token_header(
+            Some("NL.24244".to_owned()),
+        );
+
+        // the evidence stays issued to the company and names the user next to it
+        for authorization in [user_token.clone(), machine_token.clone()] {
+            let response = user_request(&app, authorization, Some("bad-user")).await;
+            assert_eq!(response.status(), StatusCode::OK);
+            let body: serde_json::Value = serde_json::from_str(
+                std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes())
+                    .unwrap(),
+            )
+            .unwrap();
+            assert_eq!(
+                body["delegationEvidence"]["target"],
+                json!({ "accessSubject": "NL.44444", "accessSubjectUser": "bad-user" })
+            );
+            assert_eq!(
+                body["delegationEvidence"]["policySets"][0]["policies"][0]["rules"][0]["effect"],
+                "Permit"
+            );
+        }
+        // the policy set doesn't apply to the company or its other users
+        assert_eq!(
+            response_effect(user_request(&app, user_token.clone(), None).await).await,
+            "Deny"
+        );
+        assert_eq!(
+            response_effect(user_request(&app, other_user_token.clone(), Some("other-user")).await)
+                .await,
+            "Deny"
+        );
+        // only the company itself requests the rights of its users
+        assert_eq!(
+            user_request(&app, issuer_machine_token, Some("bad-user"))
+                .await
+                .status(),
+            StatusCode::FORBIDDEN
+        );
+        // a user can't request the rights of another user of the company
+        assert_eq!(
+            user_request(&app, other_user_token.clone(), Some("bad-user"))
+                .await
+                .status(),
+            StatusCode::FORBIDDEN
+        );
+
+        for (authorization, status, count) in [
+            (user_token, StatusCode::OK, 1),
+            (other_user_token, StatusCode::OK, 0),
+            (machine_token, StatusCode::FORBIDDEN, 0),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(
+                    Request::builder()
+                        .uri("/policy-set/granted-to-me")
+                        .method("GET")
+                        .header(AUTHORIZATION, authorization)
+                        .body(Body::empty())
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+            assert_eq!(response.status(), status);
+
+            if status == StatusCode::OK {
+                let body: Vec<serde_json::Value> = serde_json::from_str(
+                    std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes())
+                        .unwrap(),
+                )
+                .unwrap();
+                assert_eq!(body.len(), count);
+            }
+        }
+
+        // the user has to be a user of the access subject
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/policy-set")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(create_request_body(&json!({
+                        "policies": [{
+                            "target": {
+                                "resource": {
+                                    "type": "test-access-subject-user",
+                                    "identifiers": ["*"],
+                                    "attributes": ["*"]
+                                },
+                                "actions": ["Read"],
+                                "environment": {
+                                    "serviceProviders": ["good-company"]
+                                }
+                            },
+                            "rules": [{ "effect": "Permit" }]
+                        }],
+                        "target": {
+                            "accessSubject": "NL.44444",
+                            "accessSubjectUser": "lovely-user"
+                        },
+                        "policyIssuer": "NL.24244",
+                        "licences": [],
+                        "maxDelegationDepth": 0
+                    }))))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_party_groups(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set16.json", &db).await;
+        for (name, members) in [
+            ("CARRIERS", vec!["NL.CARRIER.1".to_owned()]),
+            ("TERMINALS", vec!["NL.TERMINAL".to_owned()]),
+        ] {
+            party_group_store::insert_party_group(name, "", &db)
+                .await
+                .unwrap();
+            party_group_store::insert_party_group_members(name, &members, &db)
+                .await
+                .unwrap();
+        }
+        let app = get_test_app(db);
+
+        assert_eq!(
+            party_request_effect(&app, "test-party-groups", "NL.CARRIER.1", "NL.TERMINAL").await,
+            "Permit"
+        );
+        assert_eq!(
+            party_request_effect(&app, "test-party-groups", "NL.CARRIER.1", "NL.OTHER").await,
+            "Deny"
+        );
+        assert_eq!(
+            party_request_effect(&app, "test-party-groups", "NL.CARRIER.2", "NL.TERMINAL").await,
+            "Deny"
+        );
+
+        // removing the member invalidates the cached decision
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/party-group/carriers/member/NL.CARRIER.1")
+                    .method("DELETE")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        assert_eq!(
+            party_request_effect(&app, "test-party-groups", "NL.CARRIER.1", "NL.TERMINAL").await,
+            "Deny"
+        );
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_party_group_chain(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set19.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set20.json", &db).await;
+        party_group_store::insert_party_group("FORWARDERS", "", &db)
+            .await
+            .unwrap();
+        party_group_store::insert_party_group_members(
+            "FORWARDERS",
+            &vec!["NL.FORWARDER".to_owned()],
+            &db,
+        )
+        .await
+        .unwrap();
+        let app = get_test_app(db.clone());
+
+        // the member of the group the policy issuer delegated to can delegate further
+        assert_eq!(
+            party_request_effect(&app, "test-group-chain", "NL.44444", "good-company").await,
+            "Permit"
+        );
+
+        let now = FakeTimeProvider::new().now();
+        let policy_sets = crate::db::policy::get_policy_sets_with_policies_for_policy_issuers(
+            now,
+            vec!["NL.24244".to_owned()],
+            vec!["NL.44444".to_owned()],
+            2,
+            &db,
+        )
+        .await
+        .unwrap();
+        assert!(policy_sets
+            .iter()
+            .any(|ps| ps.policy_issuer == "NL.FORWARDER"));
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_identifier_patterns(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set13.json", &db).await;
+
+        for (identifier, expected_effect) in [
+            ("urn:container:NL1234", "Permit"),
+            ("urn:vessel:9074729", "Permit"),
+            ("urn:container:BE1234", "Deny"),
+            ("urn:vessel:90747290", "Deny"),
+            // denied by the deny rule
+            ("urn:container:NLX1234", "Deny"),
+        ] {
+            let app = get_test_app(db.clone());
+            let request_body = create_request_body(&json!({
+                "delegationRequest": {
+                    "policyIssuer": "NL.24244",
+                    "target": {
+                        "accessSubject": "NL.44444"
+                    },
+                    "policySets": [
+                        {
+                            "policies": [
+                                {
+                                    "target": {
+                                        "resource": {
+                                            "type": "test-pattern",
+                                            "identifiers": [identifier],
+                                            "attributes": ["zingers"]
+                                        },
+                                        "actions": ["Read"],
+                                        "environment": {
+                                            "serviceProviders": ["good-company"]
+                                        }
+                                    },
+                                    "rules": [
+                                        {
+                                            "effect": "Permit"
+                                        }
+                                    ]
+                                }
+                            ]
+                        }
+                    ]
+                }
+            }));
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/delegation")
+                        .method("POST")
+                        .header(
+                            AUTHORIZATION,
+                            server_token::server_token_test_helper::get_human_token_header(
+                                Some("NL.44444".to_owned()),
+                                None,
+                            ),
+                        )
+                        .header("Content-Type", "application/json")
+                        .header("Accept", "application/json")
+                        .body(Body::new(request_body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body: DelegationEvidenceContainer = serde_json::from_str(
+                std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes())
+                    .unwrap(),
+            )
+            .unwrap();
+
+            let policy_set = body.delegation_evidence.policy_sets.get(0).unwrap();
+            assert_eq!(
+                policy_set
+                    .policies
+                    .get(0)
+                    .unwrap()
+                    .rules
+                    .get(0)
+                    .unwrap()
+                    .effect,
+                expected_effect
+            );
+        }
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_exact_party_match(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set14.json", &db).await;
+
+        for (access_subject, expected_effect) in [
+            ("EU.EORI.NL12", "Permit"),
+            // identifiers are normalized before matching
+            (" eu.eori.nl12 ", "Permit"),
+            // a prefix of the access subject is a different party
+            ("EU.EORI.NL1", "Deny"),
+            ("EU.EORI.NL123", "Deny"),
+        ] {
+            let app = get_test_app(db.clone());
+            let request_body = create_request_body(&json!({
+                "delegationRequest": {
+                    "policyIssuer": "nl.24244",
+                    "target": {
+                        "accessSubject": access_subject
+                    },
+                    "policySets": [
+                        {
+                            "policies": [
+                                {
+                                    "target": {
+                                        "resource": {
+                                            "type": "test-exact-party",
+                                            "identifiers": ["container-1"],
+                                            "attributes": ["*"]
+                                        },
+                                        "actions": ["Read"],
+                                        "environment": {
+                                            "serviceProviders": ["good-company"]
+                                        }
+                                    },
+                                    "rules": [
+                                        {
+                                            "effect": "Permit"
+                                        }
+                                    ]
+                                }
+                            ]
+                        }
+                    ]
+                }
+            }));
+            let response = app
+                .oneshot(
+                    Request::builder()
+                        .uri("/delegation")
+                        .method("POST")
+                        .header(
+                            AUTHORIZATION,
+                            server_token::server_token_test_helper::get_human_token_header(
+                                Some("NL.24244".to_owned()),
+                                None,
+                            ),
+                        )
+                        .header("Content-Type", "application/json")
+                        .header("Accept", "application/json")
+                        .body(Body::new(request_body))
+                        .unwrap(),
+                )
+                .await
+                .unwrap();
+
+            assert_eq!(response.status(), StatusCode::OK);
+
+            let body: DelegationEvidenceContainer = serde_json::from_str(
+                std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes())
                     .unwrap(),
             )
-            .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::OK);
+            let policy_set = body.delegation_evidence.policy_sets.get(0).unwrap();
+            assert_eq!(
+                policy_set
+                    .policies
+                    .get(0)
+                    .unwrap()
+                    .rules
+                    .get(0)
+                    .unwrap()
+                    .effect,
+                expected_effect,
+                "access subject '{}'",
+                access_subject
+            );
+        }
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_delegation_evidence_wildcard(
+    async fn test_delegation_evidence_explain(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set3.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set13.json", &db).await;
+
+        // a delegation of the policy issuer to a third party is not on a chain to the access
+        // subject, so it is no candidate
+        insert_policy_set_with_policies_into_db(
+            chrono::Utc::now(),
+            &InsertPolicySetWithPolicies {
+                policy_issuer: "NL.24244".to_owned(),
+                target: AccessSubjectTarget {
+                    access_subject: "NL.THIRD_PARTY".to_owned(),
+                    access_subject_attributes: None,
+                    access_subject_user: None,
+                },
+                licences: vec![],
+                policies: vec![Policy {
+                    target: ResourceTarget {
+                        resource: Resource {
+                            identifiers: vec!["*".to_owned()],
+                            attributes: vec!["*".to_owned()],
+                            resource_type: "test-pattern".to_owned(),
+                        },
+                        actions: vec!["Read".to_owned()],
+                        environment: Environment {
+                            service_providers: vec![],
+                        },
+                    },
+                    rules: vec![ResourceRule::Permit(Permit::default())],
+                }],
+                max_delegation_depth: 1,
+                not_before: None,
+                not_on_or_after: None,
+            },
+            PolicySetStatus::Active,
+            &db,
+        )
+        .await
+        .unwrap();
 
         let app = get_test_app(db);
         let request_body = create_request_body(&json!({
@@ -459,11 +3052,11 @@ mod test {
                             {
                                 "target": {
                                     "resource": {
-                                        "type": "test-iden",
-                                        "identifiers": ["test4"],
+                                        "type": "test-pattern",
+                                        "identifiers": ["urn:container:NLX1234"],
                                         "attributes": ["zingers"]
                                     },
-                                    "actions": ["Read", "Delete"],
+                                    "actions": ["Read"],
                                     "environment": {
                                         "serviceProviders": ["good-company"]
                                     }
@@ -482,7 +3075,7 @@ mod test {
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/delegation")
+                    .uri("/delegation?explain=true")
                     .method("POST")
                     .header(
                         AUTHORIZATION,
@@ -501,29 +3094,36 @@ mod test {
 
         assert_eq!(response.status(), StatusCode::OK);
 
-        let body: DelegationEvidenceContainer = serde_json::from_str(
+        let body: serde_json::Value = serde_json::from_str(
             std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
         )
         .unwrap();
 
-        assert_eq!(body.delegation_evidence.policy_sets.len() > 0, true);
-
-        for ps in body.delegation_evidence.policy_sets.iter() {
-            for p in ps.policies.iter() {
-                assert_eq!(p.rules.get(0).unwrap().effect, "Permit")
-            }
-        }
+        let explanation = &body["explanation"][0];
+        assert_eq!(explanation["effect"], "Deny");
+        assert_eq!(
+            explanation["candidatePolicySetIds"],
+            json!(["c7a1e3b5-2d4f-4a6c-8e0b-3d5f7a9c1e13"])
+        );
+        assert_eq!(
+            explanation["matchingChains"][0]["hops"][0]["matchingPolicyIds"],
+            json!(["1d3f5b7a-9c2e-4f6a-b8d0-4e6a8c0b2d13"])
+        );
+        assert_eq!(
+            explanation["reason"],
+            "Deny rule of policy 1d3f5b7a-9c2e-4f6a-b8d0-4e6a8c0b2d13 decided: deny matches the requested resource and actions"
+        );
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_delegation_evidence_unallowed_action(
+    async fn test_delegation_evidence_explain_not_allowed(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set13.json", &db).await;
 
         let app = get_test_app(db);
         let request_body = create_request_body(&json!({
@@ -538,11 +3138,11 @@ mod test {
                             {
                                 "target": {
                                     "resource": {
-                                        "type": "test-iden",
-                                        "identifiers": ["test4"],
+                                        "type": "test-pattern",
+                                        "identifiers": ["urn:container:NLX1234"],
                                         "attributes": ["zingers"]
                                     },
-                                    "actions": ["Read", "Delete", "Fish"],
+                                    "actions": ["Read"],
                                     "environment": {
                                         "serviceProviders": ["good-company"]
                                     }
@@ -561,12 +3161,12 @@ mod test {
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/delegation")
+                    .uri("/delegation?explain=true")
                     .method("POST")
                     .header(
                         AUTHORIZATION,
                         server_token::server_token_test_helper::get_human_token_header(
-                            Some("NL.44444".to_owned()),
+                            Some("good-company".to_owned()),
                             None,
                         ),
                     )
@@ -578,43 +3178,13 @@ mod test {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::OK);
-
-        let body: DelegationEvidenceContainer = serde_json::from_str(
-            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
-        )
-        .unwrap();
-
-        assert_eq!(body.delegation_evidence.policy_sets.len() == 1, true);
-        assert_eq!(
-            body.delegation_evidence
-                .policy_sets
-                .get(0)
-                .unwrap()
-                .policies
-                .get(0)
-                .unwrap()
-                .rules
-                .get(0)
-                .unwrap()
-                .effect
-                == "Deny",
-            true
-        );
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
 
         Ok(())
     }
 
-    #[sqlx::test]
-    async fn test_delegation_evidence_specfic_identifier_match(
-        _pool_options: PgPoolOptions,
-        conn_option: PgConnectOptions,
-    ) -> sqlx::Result<()> {
-        let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
-
-        let app = get_test_app(db);
-        let request_body = create_request_body(&json!({
+    fn simulation_request(changes: serde_json::Value) -> serde_json::Value {
+        json!({
             "delegationRequest": {
                 "policyIssuer": "NL.24244",
                 "target": {
@@ -626,11 +3196,11 @@ mod test {
                             {
                                 "target": {
                                     "resource": {
-                                        "type": "test-iden",
-                                        "identifiers": ["specific"],
+                                        "type": "test-pattern",
+                                        "identifiers": ["urn:container:NLX1234"],
                                         "attributes": ["zingers"]
                                     },
-                                    "actions": ["Read", "Delete"],
+                                    "actions": ["Read"],
                                     "environment": {
                                         "serviceProviders": ["good-company"]
                                     }
@@ -644,159 +3214,252 @@ mod test {
                         ]
                     }
                 ]
-            }
-        }));
+            },
+            "changes": changes
+        })
+    }
+
+    async fn simulate(db: &DatabaseConnection, request: serde_json::Value) -> (StatusCode, String) {
+        simulate_as(db, "NL.24244", request).await
+    }
+
+    async fn simulate_as(
+        db: &DatabaseConnection,
+        company: &str,
+        request: serde_json::Value,
+    ) -> (StatusCode, String) {
+        let app = get_test_app(db.clone());
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/delegation")
+                    .uri("/delegation/simulate")
                     .method("POST")
                     .header(
                         AUTHORIZATION,
                         server_token::server_token_test_helper::get_human_token_header(
-                            Some("NL.44444".to_owned()),
+                            Some(company.to_owned()),
                             None,
                         ),
                     )
                     .header("Content-Type", "application/json")
-                    .header("Accept", "application/json")
-                    .body(Body::new(request_body))
+                    .body(Body::new(create_request_body(&request)))
                     .unwrap(),
             )
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::OK);
-
-        let body: DelegationEvidenceContainer = serde_json::from_str(
-            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
-        )
-        .unwrap();
-
-        assert_eq!(body.delegation_evidence.policy_sets.len() > 0, true);
+        let status = response.status();
+        let body = std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes())
+            .unwrap()
+            .to_owned();
 
-        for ps in body.delegation_evidence.policy_sets.iter() {
-            for p in ps.policies.iter() {
-                assert_eq!(p.rules.get(0).unwrap().effect, "Deny")
-            }
-        }
+        (status, body)
+    }
 
-        Ok(())
+    fn first_effect(body: &str) -> String {
+        let body: DelegationEvidenceContainer = serde_json::from_str(body).unwrap();
+        body.delegation_evidence.policy_sets[0].policies[0].rules[0]
+            .effect
+            .clone()
     }
 
     #[sqlx::test]
-    async fn test_delegation_evidence_resource_type_start(
+    async fn test_simulate_delegation(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set13.json", &db).await;
 
-        let app = get_test_app(db);
-        let request_body = create_request_body(&json!({
-            "delegationRequest": {
-                "policyIssuer": "NL.24244",
+        let replacement = json!({
+            "target": {
+                "accessSubject": "NL.44444"
+            },
+            "policyIssuer": "NL.24244",
+            "licences": [],
+            "maxDelegationDepth": 0,
+            "policies": [{
                 "target": {
-                    "accessSubject": "NL.44444"
-                },
-                "policySets": [
-                    {
-                        "policies": [
-                            {
-                                "target": {
-                                    "resource": {
-                                        "type": "*",
-                                        "identifiers": ["specific"],
-                                        "attributes": ["zingers"]
-                                    },
-                                    "actions": ["Read", "Delete"],
-                                    "environment": {
-                                        "serviceProviders": ["good-company"]
-                                    }
-                                },
-                                "rules": [
-                                    {
-                                        "effect": "Permit"
-                                    }
-                                ]
-                            }
-                        ]
+                    "resource": {
+                        "type": "test-pattern",
+                        "identifiers": ["urn:container:NL*"],
+                        "attributes": ["*"]
+                    },
+                    "actions": ["Read"],
+                    "environment": {
+                        "serviceProviders": ["good-company"]
                     }
-                ]
-            }
-        }));
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .uri("/delegation")
-                    .method("POST")
-                    .header(
-                        AUTHORIZATION,
-                        server_token::server_token_test_helper::get_human_token_header(None, None),
-                    )
-                    .header("Content-Type", "application/json")
-                    .header("Accept", "application/json")
-                    .body(Body::new(request_body))
-                    .unwrap(),
-            )
+                },
+                "rules": [{ "effect": "Permit" }]
+            }]
+        });
+
+        // the live policy set denies the container
+        let (status, body) = simulate(&db, simulation_request(json!({}))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(first_effect(&body), "Deny");
+
+        // replacing it with a policy set without the deny rule permits
+        let mut replace = replacement.clone();
+        replace["policySetId"] = json!("c7a1e3b5-2d4f-4a6c-8e0b-3d5f7a9c1e13");
+        let (status, body) =
+            simulate(&db, simulation_request(json!({ "replace": [replace] }))).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(first_effect(&body), "Permit");
+
+        // adding a permitting policy set doesn't lift the deny of the live one
+        let (status, body) = simulate(
+            &db,
+            simulation_request(json!({ "add": [replacement.clone()] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        let body: DelegationEvidenceContainer = serde_json::from_str(&body).unwrap();
+        assert_eq!(body.delegation_evidence.policy_sets.len(), 2);
+
+        // removing the live one and adding the new one permits
+        let (status, body) = simulate(
+            &db,
+            simulation_request(json!({
+                "remove": ["c7a1e3b5-2d4f-4a6c-8e0b-3d5f7a9c1e13"],
+                "add": [replacement]
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(first_effect(&body), "Permit");
+
+        // nothing is written to the audit log and the live policy set is untouched
+        let audit_events = ar_entity::audit_event::Entity::find()
+            .count(&db)
             .await
             .unwrap();
+        assert_eq!(audit_events, 0);
+        let (_, body) = simulate(&db, simulation_request(json!({}))).await;
+        assert_eq!(first_effect(&body), "Deny");
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_simulate_delegation_invalid_change(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+
+        let (status, _) = simulate(
+            &db,
+            simulation_request(json!({
+                "add": [{
+                    "target": {
+                        "accessSubject": "NL.44444"
+                    },
+                    "policyIssuer": "NL.24244",
+                    "licences": [],
+                    "maxDelegationDepth": 0,
+                    "policies": [{
+                        "target": {
+                            "resource": {
+                                "type": "test-pattern",
+                                "identifiers": ["regex:urn:[a-z"],
+                                "attributes": ["*"]
+                            },
+                            "actions": ["Read"],
+                            "environment": {
+                                "serviceProviders": ["good-company"]
+                            }
+                        },
+                        "rules": [{ "effect": "Permit" }]
+                    }]
+                }]
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_delegation_evidence_pi_different_as(
+    async fn test_simulate_delegation_not_policy_issuer(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set13.json", &db).await;
 
-        let pi = "1".to_owned();
-        let as1 = "2".to_owned();
-        let as2 = "3".to_owned();
-        let target_id = "target".to_owned();
-        let resource_type = "Resource".to_owned();
-        let actions = vec!["Read".to_owned()];
-        let environment = Environment {
-            service_providers: vec!["service".to_owned()],
-        };
+        let (status, _) = simulate_as(&db, "NL.44444", simulation_request(json!({}))).await;
+        assert_eq!(status, StatusCode::FORBIDDEN);
 
-        insert_policy_set_with_policies_into_db(
-            chrono::Utc::now(),
-            &InsertPolicySetWithPolicies {
-                policy_issuer: pi.clone(),
-                target: AccessSubjectTarget {
-                    access_subject: as1.clone(),
-                },
-                licences: vec![],
-                policies: vec![Policy {
-                    target: ResourceTarget {
-                        resource: Resource {
-                            identifiers: vec![target_id],
-                            attributes: vec!["*".to_owned()],
-                            resource_type: resource_type,
-                        },
-                        actions,
-                        environment,
-                    },
-                    rules: vec![ResourceRule::Permit],
-                }],
-                max_delegation_depth: 1,
-            },
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_simulate_delegation_changes_of_other_issuer(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set13.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set_audit_log.json", &db).await;
+
+        // the policy set of another issuer can't be removed
+        let (status, _) = simulate(
             &db,
+            simulation_request(json!({ "remove": ["87fe1aaf-2aa9-47a7-b014-b44b3a8dd8d7"] })),
         )
-        .await
-        .unwrap();
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
 
-        let app = get_test_app(db);
-        let request_body = create_request_body(&json!({
+        // nor can a policy set that doesn't exist
+        let (status, _) = simulate(
+            &db,
+            simulation_request(json!({ "remove": ["00000000-0000-0000-0000-000000000000"] })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        // nor can a hypothetical policy set be issued by another party
+        let (status, _) = simulate(
+            &db,
+            simulation_request(json!({
+                "add": [{
+                    "target": {
+                        "accessSubject": "NL.44444"
+                    },
+                    "policyIssuer": "NL.CONSUME_TOO_MUCH",
+                    "licences": [],
+                    "maxDelegationDepth": 0,
+                    "policies": [{
+                        "target": {
+                            "resource": {
+                                "type": "test-pattern",
+                                "identifiers": ["*"],
+                                "attributes": ["*"]
+                            },
+                            "actions": ["Read"],
+                            "environment": {
+                                "serviceProviders": ["good-company"]
+                            }
+                        },
+                        "rules": [{ "effect": "Permit" }]
+                    }]
+                }]
+            })),
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    fn batch_item(policy_issuer: &str, resource_type: &str) -> serde_json::Value {
+        json!({
             "delegationRequest": {
-                "policyIssuer": pi,
+                "policyIssuer": policy_issuer,
                 "target": {
-                    "accessSubject": as2
+                    "accessSubject": "NL.44444"
                 },
                 "policySets": [
                     {
@@ -804,13 +3467,13 @@ mod test {
                             {
                                 "target": {
                                     "resource": {
-                                        "type": "Resource",
-                                        "identifiers": ["target"],
-                                        "attributes": ["*"]
+                                        "type": resource_type,
+                                        "identifiers": ["urn:container:NL1234"],
+                                        "attributes": ["zingers"]
                                     },
                                     "actions": ["Read"],
                                     "environment": {
-                                        "serviceProviders": ["service"]
+                                        "serviceProviders": ["good-company"]
                                     }
                                 },
                                 "rules": [
@@ -823,21 +3486,38 @@ mod test {
                     }
                 ]
             }
+        })
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_batch(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set13.json", &db).await;
+
+        let app = get_test_app(db.clone());
+        let request_body = create_request_body(&json!({
+            "requests": [
+                batch_item("NL.24244", "test-pattern"),
+                batch_item("NL.24244", "*"),
+                batch_item("NL.24244", "test-pattern"),
+            ]
         }));
         let response = app
             .oneshot(
                 Request::builder()
-                    .uri("/delegation")
+                    .uri("/delegation/batch")
                     .method("POST")
                     .header(
                         AUTHORIZATION,
                         server_token::server_token_test_helper::get_human_token_header(
-                            Some(pi),
+                            Some("NL.44444".to_owned()),
                             None,
                         ),
                     )
                     .header("Content-Type", "application/json")
-                    .header("Accept", "application/json")
                     .body(Body::new(request_body))
                     .unwrap(),
             )
@@ -846,38 +3526,82 @@ mod test {
 
         assert_eq!(response.status(), StatusCode::OK);
 
-        let body: DelegationEvidenceContainer = serde_json::from_str(
+        let body: serde_json::Value = serde_json::from_str(
             std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
         )
         .unwrap();
 
-        tracing::info!("{:?}", body);
-
-        assert_eq!(body.delegation_evidence.policy_sets.len() > 0, true);
+        let results = body["results"].as_array().unwrap();
+        assert_eq!(results.len(), 3);
+        assert_eq!(results[0]["delegation_token"], "delegation token");
+        assert_eq!(results[1]["error"]["status"], 400);
+        assert_eq!(results[1]["error"]["error"], "resource type cannot be '*'");
+        assert_eq!(results[2]["delegation_token"], "delegation token");
 
-        for ps in body.delegation_evidence.policy_sets.iter() {
-            for p in ps.policies.iter() {
-                assert_eq!(p.rules.get(0).unwrap().effect, "Deny")
-            }
-        }
+        let audit_events = ar_entity::audit_event::Entity::find()
+            .count(&db)
+            .await
+            .unwrap();
+        assert_eq!(audit_events, 3);
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_delegation_evidence_no_identifier(
+    async fn test_delegation_batch_not_allowed_item(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
 
         let app = get_test_app(db);
+        let mut other_subject = batch_item("NL.24244", "test-pattern");
+        other_subject["delegationRequest"]["target"]["accessSubject"] = json!("NL.55555");
+        let request_body = create_request_body(&json!({
+            "requests": [batch_item("NL.24244", "test-pattern"), other_subject]
+        }));
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation/batch")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: serde_json::Value = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+
+        let results = body["results"].as_array().unwrap();
+        assert_eq!(results[0]["delegation_token"], "delegation token");
+        assert_eq!(
+            results[1]["error"]["error"],
+            "not allowed to request delegation evidence"
+        );
+
+        Ok(())
+    }
+
+    async fn cached_request_effect(app: &axum::Router) -> String {
         let request_body = create_request_body(&json!({
             "delegationRequest": {
                 "policyIssuer": "NL.24244",
                 "target": {
-                    "accessSubject": "NL.44444"
+                    "accessSubject": "EU.EORI.NL12"
                 },
                 "policySets": [
                     {
@@ -885,11 +3609,11 @@ mod test {
                             {
                                 "target": {
                                     "resource": {
-                                        "type": "something",
-                                        "identifiers": [],
-                                        "attributes": ["zingers"]
+                                        "type": "test-exact-party",
+                                        "identifiers": ["container-1"],
+                                        "attributes": ["*"]
                                     },
-                                    "actions": ["Read", "Delete"],
+                                    "actions": ["Read"],
                                     "environment": {
                                         "serviceProviders": ["good-company"]
                                     }
@@ -906,13 +3630,17 @@ mod test {
             }
         }));
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
                     .uri("/delegation")
                     .method("POST")
                     .header(
                         AUTHORIZATION,
-                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.24244".to_owned()),
+                            None,
+                        ),
                     )
                     .header("Content-Type", "application/json")
                     .header("Accept", "application/json")
@@ -922,20 +3650,77 @@ mod test {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::OK);
 
-        Ok(())
+        first_effect(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
     }
 
     #[sqlx::test]
-    async fn test_delegation_evidence_no_attributes(
+    async fn test_delegation_evidence_cached(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set14.json", &db).await;
+        let app = get_test_app(db.clone());
 
-        let app = get_test_app(db);
+        assert_eq!(cached_request_effect(&app).await, "Permit");
+        assert_eq!(cached_request_effect(&app).await, "Permit");
+
+        // deleting the policy set invalidates the cached decision
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/policy-set/a4c6e8f0-1b3d-4f5a-9c7e-2d4f6a8c0e14")
+                    .method("DELETE")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.24244".to_owned()),
+                            None,
+                        ),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        assert_eq!(cached_request_effect(&app).await, "Deny");
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/admin/delegation-cache")
+                    .method("GET")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                    )
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let body: serde_json::Value = serde_json::from_str(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
+        .unwrap();
+        assert_eq!(body["hits"], 1);
+        assert_eq!(body["misses"], 2);
+        assert_eq!(body["entries"], 1);
+
+        Ok(())
+    }
+
+    async fn licensed_request_effect(app: &axum::Router, licenses: Vec<&str>) -> String {
         let request_body = create_request_body(&json!({
             "delegationRequest": {
                 "policyIssuer": "NL.24244",
@@ -948,11 +3733,11 @@ mod test {
                             {
                                 "target": {
                                     "resource": {
-                                        "type": "something",
-                                        "identifiers": ["zainger"],
-                                        "attributes": []
+                                        "type": "test-chain",
+                                        "identifiers": ["test4"],
+                                        "attributes": ["zingers"]
                                     },
-                                    "actions": ["Read", "Delete"],
+                                    "actions": ["Read"],
                                     "environment": {
                                         "serviceProviders": ["good-company"]
                                     }
@@ -966,16 +3751,21 @@ mod test {
                         ]
                     }
                 ]
-            }
+            },
+            "licenses": licenses
         }));
         let response = app
+            .clone()
             .oneshot(
                 Request::builder()
                     .uri("/delegation")
                     .method("POST")
                     .header(
                         AUTHORIZATION,
-                        server_token::server_token_test_helper::get_human_token_header(None, None),
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
                     )
                     .header("Content-Type", "application/json")
                     .header("Accept", "application/json")
@@ -985,25 +3775,62 @@ mod test {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::OK);
 
-        Ok(())
+        first_effect(
+            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        )
     }
 
     #[sqlx::test]
-    async fn test_delegation_evidence_specfic_identifier_no_match(
+    async fn test_delegation_evidence_required_licenses(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set2.json", &db).await;
-
+        insert_policy_set_fixture("./fixtures/policy_set8.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set9.json", &db).await;
         let app = get_test_app(db);
+
+        // the last hop of the delegation chain grants ISHARE.0001
+        assert_eq!(licensed_request_effect(&app, vec![]).await, "Permit");
+        assert_eq!(
+            licensed_request_effect(&app, vec!["ISHARE.0001"]).await,
+            "Permit"
+        );
+        assert_eq!(
+            licensed_request_effect(&app, vec!["ISHARE.0001", "ISHARE.0002"]).await,
+            "Deny"
+        );
+
+        Ok(())
+    }
+
+    fn previous_step(iss: &str, aud: &str, jti: &str) -> String {
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &json!({
+                "iss": iss,
+                "sub": iss,
+                "aud": aud,
+                "jti": jti,
+                "iat": 1715247200,
+                "exp": 1715247230,
+            }),
+            &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap()
+    }
+
+    async fn previous_steps_request(
+        app: &axum::Router,
+        previous_steps: Vec<String>,
+    ) -> axum::response::Response {
         let request_body = create_request_body(&json!({
             "delegationRequest": {
                 "policyIssuer": "NL.24244",
                 "target": {
-                    "accessSubject": "NL.44444"
+                    "accessSubject": "EU.EORI.NL12"
                 },
                 "policySets": [
                     {
@@ -1011,11 +3838,11 @@ mod test {
                             {
                                 "target": {
                                     "resource": {
-                                        "type": "test-iden",
-                                        "identifiers": ["something else"],
-                                        "attributes": ["zingers"]
+                                        "type": "test-exact-party",
+                                        "identifiers": ["container-1"],
+                                        "attributes": ["*"]
                                     },
-                                    "actions": ["Read", "Delete"],
+                                    "actions": ["Read"],
                                     "environment": {
                                         "serviceProviders": ["good-company"]
                                     }
@@ -1029,9 +3856,11 @@ mod test {
                         ]
                     }
                 ]
-            }
+            },
+            "previousSteps": previous_steps
         }));
-        let response = app
+
+        app.clone()
             .oneshot(
                 Request::builder()
                     .uri("/delegation")
@@ -1039,7 +3868,7 @@ mod test {
                     .header(
                         AUTHORIZATION,
                         server_token::server_token_test_helper::get_human_token_header(
-                            Some("NL.44444".to_owned()),
+                            Some("NL.REQUESTER".to_owned()),
                             None,
                         ),
                     )
@@ -1049,22 +3878,196 @@ mod test {
                     .unwrap(),
             )
             .await
-            .unwrap();
+            .unwrap()
+    }
+
+    #[sqlx::test]
+    async fn test_delegation_evidence_previous_steps(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set14.json", &db).await;
+        let app = get_test_app(db);
 
+        // the access subject authorized a broker, that authorized the requester
+        let previous_steps = vec![
+            previous_step("EU.EORI.NL12", "NL.BROKER", "step-1"),
+            previous_step("NL.BROKER", "NL.REQUESTER", "step-2"),
+        ];
+        let response = previous_steps_request(&app, previous_steps.clone()).await;
         assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            first_effect(
+                std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes())
+                    .unwrap()
+            ),
+            "Permit"
+        );
 
-        let body: DelegationEvidenceContainer = serde_json::from_str(
-            std::str::from_utf8(&response.into_body().collect().await.unwrap().to_bytes()).unwrap(),
+        // previous steps can't be replayed
+        let response = previous_steps_request(&app, previous_steps).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        // the broker didn't authorize the requester
+        let response = previous_steps_request(
+            &app,
+            vec![
+                previous_step("EU.EORI.NL12", "NL.BROKER", "step-3"),
+                previous_step("NL.OTHER", "NL.REQUESTER", "step-4"),
+            ],
         )
-        .unwrap();
+        .await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(std::str::from_utf8(&body)
+            .unwrap()
+            .contains("previous step 2 is invalid"));
 
-        assert_eq!(body.delegation_evidence.policy_sets.len() > 0, true);
+        Ok(())
+    }
 
-        for ps in body.delegation_evidence.policy_sets.iter() {
-            for p in ps.policies.iter() {
-                assert_eq!(p.rules.get(0).unwrap().effect, "Permit")
-            }
-        }
+    fn delegation_token(iss: &str, aud: &str, exp: i64) -> String {
+        jsonwebtoken::encode(
+            &jsonwebtoken::Header::default(),
+            &json!({
+                "iss": iss,
+                "sub": iss,
+                "aud": aud,
+                "jti": "delegation-token",
+                "iat": 1715247200,
+                "exp": exp,
+                "delegationEvidence": {
+                    "notBefore": 1715247000,
+                    "notOnOrAfter": 1715248000,
+                    "policyIssuer": "NL.24244",
+                    "target": {
+                        "accessSubject": "NL.REQUESTER"
+                    },
+                    "policySets": [
+                        {
+                            "maxDelegationDepth": 0,
+                            "target": {
+                                "environment": {
+                                    "licenses": ["ISHARE.0001"]
+                                }
+                            },
+                            "policies": [
+                                {
+                                    "target": {
+                                        "resource": {
+                                            "type": "container",
+                                            "identifiers": ["container-1"],
+                                            "attributes": ["*"]
+                                        },
+                                        "actions": ["Read"]
+                                    },
+                                    "rules": [
+                                        {
+                                            "effect": "Permit"
+                                        }
+                                    ]
+                                }
+                            ]
+                        }
+                    ]
+                }
+            }),
+            &jsonwebtoken::EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap()
+    }
+
+    async fn verify_request(
+        app: &axum::Router,
+        delegation_token: String,
+        action: &str,
+    ) -> (StatusCode, serde_json::Value) {
+        let request_body = create_request_body(&json!({
+            "delegationToken": delegation_token,
+            "resourceType": "container",
+            "identifiers": ["container-1"],
+            "action": action
+        }));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .uri("/delegation/verify")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.REQUESTER".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let status = response.status();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        (status, serde_json::from_slice(&body).unwrap())
+    }
+
+    #[sqlx::test]
+    async fn test_verify_delegation_token(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        let app = get_test_app(db);
+
+        // issued by this registry
+        let (status, body) = verify_request(
+            &app,
+            delegation_token("NL.CONSUME_TOO_MUCH", "NL.REQUESTER", 1715247230),
+            "Read",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["permit"], true);
+        assert_eq!(body["policyIssuer"], "NL.24244");
+
+        // issued by another authorization registry, for an action that isn't delegated
+        let (status, body) = verify_request(
+            &app,
+            delegation_token("NL.TRUSTED_AR", "NL.REQUESTER", 1715247230),
+            "Delete",
+        )
+        .await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(body["permit"], false);
+
+        // the issuer doesn't have the authorisation registry role at the satellite
+        let (status, _) = verify_request(
+            &app,
+            delegation_token("NL.UNKNOWN_AR", "NL.REQUESTER", 1715247230),
+            "Read",
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = verify_request(
+            &app,
+            delegation_token("NL.CONSUME_TOO_MUCH", "NL.OTHER", 1715247230),
+            "Read",
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let (status, _) = verify_request(
+            &app,
+            delegation_token("NL.CONSUME_TOO_MUCH", "NL.REQUESTER", 1715247200),
+            "Read",
+        )
+        .await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
 
         Ok(())
     }
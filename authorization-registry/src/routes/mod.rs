@@ -6,3 +6,4 @@ pub mod connect;
 pub mod delegation;
 pub mod policy_set;
 pub mod policy_set_template;
+pub mod resource_type;
@@ -1,7 +1,12 @@
 use anyhow::Context;
 use ar_entity::delegation_evidence::Policy;
 use axum::extract::{Path, Query};
-use axum::routing::delete;
+use axum::http::{
+    header::{CONTENT_TYPE, ETAG},
+    HeaderMap, HeaderName,
+};
+use axum::response::{IntoResponse, Response};
+use axum::routing::{delete, get};
 use axum::{
     extract::State, middleware::from_fn_with_state, routing::post, Extension, Json, Router,
 };
@@ -13,9 +18,17 @@ use std::sync::Arc;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
-use crate::db::policy::{MatchingPolicySetRow, PolicySetsWithPagination};
+use crate::db::policy::{MatchingPolicySetRow, PartyMatch, PolicySetsWithPagination};
+use crate::db::policy_version::{PolicySetVersion, PolicySetVersionSummary};
 use crate::error::{ErrorResponse, ExpectedError};
-use crate::services::policy::{self as policy_service, InsertPolicySetWithPolicies};
+use crate::services::policy::{
+    self as policy_service, EditPolicySetMetadata, InsertPolicySetWithPolicies,
+};
+use crate::services::policy_approval::{self as policy_approval_service, PolicyIssuerSettings};
+use crate::services::policy_bulk::{self as policy_bulk_service, ExportFormat, ExportedPolicySet};
+use crate::services::policy_trash as policy_trash_service;
+use crate::services::policy_version as policy_version_service;
+use crate::utils::{extract_if_match_revisions, if_none_match, policy_set_etag};
 use crate::{db::policy as policy_store, services::server_token::Role};
 use crate::{error::AppError, AppState};
 use crate::{middleware::extract_role_middleware, services::server_token::ServerToken};
@@ -23,8 +36,28 @@ use crate::{middleware::extract_role_middleware, services::server_token::ServerT
 pub fn get_policy_set_routes(server_token: Arc<ServerToken>) -> Router<AppState> {
     return Router::new()
         .route("/", post(insert_policy_set).get(get_all_policy_sets))
-        .route("/:id", delete(delete_policy_set).get(get_policy_set))
+        .route("/granted-to-me", get(get_policy_sets_granted_to_me))
+        .route("/export", get(export_policy_sets))
+        .route("/trash", get(get_deleted_policy_sets))
+        .route("/pending", get(get_pending_policy_sets))
+        .route("/rejected", get(get_rejected_policy_sets))
+        .route(
+            "/settings",
+            get(get_policy_issuer_settings).put(save_policy_issuer_settings),
+        )
+        .route(
+            "/:id",
+            delete(delete_policy_set)
+                .get(get_policy_set)
+                .patch(edit_policy_set),
+        )
         .route("/:id/policy", post(add_policy_to_policy_set))
+        .route("/:id/restore", post(restore_policy_set))
+        .route("/:id/approve", post(approve_policy_set))
+        .route("/:id/reject", post(reject_policy_set))
+        .route("/:id/version", get(get_policy_set_versions))
+        .route("/:id/version/:version", get(get_policy_set_version))
+        .route("/:id/version/:version/rollback", post(rollback_policy_set))
         .route(
             "/:id/policy/:policy_id",
             delete(delete_policy_from_policy_set).put(replace_policy_in_policy_set),
@@ -39,7 +72,8 @@ struct GetPolicySetsQuery {
     skip: Option<u32>,
 }
 
-/// Retrieve all policy sets belonging to the authenticated company
+/// Retrieve all policy sets belonging to the authenticated company, rejected policy sets are listed
+/// separately
 #[utoipa::path(
     get,
     path = "/policy-sets",
@@ -75,6 +109,7 @@ async fn get_all_policy_sets(
     let policy_sets = policy_store::get_policy_sets_with_policies(
         Some(role.get_company_id().to_string()),
         Some(role.get_company_id().to_string()),
+        PartyMatch::Exact,
         query.q,
         query.skip,
         query.limit,
@@ -86,6 +121,66 @@ async fn get_all_policy_sets(
     Ok(Json(policy_sets))
 }
 
+/// Retrieve the policy sets issued to the authenticated user
+///
+/// Lists the valid policy sets that grant rights to the user of the human token personally, on top of the rights of their company.
+#[utoipa::path(
+    get,
+    path = "/policy-sets/granted-to-me",
+    tag = "Policy Management",
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "List of the policy sets issued to the user and their associated policies",
+            content_type = "application/json",
+            body = Vec<MatchingPolicySetRow>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized")),
+        ),
+        (
+            status = 403,
+            description = "Not a human token",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("only users can be granted policy sets")),
+        )
+    )
+ )]
+async fn get_policy_sets_granted_to_me(
+    Extension(role): Extension<Role>,
+    Extension(db): Extension<DatabaseConnection>,
+    State(app_state): State<AppState>,
+) -> Result<Json<Vec<MatchingPolicySetRow>>, AppError> {
+    let Some(user_id) = role.get_user_id() else {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::FORBIDDEN,
+            message: "only users can be granted policy sets".to_owned(),
+            reason: format!(
+                "machine token of company: {} has no user",
+                role.get_company_id()
+            ),
+            metadata: None,
+        }));
+    };
+
+    let policy_sets = policy_store::get_policy_sets_with_policies_for_user(
+        app_state.time_provider.now(),
+        role.get_company_id(),
+        user_id,
+        &db,
+    )
+    .await
+    .context("Error getting policy sets of user")?;
+
+    Ok(Json(policy_sets))
+}
+
 /// Remove a policy from a policy set
 #[utoipa::path(
     delete,
@@ -93,7 +188,8 @@ async fn get_all_policy_sets(
     tag = "Policy Management",
     params(
         ("policy_set_id" = Uuid, Path, description = "Identifier of the policy set"),
-        ("policy_id" = Uuid, Path, description = "Identifier of the policy to remove")
+        ("policy_id" = Uuid, Path, description = "Identifier of the policy to remove"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
     ),
     security(
         ("bearer" = [])
@@ -120,6 +216,12 @@ async fn get_all_policy_sets(
             description = "Policy set or policy not found",
             content_type = "application/json",
             example = json!(ErrorResponse::new("Can't find policy within policy set"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
         )
     )
  )]
@@ -128,28 +230,56 @@ async fn delete_policy_from_policy_set(
     Extension(role): Extension<Role>,
     WithRejection(Path((policy_set_id, policy_id)), _): WithRejection<Path<(Uuid, Uuid)>, AppError>,
     State(app_state): State<AppState>,
+    headers: HeaderMap,
 ) -> Result<(), AppError> {
     policy_service::remove_policy_from_policy_set(
         app_state.time_provider.now(),
         &role.get_company_id(),
         &policy_set_id,
         &policy_id,
+        &extract_if_match_revisions(&headers),
         &app_state.config.client_eori,
+        &app_state.config.combining_algorithm,
         app_state.time_provider,
         &db,
+        &app_state.decision_cache,
     )
     .await?;
 
     Ok(())
 }
 
+// responds with the policy set and its revision as ETag, or only with the ETag when the
+// If-None-Match header shows the client already has this revision
+pub fn policy_set_response(headers: &HeaderMap, policy_set: MatchingPolicySetRow) -> Response {
+    let Some(revision) = policy_set.revision else {
+        return Json(policy_set).into_response();
+    };
+
+    let etag = policy_set_etag(revision);
+    if if_none_match(headers, &etag) {
+        return (StatusCode::NOT_MODIFIED, [(ETAG, etag)]).into_response();
+    }
+
+    ([(ETAG, etag)], Json(policy_set)).into_response()
+}
+
+// responds with the edited policy set and the ETag of its new revision
+pub fn edited_policy_set_response(policy_set: MatchingPolicySetRow) -> Response {
+    match policy_set.revision {
+        Some(revision) => ([(ETAG, policy_set_etag(revision))], Json(policy_set)).into_response(),
+        None => Json(policy_set).into_response(),
+    }
+}
+
 /// Retrieve a specific policy set by its ID
 #[utoipa::path(
     get,
     path = "/policy-sets/{id}",
     tag = "Policy Management",
     params(
-        ("id" = Uuid, Path, description = "Unique identifier of the policy set to retrieve")
+        ("id" = Uuid, Path, description = "Unique identifier of the policy set to retrieve"),
+        ("If-None-Match" = Option<String>, Header, description = "Respond with 304 when the policy set still has this ETag")
     ),
     security(
         ("bearer" = [])
@@ -178,6 +308,10 @@ async fn delete_policy_from_policy_set(
             description = "Policy set not found",
             content_type = "application/json",
             example = json!(ErrorResponse::new("Can't find policy set"))
+        ),
+        (
+            status = 304,
+            description = "The policy set still has the ETag of the If-None-Match header"
         )
     )
 )]
@@ -186,18 +320,20 @@ async fn get_policy_set(
     WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
     Extension(role): Extension<Role>,
     State(app_state): State<AppState>,
-) -> Result<Json<MatchingPolicySetRow>, AppError> {
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
     let ps = policy_service::get_policy_set_with_policies(
         &role.get_company_id(),
         &id,
         &app_state.config.client_eori,
+        &app_state.config.combining_algorithm,
         app_state.time_provider,
         &db,
     )
     .await?;
 
     match ps {
-        Some(ps) => Ok(Json(ps)),
+        Some(ps) => Ok(policy_set_response(&headers, ps)),
         None => Err(AppError::Expected(ExpectedError {
             status_code: StatusCode::NOT_FOUND,
             message: "Can't find policy set".to_owned(),
@@ -214,7 +350,8 @@ async fn get_policy_set(
     tag = "Policy Management",
     params(
         ("policy_set_id" = Uuid, Path, description = "Identifier of the policy set"),
-        ("policy_id" = Uuid, Path, description = "Identifier of the policy to replace")
+        ("policy_id" = Uuid, Path, description = "Identifier of the policy to replace"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
     ),
     request_body(
         content = Policy,
@@ -254,6 +391,12 @@ async fn get_policy_set(
             description = "Policy set or policy not found",
             content_type = "application/json",
             example = json!(ErrorResponse::new("Policy set or policy not found"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
         )
     )
 )]
@@ -263,6 +406,7 @@ async fn replace_policy_in_policy_set(
     Extension(role): Extension<Role>,
     WithRejection(Path((policy_set_id, policy_id)), _): WithRejection<Path<(Uuid, Uuid)>, AppError>,
     State(app_state): State<AppState>,
+    headers: HeaderMap,
     Json(body): Json<Policy>,
 ) -> Result<Json<ar_entity::policy::Model>, AppError> {
     let policy = policy_service::replace_policy_in_policy_set(
@@ -271,10 +415,13 @@ async fn replace_policy_in_policy_set(
         policy_set_id,
         policy_id,
         body,
+        &extract_if_match_revisions(&headers),
         &app_state.config.client_eori,
+        &app_state.config.combining_algorithm,
         app_state.time_provider,
         app_state.satellite_provider,
         &db,
+        &app_state.decision_cache,
     )
     .await?;
 
@@ -287,7 +434,8 @@ async fn replace_policy_in_policy_set(
     path = "/policy-sets/{id}/policy",
     tag = "Policy Management",
     params(
-        ("id" = Uuid, Path, description = "Identifier of the policy set")
+        ("id" = Uuid, Path, description = "Identifier of the policy set"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
     ),
     request_body(
         content = Policy,
@@ -327,6 +475,12 @@ async fn replace_policy_in_policy_set(
             description = "Policy set not found",
             content_type = "application/json",
             example = json!(ErrorResponse::new("Policy set not found"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
         )
     )
  )]
@@ -336,6 +490,7 @@ async fn add_policy_to_policy_set(
     Extension(role): Extension<Role>,
     WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
     State(app_state): State<AppState>,
+    headers: HeaderMap,
     Json(body): Json<Policy>,
 ) -> Result<Json<ar_entity::policy::Model>, AppError> {
     let policy = policy_service::add_policy_to_policy_set(
@@ -343,31 +498,48 @@ async fn add_policy_to_policy_set(
         &role.get_company_id(),
         &id,
         body,
+        &extract_if_match_revisions(&headers),
         &app_state.config.client_eori,
+        &app_state.config.combining_algorithm,
         app_state.time_provider,
         app_state.satellite_provider,
         &db,
+        &app_state.decision_cache,
     )
     .await?;
 
     Ok(Json(policy))
 }
 
-/// Delete a policy set and all its associated policies
+/// Edit the access subject, licenses or maximum delegation depth of a policy set
 #[utoipa::path(
-    delete,
+    patch,
     path = "/policy-sets/{id}",
     tag = "Policy Management",
     params(
-        ("id" = Uuid, Path, description = "Identifier of the policy set to delete")
+        ("id" = Uuid, Path, description = "Identifier of the policy set to edit"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
+    ),
+    request_body(
+        content = EditPolicySetMetadata,
+        description = "Fields of the policy set to change, omitted fields are left unchanged",
+        content_type = "application/json"
     ),
     security(
         ("bearer" = [])
     ),
     responses(
         (
-            status = 204,
-            description = "Policy set successfully deleted"
+            status = 200,
+            description = "Policy set successfully edited",
+            content_type = "application/json",
+            body = MatchingPolicySetRow
+        ),
+        (
+            status = 400,
+            description = "Invalid policy set",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unknown license(s): ISHARE.9999"))
         ),
         (
             status = 401,
@@ -377,67 +549,121 @@ async fn add_policy_to_policy_set(
         ),
         (
             status = 403,
-            description = "Forbidden - insufficient permissions", 
+            description = "Forbidden - insufficient permissions",
             content_type = "application/json",
-            example = json!(ErrorResponse::new("Not allowed to delete policy set"))
+            example = json!(ErrorResponse::new("Not allowed to edit policy set"))
         ),
         (
             status = 404,
             description = "Policy set not found",
             content_type = "application/json",
             example = json!(ErrorResponse::new("Policy set not found"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
         )
     )
  )]
-async fn delete_policy_set(
+#[axum_macros::debug_handler]
+async fn edit_policy_set(
     Extension(db): Extension<DatabaseConnection>,
     Extension(role): Extension<Role>,
     WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
     State(app_state): State<AppState>,
-) -> Result<(), AppError> {
-    policy_service::delete_policy_set(
+    headers: HeaderMap,
+    Json(body): Json<EditPolicySetMetadata>,
+) -> Result<Response, AppError> {
+    let policy_set = policy_service::edit_policy_set_metadata(
         app_state.time_provider.now(),
         &role.get_company_id(),
         &id,
+        &body,
+        &extract_if_match_revisions(&headers),
         &app_state.config.client_eori,
+        &app_state.config.combining_algorithm,
         app_state.time_provider,
+        app_state.satellite_provider,
         &db,
+        &app_state.decision_cache,
     )
     .await?;
 
-    Ok(())
+    Ok(edited_policy_set_response(policy_set))
 }
 
-#[derive(Serialize, Deserialize, ToSchema)]
-pub struct InsertPolicySetResponse {
-    pub uuid: Uuid,
+#[derive(Deserialize)]
+struct ExportPolicySetsQuery {
+    q: Option<String>,
+    #[serde(default)]
+    format: ExportFormat,
 }
 
-/// Create a new policy set with associated policies
+/// Export the policy sets issued by the authenticated company, as JSON or NDJSON
 #[utoipa::path(
-    post,
-    path = "/policy-sets",
+    get,
+    path = "/policy-sets/export",
     tag = "Policy Management",
-    request_body(
-        content = InsertPolicySetWithPolicies,
-        description = "Policy set details and its initial policies",
-        content_type = "application/json"
+    params(
+        ("q" = Option<String>, Query, description = "Filter on any match in the policy set"),
+        ("format" = Option<ExportFormat>, Query, description = "'json' (default) or 'ndjson'"),
     ),
     security(
         ("bearer" = [])
     ),
     responses(
         (
-            status = 201,
-            description = "Policy set successfully created",
+            status = 200,
+            description = "The policy sets with their policies, in a form that can be imported",
             content_type = "application/json",
-            body = InsertPolicySetResponse
+            body = Vec<ExportedPolicySet>
         ),
         (
-            status = 400,
-            description = "Invalid policy set or policy definitions",
+            status = 401,
+            description = "Authentication failed",
             content_type = "application/json",
-            example = json!(ErrorResponse::new("Invalid policy set format"))
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn export_policy_sets(
+    Query(query): Query<ExportPolicySetsQuery>,
+    Extension(role): Extension<Role>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<([(HeaderName, &'static str); 1], String), AppError> {
+    let policy_sets = policy_bulk_service::export_policy_sets(
+        None,
+        Some(role.get_company_id().to_string()),
+        PartyMatch::Exact,
+        query.q,
+        &db,
+    )
+    .await?;
+
+    let (content_type, body) = policy_bulk_service::serialize_export(&policy_sets, query.format)?;
+
+    Ok(([(CONTENT_TYPE, content_type)], body))
+}
+
+/// Retrieve the versions of a policy set
+#[utoipa::path(
+    get,
+    path = "/policy-sets/{id}/version",
+    tag = "Policy Management",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the policy set")
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Versions of the policy set, oldest first",
+            content_type = "application/json",
+            body = Vec<PolicySetVersionSummary>
         ),
         (
             status = 401,
@@ -449,81 +675,786 @@ pub struct InsertPolicySetResponse {
             status = 403,
             description = "Forbidden - insufficient permissions",
             content_type = "application/json",
-            example = json!(ErrorResponse::new("Not allowed to create policy sets"))
+            example = json!(ErrorResponse::new("Not allowed to read policy set"))
+        ),
+        (
+            status = 404,
+            description = "Policy set not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set not found"))
         )
     )
  )]
-async fn insert_policy_set(
+async fn get_policy_set_versions(
     Extension(db): Extension<DatabaseConnection>,
     Extension(role): Extension<Role>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
     State(app_state): State<AppState>,
-    WithRejection(Json(body), _): WithRejection<Json<InsertPolicySetWithPolicies>, AppError>,
-) -> Result<Json<InsertPolicySetResponse>, AppError> {
-    let policy_set_id = policy_service::insert_policy_set_with_policies(
-        app_state.time_provider.now(),
+) -> Result<Json<Vec<PolicySetVersionSummary>>, AppError> {
+    let versions = policy_version_service::get_policy_set_versions(
         &role.get_company_id(),
-        &body,
-        &db,
+        &id,
         &app_state.config.client_eori,
-        app_state.time_provider.clone(),
-        app_state.satellite_provider.clone(),
+        &app_state.config.combining_algorithm,
+        app_state.time_provider,
+        &db,
     )
     .await?;
 
-    let response = InsertPolicySetResponse {
-        uuid: policy_set_id,
-    };
-
-    Ok(Json(response))
+    Ok(Json(versions))
 }
 
-#[cfg(test)]
-mod test {
-    use crate::{fixtures::fixtures::insert_policy_set_fixture, services::server_token};
-    use axum::{
-        body::Body,
-        http::{Request, StatusCode},
-    };
-    use reqwest::header::AUTHORIZATION;
-    use serde_json::json;
-    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
-    use tower::ServiceExt;
-
-    use super::super::super::test_helpers::helpers::*;
-
-    #[sqlx::test]
-    async fn test_get_policy_set_by_id(
-        _pool_options: PgPoolOptions,
-        conn_option: PgConnectOptions,
-    ) -> sqlx::Result<()> {
-        let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
-        let app = get_test_app(db);
-
-        let response = app
-            .oneshot(
-                Request::builder()
-                    .uri("/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881")
-                    .method("GET")
-                    .header(
-                        AUTHORIZATION,
-                        server_token::server_token_test_helper::get_machine_token_header(Some(
-                            "NL.24244".to_owned(),
-                        )),
-                    )
-                    .header("Content-Type", "application/json")
-                    .body(Body::empty())
-                    .unwrap(),
-            )
-            .await
-            .unwrap();
-
-        assert_eq!(response.status(), StatusCode::OK);
+/// Retrieve a policy set as it was in one of its versions
+#[utoipa::path(
+    get,
+    path = "/policy-sets/{id}/version/{version}",
+    tag = "Policy Management",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the policy set"),
+        ("version" = i32, Path, description = "Version of the policy set")
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Policy set as it was in the version",
+            content_type = "application/json",
+            body = PolicySetVersion
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 403,
+            description = "Forbidden - insufficient permissions",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Not allowed to read policy set"))
+        ),
+        (
+            status = 404,
+            description = "Policy set or version not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Can't find policy set version"))
+        )
+    )
+ )]
+async fn get_policy_set_version(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(role): Extension<Role>,
+    WithRejection(Path((id, version)), _): WithRejection<Path<(Uuid, i32)>, AppError>,
+    State(app_state): State<AppState>,
+) -> Result<Json<PolicySetVersion>, AppError> {
+    let policy_set_version = policy_version_service::get_policy_set_version(
+        &role.get_company_id(),
+        &id,
+        version,
+        &app_state.config.client_eori,
+        &app_state.config.combining_algorithm,
+        app_state.time_provider,
+        &db,
+    )
+    .await?;
 
-        Ok(())
-    }
+    Ok(Json(policy_set_version))
+}
 
-    #[sqlx::test]
+/// Roll a policy set back to one of its versions, the rollback is recorded as a new version
+#[utoipa::path(
+    post,
+    path = "/policy-sets/{id}/version/{version}/rollback",
+    tag = "Policy Management",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the policy set"),
+        ("version" = i32, Path, description = "Version of the policy set to roll back to"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "New version of the policy set created by the rollback",
+            content_type = "application/json",
+            body = PolicySetVersion
+        ),
+        (
+            status = 400,
+            description = "The version is no longer a valid policy set",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unknown license(s): ISHARE.9999"))
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 403,
+            description = "Forbidden - insufficient permissions",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Not allowed to edit policy set"))
+        ),
+        (
+            status = 404,
+            description = "Policy set or version not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Can't find policy set version"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
+        )
+    )
+ )]
+async fn rollback_policy_set(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(role): Extension<Role>,
+    WithRejection(Path((id, version)), _): WithRejection<Path<(Uuid, i32)>, AppError>,
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Json<PolicySetVersion>, AppError> {
+    let policy_set_version = policy_version_service::rollback_policy_set(
+        app_state.time_provider.now(),
+        &role.get_company_id(),
+        &id,
+        version,
+        &extract_if_match_revisions(&headers),
+        &app_state.config.client_eori,
+        &app_state.config.combining_algorithm,
+        app_state.time_provider,
+        app_state.satellite_provider,
+        &db,
+        &app_state.decision_cache,
+    )
+    .await?;
+
+    Ok(Json(policy_set_version))
+}
+
+/// Retrieve the deleted policy sets issued by the authenticated company
+///
+/// Deleted policy sets can be restored until the retention period has passed, after which they are purged.
+#[utoipa::path(
+    get,
+    path = "/policy-sets/trash",
+    tag = "Policy Management",
+    params(
+        ("limit" = Option<u32>, Query, description = "Limit the number of results for pagination"),
+        ("skip" = Option<u32>, Query, description = "Skip a number of results for pagination"),
+        ("q" = Option<String>, Query, description = "Filter on any match in the policy set"),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "List of the deleted policy sets and their associated policies",
+            content_type = "application/json",
+            body = Vec<MatchingPolicySetRow>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized")),
+        )
+    )
+ )]
+async fn get_deleted_policy_sets(
+    Query(query): Query<GetPolicySetsQuery>,
+    Extension(role): Extension<Role>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<PolicySetsWithPagination>, AppError> {
+    let policy_sets = policy_store::get_deleted_policy_sets_with_policies(
+        None,
+        Some(role.get_company_id().to_string()),
+        PartyMatch::Exact,
+        query.q,
+        query.skip,
+        query.limit,
+        &db,
+    )
+    .await
+    .context("Error getting deleted policy sets")?;
+
+    Ok(Json(policy_sets))
+}
+
+/// Restore a deleted policy set issued by the authenticated company
+#[utoipa::path(
+    post,
+    path = "/policy-sets/{id}/restore",
+    tag = "Policy Management",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the deleted policy set")
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Policy set successfully restored",
+            content_type = "application/json",
+            body = MatchingPolicySetRow
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 403,
+            description = "Forbidden - not the policy issuer",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("not allowed to restore policy set"))
+        ),
+        (
+            status = 404,
+            description = "Deleted policy set not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Can't find deleted policy set"))
+        )
+    )
+ )]
+async fn restore_policy_set(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(role): Extension<Role>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
+    State(app_state): State<AppState>,
+) -> Result<Response, AppError> {
+    let policy_set = policy_trash_service::restore_policy_set(
+        app_state.time_provider.now(),
+        &role.get_company_id(),
+        &id,
+        &db,
+        &app_state.decision_cache,
+    )
+    .await?;
+
+    Ok(edited_policy_set_response(policy_set))
+}
+
+/// Retrieve the approval settings of the authenticated company as policy issuer
+#[utoipa::path(
+    get,
+    path = "/policy-sets/settings",
+    tag = "Policy Management",
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Settings of the policy issuer",
+            content_type = "application/json",
+            body = PolicyIssuerSettings
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn get_policy_issuer_settings(
+    Extension(role): Extension<Role>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<PolicyIssuerSettings>, AppError> {
+    let settings =
+        policy_approval_service::get_policy_issuer_settings(&role.get_company_id(), &db).await?;
+
+    Ok(Json(settings))
+}
+
+/// Change the approval settings of the authenticated company as policy issuer
+///
+/// When approval is required, policy sets that other parties create on behalf of the company are
+/// pending and aren't used for delegation evidence until the company approves them.
+#[utoipa::path(
+    put,
+    path = "/policy-sets/settings",
+    tag = "Policy Management",
+    request_body(
+        content = PolicyIssuerSettings,
+        description = "Settings of the policy issuer",
+        content_type = "application/json"
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Settings successfully saved",
+            content_type = "application/json",
+            body = PolicyIssuerSettings
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        )
+    )
+ )]
+async fn save_policy_issuer_settings(
+    Extension(role): Extension<Role>,
+    Extension(db): Extension<DatabaseConnection>,
+    WithRejection(Json(body), _): WithRejection<Json<PolicyIssuerSettings>, AppError>,
+) -> Result<Json<PolicyIssuerSettings>, AppError> {
+    let settings =
+        policy_approval_service::save_policy_issuer_settings(&role.get_company_id(), &body, &db)
+            .await?;
+
+    Ok(Json(settings))
+}
+
+/// Retrieve the policy sets that were created on behalf of the authenticated company and wait for
+/// its approval
+#[utoipa::path(
+    get,
+    path = "/policy-sets/pending",
+    tag = "Policy Management",
+    params(
+        ("limit" = Option<u32>, Query, description = "Limit the number of results for pagination"),
+        ("skip" = Option<u32>, Query, description = "Skip a number of results for pagination"),
+        ("q" = Option<String>, Query, description = "Filter on any match in the policy set"),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "List of the pending policy sets and their associated policies",
+            content_type = "application/json",
+            body = Vec<MatchingPolicySetRow>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized")),
+        )
+    )
+ )]
+async fn get_pending_policy_sets(
+    Query(query): Query<GetPolicySetsQuery>,
+    Extension(role): Extension<Role>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<PolicySetsWithPagination>, AppError> {
+    let policy_sets = policy_store::get_pending_policy_sets_with_policies(
+        None,
+        Some(role.get_company_id().to_string()),
+        PartyMatch::Exact,
+        query.q,
+        query.skip,
+        query.limit,
+        &db,
+    )
+    .await
+    .context("Error getting pending policy sets")?;
+
+    Ok(Json(policy_sets))
+}
+
+/// Retrieve the policy sets the authenticated company rejected, they are kept until the retention
+/// period has passed and are not part of the current policy sets
+#[utoipa::path(
+    get,
+    path = "/policy-sets/rejected",
+    tag = "Policy Management",
+    params(
+        ("limit" = Option<u32>, Query, description = "Limit the number of results for pagination"),
+        ("skip" = Option<u32>, Query, description = "Skip a number of results for pagination"),
+        ("q" = Option<String>, Query, description = "Filter on any match in the policy set"),
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "List of the rejected policy sets and their associated policies",
+            content_type = "application/json",
+            body = Vec<MatchingPolicySetRow>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized")),
+        )
+    )
+ )]
+async fn get_rejected_policy_sets(
+    Query(query): Query<GetPolicySetsQuery>,
+    Extension(role): Extension<Role>,
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<PolicySetsWithPagination>, AppError> {
+    let policy_sets = policy_store::get_rejected_policy_sets_with_policies(
+        None,
+        Some(role.get_company_id().to_string()),
+        PartyMatch::Exact,
+        query.q,
+        query.skip,
+        query.limit,
+        &db,
+    )
+    .await
+    .context("Error getting rejected policy sets")?;
+
+    Ok(Json(policy_sets))
+}
+
+/// Approve a pending policy set issued by the authenticated company, it is used for delegation
+/// evidence from then on
+#[utoipa::path(
+    post,
+    path = "/policy-sets/{id}/approve",
+    tag = "Policy Management",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the pending policy set"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Policy set successfully approved",
+            content_type = "application/json",
+            body = MatchingPolicySetRow
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 403,
+            description = "Forbidden - not the policy issuer",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("not allowed to approve policy set"))
+        ),
+        (
+            status = 404,
+            description = "Policy set not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Can't find policy set"))
+        ),
+        (
+            status = 409,
+            description = "The policy set isn't pending approval",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set is not pending approval"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
+        )
+    )
+ )]
+async fn approve_policy_set(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(role): Extension<Role>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let policy_set = policy_approval_service::approve_policy_set(
+        app_state.time_provider.now(),
+        &role.get_company_id(),
+        &id,
+        &extract_if_match_revisions(&headers),
+        &db,
+        &app_state.decision_cache,
+    )
+    .await?;
+
+    Ok(edited_policy_set_response(policy_set))
+}
+
+/// Reject a pending policy set issued by the authenticated company, it is never used for
+/// delegation evidence
+#[utoipa::path(
+    post,
+    path = "/policy-sets/{id}/reject",
+    tag = "Policy Management",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the pending policy set"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Policy set successfully rejected",
+            content_type = "application/json",
+            body = MatchingPolicySetRow
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 403,
+            description = "Forbidden - not the policy issuer",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("not allowed to reject policy set"))
+        ),
+        (
+            status = 404,
+            description = "Policy set not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Can't find policy set"))
+        ),
+        (
+            status = 409,
+            description = "The policy set isn't pending approval",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set is not pending approval"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
+        )
+    )
+ )]
+async fn reject_policy_set(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(role): Extension<Role>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<Response, AppError> {
+    let policy_set = policy_approval_service::reject_policy_set(
+        app_state.time_provider.now(),
+        &role.get_company_id(),
+        &id,
+        &extract_if_match_revisions(&headers),
+        &db,
+        &app_state.decision_cache,
+    )
+    .await?;
+
+    Ok(edited_policy_set_response(policy_set))
+}
+
+/// Delete a policy set, it can be restored until the retention period has passed
+#[utoipa::path(
+    delete,
+    path = "/policy-sets/{id}",
+    tag = "Policy Management",
+    params(
+        ("id" = Uuid, Path, description = "Identifier of the policy set to delete"),
+        ("If-Match" = Option<String>, Header, description = "Only apply the change when the policy set still has this ETag")
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 204,
+            description = "Policy set successfully deleted"
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 403,
+            description = "Forbidden - insufficient permissions", 
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Not allowed to delete policy set"))
+        ),
+        (
+            status = 404,
+            description = "Policy set not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set not found"))
+        ),
+        (
+            status = 412,
+            description = "The policy set no longer has the ETag of the If-Match header",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Policy set has been changed"))
+        )
+    )
+ )]
+async fn delete_policy_set(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(role): Extension<Role>,
+    WithRejection(Path(id), _): WithRejection<Path<Uuid>, AppError>,
+    State(app_state): State<AppState>,
+    headers: HeaderMap,
+) -> Result<(), AppError> {
+    policy_service::delete_policy_set(
+        app_state.time_provider.now(),
+        &role.get_company_id(),
+        &id,
+        &extract_if_match_revisions(&headers),
+        &app_state.config.client_eori,
+        &app_state.config.combining_algorithm,
+        app_state.time_provider,
+        &db,
+        &app_state.decision_cache,
+    )
+    .await?;
+
+    Ok(())
+}
+
+#[derive(Serialize, Deserialize, ToSchema)]
+pub struct InsertPolicySetResponse {
+    pub uuid: Uuid,
+}
+
+/// Create a new policy set with associated policies
+#[utoipa::path(
+    post,
+    path = "/policy-sets",
+    tag = "Policy Management",
+    request_body(
+        content = InsertPolicySetWithPolicies,
+        description = "Policy set details and its initial policies",
+        content_type = "application/json"
+    ),
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 201,
+            description = "Policy set successfully created",
+            content_type = "application/json",
+            body = InsertPolicySetResponse
+        ),
+        (
+            status = 400,
+            description = "Invalid policy set or policy definitions",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Invalid policy set format"))
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized"))
+        ),
+        (
+            status = 403,
+            description = "Forbidden - insufficient permissions",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Not allowed to create policy sets"))
+        )
+    )
+ )]
+async fn insert_policy_set(
+    Extension(db): Extension<DatabaseConnection>,
+    Extension(role): Extension<Role>,
+    State(app_state): State<AppState>,
+    WithRejection(Json(body), _): WithRejection<Json<InsertPolicySetWithPolicies>, AppError>,
+) -> Result<Json<InsertPolicySetResponse>, AppError> {
+    let policy_set_id = policy_service::insert_policy_set_with_policies(
+        app_state.time_provider.now(),
+        &role.get_company_id(),
+        &body,
+        &db,
+        &app_state.config.client_eori,
+        &app_state.config.combining_algorithm,
+        app_state.time_provider.clone(),
+        app_state.satellite_provider.clone(),
+        &app_state.decision_cache,
+    )
+    .await?;
+
+    let response = InsertPolicySetResponse {
+        uuid: policy_set_id,
+    };
+
+    Ok(Json(response))
+}
+
+#[cfg(test)]
+mod test {
+    use super::InsertPolicySetResponse;
+    use crate::db::policy::MatchingPolicySetRow;
+    use crate::db::policy_version::{PolicySetVersion, PolicySetVersionSummary};
+    use crate::TimeProvider;
+    use crate::{fixtures::fixtures::insert_policy_set_fixture, services::server_token};
+    use ar_entity::policy_set::PolicySetStatus;
+    use axum::{
+        body::Body,
+        http::{Request, StatusCode},
+    };
+    use http_body_util::BodyExt;
+    use reqwest::header::{AUTHORIZATION, ETAG, IF_MATCH, IF_NONE_MATCH};
+    use serde_json::json;
+    use sqlx::postgres::{PgConnectOptions, PgPoolOptions};
+    use tower::ServiceExt;
+    use uuid::Uuid;
+
+    use super::super::super::test_helpers::helpers::*;
+
+    #[sqlx::test]
+    async fn test_get_policy_set_by_id(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+        let app = get_test_app(db);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881")
+                    .method("GET")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_machine_token_header(Some(
+                            "NL.24244".to_owned(),
+                        )),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
     async fn test_insert_policy_set(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
@@ -547,35 +1478,220 @@ mod test {
                     },
                     "rules": [
                         {
-                            "effect": "Permit"
+                            "effect": "Permit"
+                        }
+                    ]
+                }, {
+                    "target": {
+                        "resource": {
+                            "type": "test-iden",
+                            "identifiers": ["test4", "test-5"],
+                            "attributes": ["*"]
+                        },
+                        "actions": ["*"],
+                        "environment": {
+                            "serviceProviders": ["fffd"]
+                        }
+                    },
+                    "rules": [
+                        {
+                            "effect": "Permit"
+                        },
+                        {
+                            "effect": "Deny",
+                            "target": {
+                                "resource": {
+                                    "attributes": ["zinger"],
+                                    "identifiers": ["*"],
+                                    "type":"test-iden"
+                                },
+                                "actions": ["*"]
+                            }
+                        }
+                    ]
+                }],
+                "target": {
+                    "accessSubject": "sadfasdf"
+                },
+                "policyIssuer": "nice-company",
+                "licences": [],
+                "maxDelegationDepth": 2
+        }));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/policy-set")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_machine_token_header(Some(
+                            "nice-company".to_owned(),
+                        )),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_insert_policy_set_empty_validity_window(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        let app = get_test_app(db);
+
+        let request_body = create_request_body(&json!(
+            {
+                "policies": [{
+                    "target": {
+                        "resource": {
+                            "type": "test-iden2",
+                            "identifiers": ["test", "test-2"],
+                            "attributes": ["*"]
+                        },
+                        "actions": ["Read"],
+                        "environment": {
+                            "serviceProviders": ["asdf"]
+                        }
+                    },
+                    "rules": [
+                        {
+                            "effect": "Permit"
+                        }
+                    ]
+                }],
+                "target": {
+                    "accessSubject": "sadfasdf"
+                },
+                "policyIssuer": "nice-company",
+                "licences": [],
+                "maxDelegationDepth": 2,
+                "notBefore": "2024-06-01T00:00:00Z",
+                "notOnOrAfter": "2024-05-01T00:00:00Z"
+        }));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/policy-set")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_machine_token_header(Some(
+                            "nice-company".to_owned(),
+                        )),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_insert_policy_set_invalid_condition(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        let app = get_test_app(db);
+
+        let request_body = create_request_body(&json!(
+            {
+                "policies": [{
+                    "target": {
+                        "resource": {
+                            "type": "test-iden2",
+                            "identifiers": ["test", "test-2"],
+                            "attributes": ["*"]
+                        },
+                        "actions": ["Read"],
+                        "environment": {
+                            "serviceProviders": ["asdf"]
+                        }
+                    },
+                    "rules": [
+                        {
+                            "effect": "Permit",
+                            "conditions": [{
+                                "type": "TimeWindow",
+                                "start": "08:00",
+                                "end": "18:00",
+                                "timezone": "Mars/Olympus_Mons"
+                            }]
                         }
                     ]
-                }, {
+                }],
+                "target": {
+                    "accessSubject": "sadfasdf"
+                },
+                "policyIssuer": "nice-company",
+                "licences": [],
+                "maxDelegationDepth": 2
+        }));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/policy-set")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_machine_token_header(Some(
+                            "nice-company".to_owned(),
+                        )),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_insert_policy_set_invalid_identifier_pattern(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        let app = get_test_app(db);
+
+        let request_body = create_request_body(&json!(
+            {
+                "policies": [{
                     "target": {
                         "resource": {
-                            "type": "test-iden",
-                            "identifiers": ["test4", "test-5"],
+                            "type": "test-iden2",
+                            "identifiers": ["regex:urn:container:[A-Z"],
                             "attributes": ["*"]
                         },
-                        "actions": ["*"],
+                        "actions": ["Read"],
                         "environment": {
-                            "serviceProviders": ["fffd"]
+                            "serviceProviders": ["asdf"]
                         }
                     },
                     "rules": [
                         {
                             "effect": "Permit"
-                        },
-                        {
-                            "effect": "Deny",
-                            "target": {
-                                "resource": {
-                                    "attributes": ["zinger"],
-                                    "identifiers": ["*"],
-                                    "type":"test-iden"
-                                },
-                                "actions": ["*"]
-                            }
                         }
                     ]
                 }],
@@ -605,7 +1721,7 @@ mod test {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
 
         Ok(())
     }
@@ -1088,44 +2204,317 @@ mod test {
             .await
             .unwrap();
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_add_policy_to_policy_set_no_de(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+
+        let app = get_test_app(db);
+
+        let request_body = create_request_body(&json!({
+            "target": {
+                "resource": {
+                    "type": "TestResource",
+                    "identifiers": ["test", "test-2"],
+                    "attributes": ["*"]
+                },
+                "actions": ["Read"],
+                "environment": {
+                    "serviceProviders": ["NL.EORI.LIFEELEC4DMI"]
+                }
+            },
+            "rules": [
+                {
+                    "effect": "Permit"
+                }
+            ]
+        }));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881/policy")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_add_policy_to_policy_set_via_de(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
+
+        let app = get_test_app(db);
+
+        let request_body = create_request_body(&json!({
+            "target": {
+                "resource": {
+                    "type": "TestResource",
+                    "identifiers": ["test", "test-2"],
+                    "attributes": ["*"]
+                },
+                "actions": ["Read"],
+                "environment": {
+                    "serviceProviders": ["NL.EORI.LIFEELEC4DMI"]
+                }
+            },
+            "rules": [
+                {
+                    "effect": "Permit"
+                }
+            ]
+        }));
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881/policy")
+                    .method("POST")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.44444".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::new(request_body))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_remove_policy_from_policy_set(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+
+        let app = get_test_app(db);
+
+        let response = app
+            .oneshot(
+                Request::builder()
+                    .uri("/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881/policy/564f3b46-7127-4c3c-a0b8-2859c01cc9c1")
+                    .method("DELETE")
+                    .header(
+                        AUTHORIZATION,
+                        server_token::server_token_test_helper::get_human_token_header(
+                            Some("NL.24244".to_owned()),
+                            None,
+                        ),
+                    )
+                    .header("Content-Type", "application/json")
+                    .body(Body::empty())
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_edit_policy_set(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
+
+        let app = get_test_app(db);
+
+        let edit = |company_id: &str, body: serde_json::Value| {
+            Request::builder()
+                .uri("/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881")
+                .method("PATCH")
+                .header(
+                    AUTHORIZATION,
+                    server_token::server_token_test_helper::get_human_token_header(
+                        Some(company_id.to_owned()),
+                        None,
+                    ),
+                )
+                .header("Content-Type", "application/json")
+                .body(Body::new(create_request_body(&body)))
+                .unwrap()
+        };
+
+        // only the policy issuer can edit the policy set
+        let response = app
+            .clone()
+            .oneshot(edit("NL.44444", json!({ "maxDelegationDepth": 0 })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        // the licenses must be registered
+        let response = app
+            .clone()
+            .oneshot(edit("NL.24244", json!({ "licences": ["ISHARE.9999"] })))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+
+        let response = app
+            .clone()
+            .oneshot(edit(
+                "NL.24244",
+                json!({ "accessSubject": " nl.55555", "maxDelegationDepth": 0 }),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let policy_set: MatchingPolicySetRow =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert_eq!(
+            policy_set.policy_set_id.to_string(),
+            "84b7fba4-05f3-4af8-9d84-dde384abe881"
+        );
+        assert_eq!(policy_set.access_subject, "NL.55555");
+        assert_eq!(policy_set.max_delegation_depth, 0);
+        assert!(policy_set.licenses.is_empty());
+        assert_eq!(policy_set.policies.len(), 1);
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_add_policy_to_policy_set_no_de(
+    async fn test_policy_set_versions(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
-        insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
-
         let app = get_test_app(db);
 
-        let request_body = create_request_body(&json!({
-            "target": {
-                "resource": {
-                    "type": "TestResource",
-                    "identifiers": ["test", "test-2"],
-                    "attributes": ["*"]
+        let request = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            Request::builder()
+                .uri(uri)
+                .method(method)
+                .header(
+                    AUTHORIZATION,
+                    server_token::server_token_test_helper::get_machine_token_header(Some(
+                        "nice-company".to_owned(),
+                    )),
+                )
+                .header("Content-Type", "application/json")
+                .body(match body {
+                    Some(body) => Body::new(create_request_body(&body)),
+                    None => Body::empty(),
+                })
+                .unwrap()
+        };
+        let policy = |resource_type: &str| {
+            json!({
+                "target": {
+                    "resource": {
+                        "type": resource_type,
+                        "identifiers": ["*"],
+                        "attributes": ["*"]
+                    },
+                    "actions": ["Read"],
+                    "environment": {
+                        "serviceProviders": ["good-company"]
+                    }
                 },
-                "actions": ["Read"],
-                "environment": {
-                    "serviceProviders": ["NL.EORI.LIFEELEC4DMI"]
-                }
-            },
-            "rules": [
-                {
-                    "effect": "Permit"
-                }
-            ]
-        }));
+                "rules": [{ "effect": "Permit" }]
+            })
+        };
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                "/policy-set",
+                Some(json!({
+                    "policies": [policy("first")],
+                    "target": { "accessSubject": "NL.44444" },
+                    "policyIssuer": "nice-company",
+                    "licences": [],
+                    "maxDelegationDepth": 0
+                })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let id = serde_json::from_slice::<InsertPolicySetResponse>(
+            &response.into_body().collect().await.unwrap().to_bytes(),
+        )
+        .unwrap()
+        .uuid;
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                &format!("/policy-set/{}/policy", id),
+                Some(policy("second")),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
 
         let response = app
+            .clone()
+            .oneshot(request("GET", &format!("/policy-set/{}/version", id), None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let versions: Vec<PolicySetVersionSummary> =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert_eq!(
+            versions
+                .iter()
+                .map(|v| (v.version, v.change.as_str()))
+                .collect::<Vec<_>>(),
+            vec![(1, "created"), (2, "policy_added")]
+        );
+
+        // only the policy issuer can roll back
+        let response = app
+            .clone()
             .oneshot(
                 Request::builder()
-                    .uri("/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881/policy")
+                    .uri(format!("/policy-set/{}/version/1/rollback", id))
                     .method("POST")
                     .header(
                         AUTHORIZATION,
@@ -1134,102 +2523,603 @@ mod test {
                             None,
                         ),
                     )
-                    .header("Content-Type", "application/json")
-                    .body(Body::new(request_body))
+                    .body(Body::empty())
                     .unwrap(),
             )
             .await
             .unwrap();
-
         assert_eq!(response.status(), StatusCode::FORBIDDEN);
 
+        let response = app
+            .clone()
+            .oneshot(request(
+                "POST",
+                &format!("/policy-set/{}/version/1/rollback", id),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let rolled_back: PolicySetVersion =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert_eq!(rolled_back.summary.version, 3);
+        assert_eq!(rolled_back.summary.change, "rolled_back");
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", &format!("/policy-set/{}", id), None))
+            .await
+            .unwrap();
+        let policy_set: MatchingPolicySetRow =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert_eq!(
+            policy_set
+                .policies
+                .iter()
+                .map(|p| p.resource_type.as_str())
+                .collect::<Vec<_>>(),
+            vec!["first"]
+        );
+
+        // the versions before the rollback are kept
+        let response = app
+            .clone()
+            .oneshot(request(
+                "GET",
+                &format!("/policy-set/{}/version/2", id),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let version: PolicySetVersion =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert_eq!(version.policy_set.policies.len(), 2);
+
+        let response = app
+            .oneshot(request(
+                "GET",
+                &format!("/policy-set/{}/version/4", id),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_add_policy_to_policy_set_via_de(
+    async fn test_policy_set_etags(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
         insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
-        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
 
         let app = get_test_app(db);
 
-        let request_body = create_request_body(&json!({
-            "target": {
-                "resource": {
-                    "type": "TestResource",
-                    "identifiers": ["test", "test-2"],
-                    "attributes": ["*"]
-                },
-                "actions": ["Read"],
-                "environment": {
-                    "serviceProviders": ["NL.EORI.LIFEELEC4DMI"]
-                }
-            },
-            "rules": [
-                {
-                    "effect": "Permit"
-                }
-            ]
-        }));
+        let request = |method: &str,
+                       uri: &str,
+                       condition: Option<(&str, &str)>,
+                       body: Option<serde_json::Value>| {
+            let mut builder = Request::builder()
+                .uri(uri)
+                .method(method)
+                .header(
+                    AUTHORIZATION,
+                    server_token::server_token_test_helper::get_human_token_header(
+                        Some("NL.24244".to_owned()),
+                        None,
+                    ),
+                )
+                .header("Content-Type", "application/json");
+            if let Some((name, value)) = condition {
+                builder = builder.header(name, value);
+            }
+
+            builder
+                .body(match body {
+                    Some(body) => Body::new(create_request_body(&body)),
+                    None => Body::empty(),
+                })
+                .unwrap()
+        };
+
+        let uri = "/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881";
+        let policy_uri = format!("{}/policy/564f3b46-7127-4c3c-a0b8-2859c01cc9c1", uri);
 
         let response = app
-            .oneshot(
-                Request::builder()
-                    .uri("/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881/policy")
-                    .method("POST")
-                    .header(
-                        AUTHORIZATION,
-                        server_token::server_token_test_helper::get_human_token_header(
-                            Some("NL.44444".to_owned()),
-                            None,
-                        ),
-                    )
-                    .header("Content-Type", "application/json")
-                    .body(Body::new(request_body))
-                    .unwrap(),
-            )
+            .clone()
+            .oneshot(request("GET", uri, None, None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[ETAG], "\"1\"");
+        let policy_set: MatchingPolicySetRow =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert_eq!(policy_set.revision, Some(1));
+
+        // an unchanged policy set isn't sent again
+        let response = app
+            .clone()
+            .oneshot(request(
+                "GET",
+                uri,
+                Some((IF_NONE_MATCH.as_str(), "\"1\"")),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert!(response
+            .into_body()
+            .collect()
+            .await
+            .unwrap()
+            .to_bytes()
+            .is_empty());
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PATCH",
+                uri,
+                Some((IF_MATCH.as_str(), "\"1\"")),
+                Some(json!({ "maxDelegationDepth": 1 })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[ETAG], "\"2\"");
+
+        // a change based on the previous revision would overwrite the edit
+        let response = app
+            .clone()
+            .oneshot(request(
+                "PATCH",
+                uri,
+                Some((IF_MATCH.as_str(), "\"1\"")),
+                Some(json!({ "maxDelegationDepth": 3 })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let response = app
+            .clone()
+            .oneshot(request(
+                "DELETE",
+                &policy_uri,
+                Some((IF_MATCH.as_str(), "\"1\"")),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::PRECONDITION_FAILED);
+
+        // changes without If-Match are applied to any revision
+        let response = app
+            .clone()
+            .oneshot(request("DELETE", &policy_uri, None, None))
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
 
+        let response = app
+            .oneshot(request(
+                "GET",
+                uri,
+                Some((IF_NONE_MATCH.as_str(), "\"2\"")),
+                None,
+            ))
+            .await
+            .unwrap();
         assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[ETAG], "\"3\"");
+        let policy_set: MatchingPolicySetRow =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert_eq!(policy_set.max_delegation_depth, 1);
+        assert!(policy_set.policies.is_empty());
 
         Ok(())
     }
 
     #[sqlx::test]
-    async fn test_remove_policy_from_policy_set(
+    async fn test_restore_deleted_policy_set(
         _pool_options: PgPoolOptions,
         conn_option: PgConnectOptions,
     ) -> sqlx::Result<()> {
         let db = init_test_db(&conn_option).await;
         insert_policy_set_fixture("./fixtures/policy_set1.json", &db).await;
 
-        let app = get_test_app(db);
+        let app = get_test_app(db.clone());
+
+        let request = |method: &str, uri: &str, company: &str| {
+            Request::builder()
+                .uri(uri)
+                .method(method)
+                .header(
+                    AUTHORIZATION,
+                    server_token::server_token_test_helper::get_human_token_header(
+                        Some(company.to_owned()),
+                        None,
+                    ),
+                )
+                .header("Content-Type", "application/json")
+                .body(Body::empty())
+                .unwrap()
+        };
+        let policy_sets = |body: axum::body::Bytes| -> Vec<MatchingPolicySetRow> {
+            let value: serde_json::Value = serde_json::from_slice(&body).unwrap();
+            serde_json::from_value(value["data"].clone()).unwrap()
+        };
+
+        let uri = "/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881";
 
         let response = app
-            .oneshot(
-                Request::builder()
-                    .uri("/policy-set/84b7fba4-05f3-4af8-9d84-dde384abe881/policy/564f3b46-7127-4c3c-a0b8-2859c01cc9c1")
-                    .method("DELETE")
-                    .header(
-                        AUTHORIZATION,
-                        server_token::server_token_test_helper::get_human_token_header(
-                            Some("NL.24244".to_owned()),
-                            None,
-                        ),
-                    )
-                    .header("Content-Type", "application/json")
-                    .body(Body::empty())
-                    .unwrap(),
+            .clone()
+            .oneshot(request("DELETE", uri, "NL.24244"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", uri, "NL.24244"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/policy-set", "NL.24244"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(policy_sets(body).is_empty());
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/policy-set/trash", "NL.24244"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        let deleted = policy_sets(body);
+        assert_eq!(deleted.len(), 1);
+        assert!(deleted[0].deleted_at.is_some());
+
+        // only the issuer sees and restores its deleted policy sets
+        let response = app
+            .clone()
+            .oneshot(request("GET", "/policy-set/trash", "NL.44444"))
+            .await
+            .unwrap();
+        let body = response.into_body().collect().await.unwrap().to_bytes();
+        assert!(policy_sets(body).is_empty());
+
+        let restore_uri = format!("{}/restore", uri);
+        let response = app
+            .clone()
+            .oneshot(request("POST", &restore_uri, "NL.44444"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app
+            .clone()
+            .oneshot(request("POST", &restore_uri, "NL.24244"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let policy_set: MatchingPolicySetRow =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert_eq!(policy_set.policies.len(), 1);
+        assert!(policy_set.deleted_at.is_none());
+
+        let response = app
+            .clone()
+            .oneshot(request("POST", &restore_uri, "NL.24244"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        let response = app
+            .clone()
+            .oneshot(request("GET", uri, "NL.24244"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        // deleted policy sets are purged once the retention period has passed
+        let response = app
+            .clone()
+            .oneshot(request("DELETE", uri, "NL.24244"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let now = FakeTimeProvider::new().now();
+        let purged = crate::services::policy_trash::purge_deleted_policy_sets(
+            now + chrono::Duration::days(29),
+            30,
+            &db,
+        )
+        .await
+        .unwrap();
+        assert!(purged.is_empty());
+
+        let purged = crate::services::policy_trash::purge_deleted_policy_sets(
+            now + chrono::Duration::days(31),
+            30,
+            &db,
+        )
+        .await
+        .unwrap();
+        assert_eq!(purged.len(), 1);
+
+        let response = app
+            .oneshot(request("POST", &restore_uri, "NL.24244"))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+
+        Ok(())
+    }
+
+    #[sqlx::test]
+    async fn test_policy_set_approval(
+        _pool_options: PgPoolOptions,
+        conn_option: PgConnectOptions,
+    ) -> sqlx::Result<()> {
+        let db = init_test_db(&conn_option).await;
+        insert_policy_set_fixture("./fixtures/policy_set4.json", &db).await;
+
+        let app = get_test_app(db.clone());
+
+        let issuer_request = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            Request::builder()
+                .uri(uri)
+                .method(method)
+                .header(
+                    AUTHORIZATION,
+                    server_token::server_token_test_helper::get_human_token_header(
+                        Some("NL.24244".to_owned()),
+                        None,
+                    ),
+                )
+                .header("Content-Type", "application/json")
+                .body(match body {
+                    Some(body) => Body::new(create_request_body(&body)),
+                    None => Body::empty(),
+                })
+                .unwrap()
+        };
+        // NL.44444 can create policy sets on behalf of NL.24244 through delegation evidence
+        let manager_request = |method: &str, uri: &str, body: Option<serde_json::Value>| {
+            Request::builder()
+                .uri(uri)
+                .method(method)
+                .header(
+                    AUTHORIZATION,
+                    server_token::server_token_test_helper::get_machine_token_header(Some(
+                        "NL.44444".to_owned(),
+                    )),
+                )
+                .header("Content-Type", "application/json")
+                .body(match body {
+                    Some(body) => Body::new(create_request_body(&body)),
+                    None => Body::empty(),
+                })
+                .unwrap()
+        };
+        let policy_set = json!({
+            "policies": [{
+                "target": {
+                    "resource": {
+                        "type": "TestResource",
+                        "identifiers": ["test"],
+                        "attributes": ["*"]
+                    },
+                    "actions": ["Read"],
+                    "environment": {
+                        "serviceProviders": ["asdf"]
+                    }
+                },
+                "rules": [{ "effect": "Permit" }]
+            }],
+            "target": {
+                "accessSubject": "sadfasdf"
+            },
+            "policyIssuer": "NL.24244",
+            "licences": [],
+            "maxDelegationDepth": 2
+        });
+        let now = FakeTimeProvider::new().now();
+        let evidence_policy_sets = || {
+            crate::db::policy::get_policy_sets_with_policies_for_creating_de(
+                now,
+                "sadfasdf".to_owned(),
+                "NL.24244".to_owned(),
+                &db,
             )
+        };
+
+        let response = app
+            .clone()
+            .oneshot(issuer_request("GET", "/policy-set/settings", None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let settings: serde_json::Value =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert_eq!(settings, json!({ "requirePolicySetApproval": false }));
+
+        let response = app
+            .clone()
+            .oneshot(issuer_request(
+                "PUT",
+                "/policy-set/settings",
+                Some(json!({ "requirePolicySetApproval": true })),
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let mut ids = vec![];
+        for _ in 0..2 {
+            let response = app
+                .clone()
+                .oneshot(manager_request(
+                    "POST",
+                    "/policy-set",
+                    Some(policy_set.clone()),
+                ))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let inserted: InsertPolicySetResponse =
+                serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                    .unwrap();
+            ids.push(inserted.uuid);
+        }
+
+        // pending policy sets are never used for delegation evidence
+        assert!(evidence_policy_sets().await.unwrap().is_empty());
+
+        let response = app
+            .clone()
+            .oneshot(issuer_request("GET", "/policy-set/pending", None))
             .await
             .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let pending: serde_json::Value =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert_eq!(pending["data"].as_array().unwrap().len(), 2);
+        assert_eq!(pending["data"][0]["status"], "pending");
+
+        // the manager can't approve its own policy sets
+        let approve_uri = format!("/policy-set/{}/approve", ids[0]);
+        let response = app
+            .clone()
+            .oneshot(manager_request("POST", &approve_uri, None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+
+        let response = app
+            .clone()
+            .oneshot(issuer_request("POST", &approve_uri, None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[ETAG], "\"2\"");
+        let approved: MatchingPolicySetRow =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert_eq!(approved.status, Some(PolicySetStatus::Active));
+
+        let response = app
+            .clone()
+            .oneshot(issuer_request("POST", &approve_uri, None))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+
+        // a decision taken while the policy set was still pending doesn't change it anymore
+        let updated = crate::db::policy::update_pending_policy_set_status(
+            &ids[0],
+            PolicySetStatus::Rejected,
+            &db,
+        )
+        .await
+        .unwrap();
+        assert_eq!(updated, 0);
 
+        let response = app
+            .clone()
+            .oneshot(issuer_request(
+                "POST",
+                &format!("/policy-set/{}/reject", ids[1]),
+                None,
+            ))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        let rejected: MatchingPolicySetRow =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert_eq!(rejected.status, Some(PolicySetStatus::Rejected));
+
+        let policy_sets = evidence_policy_sets().await.unwrap();
+        assert_eq!(policy_sets.len(), 1);
+        assert_eq!(policy_sets[0].policy_set_id, ids[0]);
+
+        let response = app
+            .clone()
+            .oneshot(issuer_request("GET", "/policy-set/pending", None))
+            .await
+            .unwrap();
+        let pending: serde_json::Value =
+            serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                .unwrap();
+        assert!(pending["data"].as_array().unwrap().is_empty());
+
+        // rejected policy sets are listed apart from the current ones until they are purged
+        for (uri, listed_ids) in [
+            ("/policy-set", vec![ids[0]]),
+            ("/policy-set/rejected", vec![ids[1]]),
+        ] {
+            let response = app
+                .clone()
+                .oneshot(issuer_request("GET", uri, None))
+                .await
+                .unwrap();
+            assert_eq!(response.status(), StatusCode::OK);
+            let listed: serde_json::Value =
+                serde_json::from_slice(&response.into_body().collect().await.unwrap().to_bytes())
+                    .unwrap();
+            let listed: Vec<MatchingPolicySetRow> =
+                serde_json::from_value(listed["data"].clone()).unwrap();
+            let listed: Vec<Uuid> = listed
+                .iter()
+                .map(|ps| ps.policy_set_id)
+                .filter(|id| ids.contains(id))
+                .collect();
+            assert_eq!(listed, listed_ids);
+        }
+
+        let purged = crate::services::policy_trash::purge_rejected_policy_sets(now, 30, &db)
+            .await
+            .unwrap();
+        assert!(purged.is_empty());
+        let purged = crate::services::policy_trash::purge_rejected_policy_sets(
+            now + chrono::Duration::days(31),
+            30,
+            &db,
+        )
+        .await
+        .unwrap();
+        assert_eq!(purged, vec![ids[1]]);
+
+        // policy sets the issuer creates itself don't need approval
+        let response = app
+            .clone()
+            .oneshot(issuer_request("POST", "/policy-set", Some(policy_set)))
+            .await
+            .unwrap();
         assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(evidence_policy_sets().await.unwrap().len(), 2);
 
         Ok(())
     }
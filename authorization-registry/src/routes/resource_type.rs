@@ -0,0 +1,98 @@
+use axum::{extract::Path, middleware::from_fn_with_state, routing::get, Extension, Json, Router};
+use axum_extra::extract::WithRejection;
+use reqwest::StatusCode;
+use sea_orm::DatabaseConnection;
+
+use crate::{
+    db::resource_type as resource_type_store,
+    error::{AppError, ErrorResponse, ExpectedError},
+    middleware::extract_role_middleware,
+    services::server_token::ServerToken,
+    AppState,
+};
+
+pub fn get_resource_type_routes(server_token: std::sync::Arc<ServerToken>) -> Router<AppState> {
+    return Router::new()
+        .route("/", get(get_resource_types))
+        .route("/:name", get(get_resource_type))
+        .layer(from_fn_with_state(server_token, extract_role_middleware));
+}
+
+#[utoipa::path(
+    get,
+    path = "/resource-type",
+    tag = "Resource Types",
+    security(
+        ("bearer" = [])
+    ),
+    responses(
+        (
+            status = 200,
+            description = "List of all registered resource types with the actions, attributes and identifier format policies can use.",
+            content_type = "application/json",
+            body = Vec<ar_entity::resource_type::Model>
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized")),
+        )
+    )
+ )]
+async fn get_resource_types(
+    Extension(db): Extension<DatabaseConnection>,
+) -> Result<Json<Vec<ar_entity::resource_type::Model>>, AppError> {
+    let resource_types = resource_type_store::get_all_resource_types(&db).await?;
+
+    Ok(Json(resource_types))
+}
+
+#[utoipa::path(
+    get,
+    path = "/resource-type/{name}",
+    tag = "Resource Types",
+    security(
+        ("bearer" = [])
+    ),
+    params(
+        ("name" = String, Path, description = "Name of the resource type")
+    ),
+    responses(
+        (
+            status = 200,
+            description = "Resource type that has the name provided in the request.",
+            content_type = "application/json",
+            body = ar_entity::resource_type::Model
+        ),
+        (
+            status = 401,
+            description = "Authentication failed",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Unauthorized")),
+        ),
+        (
+            status = 404,
+            description = "Not found",
+            content_type = "application/json",
+            example = json!(ErrorResponse::new("Resource type not found")),
+        )
+    )
+ )]
+async fn get_resource_type(
+    Extension(db): Extension<DatabaseConnection>,
+    WithRejection(Path(name), _): WithRejection<Path<String>, AppError>,
+) -> Result<Json<ar_entity::resource_type::Model>, AppError> {
+    match resource_type_store::get_resource_type_by_name(&name, &db).await? {
+        Some(resource_type) => Ok(Json(resource_type)),
+        None => Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::NOT_FOUND,
+            message: "Resource type not found".to_owned(),
+            reason: format!(
+                "resource type '{}' is not in the resource type registry",
+                name
+            ),
+            metadata: None,
+        })),
+    }
+}
@@ -86,9 +86,14 @@ pub async fn apply_seeds(db: &DatabaseConnection, config: &Config) {
             }
 
             for ps in seed.policy_sets {
+                // policy sets in the trash aren't seeded again
+                let deleted = policy_store::get_deleted_policy_set_by_id(&ps.id, &db)
+                    .await
+                    .unwrap();
                 match policy_store::get_policy_set_by_id(&ps.id, &db)
                     .await
                     .unwrap()
+                    .or(deleted)
                 {
                     Some(_) => {}
                     None => {
@@ -2,6 +2,7 @@ use std::{collections::HashMap, fmt, sync::Arc};
 
 use anyhow::Context;
 use ar_entity::audit_event::{ActiveModel as AuditEventModel, Entity as AuditEventEntity};
+use ar_entity::policy_set::PolicySetStatus;
 use chrono::{DateTime, Utc};
 use ishare::{
     delegation_evidence::verify_delegation_evidence,
@@ -21,13 +22,14 @@ use uuid::Uuid;
 
 use crate::{
     error::{AppError, ExpectedError},
-    services::delegation::create_delegation_evidence,
+    services::delegation::{create_delegation_evidence, AccessSubjectDetails},
     AppConfig, TimeProvider,
 };
 
 #[derive(Serialize, Deserialize)]
 pub struct PolicySetCreatedEventMetadata {
     pub policy_set_id: Uuid,
+    pub status: PolicySetStatus,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -46,12 +48,35 @@ pub struct PolicyReplaced {
     pub new_policy_id: Uuid,
 }
 
+#[derive(Deserialize, Serialize, Clone)]
+pub struct PolicySetMetadata {
+    pub access_subject: String,
+    pub licenses: Vec<String>,
+    pub max_delegation_depth: i32,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct MetadataEdited {
+    pub previous: PolicySetMetadata,
+    pub current: PolicySetMetadata,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct RolledBack {
+    pub restored_version: i32,
+    pub version: i32,
+}
+
 #[derive(Deserialize, Serialize)]
 #[serde(tag = "edit_type")]
 pub enum EditedType {
     PolicyRemoved(PolicyRemoved),
     PolicyAdded(PolicyAdded),
     PolicyReplaced(PolicyReplaced),
+    MetadataEdited(MetadataEdited),
+    RolledBack(RolledBack),
+    // the policy set was overwritten by a policy set import
+    Imported,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -66,11 +91,37 @@ pub struct PolicySetDeletedEventMetadata {
     pub policy_set_id: Uuid,
 }
 
+#[derive(Serialize, Deserialize)]
+pub struct PolicySetRestoredEventMetadata {
+    pub policy_set_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PolicySetApprovedEventMetadata {
+    pub policy_set_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PolicySetRejectedEventMetadata {
+    pub policy_set_id: Uuid,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PartyGroupMembersEventMetadata {
+    pub group_name: String,
+    pub party_ids: Vec<String>,
+}
+
 pub enum EventType {
     DmiDelegationRequest(DelegationRequest),
     ArPolicySetCreated(PolicySetCreatedEventMetadata),
     ArPolicySetEdited(PolicySetEditedEventMetadata),
     ArPolicySetDeleted(PolicySetDeletedEventMetadata),
+    ArPolicySetRestored(PolicySetRestoredEventMetadata),
+    ArPolicySetApproved(PolicySetApprovedEventMetadata),
+    ArPolicySetRejected(PolicySetRejectedEventMetadata),
+    ArPartyGroupMembersAdded(PartyGroupMembersEventMetadata),
+    ArPartyGroupMembersRemoved(PartyGroupMembersEventMetadata),
 }
 
 impl EventType {
@@ -89,6 +140,21 @@ impl EventType {
             Self::ArPolicySetDeleted(meta_data) => Ok(Some(
                 serde_json::to_value(meta_data).context("Error parsing serde_json value")?,
             )),
+            Self::ArPolicySetRestored(meta_data) => Ok(Some(
+                serde_json::to_value(meta_data).context("Error parsing serde_json value")?,
+            )),
+            Self::ArPolicySetApproved(meta_data) => Ok(Some(
+                serde_json::to_value(meta_data).context("Error parsing serde_json value")?,
+            )),
+            Self::ArPolicySetRejected(meta_data) => Ok(Some(
+                serde_json::to_value(meta_data).context("Error parsing serde_json value")?,
+            )),
+            Self::ArPartyGroupMembersAdded(meta_data) => Ok(Some(
+                serde_json::to_value(meta_data).context("Error parsing serde_json value")?,
+            )),
+            Self::ArPartyGroupMembersRemoved(meta_data) => Ok(Some(
+                serde_json::to_value(meta_data).context("Error parsing serde_json value")?,
+            )),
         }
     }
 }
@@ -100,6 +166,11 @@ impl fmt::Display for EventType {
             EventType::ArPolicySetCreated(_) => "dmi:ar:policy_set:created",
             EventType::ArPolicySetEdited(_) => "dmi:ar:policy_set:edited",
             EventType::ArPolicySetDeleted(_) => "dmi:ar:policy_set:deleted",
+            EventType::ArPolicySetRestored(_) => "dmi:ar:policy_set:restored",
+            EventType::ArPolicySetApproved(_) => "dmi:ar:policy_set:approved",
+            EventType::ArPolicySetRejected(_) => "dmi:ar:policy_set:rejected",
+            EventType::ArPartyGroupMembersAdded(_) => "dmi:ar:party_group:members_added",
+            EventType::ArPartyGroupMembersRemoved(_) => "dmi:ar:party_group:members_removed",
         };
         write!(f, "{}", s)
     }
@@ -137,6 +208,41 @@ pub async fn log_event<T: ConnectionTrait>(
     Ok(())
 }
 
+// logs many events with a single insert
+pub async fn log_events<T: ConnectionTrait>(
+    now: DateTime<Utc>,
+    events: Vec<(String, EventType)>,
+    db: &T,
+) -> anyhow::Result<()> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let mut log_entries = vec![];
+    for (entry_id, event_type) in events.into_iter() {
+        log_entries.push(AuditEventModel {
+            entry_id: ActiveValue::Set(entry_id),
+            id: ActiveValue::Set(uuid::Uuid::new_v4()),
+            source: ActiveValue::Set(None),
+            timestamp: ActiveValue::Set(now),
+            context: ActiveValue::Set(event_type.get_context()?),
+            event_type: ActiveValue::Set(event_type.to_string()),
+            data: ActiveValue::Set(None),
+        });
+    }
+
+    let count = log_entries.len();
+
+    AuditEventEntity::insert_many(log_entries)
+        .exec(db)
+        .await
+        .context("Error inserting audit log entries")?;
+
+    tracing::info!("{} log entries saved", count);
+
+    Ok(())
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct AuditEventWithIssAndSub {
     pub timestamp: DateTime<Utc>,
@@ -237,6 +343,10 @@ pub async fn retrieve_events(
                 }],
             }],
         },
+        AccessSubjectDetails::default(),
+        &HashMap::new(),
+        &vec![],
+        &app_config.combining_algorithm,
         time_provider,
         30,
         db,
@@ -245,7 +355,7 @@ pub async fn retrieve_events(
     .context("Error creating delegation evidence")?;
 
     let access = verify_delegation_evidence(
-        &delegation_evidence_container.delegation_evidence,
+        &delegation_evidence_container.container.delegation_evidence,
         "AuditLog".to_owned(),
     );
 
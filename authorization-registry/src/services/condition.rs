@@ -0,0 +1,383 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+use ar_entity::delegation_evidence::{
+    Condition, EnvironmentAttribute, IpRange, Policy, ResourceRule, TimeWindow,
+};
+use axum::http::StatusCode;
+use chrono::{Datelike, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use ipnet::IpNet;
+
+use crate::error::{AppError, ExpectedError};
+
+use super::delegation::EvaluationContext;
+
+// name of the environment attribute the ip range condition is evaluated against
+pub const IP_ENVIRONMENT_ATTRIBUTE: &str = "ip";
+
+fn parse_time(time: &str) -> Option<NaiveTime> {
+    NaiveTime::parse_from_str(time, "%H:%M").ok()
+}
+
+fn is_time_window_met(window: &TimeWindow, context: &EvaluationContext) -> bool {
+    let (Ok(timezone), Some(start), Some(end)) = (
+        Tz::from_str(&window.timezone),
+        parse_time(&window.start),
+        parse_time(&window.end),
+    ) else {
+        tracing::warn!("invalid time window condition: {:?}", window);
+        return false;
+    };
+
+    let local = context.now.with_timezone(&timezone);
+    let time = local.time();
+
+    let is_matching_weekday = window.weekdays.is_empty()
+        || window
+            .weekdays
+            .iter()
+            .filter_map(|d| Weekday::from_str(d).ok())
+            .any(|d| d == local.weekday());
+
+    let is_within_window = if start <= end {
+        start <= time && time < end
+    } else {
+        time >= start || time < end
+    };
+
+    is_matching_weekday && is_within_window
+}
+
+fn is_ip_range_met(ip_range: &IpRange, context: &EvaluationContext) -> Option<bool> {
+    let ip = context
+        .environment
+        .get(IP_ENVIRONMENT_ATTRIBUTE)
+        .and_then(|ip| IpAddr::from_str(ip).ok())?;
+
+    Some(
+        ip_range
+            .ranges
+            .iter()
+            .filter_map(|r| IpNet::from_str(r).ok())
+            .any(|r| r.contains(&ip)),
+    )
+}
+
+fn is_environment_attribute_met(
+    attribute: &EnvironmentAttribute,
+    context: &EvaluationContext,
+) -> Option<bool> {
+    context
+        .environment
+        .get(&attribute.attribute)
+        .map(|v| attribute.values.contains(v))
+}
+
+// None when the environment of the request lacks the attribute the condition is evaluated against
+fn evaluate_condition(condition: &Condition, context: &EvaluationContext) -> Option<bool> {
+    match condition {
+        Condition::TimeWindow(w) => Some(is_time_window_met(w, context)),
+        Condition::IpRange(r) => is_ip_range_met(r, context),
+        Condition::Environment(a) => is_environment_attribute_met(a, context),
+    }
+}
+
+// a permit only applies when the request shows that its condition is met
+pub fn is_condition_met(condition: &Condition, context: &EvaluationContext) -> bool {
+    evaluate_condition(condition, context).unwrap_or(false)
+}
+
+// a deny applies unless the request shows that its condition is not met, leaving out an attribute
+// doesn't escape the deny
+pub fn is_deny_condition_met(condition: &Condition, context: &EvaluationContext) -> bool {
+    evaluate_condition(condition, context).unwrap_or(true)
+}
+
+// the address the request comes from. the rightmost addresses of the X-Forwarded-For header that
+// were added by trusted proxies are skipped, the address before them is the client
+pub fn get_client_ip(
+    peer: Option<IpAddr>,
+    forwarded_for: Option<&str>,
+    trusted_proxies: &Vec<String>,
+) -> Option<IpAddr> {
+    let trusted_proxies: Vec<IpNet> = trusted_proxies
+        .iter()
+        .filter_map(|p| IpNet::from_str(p).ok())
+        .collect();
+    let is_trusted = |ip: &IpAddr| trusted_proxies.iter().any(|p| p.contains(ip));
+
+    let mut client_ip = peer?;
+    let mut forwarded = forwarded_for
+        .unwrap_or_default()
+        .rsplit(',')
+        .map(|ip| ip.trim())
+        .filter(|ip| !ip.is_empty());
+
+    while is_trusted(&client_ip) {
+        match forwarded.next().map(IpAddr::from_str) {
+            Some(Ok(ip)) => client_ip = ip,
+            Some(Err(_)) => return None,
+            None => break,
+        }
+    }
+
+    Some(client_ip)
+}
+
+// the 'ip' attribute of the environment is always the address the request comes from, the caller
+// can't choose it
+pub fn with_client_ip(
+    environment: &HashMap<String, String>,
+    client_ip: Option<IpAddr>,
+) -> HashMap<String, String> {
+    let mut environment = environment.clone();
+    environment.remove(IP_ENVIRONMENT_ATTRIBUTE);
+    if let Some(ip) = client_ip {
+        environment.insert(IP_ENVIRONMENT_ATTRIBUTE.to_owned(), ip.to_string());
+    }
+
+    environment
+}
+
+fn validate_condition(condition: &Condition) -> Result<(), String> {
+    match condition {
+        Condition::TimeWindow(w) => {
+            if Tz::from_str(&w.timezone).is_err() {
+                return Err(format!("Unknown timezone '{}'", w.timezone));
+            }
+            for time in [&w.start, &w.end] {
+                if parse_time(time).is_none() {
+                    return Err(format!("Invalid time '{}', expected 'HH:MM'", time));
+                }
+            }
+            if let Some(day) = w.weekdays.iter().find(|d| Weekday::from_str(d).is_err()) {
+                return Err(format!("Invalid weekday '{}'", day));
+            }
+        }
+        Condition::IpRange(r) => {
+            if r.ranges.is_empty() {
+                return Err("Ip range condition requires at least one range".to_owned());
+            }
+            if let Some(range) = r.ranges.iter().find(|r| IpNet::from_str(r).is_err()) {
+                return Err(format!("Invalid ip range '{}'", range));
+            }
+        }
+        Condition::Environment(a) => {
+            if a.attribute.is_empty() || a.values.is_empty() {
+                return Err(
+                    "Environment condition requires an attribute and at least one value".to_owned(),
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_policy_conditions(policy: &Policy) -> Result<(), AppError> {
+    let conditions = policy.rules.iter().flat_map(|r| match r {
+        ResourceRule::Permit(p) => p.conditions.iter(),
+        ResourceRule::Deny(d) => d.conditions.iter(),
+    });
+
+    for condition in conditions {
+        if let Err(message) = validate_condition(condition) {
+            return Err(AppError::Expected(ExpectedError {
+                status_code: StatusCode::BAD_REQUEST,
+                message: message.clone(),
+                reason: message,
+                metadata: None,
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context(now: &str, environment: Vec<(&str, &str)>) -> EvaluationContext {
+        EvaluationContext {
+            now: chrono::DateTime::parse_from_rfc3339(now).unwrap().to_utc(),
+            environment: environment
+                .into_iter()
+                .map(|(k, v)| (k.to_owned(), v.to_owned()))
+                .collect::<HashMap<_, _>>(),
+            combining_algorithm: None,
+            required_licenses: vec![],
+        }
+    }
+
+    fn office_hours() -> Condition {
+        Condition::TimeWindow(TimeWindow {
+            weekdays: vec!["Mon", "Tue", "Wed", "Thu", "Fri"]
+                .into_iter()
+                .map(|d| d.to_owned())
+                .collect(),
+            start: "08:00".to_owned(),
+            end: "18:00".to_owned(),
+            timezone: "Europe/Amsterdam".to_owned(),
+        })
+    }
+
+    #[test]
+    fn test_time_window() {
+        // thursday 09:33 in Amsterdam
+        assert!(is_condition_met(
+            &office_hours(),
+            &context("2024-05-09T07:33:25Z", vec![])
+        ));
+        // thursday 07:33 in Amsterdam
+        assert!(!is_condition_met(
+            &office_hours(),
+            &context("2024-05-09T05:33:25Z", vec![])
+        ));
+        // saturday 09:33 in Amsterdam
+        assert!(!is_condition_met(
+            &office_hours(),
+            &context("2024-05-11T07:33:25Z", vec![])
+        ));
+    }
+
+    #[test]
+    fn test_time_window_over_midnight() {
+        let night = Condition::TimeWindow(TimeWindow {
+            weekdays: vec![],
+            start: "22:00".to_owned(),
+            end: "06:00".to_owned(),
+            timezone: "UTC".to_owned(),
+        });
+
+        assert!(is_condition_met(
+            &night,
+            &context("2024-05-09T23:00:00Z", vec![])
+        ));
+        assert!(is_condition_met(
+            &night,
+            &context("2024-05-09T05:59:00Z", vec![])
+        ));
+        assert!(!is_condition_met(
+            &night,
+            &context("2024-05-09T12:00:00Z", vec![])
+        ));
+    }
+
+    #[test]
+    fn test_ip_range() {
+        let condition = Condition::IpRange(IpRange {
+            ranges: vec!["10.0.0.0/8".to_owned(), "2001:db8::/32".to_owned()],
+        });
+
+        assert!(is_condition_met(
+            &condition,
+            &context("2024-05-09T07:33:25Z", vec![("ip", "10.1.2.3")])
+        ));
+        assert!(is_condition_met(
+            &condition,
+            &context("2024-05-09T07:33:25Z", vec![("ip", "2001:db8::1")])
+        ));
+        assert!(!is_condition_met(
+            &condition,
+            &context("2024-05-09T07:33:25Z", vec![("ip", "192.168.1.1")])
+        ));
+        assert!(!is_condition_met(
+            &condition,
+            &context("2024-05-09T07:33:25Z", vec![])
+        ));
+    }
+
+    #[test]
+    fn test_environment_attribute() {
+        let condition = Condition::Environment(EnvironmentAttribute {
+            attribute: "region".to_owned(),
+            values: vec!["EU".to_owned()],
+        });
+
+        assert!(is_condition_met(
+            &condition,
+            &context("2024-05-09T07:33:25Z", vec![("region", "EU")])
+        ));
+        assert!(!is_condition_met(
+            &condition,
+            &context("2024-05-09T07:33:25Z", vec![("region", "US")])
+        ));
+    }
+
+    #[test]
+    fn test_deny_condition_with_missing_attribute() {
+        let ip_range = Condition::IpRange(IpRange {
+            ranges: vec!["10.0.0.0/8".to_owned()],
+        });
+        let region = Condition::Environment(EnvironmentAttribute {
+            attribute: "region".to_owned(),
+            values: vec!["EU".to_owned()],
+        });
+        let empty = context("2024-05-09T07:33:25Z", vec![]);
+
+        assert!(!is_condition_met(&ip_range, &empty));
+        assert!(is_deny_condition_met(&ip_range, &empty));
+        assert!(!is_condition_met(&region, &empty));
+        assert!(is_deny_condition_met(&region, &empty));
+        assert!(!is_deny_condition_met(
+            &region,
+            &context("2024-05-09T07:33:25Z", vec![("region", "US")])
+        ));
+    }
+
+    #[test]
+    fn test_get_client_ip() {
+        let peer = Some(IpAddr::from_str("10.0.0.1").unwrap());
+        let trusted_proxies = vec!["10.0.0.0/8".to_owned()];
+
+        assert_eq!(get_client_ip(peer, Some("1.2.3.4, 5.6.7.8"), &vec![]), peer);
+        assert_eq!(
+            get_client_ip(peer, Some("1.2.3.4, 5.6.7.8, 10.0.0.2"), &trusted_proxies),
+            Some(IpAddr::from_str("5.6.7.8").unwrap())
+        );
+        assert_eq!(get_client_ip(peer, None, &trusted_proxies), peer);
+        assert_eq!(get_client_ip(None, Some("1.2.3.4"), &trusted_proxies), None);
+    }
+
+    #[test]
+    fn test_with_client_ip() {
+        let environment = HashMap::from([
+            ("ip".to_owned(), "10.1.2.3".to_owned()),
+            ("region".to_owned(), "EU".to_owned()),
+        ]);
+
+        let environment = with_client_ip(&environment, None);
+        assert_eq!(environment.get("ip"), None);
+        assert_eq!(environment.get("region").unwrap(), "EU");
+
+        let environment =
+            with_client_ip(&environment, Some(IpAddr::from_str("192.168.1.1").unwrap()));
+        assert_eq!(environment.get("ip").unwrap(), "192.168.1.1");
+    }
+
+    #[test]
+    fn test_validate_condition() {
+        assert!(validate_condition(&office_hours()).is_ok());
+        assert!(validate_condition(&Condition::TimeWindow(TimeWindow {
+            weekdays: vec![],
+            start: "08:00".to_owned(),
+            end: "18:00".to_owned(),
+            timezone: "Europe/Nowhere".to_owned(),
+        }))
+        .is_err());
+        assert!(validate_condition(&Condition::TimeWindow(TimeWindow {
+            weekdays: vec!["Someday".to_owned()],
+            start: "08:00".to_owned(),
+            end: "18:00".to_owned(),
+            timezone: "UTC".to_owned(),
+        }))
+        .is_err());
+        assert!(validate_condition(&Condition::IpRange(IpRange {
+            ranges: vec!["10.0.0.0/33".to_owned()],
+        }))
+        .is_err());
+    }
+}
@@ -0,0 +1,407 @@
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::RwLock;
+
+use chrono::{DateTime, Utc};
+use ishare::delegation_evidence::PolicySet;
+use ishare::delegation_request::DelegationRequest;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::utils::{normalize_party_id, party_group_name, ANY_PARTY};
+
+use super::delegation::IssuedDelegationEvidence;
+use super::obligation::PermitObligations;
+
+// upper bound on the number of cached decisions, new decisions are not cached when it is reached
+const MAX_ENTRIES: usize = 10_000;
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq)]
+pub struct DecisionCacheKey {
+    policy_issuer: String,
+    access_subject: String,
+    // the user of the access subject the request is made for
+    access_subject_user: Option<String>,
+    // the requested policy sets, serialized in a canonical form
+    policy_sets: String,
+    required_licenses: Vec<String>,
+}
+
+impl DecisionCacheKey {
+    pub fn new(
+        delegation_request: &DelegationRequest,
+        access_subject_user: Option<&str>,
+        required_licenses: &Vec<String>,
+    ) -> Self {
+        let mut required_licenses = required_licenses.clone();
+        required_licenses.sort();
+        required_licenses.dedup();
+
+        Self {
+            policy_issuer: normalize_party_id(&delegation_request.policy_issuer),
+            access_subject: normalize_party_id(&delegation_request.target.access_subject),
+            access_subject_user: access_subject_user.map(|u| u.to_owned()),
+            policy_sets: serde_json::to_string(&delegation_request.policy_sets).unwrap_or_default(),
+            required_licenses,
+        }
+    }
+}
+
+struct CachedDecision {
+    // the evaluated policy sets of the evidence, the timestamps are set when a decision is served
+    policy_sets: serde_json::Value,
+    obligations: serde_json::Value,
+    // the end of the validity window of the policy sets the decision is based on
+    grant_end: Option<i64>,
+    expires_at: DateTime<Utc>,
+}
+
+pub struct Decision {
+    pub policy_sets: Vec<PolicySet>,
+    pub obligations: Vec<Vec<PermitObligations>>,
+    pub grant_end: Option<i64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct DecisionCacheStats {
+    pub enabled: bool,
+    pub ttl_seconds: i64,
+    pub entries: usize,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+// caches the outcome of delegation requests. entries are removed when the policy sets they could
+// be based on change and expire when a policy set that could change them becomes valid. the cache
+// and its invalidation live in a single process, so it must be disabled when more than one
+// instance serves the same database
+pub struct DecisionCache {
+    ttl_seconds: i64,
+    entries: RwLock<HashMap<DecisionCacheKey, CachedDecision>>,
+    // incremented on every invalidation, a decision evaluated before an invalidation is not stored
+    generation: AtomicU64,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl DecisionCache {
+    // a ttl of 0 disables the cache
+    pub fn new(ttl_seconds: i64) -> Self {
+        Self {
+            ttl_seconds,
+            entries: RwLock::new(HashMap::new()),
+            generation: AtomicU64::new(0),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.ttl_seconds > 0
+    }
+
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::SeqCst)
+    }
+
+    pub fn get(&self, key: &DecisionCacheKey, now: DateTime<Utc>) -> Option<Decision> {
+        if !self.is_enabled() {
+            return None;
+        }
+
+        let decision = self
+            .entries
+            .read()
+            .expect("decision cache lock poisoned")
+            .get(key)
+            .filter(|d| d.expires_at > now && d.grant_end.is_none_or(|end| end > now.timestamp()))
+            .and_then(|d| {
+                let policy_sets =
+                    serde_json::from_value::<Vec<PolicySet>>(d.policy_sets.clone()).ok()?;
+                let obligations = serde_json::from_value(d.obligations.clone()).ok()?;
+
+                Some(Decision {
+                    policy_sets,
+                    obligations,
+                    grant_end: d.grant_end,
+                })
+            });
+
+        match decision {
+            Some(_) => self.hits.fetch_add(1, Ordering::Relaxed),
+            None => self.misses.fetch_add(1, Ordering::Relaxed),
+        };
+
+        decision
+    }
+
+    // stores the decision unless the cache was invalidated after `generation` was read. a policy
+    // set that becomes valid at `next_not_before` can change the decision, it expires by then
+    pub fn insert(
+        &self,
+        key: DecisionCacheKey,
+        evidence: &IssuedDelegationEvidence,
+        de_expiry_seconds: i64,
+        next_not_before: Option<DateTime<Utc>>,
+        generation: u64,
+        now: DateTime<Utc>,
+    ) {
+        if !self.is_enabled() {
+            return;
+        }
+
+        let (Ok(policy_sets), Ok(obligations)) = (
+            serde_json::to_value(&evidence.container.delegation_evidence.policy_sets),
+            serde_json::to_value(&evidence.obligations),
+        ) else {
+            return;
+        };
+
+        // the evidence ends before the default expiry when a policy set ends earlier
+        let evidence = &evidence.container.delegation_evidence;
+        let grant_end = if evidence.not_on_or_after < evidence.not_before + de_expiry_seconds {
+            Some(evidence.not_on_or_after)
+        } else {
+            None
+        };
+
+        let mut entries = self.entries.write().expect("decision cache lock poisoned");
+
+        if self.generation() != generation {
+            return;
+        }
+
+        if entries.len() >= MAX_ENTRIES {
+            entries.retain(|_, d| d.expires_at > now);
+            if entries.len() >= MAX_ENTRIES {
+                tracing::warn!("decision cache is full, not caching decision");
+                return;
+            }
+        }
+
+        let expires_at = now + chrono::Duration::seconds(self.ttl_seconds);
+
+        entries.insert(
+            key,
+            CachedDecision {
+                policy_sets,
+                obligations,
+                grant_end,
+                expires_at: next_not_before.map_or(expires_at, |nb| nb.min(expires_at)),
+            },
+        );
+    }
+
+    // a change to a policy set can only affect decisions for its access subject, unless it allows
+    // further delegation: then it can be part of a delegation chain to any access subject
+    pub fn invalidate(&self, access_subject: &str, max_delegation_depth: i32) {
+        let mut entries = self.entries.write().expect("decision cache lock poisoned");
+        self.generation.fetch_add(1, Ordering::SeqCst);
+
+        // a policy set issued to a party group or to any party with attributes affects decisions
+        // for many access subjects
+        if max_delegation_depth > 0
+            || party_group_name(access_subject).is_some()
+            || access_subject.trim() == ANY_PARTY
+        {
+            entries.clear();
+        } else {
+            let access_subject = normalize_party_id(access_subject);
+            entries.retain(|key, _| key.access_subject != access_subject);
+        }
+    }
+
+    // a change to the members of a party group can affect any decision
+    pub fn invalidate_all(&self) {
+        let mut entries = self.entries.write().expect("decision cache lock poisoned");
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        entries.clear();
+    }
+
+    pub fn stats(&self) -> DecisionCacheStats {
+        DecisionCacheStats {
+            enabled: self.is_enabled(),
+            ttl_seconds: self.ttl_seconds,
+            entries: self
+                .entries
+                .read()
+                .expect("decision cache lock poisoned")
+                .len(),
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ishare::delegation_evidence::{
+        DelegationEvidence, DelegationEvidenceContainer, DelegationTarget,
+    };
+
+    use super::*;
+
+    fn request(access_subject: &str) -> DelegationRequest {
+        serde_json::from_value(serde_json::json!({
+            "policyIssuer": "NL.24244",
+            "target": {
+                "accessSubject": access_subject
+            },
+            "policySets": []
+        }))
+        .unwrap()
+    }
+
+    fn evidence(not_before: i64, not_on_or_after: i64) -> IssuedDelegationEvidence {
+        IssuedDelegationEvidence {
+            container: DelegationEvidenceContainer {
+                delegation_evidence: DelegationEvidence {
+                    not_before,
+                    not_on_or_after,
+                    policy_issuer: "NL.24244".to_owned(),
+                    target: DelegationTarget {
+                        access_subject: "NL.44444".to_owned(),
+                    },
+                    policy_sets: vec![],
+                },
+            },
+            obligations: vec![],
+            access_subject_user: None,
+        }
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::from_timestamp(1715247205, 0).unwrap()
+    }
+
+    #[test]
+    fn test_decision_cache_hit_and_miss() {
+        let cache = DecisionCache::new(60);
+        let key = DecisionCacheKey::new(&request("NL.44444"), None, &vec![]);
+
+        assert!(cache.get(&key, now()).is_none());
+        cache.insert(
+            key.clone(),
+            &evidence(now().timestamp(), now().timestamp() + 3600),
+            3600,
+            None,
+            cache.generation(),
+            now(),
+        );
+
+        // identifiers are normalized in the key
+        let decision = cache.get(
+            &DecisionCacheKey::new(&request(" nl.44444"), None, &vec![]),
+            now(),
+        );
+        assert!(decision.is_some_and(|d| d.grant_end.is_none()));
+        // decisions for a user of the access subject are cached separately
+        assert!(cache
+            .get(
+                &DecisionCacheKey::new(&request("NL.44444"), Some("user-1"), &vec![]),
+                now(),
+            )
+            .is_none());
+        // expired after the ttl
+        assert!(cache
+            .get(&key, now() + chrono::Duration::seconds(60))
+            .is_none());
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.entries), (1, 3, 1));
+    }
+
+    #[test]
+    fn test_decision_cache_grant_end() {
+        let cache = DecisionCache::new(60);
+        let key = DecisionCacheKey::new(&request("NL.44444"), None, &vec![]);
+
+        cache.insert(
+            key.clone(),
+            &evidence(now().timestamp(), now().timestamp() + 30),
+            3600,
+            None,
+            cache.generation(),
+            now(),
+        );
+
+        assert!(cache
+            .get(&key, now())
+            .is_some_and(|d| d.grant_end == Some(now().timestamp() + 30)));
+        assert!(cache
+            .get(&key, now() + chrono::Duration::seconds(30))
+            .is_none());
+    }
+
+    #[test]
+    fn test_decision_cache_next_not_before() {
+        let cache = DecisionCache::new(60);
+        let key = DecisionCacheKey::new(&request("NL.44444"), None, &vec![]);
+
+        cache.insert(
+            key.clone(),
+            &evidence(now().timestamp(), now().timestamp() + 3600),
+            3600,
+            Some(now() + chrono::Duration::seconds(10)),
+            cache.generation(),
+            now(),
+        );
+
+        assert!(cache
+            .get(&key, now() + chrono::Duration::seconds(9))
+            .is_some());
+        // a policy set that could change the decision has become valid
+        assert!(cache
+            .get(&key, now() + chrono::Duration::seconds(10))
+            .is_none());
+    }
+
+    #[test]
+    fn test_decision_cache_invalidate() {
+        let cache = DecisionCache::new(60);
+        let de = evidence(now().timestamp(), now().timestamp() + 3600);
+
+        for access_subject in ["NL.44444", "NL.55555"] {
+            let key = DecisionCacheKey::new(&request(access_subject), None, &vec![]);
+            cache.insert(key, &de, 3600, None, cache.generation(), now());
+        }
+
+        cache.invalidate("nl.44444", 0);
+        assert_eq!(cache.stats().entries, 1);
+
+        cache.invalidate("NL.66666", 1);
+        assert_eq!(cache.stats().entries, 0);
+
+        // a decision evaluated before an invalidation is not stored
+        let generation = cache.generation();
+        cache.invalidate("NL.44444", 0);
+        cache.insert(
+            DecisionCacheKey::new(&request("NL.44444"), None, &vec![]),
+            &de,
+            3600,
+            None,
+            generation,
+            now(),
+        );
+        assert_eq!(cache.stats().entries, 0);
+    }
+
+    #[test]
+    fn test_decision_cache_disabled() {
+        let cache = DecisionCache::new(0);
+        let key = DecisionCacheKey::new(&request("NL.44444"), None, &vec![]);
+
+        cache.insert(
+            key.clone(),
+            &evidence(now().timestamp(), now().timestamp() + 3600),
+            3600,
+            None,
+            cache.generation(),
+            now(),
+        );
+
+        assert!(cache.get(&key, now()).is_none());
+        assert_eq!(cache.stats().misses, 0);
+    }
+}
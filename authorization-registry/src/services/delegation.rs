@@ -1,19 +1,35 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use anyhow::Context;
-use ar_entity::delegation_evidence::ResourceRule;
+use ar_entity::delegation_evidence::{Condition, DenyEnvironment, PartyAttributes, ResourceRule};
+use axum::http::StatusCode;
 use ishare::delegation_evidence::{
-    DelegationEvidence, DelegationEvidenceContainer, DelegationTarget, PolicySetTarget,
-    PolicySetTargetEnvironment, Resource, ResourceRules, ResourceTarget,
+    verify_delegation_evidence, DelegationEvidence, DelegationEvidenceContainer, DelegationTarget,
+    PolicySetTarget, PolicySetTargetEnvironment, Resource, ResourceRules, ResourceTarget,
 };
 use ishare::delegation_request::{DelegationRequest, Policy, PolicySet};
+use ishare::ishare::PartyInfo;
 use sea_orm::DatabaseConnection;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
 
+use crate::config::{CombiningAlgorithm, CombiningAlgorithmConfig};
+use crate::db::party_group as party_group_store;
 use crate::db::policy::{self as policy_store, DelegationEvidencePolicy, MatchingPolicySetRow};
-use crate::error::AppError;
+use crate::error::{AppError, ExpectedError};
+use crate::services::policy::InsertPolicySetWithPolicies;
+use crate::utils::{is_same_party, normalize_party_id, party_group_name, read_unverified_claims};
 use crate::TimeProvider;
 
-use super::ishare_provider::SatelliteProvider;
+use super::condition::{is_condition_met, is_deny_condition_met};
+use super::decision_cache::{DecisionCache, DecisionCacheKey};
+use super::ishare_provider::{PartyRegistration, SatelliteProvider};
+use super::obligation::{add_obligations_to_evidence, PermitObligations};
+use super::party_attributes::{is_matching_party, requires_registration};
+use super::pattern::star_or_matched_by;
+use super::previous_steps::PreviousStepsVerifier;
 
 pub fn is_contained_by<T: PartialEq>(vec_a: &Vec<T>, vec_b: &Vec<T>) -> bool {
     vec_a.iter().all(|x| vec_b.contains(x))
@@ -26,22 +42,37 @@ pub fn star_or_contained_by(vec_a: &Vec<String>, vec_b: &Vec<String>) -> bool {
 }
 
 pub fn is_matching_policy(dr_policy: &Policy, de_policy_set: &DelegationEvidencePolicy) -> bool {
-    return star_or_contained_by(
+    return star_or_matched_by(
         &dr_policy.target.resource.identifiers,
         &de_policy_set.identifiers,
-    ) && star_or_contained_by(
+    ) && star_or_matched_by(
         &dr_policy.target.resource.attributes,
         &de_policy_set.attributes,
     ) && star_or_contained_by(&dr_policy.target.actions, &de_policy_set.actions)
         && dr_policy.target.resource.resource_type == de_policy_set.resource_type
         && dr_policy.target.environment.as_ref().is_none_or(|e| {
-            is_contained_by(&e.service_providers, &de_policy_set.service_providers)
+            e.service_providers.iter().all(|sp| {
+                de_policy_set
+                    .service_providers
+                    .iter()
+                    .chain(de_policy_set.service_provider_members.iter())
+                    .any(|de_sp| is_same_party(sp, de_sp))
+            })
         });
 }
 
+// a policy set is issued to its access subject or, when that is a party group, to its members
+pub fn is_issued_to(policy_set: &MatchingPolicySetRow, party: &str) -> bool {
+    is_same_party(&policy_set.access_subject, party)
+        || policy_set
+            .access_subject_members
+            .iter()
+            .any(|m| is_same_party(m, party))
+}
+
 pub fn mask_matching_policy_sets<'a>(
     policy_set: &PolicySet,
-    de_policy_sets: &'a Vec<MatchingPolicySetRow>,
+    de_policy_sets: impl IntoIterator<Item = &'a MatchingPolicySetRow>,
 ) -> Vec<&'a MatchingPolicySetRow> {
     let filtered: Vec<&MatchingPolicySetRow> = de_policy_sets
         .into_iter()
@@ -56,143 +87,776 @@ pub fn mask_matching_policy_sets<'a>(
     filtered
 }
 
-pub fn is_permit(policy: &Policy, matching_row: &MatchingPolicySetRow) -> bool {
-    let mut matching_policies = matching_row
-        .policies
-        .iter()
-        .filter(|mp| is_matching_policy(policy, mp));
+// the state at decision time that the conditions of rules are evaluated against
+#[derive(Debug, Clone)]
+pub struct EvaluationContext {
+    pub now: chrono::DateTime<chrono::Utc>,
+    // environment attributes supplied with the delegation request, e.g. 'ip' or 'region'
+    pub environment: HashMap<String, String>,
+    // without an algorithm every matching chain results in its own evidence policy set
+    pub combining_algorithm: Option<CombiningAlgorithm>,
+    // licenses a policy set must grant to match the delegation request
+    pub required_licenses: Vec<String>,
+}
+
+// a deny without environment applies to every service provider and license. a deny scoped to
+// service providers applies when the delegation request doesn't restrict the service providers
+// or names one of them, a deny scoped to licenses applies when the evidence can be used under one
+// of them. that is one of the licenses the request requires or, when the request doesn't require
+// any, one of the licenses the policy set grants
+pub fn is_matching_deny_environment(
+    policy: &Policy,
+    licenses: &Vec<String>,
+    deny_environment: &Option<DenyEnvironment>,
+) -> bool {
+    let Some(deny_environment) = deny_environment else {
+        return true;
+    };
+
+    let is_matching_service_provider = deny_environment.service_providers.is_empty()
+        || policy.target.environment.as_ref().is_none_or(|e| {
+            e.service_providers.is_empty()
+                || e.service_providers.iter().any(|sp| {
+                    deny_environment
+                        .service_providers
+                        .iter()
+                        .any(|deny_sp| is_same_party(sp, deny_sp))
+                })
+        });
+
+    let is_matching_license = deny_environment.licenses.is_empty()
+        || licenses
+            .iter()
+            .any(|l| deny_environment.licenses.contains(l));
+
+    is_matching_service_provider && is_matching_license
+}
+
+// a permit rule only permits when all of its conditions are met and a deny rule only denies
+// when all of its conditions are met
+pub fn is_permit(
+    policy: &Policy,
+    matching_row: &MatchingPolicySetRow,
+    context: &EvaluationContext,
+) -> bool {
+    evaluate_policy_set(policy, matching_row, context).permit
+}
+
+// the outcome of a single rule of a policy that matches the requested policy. a permit rule
+// applies when its conditions are met, a deny rule applies when it matches the request
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleEvaluation {
+    pub policy_id: Uuid,
+    pub effect: String,
+    pub applies: bool,
+    pub reason: String,
+    #[serde(flatten)]
+    pub obligations: PermitObligations,
+}
 
-    let permit = matching_policies.all(|matching_policy| {
-        matching_policy.rules.iter().all(|r| match r {
-            ResourceRule::Permit => true,
-            ResourceRule::Deny(t) => {
-                !(star_or_contained_by(
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicySetEvaluation {
+    pub policy_set_id: Uuid,
+    pub matching_policy_ids: Vec<Uuid>,
+    pub rules: Vec<RuleEvaluation>,
+    pub permit: bool,
+}
+
+fn evaluate_rule(
+    policy: &Policy,
+    matching_row: &MatchingPolicySetRow,
+    matching_policy: &DelegationEvidencePolicy,
+    rule: &ResourceRule,
+    context: &EvaluationContext,
+) -> RuleEvaluation {
+    let unmet_condition =
+        |conditions: &Vec<Condition>, is_met: fn(&Condition, &EvaluationContext) -> bool| {
+            conditions
+                .iter()
+                .find(|c| !is_met(c, context))
+                .map(|c| serde_json::to_string(c).unwrap_or_default())
+        };
+
+    let mut obligations = PermitObligations::default();
+    let (effect, (applies, reason)) = match rule {
+        ResourceRule::Permit(p) => (
+            "Permit",
+            match unmet_condition(&p.conditions, is_condition_met) {
+                Some(condition) => (false, format!("condition not met: {}", condition)),
+                None => {
+                    obligations.add(p);
+                    (true, "permit applies".to_owned())
+                }
+            },
+        ),
+        ResourceRule::Deny(t) => {
+            let is_matching_target =
+                star_or_matched_by(
                     &policy.target.resource.identifiers,
                     &t.target.resource.identifiers,
-                ) && star_or_contained_by(
+                ) && star_or_matched_by(
                     &policy.target.resource.attributes,
                     &t.target.resource.attributes,
                 ) && star_or_contained_by(&policy.target.actions, &t.target.actions)
-                    && &policy.target.resource.resource_type == &t.target.resource.resource_type)
+                    && &policy.target.resource.resource_type == &t.target.resource.resource_type;
+
+            (
+                "Deny",
+                if !is_matching_target {
+                    (
+                        false,
+                        "deny does not match the requested resource or actions".to_owned(),
+                    )
+                } else if !is_matching_deny_environment(
+                    policy,
+                    match context.required_licenses.is_empty() {
+                        true => &matching_row.licenses,
+                        false => &context.required_licenses,
+                    },
+                    &t.target.environment,
+                ) {
+                    (
+                        false,
+                        "deny does not match the service providers or licenses".to_owned(),
+                    )
+                } else if let Some(condition) =
+                    unmet_condition(&t.conditions, is_deny_condition_met)
+                {
+                    (false, format!("condition not met: {}", condition))
+                } else {
+                    (
+                        true,
+                        "deny matches the requested resource and actions".to_owned(),
+                    )
+                },
+            )
+        }
+    };
+
+    RuleEvaluation {
+        policy_id: matching_policy.id,
+        effect: effect.to_owned(),
+        applies,
+        reason,
+        obligations,
+    }
+}
+
+// evaluates the rules of every policy in the policy set that matches the requested policy. the
+// policy set permits when all permit rules and none of the deny rules apply
+pub fn evaluate_policy_set(
+    policy: &Policy,
+    matching_row: &MatchingPolicySetRow,
+    context: &EvaluationContext,
+) -> PolicySetEvaluation {
+    let matching_policies: Vec<&DelegationEvidencePolicy> = matching_row
+        .policies
+        .iter()
+        .filter(|mp| is_matching_policy(policy, mp))
+        .collect();
+
+    let rules: Vec<RuleEvaluation> = matching_policies
+        .iter()
+        .flat_map(|mp| {
+            mp.rules
+                .iter()
+                .map(|r| evaluate_rule(policy, matching_row, mp, r, context))
+        })
+        .collect();
+
+    let permit = rules.iter().all(|r| {
+        if r.effect == "Permit" {
+            r.applies
+        } else {
+            !r.applies
+        }
+    });
+
+    PolicySetEvaluation {
+        policy_set_id: matching_row.policy_set_id,
+        matching_policy_ids: matching_policies.iter().map(|mp| mp.id).collect(),
+        rules,
+        permit,
+    }
+}
+
+// a delegation chain is an ordered list of policy sets where the first set is issued by the
+// policy issuer, every next set is issued by the access subject of the previous set and the
+// last set is issued to the access subject of the delegation request.
+pub type DelegationChain<'a> = Vec<&'a MatchingPolicySetRow>;
+
+// the maximum number of policy sets in a delegation chain
+pub const MAX_DELEGATION_CHAIN_LENGTH: i32 = 5;
+
+// returns the delegation depth that is left at the end of the chain or None when one of the
+// policy sets in the chain does not allow the number of delegations that follow it
+pub fn remaining_delegation_depth(chain: &[&MatchingPolicySetRow]) -> Option<i32> {
+    chain
+        .iter()
+        .enumerate()
+        .map(|(index, ps)| ps.max_delegation_depth - (chain.len() - 1 - index) as i32)
+        .try_fold(i32::MAX, |remaining, depth| {
+            if depth < 0 {
+                None
+            } else {
+                Some(remaining.min(depth))
+            }
+        })
+}
+
+// `visited` holds the policy issuer and the access subjects of every hop on the chain, the
+// chain is never extended to a party that is already on it
+fn extend_delegation_chains<'a>(
+    chain: &mut DelegationChain<'a>,
+    visited: &mut Vec<&'a str>,
+    party: &str,
+    access_subject: &str,
+    delegated_policy_sets: &'a Vec<MatchingPolicySetRow>,
+    chains: &mut Vec<DelegationChain<'a>>,
+) {
+    if chain.len() >= MAX_DELEGATION_CHAIN_LENGTH as usize {
+        return;
+    }
+
+    for ps in delegated_policy_sets
+        .iter()
+        .filter(|ps| is_same_party(&ps.policy_issuer, party))
+    {
+        // never visit a party twice
+        if chain
+            .iter()
+            .any(|hop| hop.policy_set_id == ps.policy_set_id)
+            || visited
+                .iter()
+                .any(|visited_party| is_same_party(visited_party, &ps.access_subject))
+        {
+            continue;
+        }
+
+        chain.push(ps);
+        visited.push(&ps.access_subject);
+
+        if is_issued_to(ps, access_subject) {
+            // a chain of length one is a direct grant, those are resolved separately
+            if chain.len() > 1 && remaining_delegation_depth(chain).is_some() {
+                chains.push(chain.clone());
             }
+        } else if chain
+            .iter()
+            .enumerate()
+            .all(|(index, hop)| hop.max_delegation_depth >= (chain.len() - index) as i32)
+        {
+            // the members of a party group the policy set is issued to delegate further themselves
+            for next_party in std::iter::once(&ps.access_subject).chain(&ps.access_subject_members)
+            {
+                extend_delegation_chains(
+                    chain,
+                    visited,
+                    next_party,
+                    access_subject,
+                    delegated_policy_sets,
+                    chains,
+                );
+            }
+        }
+
+        visited.pop();
+        chain.pop();
+    }
+}
+
+// like the licenses in the evidence, the licenses of a chain are the ones granted to the access
+// subject by the last hop
+pub fn grants_licenses(chain: &DelegationChain, licenses: &Vec<String>) -> bool {
+    chain
+        .last()
+        .is_some_and(|ps| licenses.iter().all(|l| ps.licenses.contains(l)))
+}
+
+// resolves all delegation chains from the policy issuer to the access subject. The direct
+// policy sets are chains of length one, the delegated policy sets are used to find the
+// chains that go through one or more intermediate parties.
+pub fn resolve_delegation_chains<'a>(
+    policy_issuer: &'a str,
+    access_subject: &str,
+    direct_policy_sets: &'a Vec<MatchingPolicySetRow>,
+    delegated_policy_sets: &'a Vec<MatchingPolicySetRow>,
+) -> Vec<DelegationChain<'a>> {
+    let mut chains: Vec<DelegationChain> = direct_policy_sets.iter().map(|ps| vec![ps]).collect();
+
+    extend_delegation_chains(
+        &mut vec![],
+        &mut vec![policy_issuer],
+        policy_issuer,
+        access_subject,
+        delegated_policy_sets,
+        &mut chains,
+    );
+
+    chains
+}
+
+pub fn is_matching_chain(policy_set: &PolicySet, chain: &DelegationChain) -> bool {
+    mask_matching_policy_sets(policy_set, chain.iter().copied()).len() == chain.len()
+}
+
+pub fn mask_matching_chains<'a, 'b>(
+    policy_set: &PolicySet,
+    chains: &'b Vec<DelegationChain<'a>>,
+) -> Vec<&'b DelegationChain<'a>> {
+    chains
+        .iter()
+        .filter(|chain| is_matching_chain(policy_set, chain))
+        .collect()
+}
+
+// the delegation evidence can't outlive the policy sets it is based on, returns the earliest
+// end of the validity windows of the chains that match the delegation request
+pub fn get_grant_end(
+    delegation_request: &DelegationRequest,
+    chains: &Vec<DelegationChain>,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    chains
+        .iter()
+        .filter(|chain| {
+            delegation_request
+                .policy_sets
+                .iter()
+                .any(|ps| is_matching_chain(ps, chain))
+        })
+        .flat_map(|chain| chain.iter().filter_map(|ps| ps.not_on_or_after))
+        .min()
+}
+
+// a chain only permits when every policy set in the chain permits
+pub fn is_chain_permit(
+    policy: &Policy,
+    chain: &DelegationChain,
+    context: &EvaluationContext,
+) -> bool {
+    chain.iter().all(|ps| is_permit(policy, ps, context))
+}
+
+// the obligations and advice of the permit rules that apply in every hop of the chain
+pub fn get_chain_obligations(
+    policy: &Policy,
+    chain: &DelegationChain,
+    context: &EvaluationContext,
+) -> PermitObligations {
+    let mut obligations = PermitObligations::default();
+    let permits = chain
+        .iter()
+        .flat_map(|ps| ps.policies.iter())
+        .filter(|mp| is_matching_policy(policy, mp))
+        .flat_map(|mp| mp.rules.iter())
+        .filter_map(|r| match r {
+            ResourceRule::Permit(p) => Some(p),
+            ResourceRule::Deny(_) => None,
+        })
+        .filter(|p| p.conditions.iter().all(|c| is_condition_met(c, context)));
+
+    for permit in permits {
+        obligations.add(permit);
+    }
+
+    obligations
+}
+
+fn get_policy_obligations(
+    policy_set: &PolicySet,
+    effects: &Vec<bool>,
+    chain: &DelegationChain,
+    context: &EvaluationContext,
+) -> Vec<PermitObligations> {
+    policy_set
+        .policies
+        .iter()
+        .zip(effects.iter())
+        .map(|(p, permit)| match permit {
+            true => get_chain_obligations(p, chain, context),
+            false => PermitObligations::default(),
         })
+        .collect()
+}
+
+fn to_evidence_policy(policy: &Policy, permit: bool) -> ishare::delegation_evidence::Policy {
+    ishare::delegation_evidence::Policy {
+        target: ResourceTarget {
+            actions: policy.target.actions.clone(),
+            environment: match policy.target.environment.as_ref() {
+                Some(e) => Some(ishare::delegation_evidence::Environment {
+                    service_providers: e.service_providers.clone(),
+                }),
+                None => None,
+            },
+            resource: Resource {
+                resource_type: policy.target.resource.resource_type.clone(),
+                identifiers: policy.target.resource.identifiers.clone(),
+                attributes: policy.target.resource.attributes.clone(),
+            },
+        },
+        rules: vec![ResourceRules {
+            effect: effect_name(permit),
+        }],
+    }
+}
+
+fn to_evidence_policy_set(
+    policy_set: &PolicySet,
+    effects: Vec<bool>,
+    chains: &[&DelegationChain],
+) -> ishare::delegation_evidence::PolicySet {
+    let policies = policy_set
+        .policies
+        .iter()
+        .zip(effects)
+        .map(|(p, permit)| to_evidence_policy(p, permit))
+        .collect();
+
+    // the evidence can't be delegated further, or grant more licenses, than any of the chains it
+    // is built from. the licenses are the ones granted to the access subject by the last hop
+    let max_delegation_depth = chains
+        .iter()
+        .map(|chain| remaining_delegation_depth(chain).unwrap_or(0))
+        .min()
+        .unwrap_or(0);
+    let licenses = match chains.split_first() {
+        Some((first, rest)) => first
+            .last()
+            .map(|ps| ps.licenses.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|license| {
+                rest.iter()
+                    .all(|chain| chain.last().is_some_and(|ps| ps.licenses.contains(license)))
+            })
+            .collect(),
+        None => vec![],
+    };
+
+    ishare::delegation_evidence::PolicySet {
+        max_delegation_depth,
+        policies,
+        target: PolicySetTarget {
+            environment: PolicySetTargetEnvironment { licenses },
+        },
+    }
+}
+
+// merges the effects of the matching chains for every policy of the requested policy set. returns
+// the merged effects, the chain whose effects equal the merged ones, the oldest contributing chain
+// when there is none, and the chains that contribute to the permitted policies
+pub fn combine_matching_chains<'a, 'b>(
+    algorithm: CombiningAlgorithm,
+    policy_set: &PolicySet,
+    matching_chains: &Vec<&'b DelegationChain<'a>>,
+    context: &EvaluationContext,
+) -> (
+    Vec<bool>,
+    &'b DelegationChain<'a>,
+    Vec<&'b DelegationChain<'a>>,
+) {
+    // policy sets without a creation date, the hypothetical ones of a simulation, are the newest
+    let mut chains = matching_chains.clone();
+    chains.sort_by_key(|chain| {
+        (
+            chain[0].created.is_none(),
+            chain[0].created,
+            chain[0].policy_set_id,
+        )
     });
 
-    return permit;
+    let chain_effects: Vec<Vec<bool>> = chains
+        .iter()
+        .map(|chain| {
+            policy_set
+                .policies
+                .iter()
+                .map(|p| is_chain_permit(p, chain, context))
+                .collect()
+        })
+        .collect();
+
+    let effects: Vec<bool> = match algorithm {
+        CombiningAlgorithm::FirstApplicable => chain_effects[0].clone(),
+        CombiningAlgorithm::DenyOverrides => (0..policy_set.policies.len())
+            .map(|i| chain_effects.iter().all(|e| e[i]))
+            .collect(),
+        CombiningAlgorithm::PermitOverrides => (0..policy_set.policies.len())
+            .map(|i| chain_effects.iter().any(|e| e[i]))
+            .collect(),
+    };
+
+    // only the first chain decides with first applicable, otherwise every chain that permits one
+    // of the permitted policies contributes to the evidence
+    let contributing: Vec<usize> = match algorithm {
+        CombiningAlgorithm::FirstApplicable => vec![0],
+        _ => (0..chains.len())
+            .filter(|c| (0..effects.len()).any(|i| effects[i] && chain_effects[*c][i]))
+            .collect(),
+    };
+    let contributing = match contributing.is_empty() {
+        true => vec![0],
+        false => contributing,
+    };
+
+    let deciding = chain_effects
+        .iter()
+        .position(|e| *e == effects)
+        .unwrap_or(contributing[0]);
+
+    (
+        effects,
+        chains[deciding],
+        contributing.into_iter().map(|c| chains[c]).collect(),
+    )
 }
 
-pub fn get_delegation_evidence_policy_sets(
+// the policy sets of the delegation evidence with the obligations of each of their policies
+fn evaluate_delegation_evidence_policy_sets(
     delegation_request: &DelegationRequest,
-    matching_policy_sets: &Vec<MatchingPolicySetRow>,
-) -> Vec<ishare::delegation_evidence::PolicySet> {
+    chains: &Vec<DelegationChain>,
+    context: &EvaluationContext,
+) -> Vec<(
+    ishare::delegation_evidence::PolicySet,
+    Vec<PermitObligations>,
+)> {
     let mut policy_sets = vec![];
     for ps in delegation_request.policy_sets.iter() {
-        let matching_policy_sets = mask_matching_policy_sets(ps, &matching_policy_sets);
+        let matching_chains = mask_matching_chains(ps, chains);
+
+        if matching_chains.is_empty() {
+            policy_sets.push((
+                to_evidence_policy_set(ps, vec![false; ps.policies.len()], &[]),
+                vec![PermitObligations::default(); ps.policies.len()],
+            ));
+            continue;
+        }
 
-        if matching_policy_sets.len() > 0 {
-            for matching in matching_policy_sets.into_iter() {
-                let policies: Vec<ishare::delegation_evidence::Policy> = ps
-                    .policies
-                    .iter()
-                    .map(|p| {
-                        let permit = is_permit(p, matching);
+        match context.combining_algorithm {
+            Some(algorithm) => {
+                let (effects, chain, contributing) =
+                    combine_matching_chains(algorithm, ps, &matching_chains, context);
+                let obligations = get_policy_obligations(ps, &effects, chain, context);
+                policy_sets.push((
+                    to_evidence_policy_set(ps, effects, &contributing),
+                    obligations,
+                ));
+            }
+            None => {
+                for chain in matching_chains.into_iter() {
+                    let effects = ps
+                        .policies
+                        .iter()
+                        .map(|p| is_chain_permit(p, chain, context))
+                        .collect();
+                    let obligations = get_policy_obligations(ps, &effects, chain, context);
+                    policy_sets.push((to_evidence_policy_set(ps, effects, &[chain]), obligations));
+                }
+            }
+        }
+    }
 
-                        ishare::delegation_evidence::Policy {
-                            target: ResourceTarget {
-                                actions: p.target.actions.clone(),
-                                environment: match p.target.environment.as_ref() {
-                                    Some(e) => Some(ishare::delegation_evidence::Environment {
-                                        service_providers: e.service_providers.clone(),
-                                    }),
-                                    None => None,
-                                },
-                                resource: Resource {
-                                    resource_type: p.target.resource.resource_type.clone(),
-                                    identifiers: p.target.resource.identifiers.clone(),
-                                    attributes: p.target.resource.attributes.clone(),
-                                },
-                            },
-                            rules: vec![ResourceRules {
-                                effect: if permit {
-                                    "Permit".to_string()
-                                } else {
-                                    "Deny".to_string()
-                                },
-                            }],
-                        }
-                    })
-                    .collect();
-
-                let new_policy_set = ishare::delegation_evidence::PolicySet {
-                    max_delegation_depth: matching.max_delegation_depth,
-                    policies,
-                    target: PolicySetTarget {
-                        environment: PolicySetTargetEnvironment {
-                            licenses: matching.licenses.clone(),
-                        },
-                    },
-                };
+    policy_sets
+}
 
-                policy_sets.push(new_policy_set);
-            }
-        } else {
-            let policies: Vec<ishare::delegation_evidence::Policy> = ps
-                .policies
+pub fn get_delegation_evidence_policy_sets(
+    delegation_request: &DelegationRequest,
+    chains: &Vec<DelegationChain>,
+    context: &EvaluationContext,
+) -> Vec<ishare::delegation_evidence::PolicySet> {
+    evaluate_delegation_evidence_policy_sets(delegation_request, chains, context)
+        .into_iter()
+        .map(|(ps, _)| ps)
+        .collect()
+}
+
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ChainTrace {
+    pub policy_set_ids: Vec<Uuid>,
+    pub hops: Vec<PolicySetEvaluation>,
+    pub effect: String,
+}
+
+// explains the effect of a single policy of the delegation request
+#[derive(Serialize, Deserialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyTrace {
+    pub policy_set_index: usize,
+    pub policy_index: usize,
+    pub resource_type: String,
+    pub candidate_policy_set_ids: Vec<Uuid>,
+    pub matching_chains: Vec<ChainTrace>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub combining_algorithm: Option<CombiningAlgorithm>,
+    pub effect: String,
+    pub reason: String,
+    #[serde(flatten)]
+    pub obligations: PermitObligations,
+}
+
+fn effect_name(permit: bool) -> String {
+    if permit {
+        "Permit".to_owned()
+    } else {
+        "Deny".to_owned()
+    }
+}
+
+pub fn get_delegation_trace(
+    delegation_request: &DelegationRequest,
+    candidate_policy_set_ids: &Vec<Uuid>,
+    chains: &Vec<DelegationChain>,
+    context: &EvaluationContext,
+) -> Vec<PolicyTrace> {
+    let mut traces = vec![];
+    for (policy_set_index, ps) in delegation_request.policy_sets.iter().enumerate() {
+        let matching_chains = mask_matching_chains(ps, chains);
+        let combined = match context.combining_algorithm {
+            Some(algorithm) if !matching_chains.is_empty() => Some(combine_matching_chains(
+                algorithm,
+                ps,
+                &matching_chains,
+                context,
+            )),
+            _ => None,
+        };
+
+        for (policy_index, p) in ps.policies.iter().enumerate() {
+            let chain_traces: Vec<ChainTrace> = matching_chains
                 .iter()
-                .map(|p| ishare::delegation_evidence::Policy {
-                    target: ResourceTarget {
-                        actions: p.target.actions.clone(),
-                        environment: match p.target.environment.as_ref() {
-                            Some(e) => Some(ishare::delegation_evidence::Environment {
-                                service_providers: e.service_providers.clone(),
-                            }),
-                            None => None,
-                        },
-                        resource: Resource {
-                            resource_type: p.target.resource.resource_type.clone(),
-                            identifiers: p.target.resource.identifiers.clone(),
-                            attributes: p.target.resource.attributes.clone(),
-                        },
-                    },
-                    rules: vec![ResourceRules {
-                        effect: "Deny".to_string(),
-                    }],
+                .map(|chain| {
+                    let hops: Vec<PolicySetEvaluation> = chain
+                        .iter()
+                        .map(|row| evaluate_policy_set(p, row, context))
+                        .collect();
+                    let permit = hops.iter().all(|h| h.permit);
+
+                    ChainTrace {
+                        policy_set_ids: chain.iter().map(|row| row.policy_set_id).collect(),
+                        hops,
+                        effect: effect_name(permit),
+                    }
                 })
                 .collect();
 
-            let new_policy_set = ishare::delegation_evidence::PolicySet {
-                max_delegation_depth: 0,
-                policies,
-                target: PolicySetTarget {
-                    environment: PolicySetTargetEnvironment { licenses: vec![] },
-                },
+            let permitting_chain = match &combined {
+                Some((effects, chain, _)) => effects[policy_index].then_some(*chain),
+                None => matching_chains
+                    .iter()
+                    .zip(chain_traces.iter())
+                    .find(|(_, c)| c.effect == "Permit")
+                    .map(|(chain, _)| *chain),
+            };
+            let permitting_policy_set_ids = permitting_chain.map(|chain| {
+                chain
+                    .iter()
+                    .map(|row| row.policy_set_id)
+                    .collect::<Vec<_>>()
+            });
+
+            let reason = if let Some(policy_set_ids) = &permitting_policy_set_ids {
+                format!("permitted by policy set(s) {:?}", policy_set_ids)
+            } else if candidate_policy_set_ids.is_empty() {
+                format!(
+                    "no valid policy sets issued by '{}' to '{}', directly or through delegation",
+                    delegation_request.policy_issuer, delegation_request.target.access_subject
+                )
+            } else if chain_traces.is_empty() && !context.required_licenses.is_empty() {
+                format!(
+                    "none of the candidate policy sets granting the licenses {:?} has policies matching every policy of the requested policy set",
+                    context.required_licenses
+                )
+            } else if chain_traces.is_empty() {
+                "none of the candidate policy sets has policies matching every policy of the requested policy set".to_owned()
+            } else {
+                let rule = chain_traces
+                    .iter()
+                    .flat_map(|c| c.hops.iter())
+                    .flat_map(|h| h.rules.iter())
+                    .find(|r| {
+                        if r.effect == "Permit" {
+                            !r.applies
+                        } else {
+                            r.applies
+                        }
+                    });
+
+                match rule {
+                    Some(r) => format!(
+                        "{} rule of policy {} decided: {}",
+                        r.effect, r.policy_id, r.reason
+                    ),
+                    None => "denied by every matching policy set".to_owned(),
+                }
             };
 
-            policy_sets.push(new_policy_set);
+            traces.push(PolicyTrace {
+                policy_set_index,
+                policy_index,
+                resource_type: p.target.resource.resource_type.clone(),
+                candidate_policy_set_ids: candidate_policy_set_ids.clone(),
+                effect: effect_name(permitting_policy_set_ids.is_some()),
+                matching_chains: chain_traces,
+                combining_algorithm: context.combining_algorithm,
+                reason,
+                obligations: permitting_chain
+                    .map(|chain| get_chain_obligations(p, chain, context))
+                    .unwrap_or_default(),
+            });
         }
     }
 
-    return policy_sets;
+    traces
+}
+
+// explains the delegation request with the delegation chains its evidence is built from. only the
+// policy sets on a chain between the policy issuer and the access subject are candidates
+fn explain_delegation_chains(
+    delegation_request: &DelegationRequest,
+    chains: &Vec<DelegationChain>,
+    context: &EvaluationContext,
+) -> Vec<PolicyTrace> {
+    let mut candidate_policy_set_ids: Vec<Uuid> =
+        chains.iter().flatten().map(|ps| ps.policy_set_id).collect();
+    candidate_policy_set_ids.sort();
+    candidate_policy_set_ids.dedup();
+
+    get_delegation_trace(
+        delegation_request,
+        &candidate_policy_set_ids,
+        &licensed_chains(chains, context),
+        context,
+    )
 }
 
-pub fn check_delegation_access(
+// a requester that is not a party of the delegation request gets access through a chain of
+// previous steps, a chain that doesn't verify is rejected with the reason
+pub async fn check_delegation_access(
     now: chrono::DateTime<chrono::Utc>,
     requester_company_id: &str,
     delegation_request: &DelegationRequest,
     previous_steps: &Option<Vec<String>>,
     allows_service_provider_access: bool,
     satellite_provider: Arc<dyn SatelliteProvider>,
-) -> bool {
+    previous_steps_verifier: &PreviousStepsVerifier,
+) -> Result<bool, AppError> {
     tracing::info!("checking if requester is policy issuer or access subject");
 
-    if requester_company_id == delegation_request.target.access_subject {
+    if is_same_party(
+        requester_company_id,
+        &delegation_request.target.access_subject,
+    ) {
         tracing::info!("requester company is access subject. access allowed.");
-        return true;
+        return Ok(true);
     }
 
-    if requester_company_id == delegation_request.policy_issuer {
+    if is_same_party(requester_company_id, &delegation_request.policy_issuer) {
         tracing::info!("requester company is policy issuer. access allows.");
-        return true;
+        return Ok(true);
     }
 
     if allows_service_provider_access {
@@ -210,41 +874,123 @@ pub fn check_delegation_access(
         if service_providers.len() > 0
             && service_providers
                 .iter()
-                .all(|sp| sp == requester_company_id)
+                .all(|sp| is_same_party(sp, requester_company_id))
         {
-            return true;
+            return Ok(true);
         }
     }
 
     tracing::info!("checking if previous steps gives access");
-    if let Some(previous_steps) = previous_steps {
-        match previous_steps.get(0) {
-            None => {
-                tracing::info!("previous steps is empty");
-            }
-            Some(previous_step_client_assertion) => {
-                if satellite_provider.handle_previous_step_client_assertion(
+    match previous_steps {
+        Some(previous_steps) if !previous_steps.is_empty() => {
+            previous_steps_verifier
+                .verify(
                     now,
                     requester_company_id,
-                    &previous_step_client_assertion,
-                    &delegation_request.policy_issuer,
-                    &delegation_request.target.access_subject,
-                ) {
-                    return true;
-                }
-            }
-        };
+                    delegation_request,
+                    previous_steps,
+                    satellite_provider,
+                )
+                .await?;
+            tracing::info!("previous steps verified. access allowed.");
+            Ok(true)
+        }
+        _ => Ok(false),
     }
+}
 
-    false
+// what is known about the access subject of a delegation request besides its identifier
+#[derive(Clone, Copy, Default)]
+pub struct AccessSubjectDetails<'a> {
+    // the satellite registration of the access subject
+    pub party_info: Option<&'a PartyInfo>,
+    // the satellite the roles, certifications and dataspaces of the access subject are fetched
+    // from when a policy set depends on them
+    pub satellite_provider: Option<&'a dyn SatelliteProvider>,
+    // the user of the access subject the delegation request is made for
+    pub user: Option<&'a str>,
 }
 
-pub async fn create_delegation_evidence(
+// the roles, certifications and dataspaces of the access subject are only fetched from the
+// satellite when one of the attributes depends on them. without a registration the attributes
+// that depend on it don't match
+async fn get_access_subject_registration<'a>(
+    now: chrono::DateTime<chrono::Utc>,
+    access_subject: &str,
+    subject_details: AccessSubjectDetails<'_>,
+    mut attributes: impl Iterator<Item = &'a PartyAttributes>,
+) -> Option<PartyRegistration> {
+    if !attributes.any(requires_registration) {
+        return None;
+    }
+
+    let satellite_provider = subject_details.satellite_provider?;
+    match satellite_provider
+        .get_party_registration(now, access_subject)
+        .await
+    {
+        Ok(registration) => Some(registration),
+        Err(e) => {
+            tracing::warn!(
+                "unable to get the registration of access subject '{}': {:?}",
+                access_subject,
+                e
+            );
+            None
+        }
+    }
+}
+
+fn attributes_of<'a>(
+    policy_sets: impl Iterator<Item = &'a MatchingPolicySetRow>,
+) -> impl Iterator<Item = &'a PartyAttributes> {
+    policy_sets.filter_map(|ps| ps.access_subject_attributes.as_ref())
+}
+
+// policy sets with access subject attributes apply to the access subject when its satellite
+// registration has the attributes, from then on they are issued to the access subject. without
+// the registration of the access subject they don't apply. policy sets issued to a user of the
+// access subject only apply to requests made for that user
+fn resolve_access_subjects(
+    now: chrono::DateTime<chrono::Utc>,
+    access_subject: &str,
+    subject_details: AccessSubjectDetails<'_>,
+    registration: Option<&PartyRegistration>,
+    policy_sets: Vec<MatchingPolicySetRow>,
+) -> Vec<MatchingPolicySetRow> {
+    policy_sets
+        .into_iter()
+        .filter(|ps| {
+            ps.access_subject_user
+                .as_ref()
+                .is_none_or(|user| subject_details.user == Some(user.as_str()))
+        })
+        .filter_map(|mut ps| {
+            let Some(attributes) = &ps.access_subject_attributes else {
+                return Some(ps);
+            };
+
+            if !subject_details
+                .party_info
+                .is_some_and(|info| is_matching_party(attributes, info, registration, now))
+            {
+                return None;
+            }
+
+            ps.access_subject = normalize_party_id(access_subject);
+            Some(ps)
+        })
+        .collect()
+}
+
+// returns the policy sets issued by the policy issuer to the access subject and the policy sets
+// that can be part of a delegation chain between them
+async fn get_candidate_policy_sets(
+    now: chrono::DateTime<chrono::Utc>,
     delegation_request: &DelegationRequest,
-    time_provider: std::sync::Arc<dyn TimeProvider>,
-    de_expiry_seconds: i64,
+    subject_details: AccessSubjectDetails<'_>,
     db: &DatabaseConnection,
-) -> Result<DelegationEvidenceContainer, AppError> {
+) -> Result<(Vec<MatchingPolicySetRow>, Vec<MatchingPolicySetRow>), AppError> {
     tracing::info!(
         "Retrieving policy sets for access subject '{}' and policy issuer '{}'",
         &delegation_request.target.access_subject,
@@ -252,6 +998,7 @@ pub async fn create_delegation_evidence(
     );
 
     let de_policy_sets = policy_store::get_policy_sets_with_policies_for_creating_de(
+        now,
         delegation_request.target.access_subject.to_owned(),
         delegation_request.policy_issuer.to_owned(),
         &db,
@@ -259,26 +1006,864 @@ pub async fn create_delegation_evidence(
     .await
     .context("Error getting policy sets")?;
 
-    let policy_sets = get_delegation_evidence_policy_sets(delegation_request, &de_policy_sets);
-    let now = time_provider.now().timestamp();
-    let de_container = DelegationEvidenceContainer {
-        delegation_evidence: DelegationEvidence {
-            not_before: now,
-            not_on_or_after: now + de_expiry_seconds,
-            policy_issuer: delegation_request.policy_issuer.clone(),
-            target: DelegationTarget {
-                access_subject: delegation_request.target.access_subject.clone(),
+    let delegated_policy_sets = policy_store::get_policy_sets_with_policies_for_delegation_chains(
+        now,
+        delegation_request.target.access_subject.to_owned(),
+        delegation_request.policy_issuer.to_owned(),
+        MAX_DELEGATION_CHAIN_LENGTH,
+        &db,
+    )
+    .await
+    .context("Error getting delegated policy sets")?;
+
+    let access_subject = &delegation_request.target.access_subject;
+    let registration = get_access_subject_registration(
+        now,
+        access_subject,
+        subject_details,
+        attributes_of(de_policy_sets.iter().chain(delegated_policy_sets.iter())),
+    )
+    .await;
+
+    Ok((
+        resolve_access_subjects(
+            now,
+            access_subject,
+            subject_details,
+            registration.as_ref(),
+            de_policy_sets,
+        ),
+        resolve_access_subjects(
+            now,
+            access_subject,
+            subject_details,
+            registration.as_ref(),
+            delegated_policy_sets,
+        ),
+    ))
+}
+
+// delegation evidence with the obligations and advice of its policies, indexed like the policy
+// sets and policies of the evidence. they are added to the rules when the evidence is serialized.
+// evidence requested for a user of the access subject stays issued to the access subject, the
+// user is added to the target as 'accessSubjectUser' next to it
+pub struct IssuedDelegationEvidence {
+    pub container: DelegationEvidenceContainer,
+    pub obligations: Vec<Vec<PermitObligations>>,
+    pub access_subject_user: Option<String>,
+}
+
+impl Serialize for IssuedDelegationEvidence {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut container =
+            serde_json::to_value(&self.container).map_err(serde::ser::Error::custom)?;
+        add_obligations_to_evidence(&mut container, &self.obligations);
+        if let (Some(user), Some(target)) = (
+            &self.access_subject_user,
+            container
+                .pointer_mut("/delegationEvidence/target")
+                .and_then(|target| target.as_object_mut()),
+        ) {
+            target.insert("accessSubjectUser".to_owned(), user.clone().into());
+        }
+        container.serialize(serializer)
+    }
+}
+
+// the delegation chains that grant the licenses the delegation request requires
+fn licensed_chains<'a>(
+    chains: &Vec<DelegationChain<'a>>,
+    context: &EvaluationContext,
+) -> Vec<DelegationChain<'a>> {
+    chains
+        .iter()
+        .filter(|chain| grants_licenses(chain, &context.required_licenses))
+        .cloned()
+        .collect()
+}
+
+// builds the delegation evidence from the policy sets that are valid at the time of the context
+pub fn build_delegation_evidence(
+    delegation_request: &DelegationRequest,
+    access_subject_user: Option<&str>,
+    de_policy_sets: &Vec<MatchingPolicySetRow>,
+    delegated_policy_sets: &Vec<MatchingPolicySetRow>,
+    context: &EvaluationContext,
+    de_expiry_seconds: i64,
+) -> IssuedDelegationEvidence {
+    let chains = resolve_delegation_chains(
+        &delegation_request.policy_issuer,
+        &delegation_request.target.access_subject,
+        de_policy_sets,
+        delegated_policy_sets,
+    );
+
+    build_delegation_evidence_from_chains(
+        delegation_request,
+        access_subject_user,
+        &licensed_chains(&chains, context),
+        context,
+        de_expiry_seconds,
+    )
+}
+
+fn build_delegation_evidence_from_chains(
+    delegation_request: &DelegationRequest,
+    access_subject_user: Option<&str>,
+    chains: &Vec<DelegationChain>,
+    context: &EvaluationContext,
+    de_expiry_seconds: i64,
+) -> IssuedDelegationEvidence {
+    tracing::info!("Resolved {} delegation chain(s)", chains.len());
+
+    let now = context.now;
+    let (policy_sets, obligations) =
+        evaluate_delegation_evidence_policy_sets(delegation_request, chains, context)
+            .into_iter()
+            .unzip();
+    let not_on_or_after = match get_grant_end(delegation_request, chains) {
+        Some(grant_end) => (now.timestamp() + de_expiry_seconds).min(grant_end.timestamp()),
+        None => now.timestamp() + de_expiry_seconds,
+    };
+
+    IssuedDelegationEvidence {
+        container: DelegationEvidenceContainer {
+            delegation_evidence: DelegationEvidence {
+                not_before: now.timestamp(),
+                not_on_or_after,
+                policy_issuer: delegation_request.policy_issuer.clone(),
+                target: DelegationTarget {
+                    access_subject: delegation_request.target.access_subject.clone(),
+                },
+                policy_sets,
             },
-            policy_sets,
         },
+        obligations,
+        access_subject_user: access_subject_user.map(|user| user.to_owned()),
+    }
+}
+
+pub async fn create_delegation_evidence(
+    delegation_request: &DelegationRequest,
+    subject_details: AccessSubjectDetails<'_>,
+    environment: &HashMap<String, String>,
+    required_licenses: &Vec<String>,
+    combining_algorithms: &CombiningAlgorithmConfig,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    de_expiry_seconds: i64,
+    db: &DatabaseConnection,
+) -> Result<IssuedDelegationEvidence, AppError> {
+    let now = time_provider.now();
+    let (de_policy_sets, delegated_policy_sets) =
+        get_candidate_policy_sets(now, delegation_request, subject_details, db).await?;
+
+    let context = EvaluationContext {
+        now,
+        environment: environment.clone(),
+        combining_algorithm: combining_algorithms
+            .for_policy_issuer(&delegation_request.policy_issuer),
+        required_licenses: required_licenses.clone(),
     };
 
-    Ok(de_container)
+    Ok(build_delegation_evidence(
+        delegation_request,
+        subject_details.user,
+        &de_policy_sets,
+        &delegated_policy_sets,
+        &context,
+        de_expiry_seconds,
+    ))
+}
+
+// the exact counterpart of the direct and delegated policy sets of a single delegation request,
+// taken from the policy sets of many policy issuers
+fn partition_candidate_policy_sets(
+    now: chrono::DateTime<chrono::Utc>,
+    delegation_request: &DelegationRequest,
+    subject_details: AccessSubjectDetails<'_>,
+    registration: Option<&PartyRegistration>,
+    policy_sets: &Vec<MatchingPolicySetRow>,
+) -> (Vec<MatchingPolicySetRow>, Vec<MatchingPolicySetRow>) {
+    let access_subject = &delegation_request.target.access_subject;
+    let policy_sets = resolve_access_subjects(
+        now,
+        access_subject,
+        subject_details,
+        registration,
+        policy_sets.clone(),
+    );
+
+    let direct = policy_sets
+        .iter()
+        .filter(|ps| {
+            is_same_party(&ps.policy_issuer, &delegation_request.policy_issuer)
+                && is_issued_to(ps, access_subject)
+        })
+        .cloned()
+        .collect();
+    let delegated = policy_sets
+        .iter()
+        .filter(|ps| ps.max_delegation_depth > 0 || is_issued_to(ps, access_subject))
+        .cloned()
+        .collect();
+
+    (direct, delegated)
+}
+
+// creates the delegation evidence of many delegation requests with a single query for the
+// policy sets of all of them
+pub async fn create_delegation_evidence_batch(
+    delegation_requests: &Vec<(
+        &DelegationRequest,
+        &HashMap<String, String>,
+        &Vec<String>,
+        AccessSubjectDetails<'_>,
+    )>,
+    combining_algorithms: &CombiningAlgorithmConfig,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    de_expiry_seconds: i64,
+    db: &DatabaseConnection,
+) -> Result<Vec<IssuedDelegationEvidence>, AppError> {
+    if delegation_requests.is_empty() {
+        return Ok(vec![]);
+    }
+
+    let now = time_provider.now();
+
+    let mut policy_issuers: Vec<String> = delegation_requests
+        .iter()
+        .map(|(dr, _, _, _)| normalize_party_id(&dr.policy_issuer))
+        .collect();
+    policy_issuers.sort();
+    policy_issuers.dedup();
+
+    let mut access_subjects: Vec<String> = delegation_requests
+        .iter()
+        .map(|(dr, _, _, _)| normalize_party_id(&dr.target.access_subject))
+        .collect();
+    access_subjects.sort();
+    access_subjects.dedup();
+
+    tracing::info!(
+        "Retrieving policy sets for {} delegation request(s) of {} policy issuer(s)",
+        delegation_requests.len(),
+        policy_issuers.len()
+    );
+
+    let policy_sets = policy_store::get_policy_sets_with_policies_for_policy_issuers(
+        now,
+        policy_issuers,
+        access_subjects,
+        MAX_DELEGATION_CHAIN_LENGTH,
+        &db,
+    )
+    .await
+    .context("Error getting policy sets of policy issuers")?;
+
+    let mut registrations: HashMap<String, Option<PartyRegistration>> = HashMap::new();
+    for (delegation_request, _, _, subject_details) in delegation_requests.iter() {
+        let access_subject = normalize_party_id(&delegation_request.target.access_subject);
+        if let std::collections::hash_map::Entry::Vacant(entry) =
+            registrations.entry(access_subject)
+        {
+            let registration = get_access_subject_registration(
+                now,
+                entry.key(),
+                *subject_details,
+                attributes_of(policy_sets.iter()),
+            )
+            .await;
+            entry.insert(registration);
+        }
+    }
+
+    Ok(delegation_requests
+        .iter()
+        .map(
+            |(delegation_request, environment, required_licenses, subject_details)| {
+                let (de_policy_sets, delegated_policy_sets) = partition_candidate_policy_sets(
+                    now,
+                    delegation_request,
+                    *subject_details,
+                    registrations
+                        .get(&normalize_party_id(
+                            &delegation_request.target.access_subject,
+                        ))
+                        .and_then(|registration| registration.as_ref()),
+                    &policy_sets,
+                );
+                let context = EvaluationContext {
+                    now,
+                    environment: (*environment).clone(),
+                    combining_algorithm: combining_algorithms
+                        .for_policy_issuer(&delegation_request.policy_issuer),
+                    required_licenses: (*required_licenses).clone(),
+                };
+
+                build_delegation_evidence(
+                    delegation_request,
+                    subject_details.user,
+                    &de_policy_sets,
+                    &delegated_policy_sets,
+                    &context,
+                    de_expiry_seconds,
+                )
+            },
+        )
+        .collect())
+}
+
+fn has_conditions(policy_set: &MatchingPolicySetRow) -> bool {
+    policy_set.policies.iter().any(|p| {
+        p.rules.iter().any(|r| match r {
+            ResourceRule::Permit(permit) => !permit.conditions.is_empty(),
+            ResourceRule::Deny(deny) => !deny.conditions.is_empty(),
+        })
+    })
+}
+
+// serves the evaluated policy sets from the decision cache with fresh timestamps. decisions that
+// depend on rule conditions or on the satellite registration of the access subject are evaluated
+// against every request and never cached. an explained decision is always evaluated, the trace is
+// built from the same delegation chains as the evidence
+pub async fn create_cached_delegation_evidence(
+    delegation_request: &DelegationRequest,
+    subject_details: AccessSubjectDetails<'_>,
+    environment: &HashMap<String, String>,
+    required_licenses: &Vec<String>,
+    combining_algorithms: &CombiningAlgorithmConfig,
+    explain: bool,
+    decision_cache: &DecisionCache,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    de_expiry_seconds: i64,
+    db: &DatabaseConnection,
+) -> Result<(IssuedDelegationEvidence, Option<Vec<PolicyTrace>>), AppError> {
+    let now = time_provider.now();
+    let key = DecisionCacheKey::new(delegation_request, subject_details.user, required_licenses);
+
+    if let Some(decision) = decision_cache.get(&key, now).filter(|_| !explain) {
+        tracing::info!("Serving delegation evidence from decision cache");
+
+        let not_on_or_after = match decision.grant_end {
+            Some(grant_end) => (now.timestamp() + de_expiry_seconds).min(grant_end),
+            None => now.timestamp() + de_expiry_seconds,
+        };
+
+        let evidence = IssuedDelegationEvidence {
+            container: DelegationEvidenceContainer {
+                delegation_evidence: DelegationEvidence {
+                    not_before: now.timestamp(),
+                    not_on_or_after,
+                    policy_issuer: delegation_request.policy_issuer.clone(),
+                    target: DelegationTarget {
+                        access_subject: delegation_request.target.access_subject.clone(),
+                    },
+                    policy_sets: decision.policy_sets,
+                },
+            },
+            obligations: decision.obligations,
+            access_subject_user: subject_details.user.map(|user| user.to_owned()),
+        };
+
+        return Ok((evidence, None));
+    }
+
+    let generation = decision_cache.generation();
+    let (de_policy_sets, delegated_policy_sets) =
+        get_candidate_policy_sets(now, delegation_request, subject_details, db).await?;
+
+    let context = EvaluationContext {
+        now,
+        environment: environment.clone(),
+        combining_algorithm: combining_algorithms
+            .for_policy_issuer(&delegation_request.policy_issuer),
+        required_licenses: required_licenses.clone(),
+    };
+
+    let chains = resolve_delegation_chains(
+        &delegation_request.policy_issuer,
+        &delegation_request.target.access_subject,
+        &de_policy_sets,
+        &delegated_policy_sets,
+    );
+    let evidence = build_delegation_evidence_from_chains(
+        delegation_request,
+        subject_details.user,
+        &licensed_chains(&chains, &context),
+        &context,
+        de_expiry_seconds,
+    );
+    let explanation =
+        explain.then(|| explain_delegation_chains(delegation_request, &chains, &context));
+
+    if !de_policy_sets
+        .iter()
+        .chain(delegated_policy_sets.iter())
+        .any(|ps| has_conditions(ps) || ps.access_subject_attributes.is_some())
+    {
+        let next_not_before = match decision_cache.is_enabled() {
+            true => policy_store::get_next_not_before_for_delegation(
+                now,
+                delegation_request.target.access_subject.to_owned(),
+                delegation_request.policy_issuer.to_owned(),
+                db,
+            )
+            .await
+            .context("Error getting next start of policy sets")?,
+            false => None,
+        };
+
+        decision_cache.insert(
+            key,
+            &evidence,
+            de_expiry_seconds,
+            next_not_before,
+            generation,
+            now,
+        );
+    }
+
+    Ok((evidence, explanation))
+}
+
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplacedPolicySet {
+    pub policy_set_id: Uuid,
+    #[serde(flatten)]
+    pub policy_set: InsertPolicySetWithPolicies,
+}
+
+// hypothetical changes to the live policy sets that a simulation is evaluated against
+#[derive(Deserialize, ToSchema, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicySetChanges {
+    #[serde(default)]
+    pub add: Vec<InsertPolicySetWithPolicies>,
+    #[serde(default)]
+    pub remove: Vec<Uuid>,
+    #[serde(default)]
+    pub replace: Vec<ReplacedPolicySet>,
+}
+
+fn invalid_policy_set_change(message: String) -> AppError {
+    AppError::Expected(ExpectedError {
+        status_code: StatusCode::BAD_REQUEST,
+        message: message.clone(),
+        reason: message,
+        metadata: None,
+    })
+}
+
+// the policy issuer can only simulate changes to its own policy sets, the policy sets of other
+// issuers can't be probed by removing or replacing them
+pub async fn validate_policy_set_changes(
+    policy_issuer: &str,
+    changes: &PolicySetChanges,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    for policy_set in changes
+        .add
+        .iter()
+        .chain(changes.replace.iter().map(|r| &r.policy_set))
+    {
+        if !is_same_party(&policy_set.policy_issuer, policy_issuer) {
+            return Err(invalid_policy_set_change(format!(
+                "hypothetical policy set of '{}' is not issued by the policy issuer",
+                policy_set.policy_issuer
+            )));
+        }
+    }
+
+    for policy_set_id in changes
+        .remove
+        .iter()
+        .chain(changes.replace.iter().map(|r| &r.policy_set_id))
+    {
+        let policy_set = policy_store::get_policy_set_by_id(policy_set_id, db).await?;
+
+        if !policy_set.is_some_and(|ps| is_same_party(&ps.policy_issuer, policy_issuer)) {
+            return Err(invalid_policy_set_change(format!(
+                "policy set '{}' is not a policy set of the policy issuer",
+                policy_set_id
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+// the members of the party groups among the parties, by the normalized names of the groups
+pub type PartyGroupMembers = HashMap<String, Vec<String>>;
+
+fn get_party_group_members(parties: &Vec<String>, party_groups: &PartyGroupMembers) -> Vec<String> {
+    parties
+        .iter()
+        .filter_map(|p| party_group_name(p))
+        .filter_map(|name| party_groups.get(&name))
+        .flatten()
+        .cloned()
+        .collect()
+}
+
+fn to_matching_policy_set_row(
+    policy_set_id: Uuid,
+    policy_set: &InsertPolicySetWithPolicies,
+    party_groups: &PartyGroupMembers,
+) -> MatchingPolicySetRow {
+    MatchingPolicySetRow {
+        policy_set_id,
+        access_subject: normalize_party_id(&policy_set.target.access_subject),
+        policy_issuer: normalize_party_id(&policy_set.policy_issuer),
+        licenses: policy_set.licences.clone(),
+        max_delegation_depth: policy_set.max_delegation_depth,
+        not_before: policy_set.not_before,
+        not_on_or_after: policy_set.not_on_or_after,
+        created: None,
+        revision: None,
+        status: None,
+        deleted_at: None,
+        policies: policy_set
+            .policies
+            .iter()
+            .map(|p| DelegationEvidencePolicy {
+                id: Uuid::new_v4(),
+                identifiers: p.target.resource.identifiers.clone(),
+                resource_type: p.target.resource.resource_type.clone(),
+                attributes: p.target.resource.attributes.clone(),
+                actions: p.target.actions.clone(),
+                service_providers: p.target.environment.service_providers.clone(),
+                rules: p.rules.clone(),
+                service_provider_members: get_party_group_members(
+                    &p.target.environment.service_providers,
+                    party_groups,
+                ),
+            })
+            .collect(),
+        access_subject_members: get_party_group_members(
+            &vec![policy_set.target.access_subject.clone()],
+            party_groups,
+        ),
+        access_subject_attributes: policy_set.target.access_subject_attributes.clone(),
+        access_subject_user: policy_set.target.access_subject_user.clone(),
+    }
+}
+
+fn is_valid_at(policy_set: &MatchingPolicySetRow, now: chrono::DateTime<chrono::Utc>) -> bool {
+    policy_set.not_before.is_none_or(|nb| nb <= now)
+        && policy_set.not_on_or_after.is_none_or(|noa| noa > now)
+}
+
+// applies the changes to the live policy sets. hypothetical policy sets are added to the
+// candidates of delegation chains, chain resolution ignores the ones that are not reachable
+pub fn apply_policy_set_changes(
+    now: chrono::DateTime<chrono::Utc>,
+    delegation_request: &DelegationRequest,
+    subject_details: AccessSubjectDetails<'_>,
+    registration: Option<&PartyRegistration>,
+    changes: &PolicySetChanges,
+    party_groups: &PartyGroupMembers,
+    de_policy_sets: Vec<MatchingPolicySetRow>,
+    delegated_policy_sets: Vec<MatchingPolicySetRow>,
+) -> (Vec<MatchingPolicySetRow>, Vec<MatchingPolicySetRow>) {
+    let is_changed = |ps: &MatchingPolicySetRow| {
+        changes.remove.contains(&ps.policy_set_id)
+            || changes
+                .replace
+                .iter()
+                .any(|r| r.policy_set_id == ps.policy_set_id)
+    };
+
+    let hypothetical: Vec<MatchingPolicySetRow> = changes
+        .add
+        .iter()
+        .map(|ps| to_matching_policy_set_row(Uuid::new_v4(), ps, party_groups))
+        .chain(
+            changes
+                .replace
+                .iter()
+                .map(|r| to_matching_policy_set_row(r.policy_set_id, &r.policy_set, party_groups)),
+        )
+        .filter(|ps| is_valid_at(ps, now))
+        .collect();
+    let hypothetical = resolve_access_subjects(
+        now,
+        &delegation_request.target.access_subject,
+        subject_details,
+        registration,
+        hypothetical,
+    );
+
+    let mut direct: Vec<MatchingPolicySetRow> = de_policy_sets
+        .into_iter()
+        .filter(|ps| !is_changed(ps))
+        .collect();
+    let mut delegated: Vec<MatchingPolicySetRow> = delegated_policy_sets
+        .into_iter()
+        .filter(|ps| !is_changed(ps))
+        .collect();
+
+    for ps in hypothetical.into_iter() {
+        if is_same_party(&ps.policy_issuer, &delegation_request.policy_issuer)
+            && is_issued_to(&ps, &delegation_request.target.access_subject)
+        {
+            direct.push(ps);
+        } else {
+            delegated.push(ps);
+        }
+    }
+
+    (direct, delegated)
+}
+
+// evaluates the delegation request against the live policy sets with the changes applied, without
+// storing anything
+pub async fn simulate_delegation_evidence(
+    delegation_request: &DelegationRequest,
+    subject_details: AccessSubjectDetails<'_>,
+    environment: &HashMap<String, String>,
+    required_licenses: &Vec<String>,
+    combining_algorithms: &CombiningAlgorithmConfig,
+    changes: &PolicySetChanges,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    de_expiry_seconds: i64,
+    db: &DatabaseConnection,
+) -> Result<IssuedDelegationEvidence, AppError> {
+    let now = time_provider.now();
+    let (de_policy_sets, mut delegated_policy_sets) =
+        get_candidate_policy_sets(now, delegation_request, subject_details, db).await?;
+
+    // live policy sets can become reachable through a hypothetical delegation
+    let delegates: Vec<&String> = changes
+        .add
+        .iter()
+        .chain(changes.replace.iter().map(|r| &r.policy_set))
+        .filter(|ps| ps.max_delegation_depth > 0)
+        .map(|ps| &ps.target.access_subject)
+        .collect();
+
+    for delegate in delegates {
+        let reachable = policy_store::get_policy_sets_with_policies_for_delegation_chains(
+            now,
+            delegation_request.target.access_subject.to_owned(),
+            delegate.to_owned(),
+            MAX_DELEGATION_CHAIN_LENGTH,
+            &db,
+        )
+        .await
+        .context("Error getting delegated policy sets")?;
+
+        for ps in reachable.into_iter() {
+            if !delegated_policy_sets
+                .iter()
+                .any(|d| d.policy_set_id == ps.policy_set_id)
+            {
+                delegated_policy_sets.push(ps);
+            }
+        }
+    }
+
+    let mut group_names: Vec<String> = changes
+        .add
+        .iter()
+        .chain(changes.replace.iter().map(|r| &r.policy_set))
+        .flat_map(|ps| {
+            std::iter::once(&ps.target.access_subject).chain(
+                ps.policies
+                    .iter()
+                    .flat_map(|p| p.target.environment.service_providers.iter()),
+            )
+        })
+        .filter_map(|p| party_group_name(p))
+        .collect();
+    group_names.sort();
+    group_names.dedup();
+    let party_groups = party_group_store::get_members_of_party_groups(&group_names, db)
+        .await
+        .context("Error getting members of party groups")?;
+
+    let registration = get_access_subject_registration(
+        now,
+        &delegation_request.target.access_subject,
+        subject_details,
+        changes
+            .add
+            .iter()
+            .chain(changes.replace.iter().map(|r| &r.policy_set))
+            .filter_map(|ps| ps.target.access_subject_attributes.as_ref()),
+    )
+    .await;
+
+    let (de_policy_sets, delegated_policy_sets) = apply_policy_set_changes(
+        now,
+        delegation_request,
+        subject_details,
+        registration.as_ref(),
+        changes,
+        &party_groups,
+        de_policy_sets,
+        delegated_policy_sets,
+    );
+
+    let context = EvaluationContext {
+        now,
+        environment: environment.clone(),
+        combining_algorithm: combining_algorithms
+            .for_policy_issuer(&delegation_request.policy_issuer),
+        required_licenses: required_licenses.clone(),
+    };
+
+    Ok(build_delegation_evidence(
+        delegation_request,
+        subject_details.user,
+        &de_policy_sets,
+        &delegated_policy_sets,
+        &context,
+        de_expiry_seconds,
+    ))
+}
+
+// the action on a resource a delegation token is verified for
+#[derive(Deserialize, Serialize, ToSchema, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct IntendedAccess {
+    pub resource_type: String,
+    #[serde(default)]
+    pub identifiers: Vec<String>,
+    #[serde(default)]
+    pub attributes: Vec<String>,
+    pub action: String,
+}
+
+#[derive(Deserialize, Serialize, ToSchema, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct DelegationTokenVerification {
+    pub permit: bool,
+    pub issuer: String,
+    pub policy_issuer: String,
+    pub access_subject: String,
+    pub not_on_or_after: i64,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DelegationTokenClaims {
+    iss: String,
+    aud: String,
+    exp: i64,
+    delegation_evidence: DelegationEvidence,
+}
+
+// the satellite role of the parties whose delegation tokens can be verified
+const AUTHORISATION_REGISTRY_ROLE: &str = "AuthorisationRegistry";
+
+fn invalid_delegation_token(reason: String) -> AppError {
+    AppError::Expected(ExpectedError {
+        status_code: StatusCode::BAD_REQUEST,
+        message: format!("delegation token is invalid: {}", &reason),
+        reason,
+        metadata: None,
+    })
+}
+
+fn is_covering_policy(
+    policy: &ishare::delegation_evidence::Policy,
+    intended: &IntendedAccess,
+) -> bool {
+    policy.target.resource.resource_type == intended.resource_type
+        && star_or_contained_by(&vec![intended.action.clone()], &policy.target.actions)
+        && star_or_matched_by(&intended.identifiers, &policy.target.resource.identifiers)
+        && star_or_matched_by(&intended.attributes, &policy.target.resource.attributes)
+}
+
+// evidence permits the intended access when it is valid at `now`, at least one of its policies
+// covers the access and verify_delegation_evidence accepts the covering policies
+pub fn is_evidence_permitting(
+    evidence: &DelegationEvidence,
+    now: chrono::DateTime<chrono::Utc>,
+    intended: &IntendedAccess,
+) -> bool {
+    if now.timestamp() < evidence.not_before || now.timestamp() >= evidence.not_on_or_after {
+        return false;
+    }
+
+    let mut covering = evidence.clone();
+    for policy_set in covering.policy_sets.iter_mut() {
+        policy_set
+            .policies
+            .retain(|p| is_covering_policy(p, intended));
+    }
+    covering.policy_sets.retain(|ps| !ps.policies.is_empty());
+
+    !covering.policy_sets.is_empty()
+        && verify_delegation_evidence(&covering, intended.resource_type.clone())
+}
+
+// verifies a delegation token issued by this registry or another party with the authorisation
+// registry role at the satellite and checks whether its evidence permits the intended access
+pub async fn verify_delegation_token(
+    now: chrono::DateTime<chrono::Utc>,
+    delegation_token: &str,
+    audience: &str,
+    intended: &IntendedAccess,
+    client_eori: &str,
+    satellite_provider: Arc<dyn SatelliteProvider>,
+) -> Result<DelegationTokenVerification, AppError> {
+    let claims = read_unverified_claims::<DelegationTokenClaims>(delegation_token)
+        .map_err(|e| invalid_delegation_token(format!("malformed token: {}", e)))?;
+
+    if !is_same_party(client_eori, &claims.iss) {
+        let registration = satellite_provider
+            .get_party_registration(now, &claims.iss)
+            .await
+            .map_err(|e| {
+                invalid_delegation_token(format!(
+                    "unable to get the registration of issuer '{}': {}",
+                    claims.iss, e
+                ))
+            })?;
+
+        if !registration.has_role(AUTHORISATION_REGISTRY_ROLE) {
+            return Err(invalid_delegation_token(format!(
+                "issuer '{}' is not an authorization registry",
+                claims.iss
+            )));
+        }
+    }
+
+    if !is_same_party(&claims.aud, audience) {
+        return Err(invalid_delegation_token(format!(
+            "addressed to '{}' instead of '{}'",
+            claims.aud, audience
+        )));
+    }
+
+    if claims.exp <= now.timestamp() {
+        return Err(invalid_delegation_token("token has expired".to_owned()));
+    }
+
+    satellite_provider
+        .verify_delegation_token(now, delegation_token, &claims.iss, &claims.aud)
+        .await
+        .map_err(|e| invalid_delegation_token(format!("verification failed: {}", e)))?;
+
+    let evidence = claims.delegation_evidence;
+
+    Ok(DelegationTokenVerification {
+        permit: is_evidence_permitting(&evidence, now, intended),
+        issuer: claims.iss,
+        policy_issuer: evidence.policy_issuer,
+        access_subject: evidence.target.access_subject,
+        not_on_or_after: evidence.not_on_or_after,
+    })
 }
 
 #[cfg(test)]
 mod tests {
-    use ar_entity::delegation_evidence::{Deny, Resource, ResourceRule, Target};
+    use ar_entity::delegation_evidence::{
+        Condition, Deny, DenyEnvironment, EnvironmentAttribute, Permit, Resource, ResourceRule,
+        Target,
+    };
     use ishare::delegation_request::{
         DelegationTarget, Environment, Resource as DRResource, ResourceRules, ResourceTarget,
     };
@@ -288,8 +1873,17 @@ mod tests {
 
     use super::*;
 
-    #[test]
-    fn test_check_delegation_access_as_match() {
+    fn test_context() -> EvaluationContext {
+        EvaluationContext {
+            now: chrono::DateTime::from_timestamp(1715247205, 0).unwrap(),
+            environment: HashMap::new(),
+            combining_algorithm: None,
+            required_licenses: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn test_check_delegation_access_as_match() {
         assert_eq!(
             check_delegation_access(
                 chrono::Utc::now(),
@@ -303,14 +1897,17 @@ mod tests {
                 },
                 &None,
                 false,
-                Arc::new(TestSatelliteProvider {})
-            ),
+                Arc::new(TestSatelliteProvider {}),
+                &PreviousStepsVerifier::new(30)
+            )
+            .await
+            .unwrap(),
             true
         );
     }
 
-    #[test]
-    fn test_check_delegation_access_pi_match() {
+    #[tokio::test]
+    async fn test_check_delegation_access_pi_match() {
         assert_eq!(
             check_delegation_access(
                 chrono::Utc::now(),
@@ -324,14 +1921,17 @@ mod tests {
                 },
                 &None,
                 false,
-                Arc::new(TestSatelliteProvider {})
-            ),
+                Arc::new(TestSatelliteProvider {}),
+                &PreviousStepsVerifier::new(30)
+            )
+            .await
+            .unwrap(),
             true
         );
     }
 
-    #[test]
-    fn test_check_delegation_access_service_providers_empty() {
+    #[tokio::test]
+    async fn test_check_delegation_access_service_providers_empty() {
         assert_eq!(
             check_delegation_access(
                 chrono::Utc::now(),
@@ -345,14 +1945,17 @@ mod tests {
                 },
                 &None,
                 true,
-                Arc::new(TestSatelliteProvider {})
-            ),
+                Arc::new(TestSatelliteProvider {}),
+                &PreviousStepsVerifier::new(30)
+            )
+            .await
+            .unwrap(),
             false
         );
     }
 
-    #[test]
-    fn test_check_delegation_access_service_providers_no_match() {
+    #[tokio::test]
+    async fn test_check_delegation_access_service_providers_no_match() {
         assert_eq!(
             check_delegation_access(
                 chrono::Utc::now(),
@@ -381,14 +1984,17 @@ mod tests {
                 },
                 &None,
                 true,
-                Arc::new(TestSatelliteProvider {})
-            ),
+                Arc::new(TestSatelliteProvider {}),
+                &PreviousStepsVerifier::new(30)
+            )
+            .await
+            .unwrap(),
             false
         );
     }
 
-    #[test]
-    fn test_check_delegation_access_service_providers_match() {
+    #[tokio::test]
+    async fn test_check_delegation_access_service_providers_match() {
         assert_eq!(
             check_delegation_access(
                 chrono::Utc::now(),
@@ -417,8 +2023,11 @@ mod tests {
                 },
                 &None,
                 true,
-                Arc::new(TestSatelliteProvider {})
-            ),
+                Arc::new(TestSatelliteProvider {}),
+                &PreviousStepsVerifier::new(30)
+            )
+            .await
+            .unwrap(),
             true
         );
     }
@@ -485,8 +2094,9 @@ mod tests {
                 identifiers: vec!["*".to_owned()],
                 attributes: vec!["*".to_owned()],
                 resource_type: "nice-resource".to_owned(),
-                rules: vec![ResourceRule::Permit],
+                rules: vec![ResourceRule::Permit(Permit::default())],
                 service_providers: vec!["fishery".to_owned()],
+                service_provider_members: vec![],
             },
         );
 
@@ -518,8 +2128,9 @@ mod tests {
                 identifiers: vec!["id1".to_owned()],
                 attributes: vec!["att1".to_owned()],
                 resource_type: "nice-resource".to_owned(),
-                rules: vec![ResourceRule::Permit],
+                rules: vec![ResourceRule::Permit(Permit::default())],
                 service_providers: vec!["fishery".to_owned()],
+                service_provider_members: vec![],
             },
         );
 
@@ -551,8 +2162,9 @@ mod tests {
                 identifiers: vec!["*".to_owned()],
                 attributes: vec!["*".to_owned()],
                 resource_type: "nice-resource".to_owned(),
-                rules: vec![ResourceRule::Permit],
+                rules: vec![ResourceRule::Permit(Permit::default())],
                 service_providers: vec!["fishery".to_owned()],
+                service_provider_members: vec![],
             },
         );
 
@@ -584,8 +2196,9 @@ mod tests {
                 identifiers: vec!["fish".to_owned()],
                 attributes: vec!["*".to_owned()],
                 resource_type: "nice-resource".to_owned(),
-                rules: vec![ResourceRule::Permit],
+                rules: vec![ResourceRule::Permit(Permit::default())],
                 service_providers: vec!["fishery".to_owned()],
+                service_provider_members: vec![],
             },
         );
 
@@ -617,8 +2230,9 @@ mod tests {
                 identifiers: vec!["*".to_owned()],
                 attributes: vec!["att".to_owned()],
                 resource_type: "nice-resource".to_owned(),
-                rules: vec![ResourceRule::Permit],
+                rules: vec![ResourceRule::Permit(Permit::default())],
                 service_providers: vec!["fishery".to_owned()],
+                service_provider_members: vec![],
             },
         );
 
@@ -650,8 +2264,9 @@ mod tests {
                 identifiers: vec!["*".to_owned()],
                 attributes: vec!["*".to_owned()],
                 resource_type: "nice-resource".to_owned(),
-                rules: vec![ResourceRule::Permit],
+                rules: vec![ResourceRule::Permit(Permit::default())],
                 service_providers: vec!["fishery".to_owned()],
+                service_provider_members: vec![],
             },
         );
 
@@ -666,6 +2281,12 @@ mod tests {
             policy_set_id: Uuid::new_v4(),
             policy_issuer: "issuer".to_owned(),
             max_delegation_depth: 1,
+            not_before: None,
+            not_on_or_after: None,
+            created: None,
+            revision: None,
+            status: None,
+            deleted_at: None,
             policies: vec![DelegationEvidencePolicy {
                 id: Uuid::new_v4(),
                 identifiers: vec!["fish".to_owned()],
@@ -673,8 +2294,12 @@ mod tests {
                 attributes: vec!["chicken".to_owned()],
                 actions: vec!["Read".to_owned()],
                 service_providers: vec!["fishery".to_owned()],
-                rules: vec![ResourceRule::Permit],
+                rules: vec![ResourceRule::Permit(Permit::default())],
+                service_provider_members: vec![],
             }],
+            access_subject_members: vec![],
+            access_subject_attributes: None,
+            access_subject_user: None,
         }];
 
         let matching_rows = mask_matching_policy_sets(
@@ -710,6 +2335,12 @@ mod tests {
             policy_set_id: Uuid::new_v4(),
             policy_issuer: "issuer".to_owned(),
             max_delegation_depth: 1,
+            not_before: None,
+            not_on_or_after: None,
+            created: None,
+            revision: None,
+            status: None,
+            deleted_at: None,
             policies: vec![DelegationEvidencePolicy {
                 id: Uuid::new_v4(),
                 identifiers: vec!["fish".to_owned()],
@@ -717,8 +2348,12 @@ mod tests {
                 attributes: vec!["chicken".to_owned()],
                 actions: vec!["Read".to_owned()],
                 service_providers: vec!["fishery".to_owned()],
-                rules: vec![ResourceRule::Permit],
+                rules: vec![ResourceRule::Permit(Permit::default())],
+                service_provider_members: vec![],
             }],
+            access_subject_members: vec![],
+            access_subject_attributes: None,
+            access_subject_user: None,
         }];
 
         let matching_rows = mask_matching_policy_sets(
@@ -754,6 +2389,12 @@ mod tests {
             policy_set_id: Uuid::new_v4(),
             policy_issuer: "issuer".to_owned(),
             max_delegation_depth: 1,
+            not_before: None,
+            not_on_or_after: None,
+            created: None,
+            revision: None,
+            status: None,
+            deleted_at: None,
             policies: vec![DelegationEvidencePolicy {
                 id: Uuid::new_v4(),
                 identifiers: vec!["fish".to_owned()],
@@ -761,8 +2402,12 @@ mod tests {
                 attributes: vec!["chicken".to_owned()],
                 actions: vec!["Read".to_owned()],
                 service_providers: vec!["fishery".to_owned()],
-                rules: vec![ResourceRule::Permit],
+                rules: vec![ResourceRule::Permit(Permit::default())],
+                service_provider_members: vec![],
             }],
+            access_subject_members: vec![],
+            access_subject_attributes: None,
+            access_subject_user: None,
         };
 
         let is_permit = is_permit(
@@ -783,6 +2428,7 @@ mod tests {
                 }],
             },
             &matching_policy_set_row,
+            &test_context(),
         );
 
         assert_eq!(is_permit, true)
@@ -796,6 +2442,12 @@ mod tests {
             policy_set_id: Uuid::new_v4(),
             policy_issuer: "issuer".to_owned(),
             max_delegation_depth: 1,
+            not_before: None,
+            not_on_or_after: None,
+            created: None,
+            revision: None,
+            status: None,
+            deleted_at: None,
             policies: vec![DelegationEvidencePolicy {
                 id: Uuid::new_v4(),
                 identifiers: vec!["*".to_owned()],
@@ -811,9 +2463,15 @@ mod tests {
                             attributes: vec!["chicken".to_owned()],
                         },
                         actions: vec!["Read".to_owned()],
+                        environment: None,
                     },
+                    conditions: vec![],
                 })],
+                service_provider_members: vec![],
             }],
+            access_subject_members: vec![],
+            access_subject_attributes: None,
+            access_subject_user: None,
         };
 
         let is_permit = is_permit(
@@ -834,11 +2492,205 @@ mod tests {
                 }],
             },
             &matching_policy_set_row,
+            &test_context(),
         );
 
         assert_eq!(is_permit, false)
     }
 
+    fn conditional_policy_set_row(rule: ResourceRule) -> MatchingPolicySetRow {
+        MatchingPolicySetRow {
+            access_subject: "as".to_owned(),
+            licenses: vec![],
+            policy_set_id: Uuid::new_v4(),
+            policy_issuer: "issuer".to_owned(),
+            max_delegation_depth: 1,
+            not_before: None,
+            not_on_or_after: None,
+            created: None,
+            revision: None,
+            status: None,
+            deleted_at: None,
+            policies: vec![DelegationEvidencePolicy {
+                id: Uuid::new_v4(),
+                identifiers: vec!["*".to_owned()],
+                resource_type: "nice-resource".to_owned(),
+                attributes: vec!["*".to_owned()],
+                actions: vec!["Read".to_owned()],
+                service_providers: vec![],
+                rules: vec![rule],
+                service_provider_members: vec![],
+            }],
+            access_subject_members: vec![],
+            access_subject_attributes: None,
+            access_subject_user: None,
+        }
+    }
+
+    fn read_chicken_policy() -> Policy {
+        Policy {
+            target: ResourceTarget {
+                actions: vec!["Read".to_owned()],
+                resource: DRResource {
+                    resource_type: "nice-resource".to_owned(),
+                    identifiers: vec!["chicken".to_owned()],
+                    attributes: vec!["chicken".to_owned()],
+                },
+                environment: None,
+            },
+            rules: vec![ResourceRules {
+                effect: "Permit".to_owned(),
+            }],
+        }
+    }
+
+    fn region_condition() -> Condition {
+        Condition::Environment(EnvironmentAttribute {
+            attribute: "region".to_owned(),
+            values: vec!["EU".to_owned()],
+        })
+    }
+
+    #[test]
+    fn test_is_permit_conditional_permit() {
+        let row = conditional_policy_set_row(ResourceRule::Permit(Permit {
+            conditions: vec![region_condition()],
+            ..Default::default()
+        }));
+
+        let mut context = test_context();
+        assert_eq!(is_permit(&read_chicken_policy(), &row, &context), false);
+
+        context
+            .environment
+            .insert("region".to_owned(), "EU".to_owned());
+        assert_eq!(is_permit(&read_chicken_policy(), &row, &context), true);
+    }
+
+    #[test]
+    fn test_is_permit_conditional_deny() {
+        let row = conditional_policy_set_row(ResourceRule::Deny(Deny {
+            target: Target {
+                resource: Resource {
+                    resource_type: "nice-resource".to_owned(),
+                    identifiers: vec!["chicken".to_owned()],
+                    attributes: vec!["chicken".to_owned()],
+                },
+                actions: vec!["Read".to_owned()],
+                environment: None,
+            },
+            conditions: vec![region_condition()],
+        }));
+
+        // without the attribute the deny applies
+        let mut context = test_context();
+        assert_eq!(is_permit(&read_chicken_policy(), &row, &context), false);
+
+        context
+            .environment
+            .insert("region".to_owned(), "US".to_owned());
+        assert_eq!(is_permit(&read_chicken_policy(), &row, &context), true);
+
+        context
+            .environment
+            .insert("region".to_owned(), "EU".to_owned());
+        assert_eq!(is_permit(&read_chicken_policy(), &row, &context), false);
+    }
+
+    fn scoped_deny_rule(service_providers: Vec<&str>, licenses: Vec<&str>) -> ResourceRule {
+        ResourceRule::Deny(Deny {
+            target: Target {
+                resource: Resource {
+                    resource_type: "nice-resource".to_owned(),
+                    identifiers: vec!["*".to_owned()],
+                    attributes: vec!["*".to_owned()],
+                },
+                actions: vec!["Read".to_owned()],
+                environment: Some(DenyEnvironment {
+                    service_providers: service_providers
+                        .into_iter()
+                        .map(|sp| sp.to_owned())
+                        .collect(),
+                    licenses: licenses.into_iter().map(|l| l.to_owned()).collect(),
+                }),
+            },
+            conditions: vec![],
+        })
+    }
+
+    fn read_chicken_policy_through(service_providers: Vec<&str>) -> Policy {
+        let mut policy = read_chicken_policy();
+        policy.target.environment = Some(Environment {
+            service_providers: service_providers
+                .into_iter()
+                .map(|sp| sp.to_owned())
+                .collect(),
+        });
+        policy
+    }
+
+    #[test]
+    fn test_is_permit_deny_scoped_to_service_provider() {
+        let mut row = conditional_policy_set_row(scoped_deny_rule(vec!["shady-sp"], vec![]));
+        row.policies[0].service_providers = vec!["fishery".to_owned(), "shady-sp".to_owned()];
+        let context = test_context();
+
+        assert_eq!(
+            is_permit(
+                &read_chicken_policy_through(vec!["fishery"]),
+                &row,
+                &context
+            ),
+            true
+        );
+        assert_eq!(
+            is_permit(
+                &read_chicken_policy_through(vec!["fishery", "shady-sp"]),
+                &row,
+                &context
+            ),
+            false
+        );
+        // a request that doesn't name its service providers could be used through any of them
+        assert_eq!(is_permit(&read_chicken_policy(), &row, &context), false);
+    }
+
+    #[test]
+    fn test_is_permit_deny_scoped_to_license() {
+        let mut row = conditional_policy_set_row(scoped_deny_rule(vec![], vec!["ISHARE.0002"]));
+        row.licenses = vec!["ISHARE.0001".to_owned(), "ISHARE.0002".to_owned()];
+        let mut context = test_context();
+
+        // without required licenses the evidence can be used under every granted license
+        assert_eq!(is_permit(&read_chicken_policy(), &row, &context), false);
+
+        context.required_licenses = vec!["ISHARE.0001".to_owned()];
+        assert_eq!(is_permit(&read_chicken_policy(), &row, &context), true);
+
+        context.required_licenses = vec!["ISHARE.0002".to_owned()];
+        assert_eq!(is_permit(&read_chicken_policy(), &row, &context), false);
+
+        context.required_licenses = vec![];
+        row.licenses = vec!["ISHARE.0001".to_owned()];
+        assert_eq!(is_permit(&read_chicken_policy(), &row, &context), true);
+    }
+
+    #[test]
+    fn test_is_permit_deny_scoped_to_service_provider_and_license() {
+        let mut row =
+            conditional_policy_set_row(scoped_deny_rule(vec!["shady-sp"], vec!["ISHARE.0002"]));
+        row.policies[0].service_providers = vec!["shady-sp".to_owned()];
+        row.licenses = vec!["ISHARE.0001".to_owned(), "ISHARE.0002".to_owned()];
+        let mut context = test_context();
+        let policy = read_chicken_policy_through(vec!["shady-sp"]);
+
+        context.required_licenses = vec!["ISHARE.0001".to_owned()];
+        assert_eq!(is_permit(&policy, &row, &context), true);
+
+        context.required_licenses = vec!["ISHARE.0002".to_owned()];
+        assert_eq!(is_permit(&policy, &row, &context), false);
+    }
+
     #[test]
     fn test_get_delegation_evidence_policy_sets() {
         let matching_policy_set_rows = vec![MatchingPolicySetRow {
@@ -847,6 +2699,12 @@ mod tests {
             policy_set_id: Uuid::new_v4(),
             policy_issuer: "issuer".to_owned(),
             max_delegation_depth: 1,
+            not_before: None,
+            not_on_or_after: None,
+            created: None,
+            revision: None,
+            status: None,
+            deleted_at: None,
             policies: vec![DelegationEvidencePolicy {
                 id: Uuid::new_v4(),
                 identifiers: vec!["*".to_owned()],
@@ -854,8 +2712,12 @@ mod tests {
                 attributes: vec!["*".to_owned()],
                 actions: vec!["Read".to_owned()],
                 service_providers: vec!["fishery".to_owned()],
-                rules: vec![ResourceRule::Permit],
+                rules: vec![ResourceRule::Permit(Permit::default())],
+                service_provider_members: vec![],
             }],
+            access_subject_members: vec![],
+            access_subject_attributes: None,
+            access_subject_user: None,
         }];
 
         let policy_sets = get_delegation_evidence_policy_sets(
@@ -883,7 +2745,8 @@ mod tests {
                     }],
                 }],
             },
-            &matching_policy_set_rows,
+            &matching_policy_set_rows.iter().map(|ps| vec![ps]).collect(),
+            &test_context(),
         );
 
         assert_eq!(policy_sets.len(), 1);
@@ -910,6 +2773,12 @@ mod tests {
             policy_set_id: Uuid::new_v4(),
             policy_issuer: "issuer".to_owned(),
             max_delegation_depth: 1,
+            not_before: None,
+            not_on_or_after: None,
+            created: None,
+            revision: None,
+            status: None,
+            deleted_at: None,
             policies: vec![DelegationEvidencePolicy {
                 id: Uuid::new_v4(),
                 identifiers: vec!["*".to_owned()],
@@ -918,7 +2787,7 @@ mod tests {
                 actions: vec!["Read".to_owned()],
                 service_providers: vec!["fishery".to_owned()],
                 rules: vec![
-                    ResourceRule::Permit,
+                    ResourceRule::Permit(Permit::default()),
                     ResourceRule::Deny(Deny {
                         target: Target {
                             resource: Resource {
@@ -927,10 +2796,16 @@ mod tests {
                                 attributes: vec!["chicken".to_owned()],
                             },
                             actions: vec!["Read".to_owned()],
+                            environment: None,
                         },
+                        conditions: vec![],
                     }),
                 ],
+                service_provider_members: vec![],
             }],
+            access_subject_members: vec![],
+            access_subject_attributes: None,
+            access_subject_user: None,
         }];
 
         let policy_sets = get_delegation_evidence_policy_sets(
@@ -958,7 +2833,8 @@ mod tests {
                     }],
                 }],
             },
-            &matching_policy_set_rows,
+            &matching_policy_set_rows.iter().map(|ps| vec![ps]).collect(),
+            &test_context(),
         );
 
         assert_eq!(policy_sets.len(), 1);
@@ -986,6 +2862,12 @@ mod tests {
                 policy_set_id: Uuid::new_v4(),
                 policy_issuer: "issuer".to_owned(),
                 max_delegation_depth: 1,
+                not_before: None,
+                not_on_or_after: None,
+                created: None,
+                revision: None,
+                status: None,
+                deleted_at: None,
                 policies: vec![DelegationEvidencePolicy {
                     id: Uuid::new_v4(),
                     identifiers: vec!["*".to_owned()],
@@ -993,8 +2875,12 @@ mod tests {
                     attributes: vec!["*".to_owned()],
                     actions: vec!["*".to_owned()],
                     service_providers: vec!["fishery".to_owned()],
-                    rules: vec![ResourceRule::Permit],
+                    rules: vec![ResourceRule::Permit(Permit::default())],
+                    service_provider_members: vec![],
                 }],
+                access_subject_members: vec![],
+                access_subject_attributes: None,
+                access_subject_user: None,
             },
             MatchingPolicySetRow {
                 access_subject: "as".to_owned(),
@@ -1002,6 +2888,12 @@ mod tests {
                 policy_set_id: Uuid::new_v4(),
                 policy_issuer: "issuer".to_owned(),
                 max_delegation_depth: 1,
+                not_before: None,
+                not_on_or_after: None,
+                created: None,
+                revision: None,
+                status: None,
+                deleted_at: None,
                 policies: vec![DelegationEvidencePolicy {
                     id: Uuid::new_v4(),
                     identifiers: vec!["*".to_owned()],
@@ -1009,8 +2901,12 @@ mod tests {
                     attributes: vec!["*".to_owned()],
                     actions: vec!["*".to_owned()],
                     service_providers: vec!["fishery".to_owned()],
-                    rules: vec![ResourceRule::Permit],
+                    rules: vec![ResourceRule::Permit(Permit::default())],
+                    service_provider_members: vec![],
                 }],
+                access_subject_members: vec![],
+                access_subject_attributes: None,
+                access_subject_user: None,
             },
         ];
 
@@ -1059,9 +2955,494 @@ mod tests {
                     },
                 ],
             },
-            &matching_policy_set_rows,
+            &matching_policy_set_rows.iter().map(|ps| vec![ps]).collect(),
+            &test_context(),
         );
 
         assert_eq!(policy_sets.len(), 4)
     }
+
+    fn chain_policy_set_row(
+        policy_issuer: &str,
+        access_subject: &str,
+        max_delegation_depth: i32,
+        rules: Vec<ResourceRule>,
+    ) -> MatchingPolicySetRow {
+        MatchingPolicySetRow {
+            access_subject: access_subject.to_owned(),
+            licenses: vec![],
+            policy_set_id: Uuid::new_v4(),
+            policy_issuer: policy_issuer.to_owned(),
+            max_delegation_depth,
+            not_before: None,
+            not_on_or_after: None,
+            created: None,
+            revision: None,
+            status: None,
+            deleted_at: None,
+            policies: vec![DelegationEvidencePolicy {
+                id: Uuid::new_v4(),
+                identifiers: vec!["*".to_owned()],
+                resource_type: "nice-resource".to_owned(),
+                attributes: vec!["*".to_owned()],
+                actions: vec!["Read".to_owned()],
+                service_providers: vec!["fishery".to_owned()],
+                rules,
+                service_provider_members: vec![],
+            }],
+            access_subject_members: vec![],
+            access_subject_attributes: None,
+            access_subject_user: None,
+        }
+    }
+
+    fn chain_delegation_request() -> DelegationRequest {
+        DelegationRequest {
+            policy_issuer: "pi".to_owned(),
+            target: DelegationTarget {
+                access_subject: "as".to_owned(),
+            },
+            policy_sets: vec![PolicySet {
+                policies: vec![Policy {
+                    target: ResourceTarget {
+                        actions: vec!["Read".to_owned()],
+                        resource: DRResource {
+                            resource_type: "nice-resource".to_owned(),
+                            identifiers: vec!["chicken".to_owned()],
+                            attributes: vec!["chicken".to_owned()],
+                        },
+                        environment: Some(Environment {
+                            service_providers: vec!["fishery".to_owned()],
+                        }),
+                    },
+                    rules: vec![ResourceRules {
+                        effect: "Permit".to_owned(),
+                    }],
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_partition_candidate_policy_sets() {
+        let policy_sets = vec![
+            chain_policy_set_row("pi", "as", 0, vec![]),
+            chain_policy_set_row("pi", "b", 1, vec![]),
+            chain_policy_set_row("b", "as", 0, vec![]),
+            chain_policy_set_row("b", "c", 0, vec![]),
+            chain_policy_set_row("other", "as", 0, vec![]),
+        ];
+
+        let (direct, delegated) = partition_candidate_policy_sets(
+            chrono::Utc::now(),
+            &chain_delegation_request(),
+            AccessSubjectDetails::default(),
+            None,
+            &policy_sets,
+        );
+
+        assert_eq!(direct.len(), 1);
+        assert_eq!(direct[0].policy_set_id, policy_sets[0].policy_set_id);
+        let delegated_ids: Vec<Uuid> = delegated.iter().map(|ps| ps.policy_set_id).collect();
+        assert_eq!(
+            delegated_ids,
+            vec![
+                policy_sets[0].policy_set_id,
+                policy_sets[1].policy_set_id,
+                policy_sets[2].policy_set_id,
+                policy_sets[4].policy_set_id,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_remaining_delegation_depth() {
+        let pi_to_b =
+            chain_policy_set_row("pi", "b", 3, vec![ResourceRule::Permit(Permit::default())]);
+        let b_to_as =
+            chain_policy_set_row("b", "as", 1, vec![ResourceRule::Permit(Permit::default())]);
+        let c_to_as =
+            chain_policy_set_row("c", "as", 0, vec![ResourceRule::Permit(Permit::default())]);
+        let pi_to_c =
+            chain_policy_set_row("pi", "c", 0, vec![ResourceRule::Permit(Permit::default())]);
+
+        assert_eq!(remaining_delegation_depth(&[&pi_to_b]), Some(3));
+        assert_eq!(remaining_delegation_depth(&[&pi_to_b, &b_to_as]), Some(1));
+        assert_eq!(remaining_delegation_depth(&[&pi_to_c, &c_to_as]), None);
+    }
+
+    #[test]
+    fn test_resolve_delegation_chains() {
+        let direct = vec![chain_policy_set_row(
+            "pi",
+            "as",
+            0,
+            vec![ResourceRule::Permit(Permit::default())],
+        )];
+        let delegated = vec![
+            chain_policy_set_row("pi", "b", 1, vec![ResourceRule::Permit(Permit::default())]),
+            chain_policy_set_row("b", "as", 0, vec![ResourceRule::Permit(Permit::default())]),
+            chain_policy_set_row("pi", "c", 0, vec![ResourceRule::Permit(Permit::default())]),
+            chain_policy_set_row("c", "as", 0, vec![ResourceRule::Permit(Permit::default())]),
+        ];
+
+        let chains = resolve_delegation_chains("pi", "as", &direct, &delegated);
+
+        assert_eq!(chains.len(), 2);
+        assert_eq!(chains[0].len(), 1);
+        assert_eq!(
+            chains[1]
+                .iter()
+                .map(|ps| ps.access_subject.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b", "as"]
+        );
+    }
+
+    #[test]
+    fn test_resolve_delegation_chains_depth_exceeded() {
+        let delegated = vec![
+            chain_policy_set_row("pi", "b", 1, vec![ResourceRule::Permit(Permit::default())]),
+            chain_policy_set_row("b", "d", 1, vec![ResourceRule::Permit(Permit::default())]),
+            chain_policy_set_row("d", "as", 0, vec![ResourceRule::Permit(Permit::default())]),
+        ];
+
+        let direct = vec![];
+        let chains = resolve_delegation_chains("pi", "as", &direct, &delegated);
+
+        assert_eq!(chains.len(), 0);
+    }
+
+    #[test]
+    fn test_resolve_delegation_chains_cycle() {
+        let delegated = vec![
+            chain_policy_set_row("pi", "b", 4, vec![ResourceRule::Permit(Permit::default())]),
+            chain_policy_set_row("b", "pi", 4, vec![ResourceRule::Permit(Permit::default())]),
+            chain_policy_set_row("b", "as", 0, vec![ResourceRule::Permit(Permit::default())]),
+        ];
+
+        let direct = vec![];
+        let chains = resolve_delegation_chains("pi", "as", &direct, &delegated);
+
+        assert_eq!(chains.len(), 1);
+        assert_eq!(chains[0].len(), 2);
+    }
+
+    #[test]
+    fn test_resolve_delegation_chains_revisits_intermediate_party() {
+        let delegated = vec![
+            chain_policy_set_row("pi", "b", 4, vec![ResourceRule::Permit(Permit::default())]),
+            chain_policy_set_row("b", "B", 4, vec![ResourceRule::Permit(Permit::default())]),
+            chain_policy_set_row("b", "c", 4, vec![ResourceRule::Permit(Permit::default())]),
+            chain_policy_set_row("c", "b", 4, vec![ResourceRule::Permit(Permit::default())]),
+            chain_policy_set_row("c", "as", 0, vec![ResourceRule::Permit(Permit::default())]),
+        ];
+
+        let direct = vec![];
+        let chains = resolve_delegation_chains("pi", "as", &direct, &delegated);
+
+        assert_eq!(chains.len(), 1);
+        assert_eq!(
+            chains[0]
+                .iter()
+                .map(|ps| ps.access_subject.as_str())
+                .collect::<Vec<_>>(),
+            vec!["b", "c", "as"]
+        );
+    }
+
+    #[test]
+    fn test_get_delegation_evidence_policy_sets_chain() {
+        let pi_to_b =
+            chain_policy_set_row("pi", "b", 2, vec![ResourceRule::Permit(Permit::default())]);
+        let b_to_as =
+            chain_policy_set_row("b", "as", 1, vec![ResourceRule::Permit(Permit::default())]);
+
+        let policy_sets = get_delegation_evidence_policy_sets(
+            &chain_delegation_request(),
+            &vec![vec![&pi_to_b, &b_to_as]],
+            &test_context(),
+        );
+
+        assert_eq!(policy_sets.len(), 1);
+        assert_eq!(policy_sets[0].max_delegation_depth, 1);
+        assert_eq!(policy_sets[0].policies[0].rules[0].effect, "Permit");
+    }
+
+    #[test]
+    fn test_get_delegation_evidence_policy_sets_chain_deny_on_hop() {
+        let pi_to_b = chain_policy_set_row(
+            "pi",
+            "b",
+            1,
+            vec![
+                ResourceRule::Permit(Permit::default()),
+                ResourceRule::Deny(Deny {
+                    target: Target {
+                        resource: Resource {
+                            resource_type: "nice-resource".to_owned(),
+                            identifiers: vec!["chicken".to_owned()],
+                            attributes: vec!["*".to_owned()],
+                        },
+                        actions: vec!["Read".to_owned()],
+                        environment: None,
+                    },
+                    conditions: vec![],
+                }),
+            ],
+        );
+        let b_to_as =
+            chain_policy_set_row("b", "as", 0, vec![ResourceRule::Permit(Permit::default())]);
+
+        let policy_sets = get_delegation_evidence_policy_sets(
+            &chain_delegation_request(),
+            &vec![vec![&pi_to_b, &b_to_as]],
+            &test_context(),
+        );
+
+        assert_eq!(policy_sets.len(), 1);
+        assert_eq!(policy_sets[0].policies[0].rules[0].effect, "Deny");
+    }
+
+    #[test]
+    fn test_get_delegation_evidence_policy_sets_combining_algorithm() {
+        let mut permitting = conditional_policy_set_row(ResourceRule::Permit(Permit::default()));
+        permitting.created = chrono::DateTime::from_timestamp(1715247000, 0);
+        let mut denying = conditional_policy_set_row(ResourceRule::Deny(Deny {
+            target: Target {
+                resource: Resource {
+                    resource_type: "nice-resource".to_owned(),
+                    identifiers: vec!["chicken".to_owned()],
+                    attributes: vec!["chicken".to_owned()],
+                },
+                actions: vec!["Read".to_owned()],
+                environment: None,
+            },
+            conditions: vec![],
+        }));
+        denying.created = chrono::DateTime::from_timestamp(1715247100, 0);
+
+        let delegation_request = DelegationRequest {
+            policy_issuer: "issuer".to_owned(),
+            target: DelegationTarget {
+                access_subject: "as".to_owned(),
+            },
+            policy_sets: vec![PolicySet {
+                policies: vec![read_chicken_policy()],
+            }],
+        };
+        let chains = vec![vec![&denying], vec![&permitting]];
+
+        let effects = |combining_algorithm: Option<CombiningAlgorithm>| {
+            let context = EvaluationContext {
+                combining_algorithm,
+                ..test_context()
+            };
+            get_delegation_evidence_policy_sets(&delegation_request, &chains, &context)
+                .iter()
+                .map(|ps| ps.policies[0].rules[0].effect.clone())
+                .collect::<Vec<_>>()
+        };
+
+        // without an algorithm every matching policy set ends up in the evidence
+        assert_eq!(effects(None), vec!["Deny", "Permit"]);
+        assert_eq!(
+            effects(Some(CombiningAlgorithm::DenyOverrides)),
+            vec!["Deny"]
+        );
+        assert_eq!(
+            effects(Some(CombiningAlgorithm::PermitOverrides)),
+            vec!["Permit"]
+        );
+        // the oldest policy set decides
+        assert_eq!(
+            effects(Some(CombiningAlgorithm::FirstApplicable)),
+            vec!["Permit"]
+        );
+
+        denying.created = chrono::DateTime::from_timestamp(1715246900, 0);
+        let chains = vec![vec![&denying], vec![&permitting]];
+        let context = EvaluationContext {
+            combining_algorithm: Some(CombiningAlgorithm::FirstApplicable),
+            ..test_context()
+        };
+        let policy_sets =
+            get_delegation_evidence_policy_sets(&delegation_request, &chains, &context);
+        assert_eq!(policy_sets[0].policies[0].rules[0].effect, "Deny");
+    }
+
+    #[test]
+    fn test_get_delegation_evidence_policy_sets_combined_depth_and_licenses() {
+        let mut older = conditional_policy_set_row(ResourceRule::Permit(Permit::default()));
+        older.created = chrono::DateTime::from_timestamp(1715247000, 0);
+        older.max_delegation_depth = 3;
+        older.licenses = vec!["ISHARE.0001".to_owned(), "ISHARE.0002".to_owned()];
+        let mut newer = conditional_policy_set_row(ResourceRule::Permit(Permit::default()));
+        newer.created = chrono::DateTime::from_timestamp(1715247100, 0);
+        newer.max_delegation_depth = 1;
+        newer.licenses = vec!["ISHARE.0002".to_owned()];
+
+        let delegation_request = DelegationRequest {
+            policy_issuer: "issuer".to_owned(),
+            target: DelegationTarget {
+                access_subject: "as".to_owned(),
+            },
+            policy_sets: vec![PolicySet {
+                policies: vec![read_chicken_policy()],
+            }],
+        };
+        let chains = vec![vec![&older], vec![&newer]];
+
+        for algorithm in [
+            CombiningAlgorithm::DenyOverrides,
+            CombiningAlgorithm::PermitOverrides,
+        ] {
+            let context = EvaluationContext {
+                combining_algorithm: Some(algorithm),
+                ..test_context()
+            };
+            let policy_sets =
+                get_delegation_evidence_policy_sets(&delegation_request, &chains, &context);
+
+            assert_eq!(policy_sets.len(), 1);
+            assert_eq!(policy_sets[0].max_delegation_depth, 1);
+            assert_eq!(
+                policy_sets[0].target.environment.licenses,
+                vec!["ISHARE.0002".to_owned()]
+            );
+        }
+
+        // only the oldest chain decides with first applicable
+        let context = EvaluationContext {
+            combining_algorithm: Some(CombiningAlgorithm::FirstApplicable),
+            ..test_context()
+        };
+        let policy_sets =
+            get_delegation_evidence_policy_sets(&delegation_request, &chains, &context);
+        assert_eq!(policy_sets[0].max_delegation_depth, 3);
+        assert_eq!(policy_sets[0].target.environment.licenses.len(), 2);
+    }
+
+    fn evidence(effect: &str) -> DelegationEvidence {
+        DelegationEvidence {
+            not_before: 1715247000,
+            not_on_or_after: 1715248000,
+            policy_issuer: "NL.ISSUER".to_owned(),
+            target: ishare::delegation_evidence::DelegationTarget {
+                access_subject: "NL.SUBJECT".to_owned(),
+            },
+            policy_sets: vec![ishare::delegation_evidence::PolicySet {
+                max_delegation_depth: 0,
+                target: PolicySetTarget {
+                    environment: PolicySetTargetEnvironment { licenses: vec![] },
+                },
+                policies: vec![ishare::delegation_evidence::Policy {
+                    target: ishare::delegation_evidence::ResourceTarget {
+                        resource: ishare::delegation_evidence::Resource {
+                            resource_type: "container".to_owned(),
+                            identifiers: vec!["container-*".to_owned()],
+                            attributes: vec!["*".to_owned()],
+                        },
+                        actions: vec!["Read".to_owned()],
+                        environment: None,
+                    },
+                    rules: vec![ishare::delegation_evidence::ResourceRules {
+                        effect: effect.to_owned(),
+                    }],
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_is_evidence_permitting() {
+        let now = chrono::DateTime::from_timestamp(1715247205, 0).unwrap();
+        let intended = |resource_type: &str, identifier: &str, action: &str| IntendedAccess {
+            resource_type: resource_type.to_owned(),
+            identifiers: vec![identifier.to_owned()],
+            attributes: vec![],
+            action: action.to_owned(),
+        };
+
+        assert!(is_evidence_permitting(
+            &evidence("Permit"),
+            now,
+            &intended("container", "container-1", "Read")
+        ));
+        assert!(!is_evidence_permitting(
+            &evidence("Deny"),
+            now,
+            &intended("container", "container-1", "Read")
+        ));
+        assert!(!is_evidence_permitting(
+            &evidence("Permit"),
+            now,
+            &intended("container", "container-1", "Delete")
+        ));
+        assert!(!is_evidence_permitting(
+            &evidence("Permit"),
+            now,
+            &intended("container", "other-1", "Read")
+        ));
+        assert!(!is_evidence_permitting(
+            &evidence("Permit"),
+            now,
+            &intended("vehicle", "container-1", "Read")
+        ));
+        // outside the validity window of the evidence
+        assert!(!is_evidence_permitting(
+            &evidence("Permit"),
+            chrono::DateTime::from_timestamp(1715248000, 0).unwrap(),
+            &intended("container", "container-1", "Read")
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_get_access_subject_registration_only_when_required() {
+        let now = chrono::Utc::now();
+        let satellite_provider = TestSatelliteProvider {};
+        let subject_details = AccessSubjectDetails {
+            satellite_provider: Some(&satellite_provider),
+            ..Default::default()
+        };
+        let adherence = PartyAttributes {
+            adherence_status: Some("Active".to_owned()),
+            ..Default::default()
+        };
+        let roles = PartyAttributes {
+            roles: vec!["ServiceProvider".to_owned()],
+            ..Default::default()
+        };
+
+        assert!(
+            get_access_subject_registration(now, "NL.44444", subject_details, [].iter())
+                .await
+                .is_none()
+        );
+        assert!(get_access_subject_registration(
+            now,
+            "NL.44444",
+            subject_details,
+            [&adherence].into_iter()
+        )
+        .await
+        .is_none());
+        assert!(get_access_subject_registration(
+            now,
+            "NL.44444",
+            subject_details,
+            [&adherence, &roles].into_iter()
+        )
+        .await
+        .is_some_and(|registration| registration.has_role("ServiceProvider")));
+        // without a satellite to fetch it from there is no registration
+        assert!(get_access_subject_registration(
+            now,
+            "NL.44444",
+            AccessSubjectDetails::default(),
+            [&roles].into_iter()
+        )
+        .await
+        .is_none());
+    }
 }
@@ -4,20 +4,20 @@ use sea_orm::DatabaseConnection;
 use serde::{Deserialize, Serialize};
 
 use axum::async_trait;
-use ishare::{
-    delegation_evidence::DelegationEvidenceContainer,
-    ishare::{Capabilities, CertificatesOrSpor, PartyInfo, ValidatePartyError, ISHARE},
-};
-use std::sync::Arc;
+use ishare::ishare::{Capabilities, CertificatesOrSpor, PartyInfo, ValidatePartyError, ISHARE};
+use std::{collections::HashMap, sync::Arc};
 use tokio::sync::RwLock;
 
 use crate::{
     db::{company as company_store, user::insert_if_not_exists},
     error::{AppError, ExpectedError},
     token_cache::TokenCache,
+    utils::{is_same_party, normalize_party_id},
 };
 
-use super::{idp_connector::IdpConnector, server_token::UserOption};
+use super::{
+    delegation::IssuedDelegationEvidence, idp_connector::IdpConnector, server_token::UserOption,
+};
 
 #[derive(Deserialize)]
 struct RealmAccess {
@@ -34,6 +34,68 @@ struct IdTokenClaims {
     pub last_name: String,
 }
 
+#[derive(Deserialize)]
+struct PartiesResponse {
+    parties_token: String,
+}
+
+#[derive(Deserialize)]
+struct PartiesTokenClaims {
+    parties_info: PartiesInfo,
+}
+
+#[derive(Deserialize)]
+struct PartiesInfo {
+    data: Vec<SatelliteParty>,
+}
+
+#[derive(Deserialize)]
+struct SatelliteParty {
+    party_id: String,
+    #[serde(default)]
+    roles: Vec<SatellitePartyRole>,
+    #[serde(default)]
+    certifications: Vec<SatellitePartyRole>,
+    #[serde(default)]
+    agreements: Vec<SatellitePartyAgreement>,
+}
+
+#[derive(Deserialize)]
+struct SatellitePartyRole {
+    role: String,
+    end_date: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct SatellitePartyAgreement {
+    dataspace_id: Option<String>,
+}
+
+// the roles, certifications and dataspaces of the registration of a party at the satellite, they
+// are not part of the party info
+#[derive(Debug, Clone, Default)]
+pub struct PartyRegistration {
+    pub roles: Vec<String>,
+    pub certifications: Vec<String>,
+    pub dataspaces: Vec<String>,
+}
+
+impl PartyRegistration {
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.iter().any(|r| r.eq_ignore_ascii_case(role))
+    }
+}
+
+// roles and certifications without an end date don't expire
+fn is_role_active(role: &SatellitePartyRole, now: chrono::DateTime<chrono::Utc>) -> bool {
+    match &role.end_date {
+        None => true,
+        Some(end_date) => chrono::DateTime::parse_from_rfc3339(end_date)
+            .map(|end_date| end_date > now)
+            .unwrap_or(false),
+    }
+}
+
 #[derive(Serialize)]
 pub struct OAuthRequestForm {
     pub response_type: String,
@@ -84,10 +146,16 @@ pub trait SatelliteProvider: Send + Sync {
         eori: &str,
     ) -> Result<PartyInfo, ValidatePartyError>;
 
+    async fn get_party_registration(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        eori: &str,
+    ) -> anyhow::Result<PartyRegistration>;
+
     fn create_delegation_token(
         &self,
         audience: &str,
-        de_container: &DelegationEvidenceContainer,
+        evidence: &IssuedDelegationEvidence,
     ) -> anyhow::Result<String>;
 
     fn create_capabilities_token(
@@ -96,37 +164,188 @@ pub trait SatelliteProvider: Send + Sync {
         capabilities: &Capabilities,
     ) -> anyhow::Result<String>;
 
-    fn handle_previous_step_client_assertion(
+    // verifies that the client assertion of a previous step is signed by the issuer, a party
+    // known to the satellite, and addressed to the audience
+    async fn verify_previous_step(
         &self,
         now: chrono::DateTime<chrono::Utc>,
-        requestor_company_id: &str,
         client_assertion: &str,
-        policy_issuer: &str,
-        access_subject: &str,
-    ) -> bool;
+        issuer: &str,
+        audience: &str,
+    ) -> anyhow::Result<()>;
+
+    // verifies that a delegation token is signed by the issuer, an authorization registry known
+    // to the satellite, and addressed to the audience
+    async fn verify_delegation_token(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        delegation_token: &str,
+        issuer: &str,
+        audience: &str,
+    ) -> anyhow::Result<()>;
 }
 
 #[derive(Clone)]
 pub struct ISHAREProvider {
     ishare: Arc<ISHARE>,
+    satellite_url: String,
     db: DatabaseConnection,
     idp_connector: IdpConnector,
     satellite_token_cache: Arc<RwLock<TokenCache>>,
+    http_client: reqwest::Client,
+    // the registrations of parties with the timestamp they expire from the cache at
+    party_registration_cache: Arc<RwLock<HashMap<String, (i64, PartyRegistration)>>>,
+    party_registration_cache_ttl_seconds: i64,
 }
 
 impl ISHAREProvider {
     pub fn new(
         ishare: Arc<ISHARE>,
+        satellite_url: &str,
         db: &DatabaseConnection,
         idp_connector: &IdpConnector,
+        party_registration_cache_ttl_seconds: i64,
     ) -> ISHAREProvider {
         return ISHAREProvider {
             ishare: ishare.clone(),
+            satellite_url: satellite_url.to_owned(),
             db: db.clone(),
             idp_connector: idp_connector.clone(),
             satellite_token_cache: TokenCache::new(),
+            http_client: reqwest::Client::new(),
+            party_registration_cache: Arc::new(RwLock::new(HashMap::new())),
+            party_registration_cache_ttl_seconds,
         };
     }
+
+    // verifies the signature of a token against the certificates of the issuer at the satellite
+    async fn verify_signed_token(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        token: &str,
+        issuer: &str,
+        audience: &str,
+    ) -> anyhow::Result<()> {
+        if !self
+            .ishare
+            .validate_token(&token.to_string())
+            .context("Error validating token")?
+        {
+            anyhow::bail!("certificate chain of token is invalid");
+        }
+
+        let decoded_token = self
+            .ishare
+            .decode_token(now, token, issuer, Some(audience))
+            .context("Error decoding token")?;
+
+        let satellite_token = self.get_satellite_token().await?;
+        let party_info = self
+            .ishare
+            .validate_party(now, issuer, &satellite_token)
+            .await
+            .context(format!("error validating ishare party '{}'", issuer))?;
+
+        if !self
+            .ishare
+            .validate_party_certificate(&decoded_token, &party_info)
+            .context(format!(
+                "Error validating party certificate for ishare party: '{}'",
+                issuer
+            ))?
+        {
+            anyhow::bail!("token is not signed with a certificate of '{}'", issuer);
+        }
+
+        Ok(())
+    }
+
+    // the roles, certifications and dataspaces of the party from the parties endpoint of the
+    // satellite
+    async fn fetch_party_registration(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        eori: &str,
+    ) -> anyhow::Result<PartyRegistration> {
+        let token = self
+            .get_satellite_token()
+            .await
+            .context("Error getting sattelite token")?;
+
+        let response = self
+            .http_client
+            .get(format!("{}/parties", self.satellite_url))
+            .query(&[("eori", eori)])
+            .bearer_auth(token)
+            .send()
+            .await
+            .context(format!("error fetching party '{}' from satellite", eori))?;
+
+        if !response.status().is_success() {
+            anyhow::bail!("error response from satellite: {:?}", response);
+        }
+
+        let parties_token = response
+            .json::<PartiesResponse>()
+            .await
+            .context("Error decoding parties response")?
+            .parties_token;
+
+        if !self
+            .ishare
+            .validate_token(&parties_token)
+            .context("Error validating parties token")?
+        {
+            anyhow::bail!("certificate chain of parties token is invalid");
+        }
+
+        let decoded_token = self
+            .ishare
+            .decode_token_custom_claims::<PartiesTokenClaims>(&parties_token, None)
+            .context("Error decoding parties token")?;
+
+        if !is_same_party(
+            &decoded_token.claims.ishare_claims.iss,
+            &self.ishare.satellite_eori,
+        ) {
+            anyhow::bail!(
+                "parties token is issued by '{}' instead of the satellite",
+                decoded_token.claims.ishare_claims.iss
+            );
+        }
+
+        let party = decoded_token
+            .claims
+            .extra
+            .parties_info
+            .data
+            .into_iter()
+            .find(|p| is_same_party(&p.party_id, eori))
+            .context(format!(
+                "party '{}' is not registered at the satellite",
+                eori
+            ))?;
+
+        Ok(PartyRegistration {
+            roles: party
+                .roles
+                .iter()
+                .filter(|r| is_role_active(r, now))
+                .map(|r| r.role.clone())
+                .collect(),
+            certifications: party
+                .certifications
+                .iter()
+                .filter(|c| is_role_active(c, now))
+                .map(|c| c.role.clone())
+                .collect(),
+            dataspaces: party
+                .agreements
+                .into_iter()
+                .filter_map(|a| a.dataspace_id)
+                .collect(),
+        })
+    }
 }
 
 #[async_trait]
@@ -134,10 +353,10 @@ impl SatelliteProvider for ISHAREProvider {
     fn create_delegation_token(
         &self,
         audience: &str,
-        de_container: &DelegationEvidenceContainer,
+        evidence: &IssuedDelegationEvidence,
     ) -> anyhow::Result<String> {
         self.ishare
-            .create_client_assertion_with_extra_claims(audience.to_owned(), de_container)
+            .create_client_assertion_with_extra_claims(audience.to_owned(), evidence)
             .context("Error creating delegation token")
     }
 
@@ -201,6 +420,37 @@ impl SatelliteProvider for ISHAREProvider {
         return Ok(party_info);
     }
 
+    async fn get_party_registration(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        eori: &str,
+    ) -> anyhow::Result<PartyRegistration> {
+        let party_id = normalize_party_id(eori);
+
+        if let Some((expires_at, registration)) =
+            self.party_registration_cache.read().await.get(&party_id)
+        {
+            if *expires_at > now.timestamp() {
+                tracing::info!("retrieving registration of '{}' from cache", eori);
+                return Ok(registration.clone());
+            }
+        }
+
+        let registration = self.fetch_party_registration(now, eori).await?;
+
+        if self.party_registration_cache_ttl_seconds > 0 {
+            self.party_registration_cache.write().await.insert(
+                party_id,
+                (
+                    now.timestamp() + self.party_registration_cache_ttl_seconds,
+                    registration.clone(),
+                ),
+            );
+        }
+
+        Ok(registration)
+    }
+
     fn handle_h2m_redirect_url_request(
         &self,
         server_url: &str,
@@ -275,47 +525,26 @@ impl SatelliteProvider for ISHAREProvider {
         Ok(oauth_params)
     }
 
-    fn handle_previous_step_client_assertion(
+    async fn verify_previous_step(
         &self,
         now: chrono::DateTime<chrono::Utc>,
-        requestor_company_id: &str,
         client_assertion: &str,
-        policy_issuer: &str,
-        access_subject: &str,
-    ) -> bool {
-        let policy_issuer_access = match self.ishare.decode_token(
-            now,
-            client_assertion,
-            policy_issuer,
-            Some(requestor_company_id),
-        ) {
-            Err(e) => {
-                tracing::info!("no acces for policy issuer via previous step: {}", e);
-                false
-            }
-            Ok(_) => {
-                tracing::info!("access granted for policy issuer via previous step");
-                true
-            }
-        };
-
-        let access_subject_access = match self.ishare.decode_token(
-            now,
-            client_assertion,
-            access_subject,
-            Some(requestor_company_id),
-        ) {
-            Err(e) => {
-                tracing::info!("no acces for access subject via previous step: {}", e);
-                false
-            }
-            Ok(_) => {
-                tracing::info!("access granted for access subject via previous step");
-                true
-            }
-        };
+        issuer: &str,
+        audience: &str,
+    ) -> anyhow::Result<()> {
+        self.verify_signed_token(now, client_assertion, issuer, audience)
+            .await
+    }
 
-        policy_issuer_access || access_subject_access
+    async fn verify_delegation_token(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        delegation_token: &str,
+        issuer: &str,
+        audience: &str,
+    ) -> anyhow::Result<()> {
+        self.verify_signed_token(now, delegation_token, issuer, audience)
+            .await
     }
 
     async fn handle_h2m_auth_callback(
@@ -1,6 +1,17 @@
 pub mod audit_log;
+pub mod condition;
+pub mod decision_cache;
 pub mod delegation;
 pub mod idp_connector;
 pub mod ishare_provider;
+pub mod obligation;
+pub mod party_attributes;
+pub mod pattern;
 pub mod policy;
+pub mod policy_approval;
+pub mod policy_bulk;
+pub mod policy_trash;
+pub mod policy_version;
+pub mod previous_steps;
+pub mod resource_type;
 pub mod server_token;
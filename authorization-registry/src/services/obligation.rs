@@ -0,0 +1,196 @@
+use ar_entity::delegation_evidence::{Obligation, Permit, Policy, ResourceRule};
+use axum::http::StatusCode;
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+
+use crate::error::{AppError, ExpectedError};
+
+// the obligations and advice of the permit rules a Permit is based on. every permit rule of every
+// hop of a delegation chain adds to them, the service provider has to honour all of them
+#[derive(Serialize, Deserialize, ToSchema, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct PermitObligations {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub obligations: Vec<Obligation>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub advice: Vec<String>,
+}
+
+impl PermitObligations {
+    pub fn is_empty(&self) -> bool {
+        self.obligations.is_empty() && self.advice.is_empty()
+    }
+
+    // adds the obligations and advice of the permit that are not there yet
+    pub fn add(&mut self, permit: &Permit) {
+        for obligation in permit.obligations.iter() {
+            if !self.obligations.contains(obligation) {
+                self.obligations.push(obligation.clone());
+            }
+        }
+        for advice in permit.advice.iter() {
+            if !self.advice.contains(advice) {
+                self.advice.push(advice.clone());
+            }
+        }
+    }
+}
+
+fn validate_permit_obligations(permit: &Permit) -> Result<(), String> {
+    if permit.obligations.iter().any(|o| o.id.trim().is_empty()) {
+        return Err("Obligation requires an id".to_owned());
+    }
+    if permit.advice.iter().any(|a| a.trim().is_empty()) {
+        return Err("Advice can't be empty".to_owned());
+    }
+
+    Ok(())
+}
+
+pub fn validate_policy_obligations(policy: &Policy) -> Result<(), AppError> {
+    let permits = policy.rules.iter().filter_map(|r| match r {
+        ResourceRule::Permit(p) => Some(p),
+        ResourceRule::Deny(_) => None,
+    });
+
+    for permit in permits {
+        if let Err(message) = validate_permit_obligations(permit) {
+            return Err(AppError::Expected(ExpectedError {
+                status_code: StatusCode::BAD_REQUEST,
+                message: message.clone(),
+                reason: message,
+                metadata: None,
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+// adds the obligations and advice to the rules of the policies of a serialized delegation
+// evidence container. iSHARE evidence has no place for them, the obligations are indexed like
+// the policy sets and policies of the evidence
+pub fn add_obligations_to_evidence(
+    container: &mut serde_json::Value,
+    obligations: &Vec<Vec<PermitObligations>>,
+) {
+    let Some(policy_sets) = container
+        .pointer_mut("/delegationEvidence/policySets")
+        .and_then(|ps| ps.as_array_mut())
+    else {
+        return;
+    };
+
+    for (policy_set, policy_set_obligations) in policy_sets.iter_mut().zip(obligations.iter()) {
+        let Some(policies) = policy_set
+            .get_mut("policies")
+            .and_then(|p| p.as_array_mut())
+        else {
+            continue;
+        };
+
+        for (policy, policy_obligations) in policies.iter_mut().zip(policy_set_obligations.iter()) {
+            if policy_obligations.is_empty() {
+                continue;
+            }
+
+            let (Some(rule), Ok(serde_json::Value::Object(fields))) = (
+                policy
+                    .get_mut("rules")
+                    .and_then(|r| r.get_mut(0))
+                    .and_then(|r| r.as_object_mut()),
+                serde_json::to_value(policy_obligations),
+            ) else {
+                continue;
+            };
+
+            rule.extend(fields);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn permit(obligations: Vec<(&str, Option<&str>)>, advice: Vec<&str>) -> Permit {
+        Permit {
+            conditions: vec![],
+            obligations: obligations
+                .into_iter()
+                .map(|(id, value)| Obligation {
+                    id: id.to_owned(),
+                    value: value.map(|v| v.to_owned()),
+                })
+                .collect(),
+            advice: advice.into_iter().map(|a| a.to_owned()).collect(),
+        }
+    }
+
+    #[test]
+    fn test_add_permit_obligations() {
+        let mut obligations = PermitObligations::default();
+        obligations.add(&permit(
+            vec![("log-access", None), ("max-records", Some("100"))],
+            vec!["contact the owner"],
+        ));
+        obligations.add(&permit(
+            vec![("log-access", None), ("delete-after-days", Some("30"))],
+            vec!["contact the owner"],
+        ));
+
+        assert_eq!(
+            obligations
+                .obligations
+                .iter()
+                .map(|o| o.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["log-access", "max-records", "delete-after-days"]
+        );
+        assert_eq!(obligations.advice, vec!["contact the owner".to_owned()]);
+    }
+
+    #[test]
+    fn test_validate_permit_obligations() {
+        assert!(validate_permit_obligations(&permit(vec![("log-access", None)], vec![])).is_ok());
+        assert!(validate_permit_obligations(&permit(vec![(" ", None)], vec![])).is_err());
+        assert!(validate_permit_obligations(&permit(vec![], vec![""])).is_err());
+    }
+
+    #[test]
+    fn test_add_obligations_to_evidence() {
+        let mut container = serde_json::json!({
+            "delegationEvidence": {
+                "policySets": [
+                    {
+                        "policies": [
+                            { "rules": [{ "effect": "Permit" }] },
+                            { "rules": [{ "effect": "Deny" }] }
+                        ]
+                    }
+                ]
+            }
+        });
+
+        let mut obligations = PermitObligations::default();
+        obligations.add(&permit(vec![("log-access", None)], vec![]));
+
+        add_obligations_to_evidence(
+            &mut container,
+            &vec![vec![obligations, PermitObligations::default()]],
+        );
+
+        let policies = &container["delegationEvidence"]["policySets"][0]["policies"];
+        assert_eq!(
+            policies[0]["rules"][0],
+            serde_json::json!({
+                "effect": "Permit",
+                "obligations": [{ "id": "log-access" }]
+            })
+        );
+        assert_eq!(
+            policies[1]["rules"][0],
+            serde_json::json!({ "effect": "Deny" })
+        );
+    }
+}
@@ -0,0 +1,261 @@
+use ar_entity::delegation_evidence::{PartyAttributes, PartyAuthentication};
+use axum::http::StatusCode;
+use ishare::ishare::{CertificatesOrSpor, PartyInfo};
+
+use crate::error::{AppError, ExpectedError};
+use crate::utils::ANY_PARTY;
+
+use super::ishare_provider::PartyRegistration;
+
+fn invalid_access_subject_attributes(message: &str) -> AppError {
+    AppError::Expected(ExpectedError {
+        status_code: StatusCode::BAD_REQUEST,
+        message: message.to_owned(),
+        reason: message.to_owned(),
+        metadata: None,
+    })
+}
+
+// a policy set applies to any party only through its attributes, and only to the party the
+// evidence is issued to as its satellite registration is not known for the hops of a chain
+pub fn validate_access_subject_attributes(
+    access_subject: &str,
+    attributes: &Option<PartyAttributes>,
+    max_delegation_depth: i32,
+) -> Result<(), AppError> {
+    match attributes {
+        None if access_subject.trim() == ANY_PARTY => Err(invalid_access_subject_attributes(
+            "Access subject '*' requires access subject attributes",
+        )),
+        None => Ok(()),
+        Some(_) if access_subject.trim() != ANY_PARTY => Err(invalid_access_subject_attributes(
+            "Access subject attributes require the access subject '*'",
+        )),
+        Some(attributes) if *attributes == PartyAttributes::default() => Err(
+            invalid_access_subject_attributes("Access subject attributes can't be empty"),
+        ),
+        Some(_) if max_delegation_depth > 0 => Err(invalid_access_subject_attributes(
+            "A policy set with access subject attributes can't allow further delegation",
+        )),
+        Some(_) => Ok(()),
+    }
+}
+
+fn is_adherence_valid(party: &PartyInfo, now: chrono::DateTime<chrono::Utc>) -> bool {
+    match chrono::DateTime::parse_from_rfc3339(&party.adherence.end_date) {
+        Ok(end_date) => end_date > now,
+        Err(e) => {
+            tracing::warn!(
+                "unable to parse adherence end date '{}' of party '{}': {}",
+                &party.adherence.end_date,
+                &party.party_id,
+                e
+            );
+            false
+        }
+    }
+}
+
+fn contains_ignore_case(values: &[String], value: &str) -> bool {
+    values.iter().any(|v| v.eq_ignore_ascii_case(value))
+}
+
+// whether the attributes can only be matched against the satellite registration of the party
+pub fn requires_registration(attributes: &PartyAttributes) -> bool {
+    !attributes.roles.is_empty()
+        || !attributes.certifications.is_empty()
+        || attributes.dataspace.is_some()
+}
+
+// whether the registration satisfies the roles, certifications and dataspace of the attributes.
+// attributes that require any of them don't match without the registration
+fn is_matching_registration(
+    attributes: &PartyAttributes,
+    registration: Option<&PartyRegistration>,
+) -> bool {
+    if !requires_registration(attributes) {
+        return true;
+    }
+
+    let Some(registration) = registration else {
+        return false;
+    };
+
+    attributes
+        .roles
+        .iter()
+        .all(|role| contains_ignore_case(&registration.roles, role))
+        && attributes
+            .certifications
+            .iter()
+            .all(|certification| contains_ignore_case(&registration.certifications, certification))
+        && attributes
+            .dataspace
+            .as_ref()
+            .is_none_or(|dataspace| contains_ignore_case(&registration.dataspaces, dataspace))
+}
+
+// whether the satellite registration of the party satisfies all of the attributes
+pub fn is_matching_party(
+    attributes: &PartyAttributes,
+    party: &PartyInfo,
+    registration: Option<&PartyRegistration>,
+    now: chrono::DateTime<chrono::Utc>,
+) -> bool {
+    let adherence_status = attributes
+        .adherence_status
+        .as_ref()
+        .is_none_or(|status| status.eq_ignore_ascii_case(&party.adherence.status));
+    let adherence_valid = !attributes.adherence_valid || is_adherence_valid(party, now);
+    let authentication = matches!(
+        (&attributes.authentication, &party.certificates_or_spor),
+        (None, _)
+            | (
+                Some(PartyAuthentication::Certificates),
+                CertificatesOrSpor::Certificates(_)
+            )
+            | (Some(PartyAuthentication::Spor), CertificatesOrSpor::Spor(_))
+    );
+
+    adherence_status
+        && adherence_valid
+        && authentication
+        && is_matching_registration(attributes, registration)
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::TimeZone;
+    use ishare::ishare::Adherence;
+
+    use super::*;
+
+    fn party(status: &str, end_date: &str) -> PartyInfo {
+        PartyInfo {
+            capability_url: "capabilities".to_owned(),
+            adherence: Adherence {
+                status: status.to_owned(),
+                end_date: end_date.to_owned(),
+            },
+            party_id: "NL.PARTY".to_owned(),
+            party_name: "party".to_owned(),
+            certificates_or_spor: CertificatesOrSpor::Certificates(vec![]),
+            agreements: vec![],
+        }
+    }
+
+    #[test]
+    fn test_is_matching_party() {
+        let now = chrono::Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let attributes = PartyAttributes {
+            adherence_status: Some("Active".to_owned()),
+            adherence_valid: true,
+            authentication: Some(PartyAuthentication::Certificates),
+            ..Default::default()
+        };
+
+        assert!(is_matching_party(
+            &attributes,
+            &party("active", "2026-03-25T00:00:00.000Z"),
+            None,
+            now
+        ));
+        assert!(!is_matching_party(
+            &attributes,
+            &party("Revoked", "2026-03-25T00:00:00.000Z"),
+            None,
+            now
+        ));
+        assert!(!is_matching_party(
+            &attributes,
+            &party("Active", "2024-03-25T00:00:00.000Z"),
+            None,
+            now
+        ));
+        assert!(!is_matching_party(
+            &attributes,
+            &party("Active", "not a date"),
+            None,
+            now
+        ));
+        assert!(!is_matching_party(
+            &PartyAttributes {
+                authentication: Some(PartyAuthentication::Spor),
+                ..Default::default()
+            },
+            &party("Active", "2026-03-25T00:00:00.000Z"),
+            None,
+            now
+        ));
+    }
+
+    #[test]
+    fn test_is_matching_party_registration() {
+        let now = chrono::Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap();
+        let attributes = PartyAttributes {
+            roles: vec!["ServiceProvider".to_owned()],
+            certifications: vec!["ISO27001".to_owned()],
+            dataspace: Some("dexes".to_owned()),
+            ..Default::default()
+        };
+        let registration = PartyRegistration {
+            roles: vec!["ServiceConsumer".to_owned(), "serviceprovider".to_owned()],
+            certifications: vec!["ISO27001".to_owned()],
+            dataspaces: vec!["DEXES".to_owned()],
+        };
+        let party = party("Active", "2026-03-25T00:00:00.000Z");
+
+        assert!(is_matching_party(
+            &attributes,
+            &party,
+            Some(&registration),
+            now
+        ));
+        // the registration is required for roles, certifications and dataspaces
+        assert!(!is_matching_party(&attributes, &party, None, now));
+        assert!(!is_matching_party(
+            &attributes,
+            &party,
+            Some(&PartyRegistration {
+                certifications: vec![],
+                ..registration.clone()
+            }),
+            now
+        ));
+        assert!(!is_matching_party(
+            &attributes,
+            &party,
+            Some(&PartyRegistration {
+                dataspaces: vec!["other".to_owned()],
+                ..registration.clone()
+            }),
+            now
+        ));
+        assert!(!is_matching_party(
+            &PartyAttributes {
+                roles: vec!["AuthorisationRegistry".to_owned()],
+                ..Default::default()
+            },
+            &party,
+            Some(&registration),
+            now
+        ));
+    }
+
+    #[test]
+    fn test_validate_access_subject_attributes() {
+        let attributes = Some(PartyAttributes {
+            adherence_status: Some("Active".to_owned()),
+            ..Default::default()
+        });
+
+        assert!(validate_access_subject_attributes("NL.PARTY", &None, 1).is_ok());
+        assert!(validate_access_subject_attributes("*", &attributes, 0).is_ok());
+        assert!(validate_access_subject_attributes("*", &None, 0).is_err());
+        assert!(validate_access_subject_attributes("NL.PARTY", &attributes, 0).is_err());
+        assert!(validate_access_subject_attributes("*", &attributes, 1).is_err());
+        assert!(
+            validate_access_subject_attributes("*", &Some(PartyAttributes::default()), 0).is_err()
+        );
+    }
+}
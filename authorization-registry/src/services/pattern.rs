@@ -0,0 +1,248 @@
+use std::collections::HashMap;
+use std::sync::{LazyLock, RwLock};
+
+use ar_entity::delegation_evidence::{Policy, ResourceRule};
+use axum::http::StatusCode;
+use regex::Regex;
+
+use crate::error::{AppError, ExpectedError};
+
+// identifiers and attributes of a policy can be patterns:
+// - 'regex:<expression>' matches when the whole value matches the regular expression
+// - a value containing '*' or '?' is a glob, e.g. 'urn:container:NL*'
+// - any other value, including a '*' that is not the first element, matches exactly
+pub const REGEX_PREFIX: &str = "regex:";
+
+fn is_glob(pattern: &str) -> bool {
+    pattern != "*" && pattern.contains(['*', '?'])
+}
+
+// true when the value matches more than itself: '*', a glob or a regex
+pub fn is_pattern(value: &str) -> bool {
+    value == "*" || value.starts_with(REGEX_PREFIX) || is_glob(value)
+}
+
+fn compile_regex(expression: &str) -> Result<Regex, regex::Error> {
+    Regex::new(&format!("^(?:{})$", expression))
+}
+
+// '*' matches any sequence of characters and '?' matches a single character
+fn is_matching_glob(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+
+    let (mut p, mut v) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == value[v]) {
+            p += 1;
+            v += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, v));
+            p += 1;
+        } else if let Some((star_p, star_v)) = backtrack {
+            p = star_p + 1;
+            v = star_v + 1;
+            backtrack = Some((star_p, star_v + 1));
+        } else {
+            return false;
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// the maximum number of compiled regexes kept, the cache starts over when it is full
+const MAX_CACHED_REGEXES: usize = 10_000;
+
+// patterns are evaluated for every policy of every delegation request, each regex is compiled
+// once. None for an expression that doesn't compile
+static REGEX_CACHE: LazyLock<RwLock<HashMap<String, Option<Regex>>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+fn get_compiled_regex(expression: &str) -> Option<Regex> {
+    if let Some(regex) = REGEX_CACHE
+        .read()
+        .expect("regex cache lock poisoned")
+        .get(expression)
+    {
+        return regex.clone();
+    }
+
+    let regex = match compile_regex(expression) {
+        Ok(regex) => Some(regex),
+        Err(e) => {
+            tracing::warn!("invalid pattern '{}{}': {}", REGEX_PREFIX, expression, e);
+            None
+        }
+    };
+
+    let mut cache = REGEX_CACHE.write().expect("regex cache lock poisoned");
+    if cache.len() >= MAX_CACHED_REGEXES {
+        cache.clear();
+    }
+    cache.insert(expression.to_owned(), regex.clone());
+
+    regex
+}
+
+pub fn is_matching_pattern(pattern: &str, value: &str) -> bool {
+    if let Some(expression) = pattern.strip_prefix(REGEX_PREFIX) {
+        return get_compiled_regex(expression).is_some_and(|regex| regex.is_match(value));
+    }
+
+    if is_glob(pattern) {
+        return is_matching_glob(pattern, value);
+    }
+
+    pattern == value
+}
+
+// returns true if either the first element of patterns is a star: ['*']
+// or if all the values are matched by one of the patterns
+pub fn star_or_matched_by(values: &Vec<String>, patterns: &Vec<String>) -> bool {
+    patterns.get(0).is_some_and(|i| i == "*")
+        || values
+            .iter()
+            .all(|v| patterns.iter().any(|p| is_matching_pattern(p, v)))
+}
+
+pub fn validate_pattern(pattern: &str) -> Result<(), String> {
+    if let Some(expression) = pattern.strip_prefix(REGEX_PREFIX) {
+        if expression.is_empty() {
+            return Err(format!("Pattern '{}' has an empty expression", pattern));
+        }
+        if let Err(e) = compile_regex(expression) {
+            return Err(format!("Pattern '{}' is not a valid regex: {}", pattern, e));
+        }
+    }
+
+    Ok(())
+}
+
+pub fn validate_policy_patterns(policy: &Policy) -> Result<(), AppError> {
+    let deny_patterns = policy.rules.iter().flat_map(|r| match r {
+        ResourceRule::Permit(_) => vec![],
+        ResourceRule::Deny(d) => d
+            .target
+            .resource
+            .identifiers
+            .iter()
+            .chain(d.target.resource.attributes.iter())
+            .collect(),
+    });
+
+    let patterns = policy
+        .target
+        .resource
+        .identifiers
+        .iter()
+        .chain(policy.target.resource.attributes.iter())
+        .chain(deny_patterns);
+
+    for pattern in patterns {
+        if let Err(message) = validate_pattern(pattern) {
+            return Err(AppError::Expected(ExpectedError {
+                status_code: StatusCode::BAD_REQUEST,
+                message: message.clone(),
+                reason: message,
+                metadata: None,
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_matching_pattern_exact() {
+        assert!(is_matching_pattern("container-1", "container-1"));
+        assert!(!is_matching_pattern("container-1", "container-2"));
+        assert!(is_matching_pattern("*", "*"));
+        assert!(!is_matching_pattern("*", "fish"));
+    }
+
+    #[test]
+    fn test_is_matching_pattern_glob() {
+        assert!(is_matching_pattern(
+            "urn:container:NL*",
+            "urn:container:NL1234"
+        ));
+        assert!(is_matching_pattern("urn:container:NL*", "urn:container:NL"));
+        assert!(!is_matching_pattern(
+            "urn:container:NL*",
+            "urn:container:DE1234"
+        ));
+        assert!(is_matching_pattern("urn:*:NL?", "urn:container:NL1"));
+        assert!(!is_matching_pattern("urn:*:NL?", "urn:container:NL12"));
+        assert!(is_matching_pattern("*-2024-*", "report-2024-05"));
+    }
+
+    #[test]
+    fn test_is_matching_pattern_regex() {
+        assert!(is_matching_pattern(
+            "regex:urn:container:(NL|BE)[0-9]{4}",
+            "urn:container:BE1234"
+        ));
+        // regexes are anchored
+        assert!(!is_matching_pattern(
+            "regex:urn:container:(NL|BE)[0-9]{4}",
+            "urn:container:BE12345"
+        ));
+        assert!(!is_matching_pattern("regex:(", "("));
+    }
+
+    #[test]
+    fn test_get_compiled_regex_is_cached() {
+        let expression = "urn:cached:[0-9]+";
+        let regex = get_compiled_regex(expression).unwrap();
+
+        assert!(REGEX_CACHE.read().unwrap().contains_key(expression));
+        assert_eq!(
+            get_compiled_regex(expression).unwrap().as_str(),
+            regex.as_str()
+        );
+        assert!(get_compiled_regex("(").is_none());
+    }
+
+    #[test]
+    fn test_star_or_matched_by() {
+        assert!(star_or_matched_by(
+            &vec!["fish".to_owned()],
+            &vec!["*".to_owned()]
+        ));
+        assert!(star_or_matched_by(
+            &vec!["urn:NL1".to_owned(), "urn:BE1".to_owned()],
+            &vec!["urn:NL*".to_owned(), "urn:BE1".to_owned()]
+        ));
+        assert!(!star_or_matched_by(
+            &vec!["urn:NL1".to_owned(), "urn:DE1".to_owned()],
+            &vec!["urn:NL*".to_owned(), "urn:BE1".to_owned()]
+        ));
+        assert!(!star_or_matched_by(
+            &vec!["fish".to_owned()],
+            &vec!["chicken".to_owned(), "*".to_owned()]
+        ));
+    }
+
+    #[test]
+    fn test_is_pattern() {
+        assert!(is_pattern("*"));
+        assert!(is_pattern("urn:container:NL*"));
+        assert!(is_pattern("regex:urn:[a-z]+"));
+        assert!(!is_pattern("urn:container:NL1"));
+    }
+
+    #[test]
+    fn test_validate_pattern() {
+        assert!(validate_pattern("urn:container:NL*").is_ok());
+        assert!(validate_pattern("regex:urn:[a-z]+").is_ok());
+        assert!(validate_pattern("regex:urn:[a-z").is_err());
+        assert!(validate_pattern("regex:").is_err());
+    }
+}
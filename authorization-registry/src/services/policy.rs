@@ -1,44 +1,69 @@
+use std::collections::HashMap;
+
 use anyhow::Context;
 use ar_entity::delegation_evidence::ResourceRule;
+use ar_entity::policy_set::PolicySetStatus;
 use chrono::Utc;
 use ishare::delegation_evidence::verify_delegation_evidence;
 use ishare::delegation_request::{DelegationRequest, DelegationTarget, ResourceTarget};
 use reqwest::StatusCode;
-use sea_orm::{DatabaseConnection, TransactionTrait};
-use serde::Deserialize;
+use sea_orm::{ConnectionTrait, DatabaseConnection, TransactionTrait};
+use serde::{Deserialize, Serialize};
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::config::CombiningAlgorithmConfig;
+use crate::db::license as license_store;
+use crate::db::party_group as party_group_store;
 use crate::db::policy::{self as policy_store, AccessSubjectTarget, MatchingPolicySetRow};
+use crate::db::policy_version::{self as policy_version_store, PolicySetChange};
+use crate::db::user as user_store;
 use crate::error::{AppError, ExpectedError};
 use crate::services::audit_log::{
-    log_event, PolicyAdded, PolicyRemoved, PolicyReplaced, PolicySetCreatedEventMetadata,
-    PolicySetDeletedEventMetadata, PolicySetEditedEventMetadata,
+    log_event, MetadataEdited, PolicyAdded, PolicyRemoved, PolicyReplaced,
+    PolicySetCreatedEventMetadata, PolicySetDeletedEventMetadata, PolicySetEditedEventMetadata,
+    PolicySetMetadata,
+};
+use crate::services::condition::validate_policy_conditions;
+use crate::services::decision_cache::DecisionCache;
+use crate::services::delegation::{create_delegation_evidence, AccessSubjectDetails};
+use crate::services::obligation::validate_policy_obligations;
+use crate::services::party_attributes::validate_access_subject_attributes;
+use crate::services::pattern::validate_policy_patterns;
+use crate::services::policy_approval::initial_policy_set_status;
+use crate::services::resource_type::{
+    validate_policies_resource_types, validate_policy_resource_type,
 };
-use crate::services::delegation::create_delegation_evidence;
+use crate::utils::{is_same_party, normalize_party_id, party_group_name, ANY_PARTY};
 use crate::TimeProvider;
 
 use super::ishare_provider::SatelliteProvider;
 
+// party groups are not iSHARE parties, their members are validated when they are added. an
+// access subject with attributes is validated when it requests delegation evidence
 pub async fn validate_policy_set_ishare_parties(
     now: chrono::DateTime<chrono::Utc>,
     args: &InsertPolicySetWithPolicies,
     ishare: std::sync::Arc<dyn SatelliteProvider>,
 ) -> Result<(), AppError> {
-    ishare
-        .validate_party(now, &args.target.access_subject)
-        .await
-        .map_err(|e| {
-            AppError::Expected(ExpectedError {
-                status_code: StatusCode::BAD_REQUEST,
-                message: format!(
-                    "Unable to verify access subject '{}' as valid iSHARE party",
-                    &args.target.access_subject
-                ),
-                reason: format!("{:?}", e),
-                metadata: None,
-            })
-        })?;
+    if party_group_name(&args.target.access_subject).is_none()
+        && args.target.access_subject_attributes.is_none()
+    {
+        ishare
+            .validate_party(now, &args.target.access_subject)
+            .await
+            .map_err(|e| {
+                AppError::Expected(ExpectedError {
+                    status_code: StatusCode::BAD_REQUEST,
+                    message: format!(
+                        "Unable to verify access subject '{}' as valid iSHARE party",
+                        &args.target.access_subject
+                    ),
+                    reason: format!("{:?}", e),
+                    metadata: None,
+                })
+            })?;
+    }
 
     ishare
         .validate_party(now, &args.policy_issuer)
@@ -56,7 +81,13 @@ pub async fn validate_policy_set_ishare_parties(
         })?;
 
     for p in args.policies.iter() {
-        for sp in p.target.environment.service_providers.iter() {
+        for sp in p
+            .target
+            .environment
+            .service_providers
+            .iter()
+            .filter(|sp| party_group_name(sp).is_none())
+        {
             ishare.validate_party(now, sp).await.map_err(|e| {
                 AppError::Expected(ExpectedError {
                     status_code: StatusCode::BAD_REQUEST,
@@ -74,16 +105,135 @@ pub async fn validate_policy_set_ishare_parties(
     Ok(())
 }
 
+// the licenses of a policy set must be registered in the license registry
+pub async fn validate_policy_set_licenses(
+    args: &InsertPolicySetWithPolicies,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    let unknown = license_store::get_unknown_licenses(&args.licences, db)
+        .await
+        .context("Error validating licenses of policy set")?;
+
+    if !unknown.is_empty() {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::BAD_REQUEST,
+            message: format!("Unknown license(s): {}", unknown.join(", ")),
+            reason: format!("licenses {:?} are not in the license registry", unknown),
+            metadata: None,
+        }));
+    }
+
+    Ok(())
+}
+
+// the party groups among the parties must exist
+pub async fn validate_party_group_references(
+    parties: &Vec<String>,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    let mut names: Vec<String> = parties.iter().filter_map(|p| party_group_name(p)).collect();
+    names.sort();
+    names.dedup();
+
+    let unknown = party_group_store::get_unknown_party_groups(&names, db)
+        .await
+        .context("Error validating party groups")?;
+
+    if !unknown.is_empty() {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::BAD_REQUEST,
+            message: format!("Unknown party group(s): {}", unknown.join(", ")),
+            reason: format!("party groups {:?} don't exist", unknown),
+            metadata: None,
+        }));
+    }
+
+    Ok(())
+}
+
+pub async fn validate_policy_set_party_groups(
+    args: &InsertPolicySetWithPolicies,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    let parties: Vec<String> = std::iter::once(&args.target.access_subject)
+        .chain(
+            args.policies
+                .iter()
+                .flat_map(|p| p.target.environment.service_providers.iter()),
+        )
+        .cloned()
+        .collect();
+
+    validate_party_group_references(&parties, db).await
+}
+
+fn invalid_access_subject_user(message: String) -> AppError {
+    AppError::Expected(ExpectedError {
+        status_code: StatusCode::BAD_REQUEST,
+        message: message.clone(),
+        reason: message,
+        metadata: None,
+    })
+}
+
+// a policy set issued to a user of the access subject grants rights to that user only, the user
+// must be known to the registry as a user of the access subject and can't delegate them further
+pub async fn validate_policy_set_access_subject_user(
+    args: &InsertPolicySetWithPolicies,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    let Some(user_id) = &args.target.access_subject_user else {
+        return Ok(());
+    };
+
+    let access_subject = &args.target.access_subject;
+    if party_group_name(access_subject).is_some() || access_subject.trim() == ANY_PARTY {
+        return Err(invalid_access_subject_user(format!(
+            "Access subject '{}' of a policy set issued to a user must be a single party",
+            access_subject
+        )));
+    }
+
+    if args.max_delegation_depth > 0 {
+        return Err(invalid_access_subject_user(
+            "Policy sets issued to a user can't be delegated".to_owned(),
+        ));
+    }
+
+    let user = user_store::get_user_by_id(user_id, db)
+        .await
+        .context("Error validating user of access subject")?;
+
+    match user {
+        Some(user) if is_same_party(&user.company, access_subject) => Ok(()),
+        _ => Err(invalid_access_subject_user(format!(
+            "Unknown user '{}' of access subject '{}'",
+            user_id, access_subject
+        ))),
+    }
+}
+
 pub async fn insert_policy_set_with_policies(
     now: chrono::DateTime<chrono::Utc>,
     requester_company_id: &str,
     args: &InsertPolicySetWithPolicies,
     db: &DatabaseConnection,
     client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
     time_provider: std::sync::Arc<dyn TimeProvider>,
     ishare: std::sync::Arc<dyn SatelliteProvider>,
+    decision_cache: &DecisionCache,
 ) -> Result<Uuid, AppError> {
+    validate_policy_set_validity_window(args)?;
+    validate_policy_set_access_subject(args)?;
+    for policy in args.policies.iter() {
+        validate_policy(policy)?;
+    }
     validate_policy_set_ishare_parties(now, args, ishare).await?;
+    validate_policy_set_licenses(args, db).await?;
+    validate_policy_set_party_groups(args, db).await?;
+    validate_policy_set_access_subject_user(args, db).await?;
+    validate_policies_resource_types(&args.policies, db).await?;
 
     let identifiers = args
         .policies
@@ -98,6 +248,7 @@ pub async fn insert_policy_set_with_policies(
         &args.target.access_subject,
         identifiers,
         client_eori,
+        combining_algorithms,
         time_provider,
         &db,
     )
@@ -113,14 +264,18 @@ pub async fn insert_policy_set_with_policies(
         }));
     }
 
-    let policy_set_id = insert_policy_set_with_policies_into_db(now, args, db)
+    let status = initial_policy_set_status(requester_company_id, &args.policy_issuer, db).await?;
+
+    let policy_set_id = insert_policy_set_with_policies_into_db(now, args, status, db)
         .await
         .context("Error inserting policy set with policies")?;
 
+    decision_cache.invalidate(&args.target.access_subject, args.max_delegation_depth);
+
     Ok(policy_set_id)
 }
 
-#[derive(Deserialize, ToSchema)]
+#[derive(Deserialize, Serialize, ToSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct InsertPolicySetWithPolicies {
     pub target: AccessSubjectTarget,
@@ -128,51 +283,164 @@ pub struct InsertPolicySetWithPolicies {
     pub licences: Vec<String>,
     pub policies: Vec<ar_entity::delegation_evidence::Policy>,
     pub max_delegation_depth: i32,
+    #[serde(default)]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub not_before: Option<chrono::DateTime<Utc>>,
+    #[serde(default)]
+    #[schema(value_type = Option<String>, format = DateTime)]
+    pub not_on_or_after: Option<chrono::DateTime<Utc>>,
+}
+
+impl From<&MatchingPolicySetRow> for InsertPolicySetWithPolicies {
+    fn from(policy_set: &MatchingPolicySetRow) -> Self {
+        Self {
+            target: AccessSubjectTarget {
+                access_subject: policy_set.access_subject.clone(),
+                access_subject_attributes: policy_set.access_subject_attributes.clone(),
+                access_subject_user: policy_set.access_subject_user.clone(),
+            },
+            policy_issuer: policy_set.policy_issuer.clone(),
+            licences: policy_set.licenses.clone(),
+            policies: policy_set.policies.iter().map(|p| p.to_policy()).collect(),
+            max_delegation_depth: policy_set.max_delegation_depth,
+            not_before: policy_set.not_before,
+            not_on_or_after: policy_set.not_on_or_after,
+        }
+    }
+}
+
+pub fn validate_policy_set_access_subject(
+    args: &InsertPolicySetWithPolicies,
+) -> Result<(), AppError> {
+    validate_access_subject_attributes(
+        &args.target.access_subject,
+        &args.target.access_subject_attributes,
+        args.max_delegation_depth,
+    )
+}
+
+pub fn validate_policy_set_validity_window(
+    args: &InsertPolicySetWithPolicies,
+) -> Result<(), AppError> {
+    if let (Some(not_before), Some(not_on_or_after)) = (args.not_before, args.not_on_or_after) {
+        if not_before >= not_on_or_after {
+            return Err(AppError::Expected(ExpectedError {
+                status_code: StatusCode::BAD_REQUEST,
+                message: "'notBefore' must be before 'notOnOrAfter'".to_owned(),
+                reason: format!(
+                    "validity window of policy set is empty: '{}' - '{}'",
+                    not_before, not_on_or_after
+                ),
+                metadata: None,
+            }));
+        }
+    }
+
+    Ok(())
+}
+
+// validates the parts of a policy that can't be expressed in its type, e.g. conditions and patterns
+pub fn validate_policy(policy: &ar_entity::delegation_evidence::Policy) -> Result<(), AppError> {
+    validate_policy_conditions(policy)?;
+    validate_policy_patterns(policy)?;
+    validate_policy_obligations(policy)?;
+
+    Ok(())
+}
+
+// the policies of a template become the policies of policy sets, so they are validated the same way
+pub fn validate_template_policy(
+    policy: &ar_entity::policy_set_template::Policy,
+) -> Result<(), AppError> {
+    validate_policy(&ar_entity::delegation_evidence::Policy {
+        target: ar_entity::delegation_evidence::ResourceTarget {
+            resource: ar_entity::delegation_evidence::Resource {
+                resource_type: policy.resource_type.clone(),
+                identifiers: policy.identifiers.clone(),
+                attributes: policy.attributes.clone(),
+            },
+            actions: policy.actions.clone(),
+            environment: ar_entity::delegation_evidence::Environment {
+                service_providers: policy.service_providers.clone(),
+            },
+        },
+        rules: policy.rules.clone(),
+    })
 }
 
 pub async fn insert_policy_set_with_policies_into_db(
     now: chrono::DateTime<Utc>,
     args: &InsertPolicySetWithPolicies,
+    status: PolicySetStatus,
     db: &DatabaseConnection,
 ) -> anyhow::Result<Uuid> {
     let transaction = db.begin().await.context("Error opening db transaction")?;
 
-    let policy_set_id = policy_store::insert_policy_set(
+    let policy_set_id = Uuid::new_v4();
+    insert_policy_set_with_policies_in_transaction(now, policy_set_id, args, status, &transaction)
+        .await?;
+
+    transaction
+        .commit()
+        .await
+        .context("Error commiting transaction to db")?;
+
+    Ok(policy_set_id)
+}
+
+// inserts the policy set with its first version and audit event, as part of a larger transaction
+pub async fn insert_policy_set_with_policies_in_transaction<C: ConnectionTrait>(
+    now: chrono::DateTime<Utc>,
+    policy_set_id: Uuid,
+    args: &InsertPolicySetWithPolicies,
+    status: PolicySetStatus,
+    transaction: &C,
+) -> anyhow::Result<()> {
+    policy_store::insert_policy_set(
         now,
+        policy_set_id,
         &args.target,
         &args.policy_issuer,
         &args.licences,
         &args.max_delegation_depth,
-        &transaction,
+        args.not_before,
+        args.not_on_or_after,
+        status,
+        transaction,
     )
     .await
     .context("Error inserting policy set into db")?;
 
     for policy in args.policies.iter() {
-        policy_store::insert_policy(policy_set_id, &policy, &transaction)
+        policy_store::insert_policy(policy_set_id, policy, transaction)
             .await
             .context("Error inserting policy into db")?;
     }
 
+    policy_version_store::insert_policy_set_version(
+        now,
+        &policy_set_id,
+        PolicySetChange::Created,
+        transaction,
+    )
+    .await
+    .context("Error recording policy set version")?;
+
     log_event(
         now,
         policy_set_id.to_string(),
         super::audit_log::EventType::ArPolicySetCreated(PolicySetCreatedEventMetadata {
             policy_set_id: policy_set_id.to_owned(),
+            status,
         }),
         None,
         None,
-        &transaction,
+        transaction,
     )
     .await
     .context("error logging policy set created event")?;
 
-    transaction
-        .commit()
-        .await
-        .context("Error commiting transaction to db")?;
-
-    Ok(policy_set_id)
+    Ok(())
 }
 
 pub async fn insert_policy_set_with_policies_admin(
@@ -180,12 +448,25 @@ pub async fn insert_policy_set_with_policies_admin(
     args: &InsertPolicySetWithPolicies,
     db: &DatabaseConnection,
     ishare: std::sync::Arc<dyn SatelliteProvider>,
+    decision_cache: &DecisionCache,
 ) -> Result<Uuid, AppError> {
+    validate_policy_set_validity_window(args)?;
+    validate_policy_set_access_subject(args)?;
+    for policy in args.policies.iter() {
+        validate_policy(policy)?;
+    }
     validate_policy_set_ishare_parties(now, args, ishare).await?;
+    validate_policy_set_licenses(args, db).await?;
+    validate_policy_set_party_groups(args, db).await?;
+    validate_policy_set_access_subject_user(args, db).await?;
+    validate_policies_resource_types(&args.policies, db).await?;
 
-    let policy_set_id = insert_policy_set_with_policies_into_db(now, args, db)
-        .await
-        .context("Error inserting policy set with policies")?;
+    let policy_set_id =
+        insert_policy_set_with_policies_into_db(now, args, PolicySetStatus::Active, db)
+            .await
+            .context("Error inserting policy set with policies")?;
+
+    decision_cache.invalidate(&args.target.access_subject, args.max_delegation_depth);
 
     Ok(policy_set_id)
 }
@@ -215,6 +496,7 @@ pub async fn verify_policy_set_access(
     access_subject: &str,
     resource_types: Vec<String>,
     client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
     time_provider: std::sync::Arc<dyn TimeProvider>,
     db: &DatabaseConnection,
 ) -> anyhow::Result<bool> {
@@ -224,12 +506,14 @@ pub async fn verify_policy_set_access(
         action.to_string()
     );
 
-    if requestor_company_id == policy_issuer {
+    if is_same_party(requestor_company_id, policy_issuer) {
         tracing::info!("access granted because issuer matches requestor");
         return Ok(true);
     }
 
-    if matches!(action, PolicySetAction::Read) && requestor_company_id == access_subject {
+    if matches!(action, PolicySetAction::Read)
+        && is_same_party(requestor_company_id, access_subject)
+    {
         tracing::info!("access granted for action read because access subject matches requestor");
         return Ok(true);
     }
@@ -268,13 +552,21 @@ pub async fn verify_policy_set_access(
         &client_eori,
     );
 
-    let delegation_evidence_container =
-        create_delegation_evidence(&delegation_request, time_provider, 30, db)
-            .await
-            .context("Error creating delegation evidence")?;
+    let delegation_evidence_container = create_delegation_evidence(
+        &delegation_request,
+        AccessSubjectDetails::default(),
+        &HashMap::new(),
+        &vec![],
+        combining_algorithms,
+        time_provider,
+        30,
+        db,
+    )
+    .await
+    .context("Error creating delegation evidence")?;
 
     let access = verify_delegation_evidence(
-        &delegation_evidence_container.delegation_evidence,
+        &delegation_evidence_container.container.delegation_evidence,
         "PDP.Policy".to_owned(),
     );
 
@@ -287,28 +579,107 @@ pub async fn verify_policy_set_access(
     return Ok(access);
 }
 
+// the revisions from the If-Match header of a request that changes a policy set, the change is only
+// applied when the policy set still has one of them. `None` applies the change to any revision
+pub type ExpectedRevisions = Option<Vec<i32>>;
+
+// increments the revision as the first statement of the transaction that changes the policy set, so
+// a change based on an outdated revision fails with a 412 instead of overwriting another change
+pub async fn increment_policy_set_revision<C: ConnectionTrait>(
+    policy_set_id: &Uuid,
+    expected_revisions: &ExpectedRevisions,
+    transaction: &C,
+) -> Result<i32, AppError> {
+    policy_store::increment_policy_set_revision(
+        policy_set_id,
+        expected_revisions.as_ref(),
+        transaction,
+    )
+    .await?
+    .ok_or_else(|| {
+        AppError::Expected(ExpectedError {
+            status_code: StatusCode::PRECONDITION_FAILED,
+            message: "Policy set has been changed".to_owned(),
+            reason: format!(
+                "policy set '{}' doesn't have the expected revision",
+                policy_set_id
+            ),
+            metadata: None,
+        })
+    })
+}
+
+pub(crate) fn policy_set_not_found() -> AppError {
+    AppError::Expected(ExpectedError {
+        status_code: StatusCode::NOT_FOUND,
+        message: "Can't find policy set".to_owned(),
+        reason: "not found".to_owned(),
+        metadata: None,
+    })
+}
+
+// the policy set when it exists and hasn't been deleted
+async fn get_existing_policy_set(
+    policy_set_id: &Uuid,
+    db: &DatabaseConnection,
+) -> Result<ar_entity::policy_set::Model, AppError> {
+    policy_store::get_policy_set_by_id(policy_set_id, db)
+        .await
+        .context("Error getting policy set")?
+        .ok_or_else(policy_set_not_found)
+}
+
+async fn apply_policy_set_deletion(
+    now: chrono::DateTime<Utc>,
+    policy_set: &ar_entity::policy_set::Model,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<(), AppError> {
+    let id = &policy_set.id;
+    let transaction = db.begin().await.context("error starting db transaction")?;
+
+    increment_policy_set_revision(id, expected_revisions, &transaction).await?;
+
+    policy_store::delete_policy_set(now, id, &transaction)
+        .await
+        .context(format!("Error deleting policy set: {}", id))?;
+
+    log_event(
+        now,
+        id.to_string(),
+        crate::services::audit_log::EventType::ArPolicySetDeleted(PolicySetDeletedEventMetadata {
+            policy_set_id: id.to_owned(),
+        }),
+        None,
+        None,
+        &transaction,
+    )
+    .await
+    .context("Error logging policy set deleted event")?;
+
+    transaction
+        .commit()
+        .await
+        .context("error commiting transaction to db")?;
+
+    decision_cache.invalidate(&policy_set.access_subject, policy_set.max_delegation_depth);
+
+    Ok(())
+}
+
 pub async fn delete_policy_set(
     now: chrono::DateTime<Utc>,
     requester_company_id: &str,
     id: &Uuid,
+    expected_revisions: &ExpectedRevisions,
     client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
     time_provider: std::sync::Arc<dyn TimeProvider>,
     db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
 ) -> Result<(), AppError> {
-    let policy_set = match policy_store::get_policy_set_by_id(&id, &db)
-        .await
-        .context("Error getting policy set")?
-    {
-        None => {
-            return Err(AppError::Expected(ExpectedError {
-                status_code: StatusCode::NOT_FOUND,
-                message: "Can't find policy set".to_owned(),
-                reason: "not found".to_owned(),
-                metadata: None,
-            }));
-        }
-        Some(ps) => ps,
-    };
+    let policy_set = get_existing_policy_set(id, db).await?;
 
     let policies = policy_store::get_policies_by_policy_set(id, db)
         .await
@@ -326,6 +697,7 @@ pub async fn delete_policy_set(
         &policy_set.access_subject,
         identifiers,
         client_eori,
+        combining_algorithms,
         time_provider,
         &db,
     )
@@ -341,45 +713,30 @@ pub async fn delete_policy_set(
         }));
     }
 
-    let transaction = db.begin().await.context("error starting db transaction")?;
-
-    policy_store::delete_policy_set(&id, &transaction)
-        .await
-        .context(format!("Error deleting policy set: {}", id))?;
-
-    log_event(
-        now,
-        id.to_string(),
-        crate::services::audit_log::EventType::ArPolicySetDeleted(PolicySetDeletedEventMetadata {
-            policy_set_id: id.to_owned(),
-        }),
-        None,
-        None,
-        &transaction,
-    )
-    .await
-    .context("Error logging policy set deleted event")?;
+    apply_policy_set_deletion(now, &policy_set, expected_revisions, db, decision_cache).await
+}
 
-    transaction
-        .commit()
-        .await
-        .context("error commiting transaction to db")?;
+pub async fn delete_policy_set_admin(
+    now: chrono::DateTime<Utc>,
+    id: &Uuid,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<(), AppError> {
+    let policy_set = get_existing_policy_set(id, db).await?;
 
-    Ok(())
+    apply_policy_set_deletion(now, &policy_set, expected_revisions, db, decision_cache).await
 }
 
-pub async fn add_policy_to_policy_set(
+// the validations of a policy that is added to or replaces a policy of a policy set
+async fn validate_policy_change(
     now: chrono::DateTime<chrono::Utc>,
-    requester_company_id: &str,
-    policy_set_id: &Uuid,
-    policy: ar_entity::delegation_evidence::Policy,
-    client_eori: &str,
-    time_provider: std::sync::Arc<dyn TimeProvider>,
+    policy: &ar_entity::delegation_evidence::Policy,
     satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
     db: &DatabaseConnection,
-) -> Result<ar_entity::policy::Model, AppError> {
+) -> Result<(), AppError> {
     match policy.rules.get(0) {
-        Some(ResourceRule::Permit) => {}
+        Some(ResourceRule::Permit(_)) => {}
         _ => {
             return Err(AppError::Expected(ExpectedError {
                 status_code: StatusCode::BAD_REQUEST,
@@ -390,7 +747,17 @@ pub async fn add_policy_to_policy_set(
         }
     }
 
-    for sp in policy.target.environment.service_providers.iter() {
+    validate_policy(policy)?;
+    validate_party_group_references(&policy.target.environment.service_providers, db).await?;
+    validate_policy_resource_type(policy, db).await?;
+
+    for sp in policy
+        .target
+        .environment
+        .service_providers
+        .iter()
+        .filter(|sp| party_group_name(sp).is_none())
+    {
         satellite_provider
             .validate_party(now, sp)
             .await
@@ -407,65 +774,386 @@ pub async fn add_policy_to_policy_set(
             })?;
     }
 
-    let policy_set = match policy_store::get_policy_set_by_id(&policy_set_id, &db)
-        .await
-        .context("Error getting policy set")?
-    {
-        None => {
-            return Err(AppError::Expected(ExpectedError {
-                status_code: StatusCode::NOT_FOUND,
-                message: "Can't find policy set".to_owned(),
-                reason: "not found".to_owned(),
-                metadata: None,
-            }));
-        }
-        Some(ps) => ps,
-    };
+    Ok(())
+}
 
-    let policies = policy_store::get_policies_by_policy_set(policy_set_id, db)
+async fn verify_policy_set_edit_access(
+    requester_company_id: &str,
+    policy_set: &ar_entity::policy_set::Model,
+    client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    let policies = policy_store::get_policies_by_policy_set(&policy_set.id, db)
         .await
         .context(format!(
             "Error getting policies from db for policy set: {}",
-            policy_set_id
+            policy_set.id
         ))?;
 
     let identifiers = policies.iter().map(|p| p.resource_type.clone()).collect();
 
-    let access = verify_policy_set_access(
-        &requester_company_id,
-        &PolicySetAction::Edit,
-        &policy_set.policy_issuer,
-        &policy_set.access_subject,
-        identifiers,
-        client_eori,
-        time_provider,
-        &db,
+    let access = verify_policy_set_access(
+        &requester_company_id,
+        &PolicySetAction::Edit,
+        &policy_set.policy_issuer,
+        &policy_set.access_subject,
+        identifiers,
+        client_eori,
+        combining_algorithms,
+        time_provider,
+        &db,
+    )
+    .await
+    .context("error verifying if access to edit policy set")?;
+
+    if !access {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::FORBIDDEN,
+            message: "not allowed to edit policy set".to_owned(),
+            reason: "not allowed to edit policy set".to_owned(),
+            metadata: None,
+        }));
+    }
+
+    Ok(())
+}
+
+async fn apply_policy_addition(
+    now: chrono::DateTime<chrono::Utc>,
+    policy_set: &ar_entity::policy_set::Model,
+    policy: ar_entity::delegation_evidence::Policy,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<ar_entity::policy::Model, AppError> {
+    let policy_set_id = &policy_set.id;
+    let transaction = db.begin().await.context("error starting db transaction")?;
+
+    increment_policy_set_revision(policy_set_id, expected_revisions, &transaction).await?;
+
+    let policy = policy_store::add_policy_to_policy_set(policy_set_id, policy, &transaction)
+        .await
+        .context("Error adding policy to policy set")?;
+
+    policy_version_store::insert_policy_set_version(
+        now,
+        policy_set_id,
+        PolicySetChange::PolicyAdded,
+        &transaction,
+    )
+    .await
+    .context("Error recording policy set version")?;
+
+    log_event(
+        now,
+        policy_set_id.to_string(),
+        crate::services::audit_log::EventType::ArPolicySetEdited(PolicySetEditedEventMetadata {
+            policy_set_id: policy_set_id.to_owned(),
+            edited_type: crate::services::audit_log::EditedType::PolicyAdded(PolicyAdded {
+                policy_id: policy.id,
+            }),
+        }),
+        None,
+        None,
+        &transaction,
+    )
+    .await
+    .context("error logging policy added event")?;
+
+    transaction
+        .commit()
+        .await
+        .context("error commiting transaction to db")?;
+
+    decision_cache.invalidate(&policy_set.access_subject, policy_set.max_delegation_depth);
+
+    Ok(policy)
+}
+
+pub async fn add_policy_to_policy_set(
+    now: chrono::DateTime<chrono::Utc>,
+    requester_company_id: &str,
+    policy_set_id: &Uuid,
+    policy: ar_entity::delegation_evidence::Policy,
+    expected_revisions: &ExpectedRevisions,
+    client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<ar_entity::policy::Model, AppError> {
+    validate_policy_change(now, &policy, satellite_provider, db).await?;
+
+    let policy_set = get_existing_policy_set(policy_set_id, db).await?;
+
+    verify_policy_set_edit_access(
+        requester_company_id,
+        &policy_set,
+        client_eori,
+        combining_algorithms,
+        time_provider,
+        db,
+    )
+    .await?;
+
+    apply_policy_addition(
+        now,
+        &policy_set,
+        policy,
+        expected_revisions,
+        db,
+        decision_cache,
+    )
+    .await
+}
+
+pub async fn add_policy_to_policy_set_admin(
+    now: chrono::DateTime<chrono::Utc>,
+    policy_set_id: &Uuid,
+    policy: ar_entity::delegation_evidence::Policy,
+    expected_revisions: &ExpectedRevisions,
+    satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<ar_entity::policy::Model, AppError> {
+    validate_policy_change(now, &policy, satellite_provider, db).await?;
+
+    let policy_set = get_existing_policy_set(policy_set_id, db).await?;
+
+    apply_policy_addition(
+        now,
+        &policy_set,
+        policy,
+        expected_revisions,
+        db,
+        decision_cache,
+    )
+    .await
+}
+
+async fn apply_policy_replacement(
+    now: chrono::DateTime<chrono::Utc>,
+    policy_set: &ar_entity::policy_set::Model,
+    policy_id: Uuid,
+    policy: ar_entity::delegation_evidence::Policy,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<ar_entity::policy::Model, AppError> {
+    let policy_set_id = policy_set.id;
+    let transaction = db.begin().await.context("error starting db transtaction")?;
+
+    increment_policy_set_revision(&policy_set_id, expected_revisions, &transaction).await?;
+
+    let policy = policy_store::replace_policy(policy_set_id, policy_id, &policy, &transaction)
+        .await
+        .context("Error adding policy to policy set")?;
+
+    policy_version_store::insert_policy_set_version(
+        now,
+        &policy_set_id,
+        PolicySetChange::PolicyReplaced,
+        &transaction,
+    )
+    .await
+    .context("Error recording policy set version")?;
+
+    log_event(
+        now,
+        policy_set_id.to_string(),
+        crate::services::audit_log::EventType::ArPolicySetEdited(PolicySetEditedEventMetadata {
+            policy_set_id: policy_set_id.to_owned(),
+            edited_type: crate::services::audit_log::EditedType::PolicyReplaced(PolicyReplaced {
+                old_policy_id: policy_id.to_owned(),
+                new_policy_id: policy.id.to_owned(),
+            }),
+        }),
+        None,
+        None,
+        &transaction,
+    )
+    .await
+    .context("Error logging policy set edited event")?;
+
+    transaction
+        .commit()
+        .await
+        .context("error commiting transaction to db")?;
+
+    decision_cache.invalidate(&policy_set.access_subject, policy_set.max_delegation_depth);
+
+    Ok(policy)
+}
+
+pub async fn replace_policy_in_policy_set(
+    now: chrono::DateTime<chrono::Utc>,
+    requester_company_id: &str,
+    policy_set_id: Uuid,
+    policy_id: Uuid,
+    policy: ar_entity::delegation_evidence::Policy,
+    expected_revisions: &ExpectedRevisions,
+    client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<ar_entity::policy::Model, AppError> {
+    validate_policy_change(now, &policy, satellite_provider, db).await?;
+
+    let policy_set = get_existing_policy_set(&policy_set_id, db).await?;
+
+    verify_policy_set_edit_access(
+        requester_company_id,
+        &policy_set,
+        client_eori,
+        combining_algorithms,
+        time_provider,
+        db,
+    )
+    .await?;
+
+    apply_policy_replacement(
+        now,
+        &policy_set,
+        policy_id,
+        policy,
+        expected_revisions,
+        db,
+        decision_cache,
+    )
+    .await
+}
+
+pub async fn replace_policy_in_policy_set_admin(
+    now: chrono::DateTime<chrono::Utc>,
+    policy_set_id: Uuid,
+    policy_id: Uuid,
+    policy: ar_entity::delegation_evidence::Policy,
+    expected_revisions: &ExpectedRevisions,
+    satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<ar_entity::policy::Model, AppError> {
+    validate_policy_change(now, &policy, satellite_provider, db).await?;
+
+    let policy_set = get_existing_policy_set(&policy_set_id, db).await?;
+
+    apply_policy_replacement(
+        now,
+        &policy_set,
+        policy_id,
+        policy,
+        expected_revisions,
+        db,
+        decision_cache,
     )
     .await
-    .context("error verifying if access to edit policy set")?;
+}
 
-    if !access {
-        return Err(AppError::Expected(ExpectedError {
-            status_code: StatusCode::FORBIDDEN,
-            message: "not allowed to edit policy set".to_owned(),
-            reason: "not allowed to edit policy set".to_owned(),
-            metadata: None,
-        }));
+#[derive(Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct EditPolicySetMetadata {
+    #[serde(default)]
+    pub access_subject: Option<String>,
+    #[serde(default)]
+    pub licences: Option<Vec<String>>,
+    #[serde(default)]
+    pub max_delegation_depth: Option<i32>,
+}
+
+// the policies don't change in an edit, only the validations of the policy set itself are repeated
+// for the edited policy set. the access subject is only verified again in iSHARE when it changes
+async fn validate_policy_set_metadata_edit(
+    now: chrono::DateTime<Utc>,
+    policy_set: &ar_entity::policy_set::Model,
+    edit: &EditPolicySetMetadata,
+    db: &DatabaseConnection,
+    ishare: std::sync::Arc<dyn SatelliteProvider>,
+) -> Result<PolicySetMetadata, AppError> {
+    let edited = InsertPolicySetWithPolicies {
+        target: AccessSubjectTarget {
+            access_subject: edit
+                .access_subject
+                .clone()
+                .unwrap_or_else(|| policy_set.access_subject.clone()),
+            access_subject_attributes: policy_set.access_subject_attributes.clone(),
+            access_subject_user: policy_set.access_subject_user.clone(),
+        },
+        policy_issuer: policy_set.policy_issuer.clone(),
+        licences: edit
+            .licences
+            .clone()
+            .unwrap_or_else(|| policy_set.licenses.clone()),
+        policies: vec![],
+        max_delegation_depth: edit
+            .max_delegation_depth
+            .unwrap_or(policy_set.max_delegation_depth),
+        not_before: policy_set.not_before,
+        not_on_or_after: policy_set.not_on_or_after,
+    };
+
+    validate_policy_set_access_subject(&edited)?;
+    validate_policy_set_licenses(&edited, db).await?;
+    validate_policy_set_party_groups(&edited, db).await?;
+    validate_policy_set_access_subject_user(&edited, db).await?;
+    if !is_same_party(&edited.target.access_subject, &policy_set.access_subject) {
+        validate_policy_set_ishare_parties(now, &edited, ishare).await?;
     }
 
+    Ok(PolicySetMetadata {
+        access_subject: normalize_party_id(&edited.target.access_subject),
+        licenses: edited.licences,
+        max_delegation_depth: edited.max_delegation_depth,
+    })
+}
+
+async fn update_policy_set_metadata(
+    now: chrono::DateTime<Utc>,
+    policy_set: &ar_entity::policy_set::Model,
+    metadata: PolicySetMetadata,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
     let transaction = db.begin().await.context("error starting db transaction")?;
 
-    let policy = policy_store::add_policy_to_policy_set(policy_set_id, policy, &transaction)
-        .await
-        .context("Error adding policy to policy set")?;
+    increment_policy_set_revision(&policy_set.id, expected_revisions, &transaction).await?;
+
+    policy_store::update_policy_set_metadata(
+        &policy_set.id,
+        &metadata.access_subject,
+        &metadata.licenses,
+        metadata.max_delegation_depth,
+        &transaction,
+    )
+    .await
+    .context("Error updating policy set")?;
+
+    policy_version_store::insert_policy_set_version(
+        now,
+        &policy_set.id,
+        PolicySetChange::MetadataEdited,
+        &transaction,
+    )
+    .await
+    .context("Error recording policy set version")?;
 
     log_event(
         now,
-        policy_set_id.to_string(),
+        policy_set.id.to_string(),
         crate::services::audit_log::EventType::ArPolicySetEdited(PolicySetEditedEventMetadata {
-            policy_set_id: policy_set_id.to_owned(),
-            edited_type: crate::services::audit_log::EditedType::PolicyAdded(PolicyAdded {
-                policy_id: policy.id,
+            policy_set_id: policy_set.id,
+            edited_type: crate::services::audit_log::EditedType::MetadataEdited(MetadataEdited {
+                previous: PolicySetMetadata {
+                    access_subject: policy_set.access_subject.clone(),
+                    licenses: policy_set.licenses.clone(),
+                    max_delegation_depth: policy_set.max_delegation_depth,
+                },
+                current: metadata.clone(),
             }),
         }),
         None,
@@ -473,57 +1161,38 @@ pub async fn add_policy_to_policy_set(
         &transaction,
     )
     .await
-    .context("error logging policy added event")?;
+    .context("Error logging policy set edited event")?;
 
     transaction
         .commit()
         .await
         .context("error commiting transaction to db")?;
 
-    Ok(policy)
+    // decisions for the previous and the new access subject can both be affected
+    decision_cache.invalidate(&policy_set.access_subject, policy_set.max_delegation_depth);
+    decision_cache.invalidate(&metadata.access_subject, metadata.max_delegation_depth);
+
+    let policy_set = policy_store::get_policy_set_with_policies(&policy_set.id, db)
+        .await?
+        .context("Policy set not found after edit")?;
+
+    Ok(policy_set)
 }
 
-pub async fn replace_policy_in_policy_set(
-    now: chrono::DateTime<chrono::Utc>,
+pub async fn edit_policy_set_metadata(
+    now: chrono::DateTime<Utc>,
     requester_company_id: &str,
-    policy_set_id: Uuid,
-    policy_id: Uuid,
-    policy: ar_entity::delegation_evidence::Policy,
+    policy_set_id: &Uuid,
+    edit: &EditPolicySetMetadata,
+    expected_revisions: &ExpectedRevisions,
     client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
     time_provider: std::sync::Arc<dyn TimeProvider>,
     satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
     db: &DatabaseConnection,
-) -> Result<ar_entity::policy::Model, AppError> {
-    match policy.rules.get(0) {
-        Some(ResourceRule::Permit) => {}
-        _ => {
-            return Err(AppError::Expected(ExpectedError {
-                status_code: StatusCode::BAD_REQUEST,
-                message: "First rule must have effect 'Permit'".to_owned(),
-                reason: "First rule must have effect 'Permit'".to_owned(),
-                metadata: None,
-            }))
-        }
-    }
-
-    for sp in policy.target.environment.service_providers.iter() {
-        satellite_provider
-            .validate_party(now, sp)
-            .await
-            .map_err(|e| {
-                AppError::Expected(ExpectedError {
-                    status_code: StatusCode::BAD_REQUEST,
-                    message: format!(
-                        "Unable to verify service provider '{}' as valid iSHARE party",
-                        &sp
-                    ),
-                    reason: format!("{:?}", e),
-                    metadata: None,
-                })
-            })?;
-    }
-
-    let policy_set = match policy_store::get_policy_set_by_id(&policy_set_id, &db)
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
+    let policy_set = match policy_store::get_policy_set_by_id(policy_set_id, db)
         .await
         .context("Error getting policy set")?
     {
@@ -538,7 +1207,7 @@ pub async fn replace_policy_in_policy_set(
         Some(ps) => ps,
     };
 
-    let policies = policy_store::get_policies_by_policy_set(&policy_set_id, db)
+    let policies = policy_store::get_policies_by_policy_set(policy_set_id, db)
         .await
         .context(format!(
             "Error getting policies from db for policy set: {}",
@@ -548,14 +1217,15 @@ pub async fn replace_policy_in_policy_set(
     let identifiers = policies.iter().map(|p| p.resource_type.clone()).collect();
 
     let access = verify_policy_set_access(
-        &requester_company_id,
+        requester_company_id,
         &PolicySetAction::Edit,
         &policy_set.policy_issuer,
         &policy_set.access_subject,
         identifiers,
         client_eori,
+        combining_algorithms,
         time_provider,
-        &db,
+        db,
     )
     .await
     .context("error verifying if access to edit policy set")?;
@@ -569,41 +1239,63 @@ pub async fn replace_policy_in_policy_set(
         }));
     }
 
-    let transaction = db.begin().await.context("error starting db transtaction")?;
-
-    let policy = policy_store::replace_policy(policy_set_id, policy_id, &policy, &transaction)
-        .await
-        .context("Error adding policy to policy set")?;
+    let metadata =
+        validate_policy_set_metadata_edit(now, &policy_set, edit, db, satellite_provider).await?;
 
-    log_event(
+    update_policy_set_metadata(
         now,
-        policy_set_id.to_string(),
-        crate::services::audit_log::EventType::ArPolicySetEdited(PolicySetEditedEventMetadata {
-            policy_set_id: policy_set_id.to_owned(),
-            edited_type: crate::services::audit_log::EditedType::PolicyReplaced(PolicyReplaced {
-                old_policy_id: policy_id.to_owned(),
-                new_policy_id: policy.id.to_owned(),
-            }),
-        }),
-        None,
-        None,
-        &transaction,
+        &policy_set,
+        metadata,
+        expected_revisions,
+        db,
+        decision_cache,
     )
     .await
-    .context("Error logging policy set edited event")?;
+}
 
-    transaction
-        .commit()
+pub async fn edit_policy_set_metadata_admin(
+    now: chrono::DateTime<Utc>,
+    policy_set_id: &Uuid,
+    edit: &EditPolicySetMetadata,
+    expected_revisions: &ExpectedRevisions,
+    satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
+    let policy_set = match policy_store::get_policy_set_by_id(policy_set_id, db)
         .await
-        .context("error commiting transaction to db")?;
+        .context("Error getting policy set")?
+    {
+        None => {
+            return Err(AppError::Expected(ExpectedError {
+                status_code: StatusCode::NOT_FOUND,
+                message: "Can't find policy set".to_owned(),
+                reason: "not found".to_owned(),
+                metadata: None,
+            }));
+        }
+        Some(ps) => ps,
+    };
 
-    Ok(policy)
+    let metadata =
+        validate_policy_set_metadata_edit(now, &policy_set, edit, db, satellite_provider).await?;
+
+    update_policy_set_metadata(
+        now,
+        &policy_set,
+        metadata,
+        expected_revisions,
+        db,
+        decision_cache,
+    )
+    .await
 }
 
 pub async fn get_policy_set_with_policies(
     requester_company_id: &str,
     policy_set_id: &Uuid,
     client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
     time_provider: std::sync::Arc<dyn TimeProvider>,
     db: &DatabaseConnection,
 ) -> Result<Option<MatchingPolicySetRow>, AppError> {
@@ -638,6 +1330,7 @@ pub async fn get_policy_set_with_policies(
         &policy_set.access_subject,
         identifiers,
         client_eori,
+        combining_algorithms,
         time_provider,
         &db,
     )
@@ -658,30 +1351,12 @@ pub async fn get_policy_set_with_policies(
     Ok(ps)
 }
 
-pub async fn remove_policy_from_policy_set(
-    now: chrono::DateTime<Utc>,
-    requester_company_id: &str,
+// the policy when it is one of the policies of the policy set
+async fn get_policy_of_policy_set(
     policy_set_id: &Uuid,
     policy_id: &Uuid,
-    client_eori: &str,
-    time_provider: std::sync::Arc<dyn TimeProvider>,
     db: &DatabaseConnection,
-) -> Result<(), AppError> {
-    let policy_set = match policy_store::get_policy_set_by_id(&policy_set_id, &db)
-        .await
-        .context("Error getting policy set")?
-    {
-        None => {
-            return Err(AppError::Expected(ExpectedError {
-                status_code: StatusCode::NOT_FOUND,
-                message: "Can't find policy set".to_owned(),
-                reason: "not found".to_owned(),
-                metadata: None,
-            }));
-        }
-        Some(ps) => ps,
-    };
-
+) -> Result<ar_entity::policy::Model, AppError> {
     let policies = policy_store::get_policies_by_policy_set(policy_set_id, db)
         .await
         .context(format!(
@@ -689,48 +1364,43 @@ pub async fn remove_policy_from_policy_set(
             policy_set_id
         ))?;
 
-    let policy = match policies.iter().find(|p| &p.id == policy_id) {
-        Some(p) => p,
-        None => {
-            return Err(AppError::Expected(ExpectedError {
-                status_code: StatusCode::NOT_FOUND,
-                message: "Can't find policy within policy set".to_owned(),
-                reason: "Can't find policy within policy set".to_owned(),
-                metadata: None,
-            }))
-        }
-    };
-
-    let identifiers = vec![policy.resource_type.to_owned()];
-
-    let access = verify_policy_set_access(
-        &requester_company_id,
-        &PolicySetAction::Delete,
-        &policy_set.policy_issuer,
-        &policy_set.access_subject,
-        identifiers,
-        client_eori,
-        time_provider,
-        &db,
-    )
-    .await
-    .context("error verifying if access to delete policy")?;
-
-    if !access {
-        return Err(AppError::Expected(ExpectedError {
-            status_code: StatusCode::FORBIDDEN,
-            message: "not allowed to delete policy".to_owned(),
-            reason: "not allowed to delete policy".to_owned(),
+    match policies.into_iter().find(|p| &p.id == policy_id) {
+        Some(p) => Ok(p),
+        None => Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::NOT_FOUND,
+            message: "Can't find policy within policy set".to_owned(),
+            reason: "Can't find policy within policy set".to_owned(),
             metadata: None,
-        }));
+        })),
     }
+}
 
+async fn apply_policy_removal(
+    now: chrono::DateTime<Utc>,
+    policy_set: &ar_entity::policy_set::Model,
+    policy_id: &Uuid,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<(), AppError> {
+    let policy_set_id = &policy_set.id;
     let transaction = db.begin().await.context("error starting db transaction")?;
 
+    increment_policy_set_revision(policy_set_id, expected_revisions, &transaction).await?;
+
     policy_store::delete_policy(policy_id, &transaction)
         .await
         .context("Error deleting policy")?;
 
+    policy_version_store::insert_policy_set_version(
+        now,
+        policy_set_id,
+        PolicySetChange::PolicyRemoved,
+        &transaction,
+    )
+    .await
+    .context("Error recording policy set version")?;
+
     log_event(
         now,
         policy_set_id.to_string(),
@@ -752,9 +1422,84 @@ pub async fn remove_policy_from_policy_set(
         .await
         .context("error commiting transaction to database")?;
 
+    decision_cache.invalidate(&policy_set.access_subject, policy_set.max_delegation_depth);
+
     Ok(())
 }
 
+pub async fn remove_policy_from_policy_set(
+    now: chrono::DateTime<Utc>,
+    requester_company_id: &str,
+    policy_set_id: &Uuid,
+    policy_id: &Uuid,
+    expected_revisions: &ExpectedRevisions,
+    client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<(), AppError> {
+    let policy_set = get_existing_policy_set(policy_set_id, db).await?;
+    let policy = get_policy_of_policy_set(policy_set_id, policy_id, db).await?;
+
+    let identifiers = vec![policy.resource_type.to_owned()];
+
+    let access = verify_policy_set_access(
+        &requester_company_id,
+        &PolicySetAction::Delete,
+        &policy_set.policy_issuer,
+        &policy_set.access_subject,
+        identifiers,
+        client_eori,
+        combining_algorithms,
+        time_provider,
+        &db,
+    )
+    .await
+    .context("error verifying if access to delete policy")?;
+
+    if !access {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::FORBIDDEN,
+            message: "not allowed to delete policy".to_owned(),
+            reason: "not allowed to delete policy".to_owned(),
+            metadata: None,
+        }));
+    }
+
+    apply_policy_removal(
+        now,
+        &policy_set,
+        policy_id,
+        expected_revisions,
+        db,
+        decision_cache,
+    )
+    .await
+}
+
+pub async fn remove_policy_from_policy_set_admin(
+    now: chrono::DateTime<Utc>,
+    policy_set_id: &Uuid,
+    policy_id: &Uuid,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<(), AppError> {
+    let policy_set = get_existing_policy_set(policy_set_id, db).await?;
+    get_policy_of_policy_set(policy_set_id, policy_id, db).await?;
+
+    apply_policy_removal(
+        now,
+        &policy_set,
+        policy_id,
+        expected_revisions,
+        db,
+        decision_cache,
+    )
+    .await
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -778,6 +1523,7 @@ mod test {
             "as",
             vec!["*".to_owned()],
             "antother-company",
+            &CombiningAlgorithmConfig::default(),
             time_provider,
             &db,
         )
@@ -804,6 +1550,7 @@ mod test {
             "as",
             vec!["*".to_owned()],
             "another-company",
+            &CombiningAlgorithmConfig::default(),
             time_provider,
             &db,
         )
@@ -830,6 +1577,7 @@ mod test {
             "as-company",
             vec!["*".to_owned()],
             "antother-company",
+            &CombiningAlgorithmConfig::default(),
             time_provider,
             &db,
         )
@@ -856,6 +1604,7 @@ mod test {
             "as-company",
             vec!["*".to_owned()],
             "antother-company",
+            &CombiningAlgorithmConfig::default(),
             time_provider,
             &db,
         )
@@ -903,9 +1652,14 @@ mod test {
             ]
         }))
         .unwrap();
-        insert_policy_set_with_policies_into_db(chrono::Utc::now(), &policy_set, &db)
-            .await
-            .unwrap();
+        insert_policy_set_with_policies_into_db(
+            chrono::Utc::now(),
+            &policy_set,
+            PolicySetStatus::Active,
+            &db,
+        )
+        .await
+        .unwrap();
 
         let access = verify_policy_set_access(
             "NL.24244",
@@ -914,6 +1668,7 @@ mod test {
             "as",
             vec!["LovelyResource".to_string()],
             "NL.CONSUME_TOO_MUCH",
+            &CombiningAlgorithmConfig::default(),
             time_provider,
             &db,
         )
@@ -0,0 +1,297 @@
+use anyhow::Context;
+use ar_entity::policy_set::PolicySetStatus;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use sea_orm::{ConnectionTrait, DatabaseConnection, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::policy::{self as policy_store, MatchingPolicySetRow};
+use crate::db::policy_issuer_setting as policy_issuer_setting_store;
+use crate::db::policy_version::{self as policy_version_store, PolicySetChange};
+use crate::error::{AppError, ExpectedError};
+use crate::services::audit_log::{
+    log_event, EventType, PolicySetApprovedEventMetadata, PolicySetRejectedEventMetadata,
+};
+use crate::services::decision_cache::DecisionCache;
+use crate::services::policy::{
+    increment_policy_set_revision, policy_set_not_found, ExpectedRevisions,
+};
+use crate::utils::{is_same_party, normalize_party_id};
+
+#[derive(Serialize, Deserialize, ToSchema, Debug, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyIssuerSettings {
+    /// Policy sets created on behalf of the issuer by another party are pending until the issuer
+    /// approves them
+    pub require_policy_set_approval: bool,
+}
+
+enum ApprovalDecision {
+    Approve,
+    Reject,
+}
+
+pub async fn get_policy_issuer_settings<C: ConnectionTrait>(
+    policy_issuer: &str,
+    db: &C,
+) -> Result<PolicyIssuerSettings, AppError> {
+    let setting = policy_issuer_setting_store::get_policy_issuer_setting(policy_issuer, db).await?;
+
+    Ok(match setting {
+        Some(setting) => PolicyIssuerSettings {
+            require_policy_set_approval: setting.require_policy_set_approval,
+        },
+        None => PolicyIssuerSettings::default(),
+    })
+}
+
+pub async fn save_policy_issuer_settings(
+    policy_issuer: &str,
+    settings: &PolicyIssuerSettings,
+    db: &DatabaseConnection,
+) -> Result<PolicyIssuerSettings, AppError> {
+    policy_issuer_setting_store::save_policy_issuer_setting(
+        &ar_entity::policy_issuer_setting::Model {
+            policy_issuer: normalize_party_id(policy_issuer),
+            require_policy_set_approval: settings.require_policy_set_approval,
+        },
+        db,
+    )
+    .await?;
+
+    get_policy_issuer_settings(policy_issuer, db).await
+}
+
+// policy sets created by the issuer itself are always active, policy sets created on its behalf
+// are pending when the issuer requires approval
+pub async fn initial_policy_set_status<C: ConnectionTrait>(
+    requester_company_id: &str,
+    policy_issuer: &str,
+    db: &C,
+) -> Result<PolicySetStatus, AppError> {
+    if is_same_party(requester_company_id, policy_issuer) {
+        return Ok(PolicySetStatus::Active);
+    }
+
+    let settings = get_policy_issuer_settings(policy_issuer, db).await?;
+
+    Ok(match settings.require_policy_set_approval {
+        true => PolicySetStatus::Pending,
+        false => PolicySetStatus::Active,
+    })
+}
+
+async fn decide_policy_set(
+    now: DateTime<Utc>,
+    policy_set: &ar_entity::policy_set::Model,
+    decision: ApprovalDecision,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
+    let (status, change, event) = match decision {
+        ApprovalDecision::Approve => (
+            PolicySetStatus::Active,
+            PolicySetChange::Approved,
+            EventType::ArPolicySetApproved(PolicySetApprovedEventMetadata {
+                policy_set_id: policy_set.id,
+            }),
+        ),
+        ApprovalDecision::Reject => (
+            PolicySetStatus::Rejected,
+            PolicySetChange::Rejected,
+            EventType::ArPolicySetRejected(PolicySetRejectedEventMetadata {
+                policy_set_id: policy_set.id,
+            }),
+        ),
+    };
+
+    let transaction = db.begin().await.context("error starting db transaction")?;
+
+    // the status is only changed when the policy set is still pending at the time of the update,
+    // of two concurrent decisions only the first one is applied
+    let updated =
+        policy_store::update_pending_policy_set_status(&policy_set.id, status, &transaction)
+            .await
+            .context("Error updating policy set status")?;
+
+    if updated == 0 {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::CONFLICT,
+            message: "Policy set is not pending approval".to_owned(),
+            reason: format!("policy set '{}' is not pending approval", policy_set.id),
+            metadata: None,
+        }));
+    }
+
+    increment_policy_set_revision(&policy_set.id, expected_revisions, &transaction).await?;
+
+    policy_version_store::insert_policy_set_version(now, &policy_set.id, change, &transaction)
+        .await
+        .context("Error recording policy set version")?;
+
+    log_event(
+        now,
+        policy_set.id.to_string(),
+        event,
+        None,
+        None,
+        &transaction,
+    )
+    .await
+    .context("Error logging policy set approval event")?;
+
+    transaction
+        .commit()
+        .await
+        .context("error commiting transaction to db")?;
+
+    decision_cache.invalidate(&policy_set.access_subject, policy_set.max_delegation_depth);
+
+    let policy_set = policy_store::get_policy_set_with_policies(&policy_set.id, db)
+        .await?
+        .context("Policy set not found after approval decision")?;
+
+    Ok(policy_set)
+}
+
+// only the policy issuer decides on the policy sets created on its behalf, a party that manages
+// policies for the issuer can't approve its own policy sets
+async fn decide_policy_set_as_issuer(
+    now: DateTime<Utc>,
+    requester_company_id: &str,
+    policy_set_id: &Uuid,
+    decision: ApprovalDecision,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
+    let policy_set = policy_store::get_policy_set_by_id(policy_set_id, db)
+        .await?
+        .ok_or_else(policy_set_not_found)?;
+
+    if !is_same_party(requester_company_id, &policy_set.policy_issuer) {
+        let message = match decision {
+            ApprovalDecision::Approve => "not allowed to approve policy set",
+            ApprovalDecision::Reject => "not allowed to reject policy set",
+        };
+
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::FORBIDDEN,
+            message: message.to_owned(),
+            reason: message.to_owned(),
+            metadata: None,
+        }));
+    }
+
+    decide_policy_set(
+        now,
+        &policy_set,
+        decision,
+        expected_revisions,
+        db,
+        decision_cache,
+    )
+    .await
+}
+
+async fn decide_policy_set_as_admin(
+    now: DateTime<Utc>,
+    policy_set_id: &Uuid,
+    decision: ApprovalDecision,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
+    let policy_set = policy_store::get_policy_set_by_id(policy_set_id, db)
+        .await?
+        .ok_or_else(policy_set_not_found)?;
+
+    decide_policy_set(
+        now,
+        &policy_set,
+        decision,
+        expected_revisions,
+        db,
+        decision_cache,
+    )
+    .await
+}
+
+pub async fn approve_policy_set(
+    now: DateTime<Utc>,
+    requester_company_id: &str,
+    policy_set_id: &Uuid,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
+    decide_policy_set_as_issuer(
+        now,
+        requester_company_id,
+        policy_set_id,
+        ApprovalDecision::Approve,
+        expected_revisions,
+        db,
+        decision_cache,
+    )
+    .await
+}
+
+pub async fn reject_policy_set(
+    now: DateTime<Utc>,
+    requester_company_id: &str,
+    policy_set_id: &Uuid,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
+    decide_policy_set_as_issuer(
+        now,
+        requester_company_id,
+        policy_set_id,
+        ApprovalDecision::Reject,
+        expected_revisions,
+        db,
+        decision_cache,
+    )
+    .await
+}
+
+pub async fn approve_policy_set_admin(
+    now: DateTime<Utc>,
+    policy_set_id: &Uuid,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
+    decide_policy_set_as_admin(
+        now,
+        policy_set_id,
+        ApprovalDecision::Approve,
+        expected_revisions,
+        db,
+        decision_cache,
+    )
+    .await
+}
+
+pub async fn reject_policy_set_admin(
+    now: DateTime<Utc>,
+    policy_set_id: &Uuid,
+    expected_revisions: &ExpectedRevisions,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
+    decide_policy_set_as_admin(
+        now,
+        policy_set_id,
+        ApprovalDecision::Reject,
+        expected_revisions,
+        db,
+        decision_cache,
+    )
+    .await
+}
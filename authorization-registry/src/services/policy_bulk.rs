@@ -0,0 +1,416 @@
+use std::collections::HashSet;
+
+use anyhow::Context;
+use ar_entity::policy_set::PolicySetStatus;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use sea_orm::{ConnectionTrait, DatabaseConnection, TransactionTrait};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use utoipa::ToSchema;
+use uuid::Uuid;
+
+use crate::db::policy::{self as policy_store, MatchingPolicySetRow};
+use crate::db::policy_version::{self as policy_version_store, PolicySetChange};
+use crate::error::{AppError, ExpectedError};
+use crate::services::audit_log::{log_event, EditedType, EventType, PolicySetEditedEventMetadata};
+use crate::services::decision_cache::DecisionCache;
+use crate::services::ishare_provider::SatelliteProvider;
+use crate::services::policy::{
+    increment_policy_set_revision, insert_policy_set_with_policies_in_transaction, validate_policy,
+    validate_policy_set_access_subject, validate_policy_set_access_subject_user,
+    validate_policy_set_ishare_parties, validate_policy_set_licenses,
+    validate_policy_set_party_groups, validate_policy_set_validity_window,
+    InsertPolicySetWithPolicies,
+};
+use crate::services::resource_type::validate_policies_resource_types;
+
+pub const NDJSON_CONTENT_TYPE: &str = "application/x-ndjson";
+
+// a policy set as it is exported, an export can be imported again as it is
+#[derive(Serialize, Deserialize, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedPolicySet {
+    // policy sets without an identifier are created with a new one when they are imported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    #[serde(flatten)]
+    pub policy_set: InsertPolicySetWithPolicies,
+}
+
+impl From<&MatchingPolicySetRow> for ExportedPolicySet {
+    fn from(policy_set: &MatchingPolicySetRow) -> Self {
+        Self {
+            id: Some(policy_set.policy_set_id),
+            policy_set: InsertPolicySetWithPolicies::from(policy_set),
+        }
+    }
+}
+
+#[derive(Deserialize, Clone, Copy, Default, Debug, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    // a json array of policy sets
+    #[default]
+    Json,
+    // one json policy set per line
+    Ndjson,
+}
+
+#[derive(Deserialize, Clone, Copy, Default, Debug, PartialEq, ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportMode {
+    // policy sets with an identifier that already exists are rejected
+    #[default]
+    CreateOnly,
+    // policy sets with an identifier that already exists are overwritten
+    Upsert,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, ToSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum ImportAction {
+    Created,
+    Updated,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportedPolicySet {
+    // the position of the policy set in the import
+    pub index: usize,
+    // not known for new policy sets without an identifier in a dry run
+    pub id: Option<Uuid>,
+    pub action: ImportAction,
+}
+
+#[derive(Serialize, Deserialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicySetImportResult {
+    pub dry_run: bool,
+    pub policy_sets: Vec<ImportedPolicySet>,
+}
+
+// a policy set of an import that can't be imported
+#[derive(Serialize, Debug, ToSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportEntryError {
+    pub index: usize,
+    pub id: Option<Uuid>,
+    pub message: String,
+}
+
+pub async fn export_policy_sets(
+    access_subject: Option<String>,
+    policy_issuer: Option<String>,
+    party_match: policy_store::PartyMatch,
+    q: Option<String>,
+    db: &DatabaseConnection,
+) -> anyhow::Result<Vec<ExportedPolicySet>> {
+    let policy_sets = policy_store::get_policy_sets_with_policies(
+        access_subject,
+        policy_issuer,
+        party_match,
+        q,
+        None,
+        None,
+        db,
+    )
+    .await
+    .context("Error getting policy sets to export")?;
+
+    Ok(policy_sets
+        .data
+        .iter()
+        .map(ExportedPolicySet::from)
+        .collect())
+}
+
+// returns the content type and the body of the export
+pub fn serialize_export(
+    policy_sets: &Vec<ExportedPolicySet>,
+    format: ExportFormat,
+) -> anyhow::Result<(&'static str, String)> {
+    match format {
+        ExportFormat::Json => Ok((
+            "application/json",
+            serde_json::to_string(policy_sets).context("Error serializing policy sets")?,
+        )),
+        ExportFormat::Ndjson => {
+            let mut body = String::new();
+            for policy_set in policy_sets.iter() {
+                body.push_str(
+                    &serde_json::to_string(policy_set).context("Error serializing policy set")?,
+                );
+                body.push('\n');
+            }
+
+            Ok((NDJSON_CONTENT_TYPE, body))
+        }
+    }
+}
+
+fn invalid_import(message: String) -> AppError {
+    AppError::Expected(ExpectedError {
+        status_code: StatusCode::BAD_REQUEST,
+        message: "Invalid policy set import".to_owned(),
+        reason: message,
+        metadata: None,
+    })
+}
+
+// an import is a json array of policy sets, or ndjson when it has the ndjson content type
+pub fn parse_import(
+    content_type: Option<&str>,
+    body: &[u8],
+) -> Result<Vec<ExportedPolicySet>, AppError> {
+    let is_ndjson = content_type.is_some_and(|ct| ct.starts_with(NDJSON_CONTENT_TYPE));
+
+    if !is_ndjson {
+        return serde_json::from_slice(body)
+            .map_err(|e| invalid_import(format!("invalid json: {}", e)));
+    }
+
+    let body =
+        std::str::from_utf8(body).map_err(|e| invalid_import(format!("invalid utf-8: {}", e)))?;
+
+    body.lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(i, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| invalid_import(format!("invalid json on line {}: {}", i + 1, e)))
+        })
+        .collect()
+}
+
+async fn validate_imported_policy_set(
+    now: DateTime<Utc>,
+    args: &InsertPolicySetWithPolicies,
+    satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    validate_policy_set_validity_window(args)?;
+    validate_policy_set_access_subject(args)?;
+    for policy in args.policies.iter() {
+        validate_policy(policy)?;
+    }
+    validate_policy_set_ishare_parties(now, args, satellite_provider).await?;
+    validate_policy_set_licenses(args, db).await?;
+    validate_policy_set_party_groups(args, db).await?;
+    validate_policy_set_access_subject_user(args, db).await?;
+    validate_policies_resource_types(&args.policies, db).await?;
+
+    Ok(())
+}
+
+// validates every policy set of the import and decides whether it is created or updated, all
+// problems are reported together so an import can be fixed in one go
+async fn plan_import(
+    now: DateTime<Utc>,
+    policy_sets: &Vec<ExportedPolicySet>,
+    mode: ImportMode,
+    satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
+    db: &DatabaseConnection,
+) -> Result<Vec<ImportedPolicySet>, AppError> {
+    let mut planned = vec![];
+    let mut errors = vec![];
+    let mut ids = HashSet::new();
+
+    for (index, imported) in policy_sets.iter().enumerate() {
+        let mut error = |message: String| {
+            errors.push(ImportEntryError {
+                index,
+                id: imported.id,
+                message,
+            })
+        };
+
+        if let Err(e) =
+            validate_imported_policy_set(now, &imported.policy_set, satellite_provider.clone(), db)
+                .await
+        {
+            match e {
+                AppError::Expected(e) => error(e.message),
+                e => return Err(e),
+            }
+            continue;
+        }
+
+        let Some(id) = imported.id else {
+            planned.push(ImportedPolicySet {
+                index,
+                id: None,
+                action: ImportAction::Created,
+            });
+            continue;
+        };
+
+        if !ids.insert(id) {
+            error(format!("policy set '{}' occurs more than once", id));
+            continue;
+        }
+
+        let existing = policy_store::get_policy_set_by_id(&id, db)
+            .await
+            .context("Error getting policy set")?;
+
+        // a deleted policy set still has its identifier until it is purged
+        if existing.is_none()
+            && policy_store::get_deleted_policy_set_by_id(&id, db)
+                .await
+                .context("Error getting deleted policy set")?
+                .is_some()
+        {
+            error(format!(
+                "policy set '{}' is deleted, restore it before importing it",
+                id
+            ));
+            continue;
+        }
+
+        match (existing, mode) {
+            (None, _) => planned.push(ImportedPolicySet {
+                index,
+                id: Some(id),
+                action: ImportAction::Created,
+            }),
+            (Some(_), ImportMode::Upsert) => planned.push(ImportedPolicySet {
+                index,
+                id: Some(id),
+                action: ImportAction::Updated,
+            }),
+            (Some(_), ImportMode::CreateOnly) => {
+                error(format!("policy set '{}' already exists", id));
+            }
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::BAD_REQUEST,
+            message: "Invalid policy set import".to_owned(),
+            reason: errors
+                .iter()
+                .map(|e| format!("{}: {}", e.index, e.message))
+                .collect::<Vec<String>>()
+                .join(" | "),
+            metadata: Some(json!({ "entryErrors": errors })),
+        }));
+    }
+
+    Ok(planned)
+}
+
+async fn update_imported_policy_set<C: ConnectionTrait>(
+    now: DateTime<Utc>,
+    policy_set_id: Uuid,
+    args: &InsertPolicySetWithPolicies,
+    transaction: &C,
+) -> Result<(), AppError> {
+    increment_policy_set_revision(&policy_set_id, &None, transaction).await?;
+
+    policy_store::update_policy_set(
+        &policy_set_id,
+        &args.target,
+        &args.policy_issuer,
+        &args.licences,
+        &args.max_delegation_depth,
+        args.not_before,
+        args.not_on_or_after,
+        transaction,
+    )
+    .await
+    .context("Error updating imported policy set")?;
+
+    policy_store::delete_policies_by_policy_set(&policy_set_id, transaction)
+        .await
+        .context("Error deleting policies of imported policy set")?;
+    for policy in args.policies.iter() {
+        policy_store::insert_policy(policy_set_id, policy, transaction)
+            .await
+            .context("Error inserting policy into db")?;
+    }
+
+    policy_version_store::insert_policy_set_version(
+        now,
+        &policy_set_id,
+        PolicySetChange::Imported,
+        transaction,
+    )
+    .await
+    .context("Error recording policy set version")?;
+
+    log_event(
+        now,
+        policy_set_id.to_string(),
+        EventType::ArPolicySetEdited(PolicySetEditedEventMetadata {
+            policy_set_id,
+            edited_type: EditedType::Imported,
+        }),
+        None,
+        None,
+        transaction,
+    )
+    .await
+    .context("Error logging policy set edited event")?;
+
+    Ok(())
+}
+
+// the import is applied in a single transaction, either all policy sets are imported or none
+pub async fn import_policy_sets(
+    now: DateTime<Utc>,
+    policy_sets: Vec<ExportedPolicySet>,
+    mode: ImportMode,
+    dry_run: bool,
+    satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<PolicySetImportResult, AppError> {
+    let mut planned = plan_import(now, &policy_sets, mode, satellite_provider, db).await?;
+
+    if dry_run {
+        return Ok(PolicySetImportResult {
+            dry_run,
+            policy_sets: planned,
+        });
+    }
+
+    let transaction = db.begin().await.context("error starting db transaction")?;
+
+    for (plan, imported) in planned.iter_mut().zip(policy_sets.iter()) {
+        match plan.action {
+            ImportAction::Created => {
+                let id = plan.id.unwrap_or_else(Uuid::new_v4);
+                insert_policy_set_with_policies_in_transaction(
+                    now,
+                    id,
+                    &imported.policy_set,
+                    PolicySetStatus::Active,
+                    &transaction,
+                )
+                .await?;
+                plan.id = Some(id);
+            }
+            ImportAction::Updated => {
+                let id = plan.id.context("updated policy set without identifier")?;
+                update_imported_policy_set(now, id, &imported.policy_set, &transaction).await?;
+            }
+        }
+    }
+
+    transaction
+        .commit()
+        .await
+        .context("error commiting transaction to db")?;
+
+    if !planned.is_empty() {
+        decision_cache.invalidate_all();
+    }
+
+    Ok(PolicySetImportResult {
+        dry_run,
+        policy_sets: planned,
+    })
+}
@@ -0,0 +1,170 @@
+use std::time::Duration;
+
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use uuid::Uuid;
+
+use crate::db::policy::{self as policy_store, MatchingPolicySetRow};
+use crate::db::policy_version::{self as policy_version_store, PolicySetChange};
+use crate::error::{AppError, ExpectedError};
+use crate::services::audit_log::{log_event, EventType, PolicySetRestoredEventMetadata};
+use crate::services::decision_cache::DecisionCache;
+use crate::services::policy::{increment_policy_set_revision, InsertPolicySetWithPolicies};
+use crate::services::policy_version::validate_restored_policy_set;
+use crate::utils::is_same_party;
+use crate::TimeProvider;
+
+// how often the policy sets that were deleted longer than the retention period ago are purged
+const PURGE_INTERVAL: Duration = Duration::from_secs(3600);
+
+fn deleted_policy_set_not_found() -> AppError {
+    AppError::Expected(ExpectedError {
+        status_code: StatusCode::NOT_FOUND,
+        message: "Can't find deleted policy set".to_owned(),
+        reason: "not found".to_owned(),
+        metadata: None,
+    })
+}
+
+async fn restore_deleted_policy_set(
+    now: DateTime<Utc>,
+    policy_set: &ar_entity::policy_set::Model,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
+    let transaction = db.begin().await.context("error starting db transaction")?;
+
+    policy_store::restore_deleted_policy_set(&policy_set.id, &transaction)
+        .await
+        .context("Error restoring deleted policy set")?;
+
+    // the restore is rolled back when the policy set is no longer valid
+    let restored = policy_store::get_policy_set_with_policies(&policy_set.id, &transaction)
+        .await?
+        .context("Policy set not found after restore")?;
+    validate_restored_policy_set(&InsertPolicySetWithPolicies::from(&restored), db).await?;
+
+    increment_policy_set_revision(&policy_set.id, &None, &transaction).await?;
+
+    policy_version_store::insert_policy_set_version(
+        now,
+        &policy_set.id,
+        PolicySetChange::Restored,
+        &transaction,
+    )
+    .await
+    .context("Error recording policy set version")?;
+
+    log_event(
+        now,
+        policy_set.id.to_string(),
+        EventType::ArPolicySetRestored(PolicySetRestoredEventMetadata {
+            policy_set_id: policy_set.id,
+        }),
+        None,
+        None,
+        &transaction,
+    )
+    .await
+    .context("Error logging policy set restored event")?;
+
+    transaction
+        .commit()
+        .await
+        .context("error commiting transaction to db")?;
+
+    decision_cache.invalidate(&policy_set.access_subject, policy_set.max_delegation_depth);
+
+    let policy_set = policy_store::get_policy_set_with_policies(&policy_set.id, db)
+        .await?
+        .context("Policy set not found after restore")?;
+
+    Ok(policy_set)
+}
+
+// only the issuer of a deleted policy set can restore it
+pub async fn restore_policy_set(
+    now: DateTime<Utc>,
+    requester_company_id: &str,
+    policy_set_id: &Uuid,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
+    let policy_set = policy_store::get_deleted_policy_set_by_id(policy_set_id, db)
+        .await?
+        .ok_or_else(deleted_policy_set_not_found)?;
+
+    if !is_same_party(requester_company_id, &policy_set.policy_issuer) {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::FORBIDDEN,
+            message: "not allowed to restore policy set".to_owned(),
+            reason: "not allowed to restore policy set".to_owned(),
+            metadata: None,
+        }));
+    }
+
+    restore_deleted_policy_set(now, &policy_set, db, decision_cache).await
+}
+
+pub async fn restore_policy_set_admin(
+    now: DateTime<Utc>,
+    policy_set_id: &Uuid,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<MatchingPolicySetRow, AppError> {
+    let policy_set = policy_store::get_deleted_policy_set_by_id(policy_set_id, db)
+        .await?
+        .ok_or_else(deleted_policy_set_not_found)?;
+
+    restore_deleted_policy_set(now, &policy_set, db, decision_cache).await
+}
+
+// purges the policy sets that were deleted longer than the retention period ago, the versions of
+// purged policy sets are kept
+pub async fn purge_deleted_policy_sets(
+    now: DateTime<Utc>,
+    retention_days: i64,
+    db: &DatabaseConnection,
+) -> anyhow::Result<Vec<Uuid>> {
+    policy_store::purge_deleted_policy_sets(now - chrono::Duration::days(retention_days), db).await
+}
+
+// purges the policy sets that were rejected longer than the retention period ago, the versions of
+// purged policy sets are kept
+pub async fn purge_rejected_policy_sets(
+    now: DateTime<Utc>,
+    retention_days: i64,
+    db: &DatabaseConnection,
+) -> anyhow::Result<Vec<Uuid>> {
+    policy_store::purge_rejected_policy_sets(now - chrono::Duration::days(retention_days), db).await
+}
+
+pub fn spawn_purge_task(
+    retention_days: i64,
+    rejected_retention_days: i64,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    db: DatabaseConnection,
+) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(PURGE_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            match purge_deleted_policy_sets(time_provider.now(), retention_days, &db).await {
+                Ok(purged) if purged.is_empty() => {}
+                Ok(purged) => tracing::info!("purged {} deleted policy sets", purged.len()),
+                Err(e) => tracing::error!("error purging deleted policy sets: {:?}", e),
+            }
+
+            match purge_rejected_policy_sets(time_provider.now(), rejected_retention_days, &db)
+                .await
+            {
+                Ok(purged) if purged.is_empty() => {}
+                Ok(purged) => tracing::info!("purged {} rejected policy sets", purged.len()),
+                Err(e) => tracing::error!("error purging rejected policy sets: {:?}", e),
+            }
+        }
+    });
+}
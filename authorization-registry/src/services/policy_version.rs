@@ -0,0 +1,305 @@
+use anyhow::Context;
+use chrono::{DateTime, Utc};
+use reqwest::StatusCode;
+use sea_orm::{DatabaseConnection, TransactionTrait};
+use uuid::Uuid;
+
+use crate::config::CombiningAlgorithmConfig;
+use crate::db::policy as policy_store;
+use crate::db::policy_version::{
+    self as policy_version_store, PolicySetChange, PolicySetVersion, PolicySetVersionSummary,
+};
+use crate::error::{AppError, ExpectedError};
+use crate::services::audit_log::{
+    log_event, EditedType, EventType, PolicySetEditedEventMetadata, RolledBack,
+};
+use crate::services::decision_cache::DecisionCache;
+use crate::services::ishare_provider::SatelliteProvider;
+use crate::services::policy::{
+    increment_policy_set_revision, policy_set_not_found, validate_policy_set_access_subject,
+    validate_policy_set_access_subject_user, validate_policy_set_ishare_parties,
+    validate_policy_set_licenses, validate_policy_set_party_groups,
+    validate_policy_set_validity_window, verify_policy_set_access, ExpectedRevisions,
+    InsertPolicySetWithPolicies, PolicySetAction,
+};
+use crate::services::resource_type::validate_policies_resource_types;
+use crate::utils::is_same_party;
+use crate::TimeProvider;
+
+fn version_not_found(policy_set_id: &Uuid, version: i32) -> AppError {
+    AppError::Expected(ExpectedError {
+        status_code: StatusCode::NOT_FOUND,
+        message: "Can't find policy set version".to_owned(),
+        reason: format!("policy set '{}' has no version {}", policy_set_id, version),
+        metadata: None,
+    })
+}
+
+// the versions of a policy set can be read and restored by the parties that can read and edit the
+// policy set in its current state
+async fn verify_policy_set_version_access(
+    requester_company_id: &str,
+    policy_set_id: &Uuid,
+    action: PolicySetAction,
+    client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    db: &DatabaseConnection,
+) -> Result<ar_entity::policy_set::Model, AppError> {
+    let policy_set = policy_store::get_policy_set_by_id(policy_set_id, db)
+        .await
+        .context("Error getting policy set")?
+        .ok_or_else(policy_set_not_found)?;
+
+    let policies = policy_store::get_policies_by_policy_set(policy_set_id, db)
+        .await
+        .context(format!(
+            "Error getting policies from db for policy set: {}",
+            policy_set_id
+        ))?;
+
+    let identifiers = policies.iter().map(|p| p.resource_type.clone()).collect();
+
+    let access = verify_policy_set_access(
+        requester_company_id,
+        &action,
+        &policy_set.policy_issuer,
+        &policy_set.access_subject,
+        identifiers,
+        client_eori,
+        combining_algorithms,
+        time_provider,
+        db,
+    )
+    .await
+    .context("error verifying access to policy set versions")?;
+
+    if !access {
+        let message = match action {
+            PolicySetAction::Read => "not allowed to read policy set",
+            _ => "not allowed to edit policy set",
+        };
+
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::FORBIDDEN,
+            message: message.to_owned(),
+            reason: message.to_owned(),
+            metadata: None,
+        }));
+    }
+
+    Ok(policy_set)
+}
+
+pub async fn get_policy_set_versions(
+    requester_company_id: &str,
+    policy_set_id: &Uuid,
+    client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    db: &DatabaseConnection,
+) -> Result<Vec<PolicySetVersionSummary>, AppError> {
+    verify_policy_set_version_access(
+        requester_company_id,
+        policy_set_id,
+        PolicySetAction::Read,
+        client_eori,
+        combining_algorithms,
+        time_provider,
+        db,
+    )
+    .await?;
+
+    let versions = policy_version_store::get_policy_set_versions(policy_set_id, db).await?;
+
+    Ok(versions)
+}
+
+pub async fn get_policy_set_version(
+    requester_company_id: &str,
+    policy_set_id: &Uuid,
+    version: i32,
+    client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    db: &DatabaseConnection,
+) -> Result<PolicySetVersion, AppError> {
+    verify_policy_set_version_access(
+        requester_company_id,
+        policy_set_id,
+        PolicySetAction::Read,
+        client_eori,
+        combining_algorithms,
+        time_provider,
+        db,
+    )
+    .await?;
+
+    policy_version_store::get_policy_set_version(policy_set_id, version, db)
+        .await?
+        .ok_or_else(|| version_not_found(policy_set_id, version))
+}
+
+// licenses, party groups and resource types can have changed since the policy set was stored, so a
+// restored policy set is validated like a new one
+pub(crate) async fn validate_restored_policy_set(
+    args: &InsertPolicySetWithPolicies,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    validate_policy_set_validity_window(args)?;
+    validate_policy_set_access_subject(args)?;
+    validate_policy_set_licenses(args, db).await?;
+    validate_policy_set_party_groups(args, db).await?;
+    validate_policy_set_access_subject_user(args, db).await?;
+    validate_policies_resource_types(&args.policies, db).await?;
+
+    Ok(())
+}
+
+// a version issued to another access subject is only restored when that is still an iSHARE party
+async fn validate_restored_version(
+    now: DateTime<Utc>,
+    policy_set: &ar_entity::policy_set::Model,
+    restored: &PolicySetVersion,
+    satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    let snapshot = &restored.policy_set;
+    let args = InsertPolicySetWithPolicies::from(snapshot);
+
+    validate_restored_policy_set(&args, db).await?;
+    if !is_same_party(&snapshot.access_subject, &policy_set.access_subject) {
+        validate_policy_set_ishare_parties(now, &args, satellite_provider).await?;
+    }
+
+    Ok(())
+}
+
+async fn restore_policy_set_version(
+    now: DateTime<Utc>,
+    policy_set: &ar_entity::policy_set::Model,
+    version: i32,
+    expected_revisions: &ExpectedRevisions,
+    satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<PolicySetVersion, AppError> {
+    let restored = policy_version_store::get_policy_set_version(&policy_set.id, version, db)
+        .await?
+        .ok_or_else(|| version_not_found(&policy_set.id, version))?;
+
+    validate_restored_version(now, policy_set, &restored, satellite_provider, db).await?;
+
+    let transaction = db.begin().await.context("error starting db transaction")?;
+
+    increment_policy_set_revision(&policy_set.id, expected_revisions, &transaction).await?;
+
+    policy_store::restore_policy_set(&restored.policy_set, &transaction)
+        .await
+        .context("Error restoring policy set version")?;
+
+    let new_version = policy_version_store::insert_policy_set_version(
+        now,
+        &policy_set.id,
+        PolicySetChange::RolledBack,
+        &transaction,
+    )
+    .await
+    .context("Error recording policy set version")?;
+
+    log_event(
+        now,
+        policy_set.id.to_string(),
+        EventType::ArPolicySetEdited(PolicySetEditedEventMetadata {
+            policy_set_id: policy_set.id,
+            edited_type: EditedType::RolledBack(RolledBack {
+                restored_version: version,
+                version: new_version,
+            }),
+        }),
+        None,
+        None,
+        &transaction,
+    )
+    .await
+    .context("Error logging policy set edited event")?;
+
+    transaction
+        .commit()
+        .await
+        .context("error commiting transaction to db")?;
+
+    decision_cache.invalidate(&policy_set.access_subject, policy_set.max_delegation_depth);
+    decision_cache.invalidate(
+        &restored.policy_set.access_subject,
+        restored.policy_set.max_delegation_depth,
+    );
+
+    let version = policy_version_store::get_policy_set_version(&policy_set.id, new_version, db)
+        .await?
+        .context("Policy set version not found after rollback")?;
+
+    Ok(version)
+}
+
+pub async fn rollback_policy_set(
+    now: DateTime<Utc>,
+    requester_company_id: &str,
+    policy_set_id: &Uuid,
+    version: i32,
+    expected_revisions: &ExpectedRevisions,
+    client_eori: &str,
+    combining_algorithms: &CombiningAlgorithmConfig,
+    time_provider: std::sync::Arc<dyn TimeProvider>,
+    satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<PolicySetVersion, AppError> {
+    let policy_set = verify_policy_set_version_access(
+        requester_company_id,
+        policy_set_id,
+        PolicySetAction::Edit,
+        client_eori,
+        combining_algorithms,
+        time_provider,
+        db,
+    )
+    .await?;
+
+    restore_policy_set_version(
+        now,
+        &policy_set,
+        version,
+        expected_revisions,
+        satellite_provider,
+        db,
+        decision_cache,
+    )
+    .await
+}
+
+pub async fn rollback_policy_set_admin(
+    now: DateTime<Utc>,
+    policy_set_id: &Uuid,
+    version: i32,
+    expected_revisions: &ExpectedRevisions,
+    satellite_provider: std::sync::Arc<dyn SatelliteProvider>,
+    db: &DatabaseConnection,
+    decision_cache: &DecisionCache,
+) -> Result<PolicySetVersion, AppError> {
+    let policy_set = policy_store::get_policy_set_by_id(policy_set_id, db)
+        .await
+        .context("Error getting policy set")?
+        .ok_or_else(policy_set_not_found)?;
+
+    restore_policy_set_version(
+        now,
+        &policy_set,
+        version,
+        expected_revisions,
+        satellite_provider,
+        db,
+        decision_cache,
+    )
+    .await
+}
@@ -0,0 +1,319 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use ishare::delegation_request::DelegationRequest;
+use reqwest::StatusCode;
+use serde::Deserialize;
+
+use crate::error::{AppError, ExpectedError};
+use crate::utils::{is_same_party, normalize_party_id, read_unverified_claims};
+
+use super::ishare_provider::SatelliteProvider;
+
+// tolerated difference between our clock and the clock of the issuer of a previous step
+const CLOCK_SKEW_SECONDS: i64 = 5;
+
+#[derive(Deserialize, Debug)]
+struct PreviousStepClaims {
+    iss: String,
+    aud: String,
+    jti: String,
+    iat: i64,
+    exp: i64,
+}
+
+fn previous_step_error(step: usize, reason: String) -> AppError {
+    AppError::Expected(ExpectedError {
+        status_code: StatusCode::BAD_REQUEST,
+        message: format!("previous step {} is invalid: {}", step, &reason),
+        reason,
+        metadata: None,
+    })
+}
+
+// verifies previous steps of delegation requests and remembers the ones that were used, so a
+// previous step can't be replayed while it is valid. the used previous steps are kept in memory
+// and aren't shared between instances of the authorization registry
+pub struct PreviousStepsVerifier {
+    max_age_seconds: i64,
+    // expiry of the used previous steps by issuer and jti
+    used: RwLock<HashMap<(String, String), i64>>,
+}
+
+impl PreviousStepsVerifier {
+    pub fn new(max_age_seconds: i64) -> Self {
+        Self {
+            max_age_seconds,
+            used: RwLock::new(HashMap::new()),
+        }
+    }
+
+    // the previous steps form a chain of client assertions: the first is issued by the policy
+    // issuer or the access subject, every other one by the audience of the step before it and the
+    // last one is addressed to the requester
+    pub async fn verify(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        requester_company_id: &str,
+        delegation_request: &DelegationRequest,
+        previous_steps: &Vec<String>,
+        satellite_provider: Arc<dyn SatelliteProvider>,
+    ) -> Result<(), AppError> {
+        let mut steps: Vec<PreviousStepClaims> = vec![];
+
+        for (index, client_assertion) in previous_steps.iter().enumerate() {
+            let step = index + 1;
+            let claims =
+                read_unverified_claims::<PreviousStepClaims>(client_assertion).map_err(|e| {
+                    previous_step_error(step, format!("malformed client assertion: {}", e))
+                })?;
+
+            match steps.last() {
+                None => {
+                    if !is_same_party(&claims.iss, &delegation_request.policy_issuer)
+                        && !is_same_party(&claims.iss, &delegation_request.target.access_subject)
+                    {
+                        return Err(previous_step_error(
+                            step,
+                            format!(
+                                "issued by '{}' instead of the policy issuer or access subject",
+                                claims.iss
+                            ),
+                        ));
+                    }
+                }
+                Some(previous) => {
+                    if !is_same_party(&claims.iss, &previous.aud) {
+                        return Err(previous_step_error(
+                            step,
+                            format!(
+                                "issued by '{}' instead of '{}', the audience of previous step {}",
+                                claims.iss, previous.aud, index
+                            ),
+                        ));
+                    }
+                }
+            }
+
+            if claims.exp <= now.timestamp() {
+                return Err(previous_step_error(
+                    step,
+                    "client assertion has expired".to_owned(),
+                ));
+            }
+
+            if claims.iat > now.timestamp() + CLOCK_SKEW_SECONDS
+                || now.timestamp() - claims.iat > self.max_age_seconds
+            {
+                return Err(previous_step_error(
+                    step,
+                    format!(
+                        "client assertion issued at {} is older than {} seconds or in the future",
+                        claims.iat, self.max_age_seconds
+                    ),
+                ));
+            }
+
+            // the signature is verified once the expected issuer is known
+            satellite_provider
+                .verify_previous_step(now, client_assertion, &claims.iss, &claims.aud)
+                .await
+                .map_err(|e| {
+                    previous_step_error(step, format!("verification by satellite failed: {}", e))
+                })?;
+
+            steps.push(claims);
+        }
+
+        let Some(last) = steps.last() else {
+            return Err(previous_step_error(0, "previous steps is empty".to_owned()));
+        };
+
+        if !is_same_party(&last.aud, requester_company_id) {
+            return Err(previous_step_error(
+                steps.len(),
+                format!(
+                    "addressed to '{}' instead of the requester '{}'",
+                    last.aud, requester_company_id
+                ),
+            ));
+        }
+
+        self.register(now, &steps)
+    }
+
+    // marks the steps as used, all of them or none when one of them was used before
+    fn register(
+        &self,
+        now: chrono::DateTime<chrono::Utc>,
+        steps: &Vec<PreviousStepClaims>,
+    ) -> Result<(), AppError> {
+        let mut used = self.used.write().expect("previous steps lock poisoned");
+        used.retain(|_, exp| *exp > now.timestamp());
+
+        let keys: Vec<(String, String)> = steps
+            .iter()
+            .map(|s| (normalize_party_id(&s.iss), s.jti.clone()))
+            .collect();
+
+        if let Some(index) = keys.iter().position(|k| used.contains_key(k)) {
+            return Err(previous_step_error(
+                index + 1,
+                format!(
+                    "client assertion with jti '{}' was already used",
+                    keys[index].1
+                ),
+            ));
+        }
+
+        for (key, step) in keys.into_iter().zip(steps.iter()) {
+            used.insert(key, step.exp);
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ishare::delegation_request::DelegationTarget;
+    use jsonwebtoken::{EncodingKey, Header};
+
+    use crate::test_helpers::helpers::TestSatelliteProvider;
+
+    use super::*;
+
+    fn now() -> chrono::DateTime<chrono::Utc> {
+        chrono::DateTime::from_timestamp(1715247205, 0).unwrap()
+    }
+
+    fn step(iss: &str, aud: &str, jti: &str, iat: i64) -> String {
+        jsonwebtoken::encode(
+            &Header::default(),
+            &serde_json::json!({
+                "iss": iss,
+                "sub": iss,
+                "aud": aud,
+                "jti": jti,
+                "iat": iat,
+                "exp": iat + 30,
+            }),
+            &EncodingKey::from_secret(b"secret"),
+        )
+        .unwrap()
+    }
+
+    fn delegation_request() -> DelegationRequest {
+        DelegationRequest {
+            policy_issuer: "NL.ISSUER".to_owned(),
+            target: DelegationTarget {
+                access_subject: "NL.SUBJECT".to_owned(),
+            },
+            policy_sets: vec![],
+        }
+    }
+
+    async fn verify(
+        verifier: &PreviousStepsVerifier,
+        previous_steps: Vec<String>,
+    ) -> Result<(), String> {
+        verifier
+            .verify(
+                now(),
+                "NL.REQUESTER",
+                &delegation_request(),
+                &previous_steps,
+                Arc::new(TestSatelliteProvider {}),
+            )
+            .await
+            .map_err(|e| match e {
+                AppError::Expected(e) => e.message,
+                _ => "unexpected error".to_owned(),
+            })
+    }
+
+    #[tokio::test]
+    async fn test_verify_previous_steps_chain() {
+        let verifier = PreviousStepsVerifier::new(30);
+        let iat = now().timestamp() - 10;
+
+        assert!(verify(
+            &verifier,
+            vec![
+                step("NL.SUBJECT", "NL.BROKER", "1", iat),
+                step("nl.broker", "NL.REQUESTER", "2", iat),
+            ]
+        )
+        .await
+        .is_ok());
+
+        // the same chain can't be used twice
+        let result = verify(
+            &verifier,
+            vec![
+                step("NL.SUBJECT", "NL.BROKER", "1", iat),
+                step("NL.BROKER", "NL.REQUESTER", "3", iat),
+            ],
+        )
+        .await;
+        assert!(result.is_err_and(|e| e.contains("previous step 1") && e.contains("already used")));
+    }
+
+    #[tokio::test]
+    async fn test_verify_previous_steps_broken_chain() {
+        let verifier = PreviousStepsVerifier::new(30);
+        let iat = now().timestamp() - 10;
+
+        let result = verify(&verifier, vec![step("NL.OTHER", "NL.REQUESTER", "1", iat)]).await;
+        assert!(result.is_err_and(|e| e.contains("previous step 1") && e.contains("NL.OTHER")));
+
+        let result = verify(
+            &verifier,
+            vec![
+                step("NL.ISSUER", "NL.BROKER", "2", iat),
+                step("NL.OTHER", "NL.REQUESTER", "3", iat),
+            ],
+        )
+        .await;
+        assert!(result.is_err_and(|e| e.contains("previous step 2") && e.contains("NL.BROKER")));
+
+        let result = verify(&verifier, vec![step("NL.ISSUER", "NL.BROKER", "4", iat)]).await;
+        assert!(result.is_err_and(|e| e.contains("previous step 1") && e.contains("requester")));
+
+        let result = verify(&verifier, vec!["not a jwt".to_owned()]).await;
+        assert!(result.is_err_and(|e| e.contains("malformed")));
+
+        // steps of a broken chain are not marked as used
+        let result = verify(&verifier, vec![step("NL.ISSUER", "NL.REQUESTER", "2", iat)]).await;
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_verify_previous_steps_freshness() {
+        let verifier = PreviousStepsVerifier::new(30);
+
+        let result = verify(
+            &verifier,
+            vec![step(
+                "NL.ISSUER",
+                "NL.REQUESTER",
+                "1",
+                now().timestamp() - 40,
+            )],
+        )
+        .await;
+        assert!(result.is_err_and(|e| e.contains("expired")));
+
+        let result = verify(
+            &verifier,
+            vec![step(
+                "NL.ISSUER",
+                "NL.REQUESTER",
+                "2",
+                now().timestamp() + 60,
+            )],
+        )
+        .await;
+        assert!(result.is_err_and(|e| e.contains("in the future")));
+    }
+}
@@ -0,0 +1,386 @@
+use std::collections::HashMap;
+
+use anyhow::Context;
+use ar_entity::delegation_evidence::{Policy, ResourceRule};
+use ar_entity::resource_type::Model as ResourceTypeModel;
+use axum::http::StatusCode;
+use sea_orm::DatabaseConnection;
+use serde::Serialize;
+use serde_json::json;
+use utoipa::ToSchema;
+
+use crate::db::resource_type as resource_type_store;
+use crate::error::{AppError, ExpectedError};
+
+use super::pattern::{is_matching_pattern, is_pattern, validate_pattern};
+
+// a field of a request that doesn't match the resource type registry
+#[derive(Serialize, Debug, Clone, PartialEq, ToSchema)]
+pub struct FieldError {
+    // the path of the field in the request, e.g. 'policies[0].target.actions[1]'
+    pub field: String,
+    pub message: String,
+}
+
+// the names of the fields of a resource target, relative to the target
+struct FieldNames {
+    resource_type: &'static str,
+    identifiers: &'static str,
+    attributes: &'static str,
+    actions: &'static str,
+}
+
+const POLICY_FIELD_NAMES: FieldNames = FieldNames {
+    resource_type: "resource.type",
+    identifiers: "resource.identifiers",
+    attributes: "resource.attributes",
+    actions: "actions",
+};
+
+const TEMPLATE_FIELD_NAMES: FieldNames = FieldNames {
+    resource_type: "resource_type",
+    identifiers: "identifiers",
+    attributes: "attributes",
+    actions: "actions",
+};
+
+// the resource and actions of a policy, or of one of its deny rules, and where they are in the request
+struct ResourceTarget<'a> {
+    path: String,
+    field_names: &'a FieldNames,
+    resource_type: &'a str,
+    identifiers: &'a Vec<String>,
+    attributes: &'a Vec<String>,
+    actions: &'a Vec<String>,
+}
+
+impl ResourceTarget<'_> {
+    fn field(&self, name: &str, index: usize) -> String {
+        format!("{}{}[{}]", self.path, name, index)
+    }
+}
+
+fn policy_targets<'a>(path: String, policy: &'a Policy) -> Vec<ResourceTarget<'a>> {
+    let deny_targets = policy
+        .rules
+        .iter()
+        .enumerate()
+        .filter_map(|(index, rule)| match rule {
+            ResourceRule::Permit(_) => None,
+            ResourceRule::Deny(deny) => Some(ResourceTarget {
+                path: format!("{}rules[{}].target.", path, index),
+                field_names: &POLICY_FIELD_NAMES,
+                resource_type: &deny.target.resource.resource_type,
+                identifiers: &deny.target.resource.identifiers,
+                attributes: &deny.target.resource.attributes,
+                actions: &deny.target.actions,
+            }),
+        });
+
+    std::iter::once(ResourceTarget {
+        path: format!("{}target.", path),
+        field_names: &POLICY_FIELD_NAMES,
+        resource_type: &policy.target.resource.resource_type,
+        identifiers: &policy.target.resource.identifiers,
+        attributes: &policy.target.resource.attributes,
+        actions: &policy.target.actions,
+    })
+    .chain(deny_targets)
+    .collect()
+}
+
+// checks the resource target against its registered resource type. identifiers and attributes
+// that are patterns match values of any format, they are not checked
+fn check_resource_target(
+    target: &ResourceTarget,
+    resource_type: &ResourceTypeModel,
+) -> Vec<FieldError> {
+    let mut errors = vec![];
+
+    for (index, action) in target.actions.iter().enumerate() {
+        if action != "*" && !resource_type.actions.contains(action) {
+            errors.push(FieldError {
+                field: target.field(target.field_names.actions, index),
+                message: format!(
+                    "Action '{}' is not allowed for resource type '{}', allowed actions: {}",
+                    action,
+                    resource_type.name,
+                    resource_type.actions.join(", ")
+                ),
+            });
+        }
+    }
+
+    if !resource_type.attributes.is_empty() {
+        for (index, attribute) in target.attributes.iter().enumerate() {
+            if !is_pattern(attribute) && !resource_type.attributes.contains(attribute) {
+                errors.push(FieldError {
+                    field: target.field(target.field_names.attributes, index),
+                    message: format!(
+                        "Attribute '{}' is not allowed for resource type '{}', allowed attributes: {}",
+                        attribute,
+                        resource_type.name,
+                        resource_type.attributes.join(", ")
+                    ),
+                });
+            }
+        }
+    }
+
+    if let Some(identifier_format) = &resource_type.identifier_format {
+        for (index, identifier) in target.identifiers.iter().enumerate() {
+            if !is_pattern(identifier) && !is_matching_pattern(identifier_format, identifier) {
+                errors.push(FieldError {
+                    field: target.field(target.field_names.identifiers, index),
+                    message: format!(
+                        "Identifier '{}' doesn't match the format '{}' of resource type '{}'",
+                        identifier, identifier_format, resource_type.name
+                    ),
+                });
+            }
+        }
+    }
+
+    errors
+}
+
+fn field_errors_response(errors: Vec<FieldError>) -> AppError {
+    AppError::Expected(ExpectedError {
+        status_code: StatusCode::BAD_REQUEST,
+        message: "Policy doesn't match the resource type registry".to_owned(),
+        reason: errors
+            .iter()
+            .map(|e| format!("{}: {}", e.field, e.message))
+            .collect::<Vec<String>>()
+            .join(" | "),
+        metadata: Some(json!({ "fieldErrors": errors })),
+    })
+}
+
+// an empty registry doesn't validate the resource targets, once resource types are registered
+// every resource type has to be registered
+async fn validate_resource_targets(
+    targets: Vec<ResourceTarget<'_>>,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    let mut names: Vec<String> = targets
+        .iter()
+        .filter(|t| !is_pattern(t.resource_type))
+        .map(|t| t.resource_type.to_owned())
+        .collect();
+    names.sort();
+    names.dedup();
+
+    let resource_types: HashMap<String, ResourceTypeModel> =
+        resource_type_store::get_resource_types_by_names(&names, db)
+            .await
+            .context("Error getting resource types")?
+            .into_iter()
+            .map(|rt| (rt.name.clone(), rt))
+            .collect();
+
+    let is_registry_in_use = !resource_types.is_empty()
+        || (resource_types.len() < names.len()
+            && resource_type_store::has_resource_types(db)
+                .await
+                .context("Error getting resource types")?);
+
+    let errors: Vec<FieldError> = targets
+        .iter()
+        .filter(|t| !is_pattern(t.resource_type))
+        .flat_map(|t| match resource_types.get(t.resource_type) {
+            Some(rt) => check_resource_target(t, rt),
+            None if is_registry_in_use => vec![FieldError {
+                field: format!("{}{}", t.path, t.field_names.resource_type),
+                message: format!("Resource type '{}' is not registered", t.resource_type),
+            }],
+            None => vec![],
+        })
+        .collect();
+
+    if !errors.is_empty() {
+        return Err(field_errors_response(errors));
+    }
+
+    Ok(())
+}
+
+pub async fn validate_policy_resource_type(
+    policy: &Policy,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    validate_resource_targets(policy_targets("".to_owned(), policy), db).await
+}
+
+pub async fn validate_policies_resource_types(
+    policies: &Vec<Policy>,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    let targets = policies
+        .iter()
+        .enumerate()
+        .flat_map(|(index, policy)| policy_targets(format!("policies[{}].", index), policy))
+        .collect();
+
+    validate_resource_targets(targets, db).await
+}
+
+pub async fn validate_template_resource_types(
+    policies: &Vec<ar_entity::policy_set_template::Policy>,
+    db: &DatabaseConnection,
+) -> Result<(), AppError> {
+    let targets = policies
+        .iter()
+        .enumerate()
+        .map(|(index, policy)| ResourceTarget {
+            path: format!("policies[{}].", index),
+            field_names: &TEMPLATE_FIELD_NAMES,
+            resource_type: &policy.resource_type,
+            identifiers: &policy.identifiers,
+            attributes: &policy.attributes,
+            actions: &policy.actions,
+        })
+        .collect();
+
+    validate_resource_targets(targets, db).await
+}
+
+// validates a resource type before it is added to or updated in the registry
+pub fn validate_resource_type(resource_type: &ResourceTypeModel) -> Result<(), AppError> {
+    let mut errors = vec![];
+
+    if resource_type.name.trim().is_empty() || resource_type.name == "*" {
+        errors.push(FieldError {
+            field: "name".to_owned(),
+            message: "Name has to be a resource type, not empty or '*'".to_owned(),
+        });
+    }
+
+    if resource_type.actions.is_empty() {
+        errors.push(FieldError {
+            field: "actions".to_owned(),
+            message: "At least one action is required".to_owned(),
+        });
+    }
+
+    if let Some(identifier_format) = &resource_type.identifier_format {
+        if let Err(message) = validate_pattern(identifier_format) {
+            errors.push(FieldError {
+                field: "identifierFormat".to_owned(),
+                message,
+            });
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(AppError::Expected(ExpectedError {
+            status_code: StatusCode::BAD_REQUEST,
+            message: "Invalid resource type".to_owned(),
+            reason: format!("{:?}", errors),
+            metadata: Some(json!({ "fieldErrors": errors })),
+        }));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use ar_entity::delegation_evidence::{Deny, Environment, Permit, Resource, Target};
+
+    use super::*;
+
+    fn resource_type() -> ResourceTypeModel {
+        ResourceTypeModel {
+            name: "Container".to_owned(),
+            description: "".to_owned(),
+            actions: vec!["Read".to_owned(), "Edit".to_owned()],
+            attributes: vec!["weight".to_owned()],
+            identifier_format: Some("urn:container:*".to_owned()),
+        }
+    }
+
+    fn policy(identifiers: Vec<&str>, attributes: Vec<&str>, actions: Vec<&str>) -> Policy {
+        let resource = Resource {
+            resource_type: "Container".to_owned(),
+            identifiers: identifiers.iter().map(|i| i.to_string()).collect(),
+            attributes: attributes.iter().map(|a| a.to_string()).collect(),
+        };
+
+        Policy {
+            target: ar_entity::delegation_evidence::ResourceTarget {
+                resource: resource.clone(),
+                actions: actions.iter().map(|a| a.to_string()).collect(),
+                environment: Environment {
+                    service_providers: vec![],
+                },
+            },
+            rules: vec![
+                ResourceRule::Permit(Permit::default()),
+                ResourceRule::Deny(Deny {
+                    target: Target {
+                        resource,
+                        actions: vec!["Delete".to_owned()],
+                        environment: None,
+                    },
+                    conditions: vec![],
+                }),
+            ],
+        }
+    }
+
+    fn fields(policy: &Policy) -> Vec<String> {
+        policy_targets("policies[0].".to_owned(), policy)
+            .iter()
+            .flat_map(|t| check_resource_target(t, &resource_type()))
+            .map(|e| e.field)
+            .collect()
+    }
+
+    #[test]
+    fn test_check_resource_target() {
+        assert_eq!(
+            fields(&policy(
+                vec!["urn:container:1"],
+                vec!["weight"],
+                vec!["Read"]
+            )),
+            vec!["policies[0].rules[1].target.actions[0]"]
+        );
+        // patterns match values of any format
+        assert_eq!(
+            fields(&policy(vec!["*"], vec!["*"], vec!["*"])),
+            vec!["policies[0].rules[1].target.actions[0]"]
+        );
+        assert_eq!(
+            fields(&policy(
+                vec!["urn:container:1", "urn:truck:1"],
+                vec!["colour"],
+                vec!["Read", "Create"]
+            )),
+            vec![
+                "policies[0].target.actions[1]",
+                "policies[0].target.resource.attributes[0]",
+                "policies[0].target.resource.identifiers[1]",
+                "policies[0].rules[1].target.actions[0]",
+                "policies[0].rules[1].target.resource.attributes[0]",
+                "policies[0].rules[1].target.resource.identifiers[1]",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_resource_type() {
+        assert!(validate_resource_type(&resource_type()).is_ok());
+        assert!(validate_resource_type(&ResourceTypeModel {
+            actions: vec![],
+            ..resource_type()
+        })
+        .is_err());
+        assert!(validate_resource_type(&ResourceTypeModel {
+            identifier_format: Some("regex:(".to_owned()),
+            ..resource_type()
+        })
+        .is_err());
+    }
+}
@@ -36,6 +36,14 @@ impl Role {
             Self::Machine(Machine { company_id }) => company_id.to_owned(),
         }
     }
+
+    // the ishare user behind a human token
+    pub fn get_user_id(&self) -> Option<String> {
+        match self {
+            Self::Human(Human { user_id, .. }) => Some(user_id.to_owned()),
+            Self::Machine(_) => None,
+        }
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
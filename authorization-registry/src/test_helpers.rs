@@ -3,7 +3,6 @@ pub mod helpers {
     use ar_migration::{Migrator, MigratorTrait};
     use axum::body::Body;
     use axum::{async_trait, Router};
-    use ishare::delegation_evidence::DelegationEvidenceContainer;
     use ishare::ishare::{Adherence, Capabilities, PartyInfo, ValidatePartyError};
     use sea_orm::{Database, DatabaseConnection};
     use serde_json::Value;
@@ -15,12 +14,17 @@ pub mod helpers {
     static INIT: Once = Once::new();
 
     use crate::config::{
-        AddressConfig, ContactConfig, FooterConfig, FrontendConfig, GeneralConfig,
-        NavigationConfig, SocialsConfig,
+        AddressConfig, CombiningAlgorithmConfig, ContactConfig, FooterConfig, FrontendConfig,
+        GeneralConfig, NavigationConfig, SocialsConfig,
     };
     use crate::error::AppError;
     use crate::get_app;
-    use crate::services::ishare_provider::{OAuthRequestForm, SatelliteProvider};
+    use crate::services::decision_cache::DecisionCache;
+    use crate::services::delegation::IssuedDelegationEvidence;
+    use crate::services::ishare_provider::{
+        OAuthRequestForm, PartyRegistration, SatelliteProvider,
+    };
+    use crate::services::previous_steps::PreviousStepsVerifier;
     use crate::services::server_token::{server_token_test_helper, UserOption};
     use crate::AppState;
     use crate::TimeProvider;
@@ -79,6 +83,8 @@ pub mod helpers {
             satellite_provider: Arc::new(sat_provider.clone()),
             time_provider: Arc::new(FakeTimeProvider::new()),
             de_expiry_seconds: 3600,
+            decision_cache: Arc::new(DecisionCache::new(60)),
+            previous_steps_verifier: Arc::new(PreviousStepsVerifier::new(30)),
             config: Arc::new(crate::AppConfig {
                 service_name: "AR".to_owned(),
                 deploy_route: "".to_owned(),
@@ -86,6 +92,8 @@ pub mod helpers {
                 client_eori: "NL.CONSUME_TOO_MUCH".to_owned(),
                 validate_m2m_certificate: true,
                 delegation_allows_service_providers: false,
+                combining_algorithm: CombiningAlgorithmConfig::default(),
+                trusted_proxies: vec![],
                 frontend: FrontendConfig {
                     footer: FooterConfig {
                         navigation: NavigationConfig {
@@ -131,21 +139,30 @@ pub mod helpers {
             return Ok("token".to_string());
         }
 
-        fn handle_previous_step_client_assertion(
+        async fn verify_previous_step(
             &self,
             _now: chrono::DateTime<chrono::Utc>,
-            _requestor_company_id: &str,
             _client_assertion: &str,
-            _policy_issuer: &str,
-            _access_subject: &str,
-        ) -> bool {
-            true
+            _issuer: &str,
+            _audience: &str,
+        ) -> anyhow::Result<()> {
+            Ok(())
+        }
+
+        async fn verify_delegation_token(
+            &self,
+            _now: chrono::DateTime<chrono::Utc>,
+            _delegation_token: &str,
+            _issuer: &str,
+            _audience: &str,
+        ) -> anyhow::Result<()> {
+            Ok(())
         }
 
         fn create_delegation_token(
             &self,
             _audience: &str,
-            _de_container: &DelegationEvidenceContainer,
+            _evidence: &IssuedDelegationEvidence,
         ) -> anyhow::Result<String> {
             Ok("delegation token".to_owned())
         }
@@ -176,6 +193,23 @@ pub mod helpers {
             });
         }
 
+        async fn get_party_registration(
+            &self,
+            _now: chrono::DateTime<chrono::Utc>,
+            eori: &str,
+        ) -> anyhow::Result<PartyRegistration> {
+            let roles = match eori {
+                "NL.CONSUME_TOO_MUCH" | "NL.TRUSTED_AR" => vec!["AuthorisationRegistry"],
+                _ => vec!["ServiceConsumer", "ServiceProvider"],
+            };
+
+            Ok(PartyRegistration {
+                roles: roles.into_iter().map(|r| r.to_owned()).collect(),
+                certifications: vec![],
+                dataspaces: vec!["test-dataspace".to_owned()],
+            })
+        }
+
         fn handle_h2m_redirect_url_request(
             &self,
             _server_url: &str,
@@ -1,6 +1,9 @@
 use anyhow::Context;
+use axum::http::header::{HeaderName, IF_MATCH, IF_NONE_MATCH};
 use axum::http::HeaderMap;
+use jsonwebtoken::{DecodingKey, Validation};
 use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
 
 use crate::error::{AppError, ExpectedError};
 
@@ -36,12 +39,148 @@ pub fn extract_bearer_token(header_map: &HeaderMap) -> Result<String, AppError>
     };
 }
 
+// eori shaped identifiers start with a country code, e.g. 'EU.EORI.NL1' or 'NL.24244'. like party
+// groups they are case insensitive, other identifiers like DIDs are case sensitive
+fn is_case_insensitive_party_id(party_id: &str) -> bool {
+    let is_eori_shaped = party_id
+        .as_bytes()
+        .get(..3)
+        .is_some_and(|p| p[0].is_ascii_alphabetic() && p[1].is_ascii_alphabetic() && p[2] == b'.');
+    let is_party_group = party_id
+        .get(..PARTY_GROUP_PREFIX.len())
+        .is_some_and(|p| p.eq_ignore_ascii_case(PARTY_GROUP_PREFIX));
+
+    is_eori_shaped || is_party_group
+}
+
+// party identifiers are stored and compared in a canonical form so that
+// 'eu.eori.nl1 ' and 'EU.EORI.NL1' refer to the same party
+pub fn normalize_party_id(party_id: &str) -> String {
+    let party_id = party_id.trim();
+
+    match is_case_insensitive_party_id(party_id) {
+        true => party_id.to_uppercase(),
+        false => party_id.to_owned(),
+    }
+}
+
+pub fn is_same_party(party_id: &str, other_party_id: &str) -> bool {
+    normalize_party_id(party_id) == normalize_party_id(other_party_id)
+}
+
+pub fn normalize_party_ids(party_ids: &Vec<String>) -> Vec<String> {
+    party_ids.iter().map(|p| normalize_party_id(p)).collect()
+}
+
+// the access subject of a policy set that applies to every party with its access subject attributes
+pub const ANY_PARTY: &str = "*";
+
+// access subjects and service providers can refer to a party group as 'group:<name>'
+pub const PARTY_GROUP_PREFIX: &str = "GROUP:";
+
+// party group names are case insensitive
+pub fn normalize_party_group_name(name: &str) -> String {
+    name.trim().to_uppercase()
+}
+
+// the normalized name of the party group the identifier refers to, if it refers to one
+pub fn party_group_name(party_id: &str) -> Option<String> {
+    normalize_party_id(party_id)
+        .strip_prefix(PARTY_GROUP_PREFIX)
+        .map(normalize_party_group_name)
+}
+
+// reads the claims of a jwt without verifying it, only to find out who should have signed it
+pub fn read_unverified_claims<T: DeserializeOwned>(token: &str) -> Result<T, String> {
+    let mut validation = Validation::default();
+    validation.insecure_disable_signature_validation();
+    validation.validate_exp = false;
+    validation.validate_aud = false;
+    validation.required_spec_claims.clear();
+
+    jsonwebtoken::decode::<T>(token, &DecodingKey::from_secret(&[]), &validation)
+        .map(|t| t.claims)
+        .map_err(|e| e.to_string())
+}
+
+// the etag of a policy set is its revision
+pub fn policy_set_etag(revision: i32) -> String {
+    format!("\"{}\"", revision)
+}
+
+fn header_etags(header_map: &HeaderMap, name: HeaderName) -> Option<Vec<String>> {
+    let mut values = header_map.get_all(name).iter().peekable();
+    values.peek()?;
+
+    Some(
+        values
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(|etag| etag.trim().to_owned())
+            .filter(|etag| !etag.is_empty())
+            .collect(),
+    )
+}
+
+// the revisions in the If-Match header, `None` when there is no If-Match header or it is '*'. an
+// If-Match header without revisions, like one with only weak etags, matches no revision at all
+pub fn extract_if_match_revisions(header_map: &HeaderMap) -> Option<Vec<i32>> {
+    let etags = header_etags(header_map, IF_MATCH)?;
+
+    if etags.iter().any(|etag| etag == "*") {
+        return None;
+    }
+
+    Some(
+        etags
+            .iter()
+            .filter_map(|etag| etag.strip_prefix('"')?.strip_suffix('"')?.parse().ok())
+            .collect(),
+    )
+}
+
+// whether the If-None-Match header matches the etag, weak etags in the header match as well
+pub fn if_none_match(header_map: &HeaderMap, etag: &str) -> bool {
+    header_etags(header_map, IF_NONE_MATCH).is_some_and(|etags| {
+        etags
+            .iter()
+            .any(|e| e == "*" || e.strip_prefix("W/").unwrap_or(e) == etag)
+    })
+}
+
 #[cfg(test)]
 mod test {
     use axum::http::{HeaderMap, HeaderValue};
-    use reqwest::header::AUTHORIZATION;
+    use reqwest::header::{AUTHORIZATION, IF_MATCH, IF_NONE_MATCH};
+
+    use crate::{
+        error::AppError,
+        utils::{
+            extract_bearer_token, extract_if_match_revisions, if_none_match, is_same_party,
+            normalize_party_id, party_group_name, policy_set_etag,
+        },
+    };
+
+    #[test]
+    fn test_normalize_party_id() {
+        assert_eq!(normalize_party_id(" eu.eori.nl1 "), "EU.EORI.NL1");
+        assert!(is_same_party("eu.eori.NL1", "EU.EORI.NL1\n"));
+        assert!(!is_same_party("EU.EORI.NL1", "EU.EORI.NL12"));
+        assert_eq!(
+            normalize_party_id(" did:web:Example.com "),
+            "did:web:Example.com"
+        );
+        assert!(!is_same_party("did:web:Example.com", "did:web:example.com"));
+    }
 
-    use crate::{error::AppError, utils::extract_bearer_token};
+    #[test]
+    fn test_party_group_name() {
+        assert_eq!(
+            party_group_name(" group:carriers"),
+            Some("CARRIERS".to_owned())
+        );
+        assert_eq!(party_group_name("EU.EORI.NL1"), None);
+    }
 
     #[test]
     fn test_extract_bearer_no_authorization() {
@@ -92,4 +231,36 @@ mod test {
             }
         };
     }
+
+    #[test]
+    fn test_extract_if_match_revisions() {
+        let mut header_map = HeaderMap::new();
+        assert_eq!(extract_if_match_revisions(&header_map), None);
+
+        header_map.insert(IF_MATCH, HeaderValue::from_static("\"3\", W/\"4\", \"x\""));
+        header_map.append(IF_MATCH, HeaderValue::from_static("\"5\""));
+        assert_eq!(extract_if_match_revisions(&header_map), Some(vec![3, 5]));
+
+        header_map.insert(IF_MATCH, HeaderValue::from_static("W/\"4\""));
+        assert_eq!(extract_if_match_revisions(&header_map), Some(vec![]));
+
+        header_map.insert(IF_MATCH, HeaderValue::from_static("*"));
+        assert_eq!(extract_if_match_revisions(&header_map), None);
+    }
+
+    #[test]
+    fn test_if_none_match() {
+        let etag = policy_set_etag(2);
+        let mut header_map = HeaderMap::new();
+        assert!(!if_none_match(&header_map, &etag));
+
+        header_map.insert(IF_NONE_MATCH, HeaderValue::from_static("\"1\", W/\"2\""));
+        assert!(if_none_match(&header_map, &etag));
+
+        header_map.insert(IF_NONE_MATCH, HeaderValue::from_static("\"1\""));
+        assert!(!if_none_match(&header_map, &etag));
+
+        header_map.insert(IF_NONE_MATCH, HeaderValue::from_static("*"));
+        assert!(if_none_match(&header_map, &etag));
+    }
 }